@@ -0,0 +1,148 @@
+use crate::jwe::{Dir, JweAlgorithm, JweContext};
+#[cfg(feature = "jwe-aeskw")]
+use crate::jwe::{A128GCMKW, A128KW, A192GCMKW, A192KW, A256GCMKW, A256KW};
+#[cfg(feature = "jwe-ecdh")]
+use crate::jwe::{ECDH_ES, ECDH_ES_A128KW, ECDH_ES_A192KW, ECDH_ES_A256KW};
+#[cfg(feature = "jwe-pbes2")]
+use crate::jwe::{PBES2_HS256_A128KW, PBES2_HS384_A192KW, PBES2_HS512_A256KW};
+#[cfg(feature = "jwe-rsa")]
+#[allow(deprecated)]
+use crate::jwe::{RSA1_5, RSA_OAEP, RSA_OAEP_256, RSA_OAEP_384, RSA_OAEP_512};
+#[cfg(feature = "jws-eddsa")]
+use crate::jws::EdDSA;
+#[allow(unused_imports)]
+use crate::jws::JwsAlgorithm;
+#[cfg(feature = "jws-ecdsa")]
+use crate::jws::{ES256, ES256K, ES384, ES512};
+#[cfg(all(feature = "jws-ecdsa", feature = "brainpool"))]
+use crate::jws::{ESB256, ESB384, ESB512};
+#[cfg(feature = "jws-hmac")]
+use crate::jws::{HS256, HS384, HS512};
+#[cfg(feature = "jws-rsa")]
+use crate::jws::{PS256, PS384, PS512, RS256, RS384, RS512};
+
+/// The algorithms that are compiled into this build.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Capabilities {
+    jws_algorithms: Vec<String>,
+    jwe_algorithms: Vec<String>,
+    content_encryptions: Vec<String>,
+    compressions: Vec<String>,
+}
+
+impl Capabilities {
+    /// Return the names of the supported JWS algorithms.
+    pub fn jws_algorithms(&self) -> &[String] {
+        &self.jws_algorithms
+    }
+
+    /// Return the names of the supported JWE key management algorithms.
+    pub fn jwe_algorithms(&self) -> &[String] {
+        &self.jwe_algorithms
+    }
+
+    /// Return the names of the supported JWE content encryption algorithms.
+    pub fn content_encryptions(&self) -> &[String] {
+        &self.content_encryptions
+    }
+
+    /// Return the names of the supported JWE compression algorithms.
+    pub fn compressions(&self) -> &[String] {
+        &self.compressions
+    }
+
+    /// Test a JWS algorithm is supported.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - a JWS algorithm name
+    pub fn is_supported_jws_algorithm(&self, name: &str) -> bool {
+        self.jws_algorithms.iter().any(|val| val == name)
+    }
+
+    /// Test a JWE key management algorithm is supported.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - a JWE key management algorithm name
+    pub fn is_supported_jwe_algorithm(&self, name: &str) -> bool {
+        self.jwe_algorithms.iter().any(|val| val == name)
+    }
+
+    /// Test a JWE content encryption algorithm is supported.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - a JWE content encryption algorithm name
+    pub fn is_supported_content_encryption(&self, name: &str) -> bool {
+        self.content_encryptions.iter().any(|val| val == name)
+    }
+}
+
+/// Return the algorithms that are enabled by cargo features.
+pub fn capabilities() -> Capabilities {
+    #[allow(unused_mut)]
+    let mut jws_algorithms: Vec<&dyn JwsAlgorithm> = Vec::new();
+    #[cfg(feature = "jws-hmac")]
+    jws_algorithms.extend_from_slice(&[&HS256, &HS384, &HS512]);
+    #[cfg(feature = "jws-rsa")]
+    jws_algorithms.extend_from_slice(&[&RS256, &RS384, &RS512, &PS256, &PS384, &PS512]);
+    #[cfg(feature = "jws-ecdsa")]
+    for alg in &[ES256, ES384, ES512, ES256K] {
+        if alg.is_available() {
+            jws_algorithms.push(alg);
+        }
+    }
+    #[cfg(all(feature = "jws-ecdsa", feature = "brainpool"))]
+    for alg in &[ESB256, ESB384, ESB512] {
+        if alg.is_available() {
+            jws_algorithms.push(alg);
+        }
+    }
+    #[cfg(feature = "jws-eddsa")]
+    jws_algorithms.push(&EdDSA);
+
+    #[allow(unused_mut)]
+    let mut jwe_algorithms: Vec<&dyn JweAlgorithm> = vec![&Dir];
+    #[cfg(feature = "jwe-rsa")]
+    #[allow(deprecated)]
+    jwe_algorithms.extend_from_slice(&[
+        &RSA1_5,
+        &RSA_OAEP,
+        &RSA_OAEP_256,
+        &RSA_OAEP_384,
+        &RSA_OAEP_512,
+    ]);
+    #[cfg(feature = "jwe-ecdh")]
+    jwe_algorithms.extend_from_slice(&[
+        &ECDH_ES,
+        &ECDH_ES_A128KW,
+        &ECDH_ES_A192KW,
+        &ECDH_ES_A256KW,
+    ]);
+    #[cfg(feature = "jwe-aeskw")]
+    jwe_algorithms.extend_from_slice(&[
+        &A128KW, &A192KW, &A256KW, &A128GCMKW, &A192GCMKW, &A256GCMKW,
+    ]);
+    #[cfg(feature = "jwe-pbes2")]
+    jwe_algorithms.extend_from_slice(&[
+        &PBES2_HS256_A128KW,
+        &PBES2_HS384_A192KW,
+        &PBES2_HS512_A256KW,
+    ]);
+
+    let context = JweContext::new();
+
+    Capabilities {
+        jws_algorithms: jws_algorithms
+            .iter()
+            .map(|alg| alg.name().to_string())
+            .collect(),
+        jwe_algorithms: jwe_algorithms
+            .iter()
+            .map(|alg| alg.name().to_string())
+            .collect(),
+        content_encryptions: context.content_encryption_names(),
+        compressions: context.compression_names(),
+    }
+}
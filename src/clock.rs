@@ -0,0 +1,73 @@
+use std::fmt::Debug;
+use std::time::{Duration, SystemTime};
+
+/// A source of the current time for time related validation.
+pub trait Clock: Debug + Send + Sync {
+    /// Return the current time.
+    fn now(&self) -> SystemTime;
+}
+
+/// A clock that returns the system time.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A clock that always returns the specified time.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct FixedClock(pub SystemTime);
+
+impl Clock for FixedClock {
+    fn now(&self) -> SystemTime {
+        self.0
+    }
+}
+
+/// A clock that returns the system time shifted by a fixed offset.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct SkewedClock {
+    offset: Duration,
+    ahead: bool,
+}
+
+impl SkewedClock {
+    /// Return a clock that is ahead of the system time.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - a offset from the system time.
+    pub fn ahead(offset: Duration) -> Self {
+        Self {
+            offset,
+            ahead: true,
+        }
+    }
+
+    /// Return a clock that is behind the system time.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - a offset from the system time.
+    pub fn behind(offset: Duration) -> Self {
+        Self {
+            offset,
+            ahead: false,
+        }
+    }
+}
+
+impl Clock for SkewedClock {
+    fn now(&self) -> SystemTime {
+        let now = SystemTime::now();
+        let shifted = if self.ahead {
+            now.checked_add(self.offset)
+        } else {
+            now.checked_sub(self.offset)
+        };
+        shifted.unwrap_or(now)
+    }
+}
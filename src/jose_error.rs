@@ -8,6 +8,9 @@ pub enum JoseError {
     #[error("Invalid JWT format: {0}")]
     InvalidJwtFormat(#[source] anyhow::Error),
 
+    #[error("Malformed JWT: {0}")]
+    MalformedJwt(#[source] anyhow::Error),
+
     #[error("Invalid JWK format: {0}")]
     InvalidJwkFormat(#[source] anyhow::Error),
 
@@ -28,4 +31,16 @@ pub enum JoseError {
 
     #[error("Invalid signature: {0}")]
     InvalidSignature(#[source] anyhow::Error),
+
+    #[error("Decompressed size exceeded: {0}")]
+    DecompressedSizeExceeded(#[source] anyhow::Error),
+
+    #[error("PBES2 limit exceeded: {0}")]
+    Pbes2LimitExceeded(#[source] anyhow::Error),
+
+    #[error("Initialization vector exhausted: {0}")]
+    IvExhausted(#[source] anyhow::Error),
+
+    #[error("Unsupported encryption algorithm: {0}")]
+    UnsupportedEncryptionAlgorithm(#[source] anyhow::Error),
 }
@@ -1,9 +1,14 @@
 use crate::Value;
 
 pub trait JoseHeader: Send + Sync {
-    // Return claim count.
+    /// Return claim count.
     fn len(&self) -> usize;
 
+    /// Test whether the header has no claims.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     /// Return the value for header claim of a specified key.
     ///
     /// # Arguments
@@ -11,6 +16,9 @@ pub trait JoseHeader: Send + Sync {
     /// * `key` - a key name of header claim
     fn claim(&self, key: &str) -> Option<&Value>;
 
+    /// Return an iterator over the key names and values of header claims.
+    fn iter(&self) -> Box<dyn Iterator<Item = (&str, &Value)> + '_>;
+
     fn box_clone(&self) -> Box<dyn JoseHeader>;
 }
 
@@ -19,3 +27,16 @@ impl Clone for Box<dyn JoseHeader> {
         self.box_clone()
     }
 }
+
+/// Represent how to resolve a claim that is in both headers when merging them.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum ConflictPolicy {
+    /// The merge fails if a claim has a different value in each header.
+    Error,
+
+    /// The value of the merged header is kept.
+    KeepExisting,
+
+    /// The value of the merged header is overwritten.
+    Overwrite,
+}
@@ -6,52 +6,79 @@ mod jwe_algorithm;
 mod jwe_compression;
 mod jwe_content_encryption;
 mod jwe_context;
+mod jwe_deserialized_json;
 mod jwe_header;
 mod jwe_header_set;
 pub mod zip;
 
 use once_cell::sync::Lazy;
 
+use crate::jwk::JwkSet;
 use crate::JoseError;
 
 pub use crate::jwe::jwe_algorithm::JweAlgorithm;
 pub use crate::jwe::jwe_algorithm::JweDecrypter;
 pub use crate::jwe::jwe_algorithm::JweEncrypter;
+pub use crate::jwe::jwe_compression::DecompressedSizeLimitError;
 pub use crate::jwe::jwe_compression::JweCompression;
 pub use crate::jwe::jwe_content_encryption::JweContentEncryption;
 pub use crate::jwe::jwe_context::JweContext;
+pub use crate::jwe::jwe_deserialized_json::JweDeserializedJson;
 pub use crate::jwe::jwe_header::JweHeader;
 pub use crate::jwe::jwe_header_set::JweHeaderSet;
 
 pub use crate::jwe::alg::direct::DirectJweAlgorithm::Dir;
 
+#[cfg(feature = "jwe-ecdh")]
 use crate::jwe::alg::ecdh_es::EcdhEsJweAlgorithm;
+#[cfg(feature = "jwe-ecdh")]
 pub use EcdhEsJweAlgorithm::EcdhEs as ECDH_ES;
+#[cfg(feature = "jwe-ecdh")]
 pub use EcdhEsJweAlgorithm::EcdhEsA128kw as ECDH_ES_A128KW;
+#[cfg(feature = "jwe-ecdh")]
 pub use EcdhEsJweAlgorithm::EcdhEsA192kw as ECDH_ES_A192KW;
+#[cfg(feature = "jwe-ecdh")]
 pub use EcdhEsJweAlgorithm::EcdhEsA256kw as ECDH_ES_A256KW;
 
+#[cfg(feature = "jwe-aeskw")]
 use crate::jwe::alg::aeskw::AeskwJweAlgorithm;
+#[cfg(feature = "jwe-aeskw")]
 pub use AeskwJweAlgorithm::A128kw as A128KW;
+#[cfg(feature = "jwe-aeskw")]
 pub use AeskwJweAlgorithm::A192kw as A192KW;
+#[cfg(feature = "jwe-aeskw")]
 pub use AeskwJweAlgorithm::A256kw as A256KW;
 
+#[cfg(feature = "jwe-aeskw")]
 use crate::jwe::alg::aesgcmkw::AesgcmkwJweAlgorithm;
+#[cfg(feature = "jwe-aeskw")]
 pub use AesgcmkwJweAlgorithm::A128gcmkw as A128GCMKW;
+#[cfg(feature = "jwe-aeskw")]
 pub use AesgcmkwJweAlgorithm::A192gcmkw as A192GCMKW;
+#[cfg(feature = "jwe-aeskw")]
 pub use AesgcmkwJweAlgorithm::A256gcmkw as A256GCMKW;
 
+#[cfg(feature = "jwe-pbes2")]
 use crate::jwe::alg::pbes2_hmac_aeskw::Pbes2HmacAeskwJweAlgorithm;
+#[cfg(feature = "jwe-pbes2")]
 pub use Pbes2HmacAeskwJweAlgorithm::Pbes2Hs256A128kw as PBES2_HS256_A128KW;
+#[cfg(feature = "jwe-pbes2")]
 pub use Pbes2HmacAeskwJweAlgorithm::Pbes2Hs384A192kw as PBES2_HS384_A192KW;
+#[cfg(feature = "jwe-pbes2")]
 pub use Pbes2HmacAeskwJweAlgorithm::Pbes2Hs512A256kw as PBES2_HS512_A256KW;
 
+#[cfg(feature = "jwe-rsa")]
 use crate::jwe::alg::rsaes::RsaesJweAlgorithm;
+#[cfg(feature = "jwe-rsa")]
 #[allow(deprecated)]
 pub use RsaesJweAlgorithm::Rsa1_5 as RSA1_5;
+#[cfg(feature = "jwe-rsa")]
 pub use RsaesJweAlgorithm::RsaOaep as RSA_OAEP;
+#[cfg(feature = "jwe-rsa")]
 pub use RsaesJweAlgorithm::RsaOaep256 as RSA_OAEP_256;
+#[cfg(feature = "jwe-rsa")]
 pub use RsaesJweAlgorithm::RsaOaep384 as RSA_OAEP_384;
+#[cfg(feature = "jwe-rsa")]
 pub use RsaesJweAlgorithm::RsaOaep512 as RSA_OAEP_512;
 
 static DEFAULT_CONTEXT: Lazy<JweContext> = Lazy::new(|| JweContext::new());
@@ -210,6 +237,19 @@ where
     DEFAULT_CONTEXT.deserialize_compact_with_selector(input, selector)
 }
 
+/// Deserialize the input that is formatted by compact serialization with a private key in a JWK set.
+///
+/// # Arguments
+///
+/// * `input` - The input data.
+/// * `jwk_set` - a JWK set.
+pub fn deserialize_compact_with_jwk_set(
+    input: &str,
+    jwk_set: &JwkSet,
+) -> Result<(Vec<u8>, JweHeader), JoseError> {
+    DEFAULT_CONTEXT.deserialize_compact_with_jwk_set(input, jwk_set)
+}
+
 /// Deserialize the input that is formatted by flattened json serialization.
 ///
 /// # Arguments
@@ -240,20 +280,82 @@ where
     DEFAULT_CONTEXT.deserialize_json_with_selector(input, selector)
 }
 
+/// Deserialize the input that is formatted by flattened json serialization.
+///
+/// # Arguments
+///
+/// * `input` - The input data.
+/// * `decrypter` - The JWE decrypter.
+pub fn deserialize_flattened_json(
+    input: &str,
+    decrypter: &dyn JweDecrypter,
+) -> Result<(Vec<u8>, JweHeader), JoseError> {
+    DEFAULT_CONTEXT.deserialize_flattened_json(input, decrypter)
+}
+
+/// Deserialize the input that is formatted by flattened json serialization.
+///
+/// # Arguments
+///
+/// * `input` - The input data.
+/// * `selector` - a function for selecting the decrypting algorithm.
+pub fn deserialize_flattened_json_with_selector<'a, F>(
+    input: &str,
+    selector: F,
+) -> Result<(Vec<u8>, JweHeader), JoseError>
+where
+    F: Fn(&JweHeader) -> Result<Option<&'a dyn JweDecrypter>, JoseError>,
+{
+    DEFAULT_CONTEXT.deserialize_flattened_json_with_selector(input, selector)
+}
+
+/// Deserialize the input that is formatted by general or flattened json serialization,
+/// and return the information of the recipient that was used to decrypt it.
+///
+/// # Arguments
+///
+/// * `input` - The input data.
+/// * `decrypter` - The JWE decrypter.
+pub fn deserialize_general_json(
+    input: &str,
+    decrypter: &dyn JweDecrypter,
+) -> Result<JweDeserializedJson, JoseError> {
+    DEFAULT_CONTEXT.deserialize_general_json(input, decrypter)
+}
+
+/// Deserialize the input that is formatted by general or flattened json serialization,
+/// and return the information of the recipient that was used to decrypt it.
+///
+/// # Arguments
+///
+/// * `input` - The input data.
+/// * `selector` - a function for selecting the decrypting algorithm.
+pub fn deserialize_general_json_with_selector<'a, F>(
+    input: &str,
+    selector: F,
+) -> Result<JweDeserializedJson, JoseError>
+where
+    F: Fn(&JweHeader) -> Result<Option<&'a dyn JweDecrypter>, JoseError>,
+{
+    DEFAULT_CONTEXT.deserialize_general_json_with_selector(input, selector)
+}
+
 #[cfg(test)]
 mod tests {
+    use std::cell::Cell;
     use std::fs;
     use std::path::PathBuf;
 
     use anyhow::Result;
 
+    use crate::jwe::enc::aesgcm::{AesgcmJweContentEncryption, AesgcmJweEncryption, IvGenerator};
     use crate::jwe::{
-        self, Dir, JweAlgorithm, JweHeader, JweHeaderSet, ECDH_ES_A128KW, PBES2_HS256_A128KW,
-        RSA_OAEP,
+        self, Dir, JweAlgorithm, JweContentEncryption, JweContext, JweDecrypter, JweEncrypter,
+        JweHeader, JweHeaderSet, A128KW, ECDH_ES_A128KW, PBES2_HS256_A128KW, RSA_OAEP,
     };
-    use crate::jwk::Jwk;
+    use crate::jwk::{self, Jwk, JwkSet};
     use crate::util;
-    use crate::Value;
+    use crate::{JoseError, Map, Value};
 
     #[test]
     fn test_jwe_compact_serialization() -> Result<()> {
@@ -275,8 +377,8 @@ mod tests {
             let alg = Dir;
             let key = match enc {
                 "A128CBC-HS256" => util::random_bytes(32),
-                "A192CBC-HS384" => util::random_bytes(40),
-                "A256CBC-HS512" => util::random_bytes(48),
+                "A192CBC-HS384" => util::random_bytes(48),
+                "A256CBC-HS512" => util::random_bytes(64),
                 "A128GCM" => util::random_bytes(16),
                 "A192GCM" => util::random_bytes(24),
                 "A256GCM" => util::random_bytes(32),
@@ -296,6 +398,80 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_jwe_compact_rfc7516_appendix_a3() -> Result<()> {
+        let jwk = Jwk::from_bytes(br#"{"kty":"oct","k":"GawgguFyGrWKav7AX4VKUg"}"#)?;
+        let input = concat!(
+            "eyJhbGciOiJBMTI4S1ciLCJlbmMiOiJBMTI4Q0JDLUhTMjU2In0.",
+            "6KB707dM9YTIgHtLvtgWQ8mKwboJW3of9locizkDTHzBC2IlrT1oOQ.",
+            "AxY8DCtDaGlsbGljb3RoZQ.",
+            "KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY.",
+            "U0m_YmjN04DJvceFICbCVQ",
+        );
+
+        let decrypter = A128KW.decrypter_from_jwk(&jwk)?;
+        let (payload, header) = jwe::deserialize_compact(input, &decrypter)?;
+        assert_eq!(payload, b"Live long and prosper.".to_vec());
+        assert_eq!(header.algorithm(), Some("A128KW"));
+        assert_eq!(header.content_encryption(), Some("A128CBC-HS256"));
+
+        let encrypter = A128KW.encrypter_from_jwk(&jwk)?;
+        let output = jwe::serialize_compact(&payload, &header, &encrypter)?;
+        let (payload2, header2) = jwe::deserialize_compact(&output, &decrypter)?;
+        assert_eq!(payload, payload2);
+        assert_eq!(header, header2);
+
+        let mut tampered = input.to_string();
+        tampered.pop();
+        tampered.push('A');
+        assert!(jwe::deserialize_compact(&tampered, &decrypter).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_compact_alg_mismatch() -> Result<()> {
+        let key = util::random_bytes(16);
+        let src_payload = b"test payload!";
+
+        let mut src_header = JweHeader::new();
+        src_header.set_algorithm("A256KW");
+        src_header.set_content_encryption("A128GCM");
+        let encrypter = A128KW.encrypter_from_bytes(&key)?;
+        assert!(jwe::serialize_compact(src_payload, &src_header, &encrypter).is_err());
+
+        src_header.set_algorithm("A128KW");
+        let jwe = jwe::serialize_compact(src_payload, &src_header, &encrypter)?;
+
+        let decrypter = Dir.decrypter_from_bytes(&key)?;
+        assert!(jwe::deserialize_compact(&jwe, &decrypter).is_err());
+
+        let mut src_header = JweHeader::new();
+        src_header.set_content_encryption("A512GCM");
+        assert!(jwe::serialize_compact(src_payload, &src_header, &encrypter).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_compact_malformed_header_claim() -> Result<()> {
+        let key = util::random_bytes(16);
+
+        let mut src_header = JweHeader::new();
+        src_header.set_content_encryption("A128GCM");
+        src_header.set_claim("apu", Some(Value::String("a+b/".to_string())))?;
+        let encrypter = A128KW.encrypter_from_bytes(&key)?;
+        let jwe = jwe::serialize_compact(b"test payload!", &src_header, &encrypter)?;
+
+        let decrypter = A128KW.decrypter_from_bytes(&key)?;
+        assert!(matches!(
+            jwe::deserialize_compact(&jwe, &decrypter),
+            Err(JoseError::InvalidJweFormat(_))
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn test_jwe_json_serialization() -> Result<()> {
         let alg = RSA_OAEP;
@@ -334,6 +510,240 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_jwe_flattened_json_with_aad() -> Result<()> {
+        let key = util::random_bytes(16);
+        let src_payload = b"test payload!";
+        let aad = b"manifest-hash";
+
+        let mut src_header = JweHeaderSet::new();
+        src_header.set_content_encryption("A128GCM", true);
+        src_header.set_token_type("JWT", false);
+
+        for alg in &[&A128KW as &dyn JweAlgorithm, &Dir] {
+            let (encrypter, decrypter) = match alg.name() {
+                "A128KW" => (
+                    Box::new(A128KW.encrypter_from_bytes(&key)?) as Box<dyn JweEncrypter>,
+                    Box::new(A128KW.decrypter_from_bytes(&key)?) as Box<dyn JweDecrypter>,
+                ),
+                _ => (
+                    Box::new(Dir.encrypter_from_bytes(&key)?) as Box<dyn JweEncrypter>,
+                    Box::new(Dir.decrypter_from_bytes(&key)?) as Box<dyn JweDecrypter>,
+                ),
+            };
+
+            let json = jwe::serialize_flattened_json(
+                src_payload,
+                Some(&src_header),
+                None,
+                Some(aad),
+                &*encrypter,
+            )?;
+            let (dst_payload, dst_header) = jwe::deserialize_flattened_json(&json, &*decrypter)?;
+            assert_eq!(dst_header.algorithm(), Some(alg.name()));
+            assert_eq!(src_payload.to_vec(), dst_payload);
+
+            let mut map: Map<String, Value> = serde_json::from_str(&json)?;
+            assert_eq!(
+                map.get("aad"),
+                Some(&Value::String(base64::encode_config(
+                    aad,
+                    base64::URL_SAFE_NO_PAD
+                )))
+            );
+            map.insert(
+                "aad".to_string(),
+                Value::String(base64::encode_config(
+                    b"manifest-hasx",
+                    base64::URL_SAFE_NO_PAD,
+                )),
+            );
+            let tampered = serde_json::to_string(&map)?;
+            assert!(jwe::deserialize_flattened_json(&tampered, &*decrypter).is_err());
+
+            map.remove("aad");
+            let tampered = serde_json::to_string(&map)?;
+            assert!(jwe::deserialize_flattened_json(&tampered, &*decrypter).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_flattened_json_without_aad() -> Result<()> {
+        // The compact serialization of RFC 7516 Appendix A.3 converted to the
+        // flattened json serialization must use the same AAD.
+        let jwk = Jwk::from_bytes(br#"{"kty":"oct","k":"GawgguFyGrWKav7AX4VKUg"}"#)?;
+        let json = r#"{
+            "protected":"eyJhbGciOiJBMTI4S1ciLCJlbmMiOiJBMTI4Q0JDLUhTMjU2In0",
+            "encrypted_key":"6KB707dM9YTIgHtLvtgWQ8mKwboJW3of9locizkDTHzBC2IlrT1oOQ",
+            "iv":"AxY8DCtDaGlsbGljb3RoZQ",
+            "ciphertext":"KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY",
+            "tag":"U0m_YmjN04DJvceFICbCVQ"
+        }"#;
+
+        let decrypter = A128KW.decrypter_from_jwk(&jwk)?;
+        let (payload, _) = jwe::deserialize_flattened_json(json, &decrypter)?;
+        assert_eq!(payload, b"Live long and prosper.".to_vec());
+
+        let general = format!(
+            "{{\"recipients\":[{}]}}",
+            serde_json::to_string(&serde_json::from_str::<Value>(json)?)?
+        );
+        assert!(jwe::deserialize_flattened_json(&general, &decrypter).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_json_duplicate_header_claims() -> Result<()> {
+        let key = util::random_bytes(16);
+        let src_payload = b"test payload!";
+        let encrypter = A128KW.encrypter_from_bytes(&key)?;
+        let decrypter = A128KW.decrypter_from_bytes(&key)?;
+
+        let mut src_header = JweHeaderSet::new();
+        src_header.set_content_encryption("A128GCM", true);
+        src_header.set_token_type("JWT", false);
+        let mut src_rheader = JweHeader::new();
+        src_rheader.set_token_type("JWT");
+        assert!(jwe::serialize_flattened_json(
+            src_payload,
+            Some(&src_header),
+            Some(&src_rheader),
+            None,
+            &encrypter,
+        )
+        .is_err());
+
+        let json =
+            jwe::serialize_flattened_json(src_payload, Some(&src_header), None, None, &encrypter)?;
+        let mut map: Map<String, Value> = serde_json::from_str(&json)?;
+        let mut header = Map::new();
+        header.insert("typ".to_string(), Value::String("JWT".to_string()));
+        map.insert("header".to_string(), Value::Object(header));
+        let json = serde_json::to_string(&map)?;
+        assert!(jwe::deserialize_flattened_json(&json, &decrypter).is_err());
+
+        let mut map: Map<String, Value> = serde_json::from_str(&json)?;
+        let mut header = Map::new();
+        header.insert("enc".to_string(), Value::String("A128GCM".to_string()));
+        map.insert("header".to_string(), Value::Object(header));
+        let json = serde_json::to_string(&map)?;
+        assert!(jwe::deserialize_flattened_json(&json, &decrypter).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_general_json_recipient_header_conflicts() -> Result<()> {
+        let key = util::random_bytes(16);
+        let src_payload = b"test payload!";
+        let encrypter = A128KW.encrypter_from_bytes(&key)?;
+        let decrypter = A128KW.decrypter_from_bytes(&key)?;
+
+        let error_message = |result: Result<String, JoseError>| -> String {
+            match result {
+                Err(JoseError::InvalidJweFormat(err)) => err.to_string(),
+                Err(err) => panic!("unexpected error: {}", err),
+                Ok(val) => panic!("unexpected success: {}", val),
+            }
+        };
+
+        // enc override by a per-recipient header
+        let mut src_header = JweHeaderSet::new();
+        src_header.set_content_encryption("A128GCM", true);
+        let mut src_rheader = JweHeader::new();
+        src_rheader.set_content_encryption("A256GCM");
+        let message = error_message(jwe::serialize_general_json(
+            src_payload,
+            Some(&src_header),
+            &[(None, &*encrypter), (Some(&src_rheader), &*encrypter)],
+            None,
+        ));
+        assert!(message.contains("enc"));
+
+        let mut src_rheader = JweHeader::new();
+        src_rheader.set_content_encryption("A128GCM");
+        let message = error_message(jwe::serialize_general_json(
+            src_payload,
+            None,
+            &[(Some(&src_rheader), &*encrypter)],
+            None,
+        ));
+        assert!(message.contains("enc"));
+
+        // kid duplicated with different values
+        let mut src_header = JweHeaderSet::new();
+        src_header.set_content_encryption("A128GCM", true);
+        src_header.set_key_id("shared", false);
+        let mut src_rheader = JweHeader::new();
+        src_rheader.set_key_id("recipient");
+        let message = error_message(jwe::serialize_general_json(
+            src_payload,
+            Some(&src_header),
+            &[(Some(&src_rheader), &*encrypter)],
+            None,
+        ));
+        assert!(message.contains("kid"));
+
+        let mut src_header = JweHeaderSet::new();
+        src_header.set_content_encryption("A128GCM", true);
+        src_header.set_key_id("shared", true);
+        let json = jwe::serialize_general_json(
+            src_payload,
+            Some(&src_header),
+            &[(None, &*encrypter)],
+            None,
+        )?;
+        jwe::deserialize_json(&json, &decrypter)?;
+
+        let mut map: Map<String, Value> = serde_json::from_str(&json)?;
+        if let Some(Value::Array(recipients)) = map.get_mut("recipients") {
+            if let Some(Value::Object(recipient)) = recipients.get_mut(0) {
+                if let Some(Value::Object(header)) = recipient.get_mut("header") {
+                    header.insert("kid".to_string(), Value::String("recipient".to_string()));
+                }
+            }
+        }
+        let json = serde_json::to_string(&map)?;
+        let message = error_message(
+            jwe::deserialize_json(&json, &decrypter).map(|(payload, _)| format!("{:?}", payload)),
+        );
+        assert!(message.contains("kid"));
+
+        // enc mismatched between recipients
+        let mut src_header = JweHeaderSet::new();
+        src_header.set_content_encryption("A128GCM", true);
+        let json = jwe::serialize_general_json(
+            src_payload,
+            Some(&src_header),
+            &[(None, &*encrypter), (None, &*encrypter)],
+            None,
+        )?;
+        let mut map: Map<String, Value> = serde_json::from_str(&json)?;
+        map.insert(
+            "protected".to_string(),
+            Value::String(base64::encode_config("{}", base64::URL_SAFE_NO_PAD)),
+        );
+        if let Some(Value::Array(recipients)) = map.get_mut("recipients") {
+            for (recipient, enc) in recipients.iter_mut().zip(&["A128GCM", "A256GCM"]) {
+                if let Value::Object(recipient) = recipient {
+                    if let Some(Value::Object(header)) = recipient.get_mut("header") {
+                        header.insert("enc".to_string(), Value::String(enc.to_string()));
+                    }
+                }
+            }
+        }
+        let json = serde_json::to_string(&map)?;
+        let message = error_message(
+            jwe::deserialize_json(&json, &decrypter).map(|(payload, _)| format!("{:?}", payload)),
+        );
+        assert!(message.contains("enc"));
+
+        Ok(())
+    }
+
     #[test]
     fn test_jwe_general_json_serialization() -> Result<()> {
         let public_key_1 = load_file("pem/RSA_2048bit_public.pem")?;
@@ -383,6 +793,708 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_jwe_deflate_compression() -> Result<()> {
+        let alg = A128KW;
+        let key = util::random_bytes(16);
+        let encrypter = alg.encrypter_from_bytes(&key)?;
+        let decrypter = alg.decrypter_from_bytes(&key)?;
+        let src_payload = "test payload! ".repeat(64);
+
+        let mut src_header = JweHeader::new();
+        src_header.set_content_encryption("A128GCM");
+        src_header.set_compression("DEF");
+        let jwe = jwe::serialize_compact(src_payload.as_bytes(), &src_header, &encrypter)?;
+        let (dst_payload, dst_header) = jwe::deserialize_compact(&jwe, &decrypter)?;
+        assert_eq!(dst_header.compression(), Some("DEF"));
+        assert_eq!(src_payload.as_bytes().to_vec(), dst_payload);
+
+        let mut src_header = JweHeaderSet::new();
+        src_header.set_content_encryption("A128GCM", true);
+        src_header.set_compression("DEF");
+        let json = jwe::serialize_flattened_json(
+            src_payload.as_bytes(),
+            Some(&src_header),
+            None,
+            None,
+            &encrypter,
+        )?;
+        let (dst_payload, dst_header) = jwe::deserialize_json(&json, &decrypter)?;
+        assert_eq!(dst_header.compression(), Some("DEF"));
+        assert_eq!(src_payload.as_bytes().to_vec(), dst_payload);
+
+        let mut src_header = JweHeader::new();
+        src_header.set_content_encryption("A128GCM");
+        src_header.set_compression("XYZ");
+        assert!(jwe::serialize_compact(src_payload.as_bytes(), &src_header, &encrypter).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_unprotected_zip() -> Result<()> {
+        let alg = A128KW;
+        let key = util::random_bytes(16);
+        let encrypter = alg.encrypter_from_bytes(&key)?;
+        let decrypter = alg.decrypter_from_bytes(&key)?;
+        let src_payload = b"test payload!";
+
+        let mut src_header = JweHeaderSet::new();
+        src_header.set_content_encryption("A128GCM", true);
+        src_header.set_claim("zip", Some(Value::String("DEF".to_string())), false)?;
+        assert!(jwe::serialize_flattened_json(
+            src_payload,
+            Some(&src_header),
+            None,
+            None,
+            &encrypter,
+        )
+        .is_err());
+
+        let mut src_header = JweHeaderSet::new();
+        src_header.set_content_encryption("A128GCM", true);
+        let mut src_rheader = JweHeader::new();
+        src_rheader.set_compression("DEF");
+        assert!(jwe::serialize_general_json(
+            src_payload,
+            Some(&src_header),
+            &[(Some(&src_rheader), &*encrypter)],
+            None,
+        )
+        .is_err());
+
+        let json =
+            jwe::serialize_flattened_json(src_payload, Some(&src_header), None, None, &encrypter)?;
+        let mut map: Map<String, Value> = serde_json::from_str(&json)?;
+        let mut unprotected = Map::new();
+        unprotected.insert("zip".to_string(), Value::String("DEF".to_string()));
+        map.insert("unprotected".to_string(), Value::Object(unprotected));
+        let json = serde_json::to_string(&map)?;
+        assert!(jwe::deserialize_json(&json, &decrypter).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_critical() -> Result<()> {
+        let key = util::random_bytes(16);
+        let src_payload = b"test payload!";
+        let encrypter = A128KW.encrypter_from_bytes(&key)?;
+        let decrypter = A128KW.decrypter_from_bytes(&key)?;
+
+        let mut src_header = JweHeader::new();
+        src_header.set_content_encryption("A128GCM");
+        src_header.set_critical(&vec!["x-ext"]);
+        src_header.set_claim("x-ext", Some(Value::Bool(true)))?;
+        let jwe = jwe::serialize_compact(src_payload, &src_header, &encrypter)?;
+
+        let mut context = JweContext::new();
+        assert!(matches!(
+            context.deserialize_compact(&jwe, &decrypter),
+            Err(JoseError::InvalidJweFormat(_))
+        ));
+
+        context.add_acceptable_critical("x-ext");
+        let (dst_payload, dst_header) = context.deserialize_compact(&jwe, &decrypter)?;
+        assert_eq!(src_payload.to_vec(), dst_payload);
+        assert_eq!(dst_header.critical(), Some(vec!["x-ext"]));
+
+        let segments: Vec<&str> = jwe.splitn(2, '.').collect();
+        for crit in &["[\"enc\"]", "[]", "[\"x-ext\",1]", "\"x-ext\""] {
+            let header = format!(
+                "{{\"alg\":\"A128KW\",\"enc\":\"A128GCM\",\"x-ext\":true,\"crit\":{}}}",
+                crit
+            );
+            let header = base64::encode_config(header, base64::URL_SAFE_NO_PAD);
+            let jwe = format!("{}.{}", header, segments[1]);
+            assert!(matches!(
+                context.deserialize_compact(&jwe, &decrypter),
+                Err(JoseError::InvalidJweFormat(_))
+            ));
+        }
+
+        let mut src_header = JweHeaderSet::new();
+        src_header.set_content_encryption("A128GCM", true);
+        src_header.set_claim("x-ext", Some(Value::Bool(true)), false)?;
+        src_header.set_claim(
+            "crit",
+            Some(Value::Array(vec![Value::String("x-ext".to_string())])),
+            false,
+        )?;
+        let json =
+            jwe::serialize_flattened_json(src_payload, Some(&src_header), None, None, &encrypter)?;
+        assert!(matches!(
+            context.deserialize_json(&json, &decrypter),
+            Err(JoseError::InvalidJweFormat(_))
+        ));
+
+        let mut src_header = JweHeaderSet::new();
+        src_header.set_content_encryption("A128GCM", true);
+        src_header.set_claim("x-ext", Some(Value::Bool(true)), true)?;
+        src_header.set_claim(
+            "crit",
+            Some(Value::Array(vec![Value::String("x-ext".to_string())])),
+            true,
+        )?;
+        let json =
+            jwe::serialize_flattened_json(src_payload, Some(&src_header), None, None, &encrypter)?;
+        assert!(JweContext::new()
+            .deserialize_json(&json, &decrypter)
+            .is_err());
+        let (dst_payload, _) = context.deserialize_json(&json, &decrypter)?;
+        assert_eq!(src_payload.to_vec(), dst_payload);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_general_json_recipient() -> Result<()> {
+        let src_payload = b"test payload!";
+        let key1 = util::random_bytes(16);
+        let key2 = util::random_bytes(16);
+        let key3 = util::random_bytes(16);
+
+        let mut encrypter1 = A128KW.encrypter_from_bytes(&key1)?;
+        encrypter1.set_key_id("key1");
+        let mut encrypter2 = A128KW.encrypter_from_bytes(&key2)?;
+        encrypter2.set_key_id("key2");
+        // A recipient for a other key that reuses the key ID.
+        let mut encrypter3 = A128KW.encrypter_from_bytes(&key3)?;
+        encrypter3.set_key_id("key2");
+
+        let mut decrypter2 = A128KW.decrypter_from_bytes(&key2)?;
+        decrypter2.set_key_id("key2");
+
+        let mut src_header = JweHeaderSet::new();
+        src_header.set_content_encryption("A128GCM", true);
+        let mut src_rheader = JweHeader::new();
+        src_rheader.set_claim("x-recipient", Some(Value::String("test".to_string())))?;
+
+        let json = jwe::serialize_general_json(
+            src_payload,
+            Some(&src_header),
+            &[
+                (None, &*encrypter1),
+                (Some(&src_rheader), &*encrypter2),
+                (None, &*encrypter2),
+            ],
+            None,
+        )?;
+        let count = Cell::new(0);
+        let result =
+            jwe::deserialize_general_json_with_selector(&json, |header| match header.key_id() {
+                Some("key2") => {
+                    count.set(count.get() + 1);
+                    Ok(Some(&*decrypter2))
+                }
+                _ => Ok(None),
+            })?;
+        assert_eq!(result.payload(), src_payload);
+        assert_eq!(result.recipient_index(), 1);
+        assert_eq!(result.header().key_id(), Some("key2"));
+        let recipient_header = result.recipient_header().unwrap();
+        assert_eq!(recipient_header.algorithm(), Some("A128KW"));
+        assert_eq!(recipient_header.key_id(), Some("key2"));
+        assert_eq!(
+            recipient_header.claim("x-recipient"),
+            Some(&Value::String("test".to_string()))
+        );
+        assert_eq!(recipient_header.content_encryption(), None);
+        // The first successful unwrap wins and the duplicate is not attempted.
+        assert_eq!(count.get(), 1);
+
+        // A recipient that cannot be unwrapped is skipped.
+        let json = jwe::serialize_general_json(
+            src_payload,
+            Some(&src_header),
+            &[(None, &*encrypter3), (None, &*encrypter2)],
+            None,
+        )?;
+        let result = jwe::deserialize_general_json(&json, &decrypter2)?;
+        assert_eq!(result.payload(), src_payload);
+        assert_eq!(result.recipient_index(), 1);
+
+        let json = jwe::serialize_general_json(
+            src_payload,
+            Some(&src_header),
+            &[(None, &*encrypter1), (None, &*encrypter3)],
+            None,
+        )?;
+        assert!(jwe::deserialize_general_json(&json, &decrypter2).is_err());
+
+        let json =
+            jwe::serialize_flattened_json(src_payload, Some(&src_header), None, None, &encrypter2)?;
+        let result = jwe::deserialize_general_json(&json, &decrypter2)?;
+        assert_eq!(result.payload(), src_payload);
+        assert_eq!(result.recipient_index(), 0);
+        assert_eq!(result.recipient_header(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_acceptable_algorithms() -> Result<()> {
+        let key = util::random_bytes(16);
+        let src_payload = b"test payload!";
+        let encrypter = A128KW.encrypter_from_bytes(&key)?;
+        let decrypter = A128KW.decrypter_from_bytes(&key)?;
+
+        let mut src_header = JweHeader::new();
+        src_header.set_content_encryption("A128GCM");
+        let jwe = jwe::serialize_compact(src_payload, &src_header, &encrypter)?;
+
+        let mut context = JweContext::new();
+        assert!(context.is_acceptable_algorithm("A128KW"));
+        assert!(context.is_acceptable_content_encryption("A128GCM"));
+        context.add_acceptable_algorithm("A128KW");
+        context.add_acceptable_content_encryption("A128GCM");
+        assert!(!context.is_acceptable_algorithm("RSA1_5"));
+        assert!(!context.is_acceptable_content_encryption("A128CBC-HS256"));
+        let (dst_payload, _) = context.deserialize_compact(&jwe, &decrypter)?;
+        assert_eq!(src_payload.to_vec(), dst_payload);
+
+        let called = Cell::new(false);
+        let selector = |_header: &JweHeader| -> Result<Option<&dyn JweDecrypter>, JoseError> {
+            called.set(true);
+            Ok(Some(&decrypter))
+        };
+
+        context.remove_acceptable_content_encryption("A128GCM");
+        context.add_acceptable_content_encryption("A256GCM");
+        assert!(context
+            .deserialize_compact_with_selector(&jwe, selector)
+            .is_err());
+        assert!(!called.get());
+
+        context.add_acceptable_content_encryption("A128GCM");
+        context.remove_acceptable_algorithm("A128KW");
+        context.add_acceptable_algorithm("A256KW");
+        assert!(context
+            .deserialize_compact_with_selector(&jwe, selector)
+            .is_err());
+        assert!(!called.get());
+
+        let mut src_header = JweHeaderSet::new();
+        src_header.set_content_encryption("A128GCM", true);
+        let json =
+            jwe::serialize_flattened_json(src_payload, Some(&src_header), None, None, &encrypter)?;
+        assert!(context.deserialize_json(&json, &decrypter).is_err());
+        context.add_acceptable_algorithm("A128KW");
+        let (dst_payload, _) = context.deserialize_json(&json, &decrypter)?;
+        assert_eq!(src_payload.to_vec(), dst_payload);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_selector_algorithm_mismatch() -> Result<()> {
+        let key = util::random_bytes(16);
+        let src_payload = b"test payload!";
+        let encrypter = A128KW.encrypter_from_bytes(&key)?;
+        let decrypter = Dir.decrypter_from_bytes(&key)?;
+
+        let mut src_header = JweHeader::new();
+        src_header.set_content_encryption("A128GCM");
+        let jwe = jwe::serialize_compact(src_payload, &src_header, &encrypter)?;
+
+        let result = jwe::deserialize_compact_with_selector(&jwe, |_header| Ok(Some(&decrypter)));
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_compact_with_jwk_set() -> Result<()> {
+        let src_payload = b"test payload!";
+
+        let mut sig_key = Jwk::generate_oct_key(128)?;
+        sig_key.set_key_id("1");
+        sig_key.set_key_use("sig");
+        let mut wrong_key = Jwk::generate_oct_key(128)?;
+        wrong_key.set_key_id("1");
+        let mut oct_key = Jwk::generate_oct_key(128)?;
+        oct_key.set_key_id("1");
+        let mut ec_key = Jwk::generate_ec_key(jwk::P_256)?;
+        ec_key.set_key_id("2");
+
+        let jwk_set = JwkSet::from_bytes(format!(
+            "{{\"keys\":[{},{},{},{}]}}",
+            sig_key, wrong_key, oct_key, ec_key
+        ))?;
+
+        let mut src_header = JweHeader::new();
+        src_header.set_content_encryption("A128GCM");
+
+        let encrypter = A128KW.encrypter_from_jwk(&oct_key)?;
+        let jwe = jwe::serialize_compact(src_payload, &src_header, &encrypter)?;
+        let (dst_payload, dst_header) = jwe::deserialize_compact_with_jwk_set(&jwe, &jwk_set)?;
+        assert_eq!(dst_header.key_id(), Some("1"));
+        assert_eq!(src_payload.to_vec(), dst_payload);
+
+        let mut oct_key_without_kid = oct_key.clone();
+        oct_key_without_kid.set_parameter("kid", None)?;
+        let encrypter = A128KW.encrypter_from_jwk(&oct_key_without_kid)?;
+        let jwe = jwe::serialize_compact(src_payload, &src_header, &encrypter)?;
+        let (dst_payload, dst_header) = jwe::deserialize_compact_with_jwk_set(&jwe, &jwk_set)?;
+        assert_eq!(dst_header.key_id(), None);
+        assert_eq!(src_payload.to_vec(), dst_payload);
+
+        let encrypter = ECDH_ES_A128KW.encrypter_from_jwk(&ec_key.to_public_key()?)?;
+        let jwe = jwe::serialize_compact(src_payload, &src_header, &encrypter)?;
+        let (dst_payload, _) = jwe::deserialize_compact_with_jwk_set(&jwe, &jwk_set)?;
+        assert_eq!(src_payload.to_vec(), dst_payload);
+
+        let mut other_key = Jwk::generate_oct_key(128)?;
+        other_key.set_key_id("1");
+        let encrypter = A128KW.encrypter_from_jwk(&other_key)?;
+        let jwe = jwe::serialize_compact(src_payload, &src_header, &encrypter)?;
+        match jwe::deserialize_compact_with_jwk_set(&jwe, &jwk_set) {
+            Err(JoseError::InvalidJweFormat(err)) => {
+                assert!(err.to_string().contains("by 2 key(s)"))
+            }
+            _ => unreachable!(),
+        }
+
+        let mut wrap_only_key = oct_key.clone();
+        wrap_only_key.set_key_operations(&[jwk::KeyOperation::WrapKey]);
+        let jwk_set = JwkSet::from_bytes(format!("{{\"keys\":[{}]}}", wrap_only_key))?;
+        let encrypter = A128KW.encrypter_from_jwk(&oct_key)?;
+        let jwe = jwe::serialize_compact(src_payload, &src_header, &encrypter)?;
+        match jwe::deserialize_compact_with_jwk_set(&jwe, &jwk_set) {
+            Err(JoseError::InvalidJweFormat(err)) => assert!(err.to_string().contains("not found")),
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_compact_segment_validation() -> Result<()> {
+        let key = util::random_bytes(16);
+        let encrypter = A128KW.encrypter_from_bytes(&key)?;
+        let decrypter = A128KW.decrypter_from_bytes(&key)?;
+
+        let mut src_header = JweHeader::new();
+        src_header.set_content_encryption("A128GCM");
+        let jwe = jwe::serialize_compact(b"test payload!", &src_header, &encrypter)?;
+        let segments: Vec<&str> = jwe.split('.').collect();
+
+        let error_message = |input: &str, context: &JweContext| -> String {
+            match context.deserialize_compact(input, &decrypter) {
+                Err(JoseError::InvalidJweFormat(err)) => err.to_string(),
+                Err(err) => panic!("unexpected error: {}", err),
+                Ok(_) => panic!("unexpected success: {}", input),
+            }
+        };
+
+        let context = JweContext::new();
+        assert!(error_message(&segments[0..4].join("."), &context).contains("five parts"));
+        assert!(error_message(&format!("{}.AA", jwe), &context).contains("five parts"));
+
+        // Truncated inputs must be rejected without panicking.
+        for i in 0..jwe.len() {
+            assert!(context.deserialize_compact(&jwe[0..i], &decrypter).is_err());
+        }
+
+        // Padded segments must be rejected.
+        for (i, name) in &[(2, "iv segment"), (4, "tag segment")] {
+            let mut padded = segments.clone();
+            let segment = format!("{}AAAA", padded[*i]);
+            padded[*i] = &segment;
+            assert!(error_message(&padded.join("."), &context).contains(name));
+        }
+        let mut padded = segments.clone();
+        padded[3] = "!!!!";
+        assert!(error_message(&padded.join("."), &context).contains("ciphertext segment"));
+
+        for (i, name) in &[
+            (0, "header segment"),
+            (1, "encrypted key segment"),
+            (2, "iv segment"),
+            (3, "ciphertext segment"),
+            (4, "tag segment"),
+        ] {
+            let mut context = JweContext::new();
+            match i {
+                0 => context.set_max_header_len(8),
+                1 => context.set_max_encrypted_key_len(8),
+                2 => context.set_max_iv_len(8),
+                3 => context.set_max_ciphertext_len(8),
+                _ => context.set_max_tag_len(8),
+            }
+            assert!(error_message(&jwe, &context).contains(name));
+            assert!(error_message(&jwe, &context).contains("8 bytes or less"));
+        }
+
+        // The largest caps must not overflow.
+        let mut context = JweContext::new();
+        context.set_max_header_len(usize::MAX);
+        context.set_max_encrypted_key_len(usize::MAX);
+        context.set_max_iv_len(usize::MAX);
+        context.set_max_ciphertext_len(usize::MAX);
+        context.set_max_tag_len(usize::MAX);
+        let (payload, _) = context.deserialize_compact(&jwe, &decrypter)?;
+        assert_eq!(payload, b"test payload!");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_content_encryption_iv_generator() -> Result<()> {
+        let key = util::random_bytes(16);
+        let encrypter = A128KW.encrypter_from_bytes(&key)?;
+        let decrypter = A128KW.decrypter_from_bytes(&key)?;
+
+        let mut enc = AesgcmJweContentEncryption::new(AesgcmJweEncryption::A256gcm);
+        enc.set_iv_generator(IvGenerator::Counter {
+            prefix: [0xFF, 0, 0, 1],
+        });
+        enc.set_iv_reuse_check(true);
+
+        let mut context = JweContext::new();
+        context.add_content_encryption(Box::new(enc));
+
+        let mut src_header = JweHeader::new();
+        src_header.set_content_encryption("A256GCM");
+        for i in 0..3u8 {
+            let jwe = context.serialize_compact(b"test payload!", &src_header, &encrypter)?;
+            let iv = jwe.split('.').nth(2).unwrap();
+            let iv = base64::decode_config(iv, base64::URL_SAFE_NO_PAD)?;
+            assert_eq!(iv, vec![0xFF, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, i]);
+
+            let (dst_payload, _) = context.deserialize_compact(&jwe, &decrypter)?;
+            assert_eq!(b"test payload!".to_vec(), dst_payload);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_unsupported_key_len() -> Result<()> {
+        #[derive(Debug, Clone)]
+        struct OddKeyLenEncryption;
+
+        impl JweContentEncryption for OddKeyLenEncryption {
+            fn name(&self) -> &str {
+                "X-ODD"
+            }
+
+            fn key_len(&self) -> usize {
+                20
+            }
+
+            fn iv_len(&self) -> usize {
+                12
+            }
+
+            fn encrypt(
+                &self,
+                _key: &[u8],
+                _iv: Option<&[u8]>,
+                _message: &[u8],
+                _aad: &[u8],
+            ) -> Result<(Vec<u8>, Option<Vec<u8>>), JoseError> {
+                unreachable!();
+            }
+
+            fn decrypt(
+                &self,
+                _key: &[u8],
+                _iv: Option<&[u8]>,
+                _encrypted_message: &[u8],
+                _aad: &[u8],
+                _tag: Option<&[u8]>,
+            ) -> Result<Vec<u8>, JoseError> {
+                unreachable!();
+            }
+
+            fn box_clone(&self) -> Box<dyn JweContentEncryption> {
+                Box::new(self.clone())
+            }
+        }
+
+        let mut context = JweContext::new();
+        context.add_content_encryption(Box::new(OddKeyLenEncryption));
+
+        let mut src_header = JweHeader::new();
+        src_header.set_content_encryption("X-ODD");
+
+        let encrypter = A128KW.encrypter_from_bytes(&util::random_bytes(16))?;
+        match context.serialize_compact(b"test payload!", &src_header, &encrypter) {
+            Err(JoseError::InvalidJweFormat(err)) => assert_eq!(
+                err.to_string(),
+                "A128KW cannot wrap a 20-byte CEK required by X-ODD."
+            ),
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let encrypter = PBES2_HS256_A128KW.encrypter_from_bytes(&util::random_bytes(16))?;
+        let mut src_header = JweHeaderSet::new();
+        src_header.set_content_encryption("X-ODD", true);
+        match context.serialize_general_json(
+            b"test payload!",
+            Some(&src_header),
+            &[(None, &*encrypter)],
+            None,
+        ) {
+            Err(JoseError::InvalidJweFormat(err)) => assert_eq!(
+                err.to_string(),
+                "PBES2-HS256+A128KW cannot wrap a 20-byte CEK required by X-ODD."
+            ),
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // A 64-byte CEK of A256CBC-HS512 can be wrapped by A128KW.
+        let key = util::random_bytes(16);
+        let encrypter = A128KW.encrypter_from_bytes(&key)?;
+        let decrypter = A128KW.decrypter_from_bytes(&key)?;
+        let mut src_header = JweHeader::new();
+        src_header.set_content_encryption("A256CBC-HS512");
+        let jwe = jwe::serialize_compact(b"test payload!", &src_header, &encrypter)?;
+        let encrypted_key = jwe.split('.').nth(1).unwrap();
+        let encrypted_key = base64::decode_config(encrypted_key, base64::URL_SAFE_NO_PAD)?;
+        assert_eq!(encrypted_key.len(), 64 + 8);
+        let (dst_payload, _) = jwe::deserialize_compact(&jwe, &decrypter)?;
+        assert_eq!(b"test payload!".to_vec(), dst_payload);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_register_content_encryption() -> Result<()> {
+        #[derive(Debug, Clone)]
+        struct CustomEncryption(&'static str);
+
+        impl JweContentEncryption for CustomEncryption {
+            fn name(&self) -> &str {
+                self.0
+            }
+
+            fn key_len(&self) -> usize {
+                AesgcmJweEncryption::A256gcm.key_len()
+            }
+
+            fn iv_len(&self) -> usize {
+                AesgcmJweEncryption::A256gcm.iv_len()
+            }
+
+            fn tag_len(&self) -> Option<usize> {
+                AesgcmJweEncryption::A256gcm.tag_len()
+            }
+
+            fn encrypt(
+                &self,
+                key: &[u8],
+                iv: Option<&[u8]>,
+                message: &[u8],
+                aad: &[u8],
+            ) -> Result<(Vec<u8>, Option<Vec<u8>>), JoseError> {
+                AesgcmJweEncryption::A256gcm.encrypt(key, iv, message, aad)
+            }
+
+            fn decrypt(
+                &self,
+                key: &[u8],
+                iv: Option<&[u8]>,
+                encrypted_message: &[u8],
+                aad: &[u8],
+                tag: Option<&[u8]>,
+            ) -> Result<Vec<u8>, JoseError> {
+                AesgcmJweEncryption::A256gcm.decrypt(key, iv, encrypted_message, aad, tag)
+            }
+
+            fn box_clone(&self) -> Box<dyn JweContentEncryption> {
+                Box::new(self.clone())
+            }
+        }
+
+        let key = util::random_bytes(16);
+        let src_payload = b"test payload!";
+        let encrypter = A128KW.encrypter_from_bytes(&key)?;
+        let decrypter = A128KW.decrypter_from_bytes(&key)?;
+
+        let mut src_header = JweHeader::new();
+        src_header.set_content_encryption("X-CUSTOM");
+        assert!(jwe::serialize_compact(src_payload, &src_header, &encrypter).is_err());
+
+        let mut context = JweContext::new();
+        context.register_content_encryption(Box::new(CustomEncryption("X-CUSTOM")))?;
+        assert!(matches!(
+            context.register_content_encryption(Box::new(CustomEncryption("A256GCM"))),
+            Err(JoseError::UnsupportedEncryptionAlgorithm(_))
+        ));
+        assert_eq!(
+            context
+                .get_content_encryption("A256GCM")
+                .map(|val| val.name()),
+            Some("A256GCM")
+        );
+
+        let jwe = context.serialize_compact(src_payload, &src_header, &encrypter)?;
+        let (dst_payload, dst_header) = context.deserialize_compact(&jwe, &decrypter)?;
+        assert_eq!(src_payload.to_vec(), dst_payload);
+        assert_eq!(dst_header.content_encryption(), Some("X-CUSTOM"));
+        assert!(jwe::deserialize_compact(&jwe, &decrypter).is_err());
+
+        let mut src_header = JweHeaderSet::new();
+        src_header.set_content_encryption("X-CUSTOM", true);
+        let json = context.serialize_flattened_json(
+            src_payload,
+            Some(&src_header),
+            None,
+            None,
+            &encrypter,
+        )?;
+        let (dst_payload, _) = context.deserialize_json(&json, &decrypter)?;
+        assert_eq!(src_payload.to_vec(), dst_payload);
+        assert!(jwe::deserialize_json(&json, &decrypter).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_max_decompressed_len() -> Result<()> {
+        let alg = A128KW;
+        let key = util::random_bytes(16);
+        let encrypter = alg.encrypter_from_bytes(&key)?;
+        let decrypter = alg.decrypter_from_bytes(&key)?;
+        let src_payload = vec![0; 1024 * 1024];
+
+        let mut src_header = JweHeader::new();
+        src_header.set_content_encryption("A128GCM");
+        src_header.set_compression("DEF");
+        let jwe = jwe::serialize_compact(&src_payload, &src_header, &encrypter)?;
+        assert!(jwe.len() < 16 * 1024);
+
+        let mut context = JweContext::new();
+        assert_eq!(context.max_decompressed_len(), 16 * 1024 * 1024);
+        let (dst_payload, _) = context.deserialize_compact(&jwe, &decrypter)?;
+        assert_eq!(src_payload, dst_payload);
+
+        context.set_max_decompressed_len(64 * 1024);
+        assert!(matches!(
+            context.deserialize_compact(&jwe, &decrypter),
+            Err(JoseError::DecompressedSizeExceeded(_))
+        ));
+
+        let mut src_header = JweHeaderSet::new();
+        src_header.set_content_encryption("A128GCM", true);
+        src_header.set_compression("DEF");
+        let json = context.serialize_flattened_json(
+            &src_payload,
+            Some(&src_header),
+            None,
+            None,
+            &encrypter,
+        )?;
+        assert!(matches!(
+            context.deserialize_json(&json, &decrypter),
+            Err(JoseError::DecompressedSizeExceeded(_))
+        ));
+
+        Ok(())
+    }
+
     fn load_file(path: &str) -> Result<Vec<u8>> {
         let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         pb.push("data");
@@ -1,6 +1,13 @@
+#[cfg(feature = "jwe-aeskw")]
 pub mod aesgcmkw;
+#[cfg(feature = "jwe-aeskw")]
 pub mod aeskw;
 pub mod direct;
+#[cfg(feature = "jwe-ecdh")]
 pub mod ecdh_es;
+#[cfg(any(feature = "jwe-aeskw", feature = "jwe-ecdh", feature = "jwe-pbes2"))]
+pub(crate) mod key_wrap;
+#[cfg(feature = "jwe-pbes2")]
 pub mod pbes2_hmac_aeskw;
+#[cfg(feature = "jwe-rsa")]
 pub mod rsaes;
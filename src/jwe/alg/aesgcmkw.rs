@@ -6,7 +6,7 @@ use anyhow::bail;
 use openssl::symm::{self, Cipher};
 
 use crate::jwe::{JweAlgorithm, JweContentEncryption, JweDecrypter, JweEncrypter, JweHeader};
-use crate::jwk::Jwk;
+use crate::jwk::{Jwk, KeyOperation};
 use crate::util;
 use crate::{JoseError, JoseHeader, Value};
 
@@ -56,7 +56,7 @@ impl AesgcmkwJweAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be enc: {}", val),
             }
-            if !jwk.is_for_key_operation("encrypt") {
+            if !jwk.is_for_key_operation(&KeyOperation::Encrypt) {
                 bail!("A parameter key_ops must contains encrypt.");
             }
             match jwk.algorithm() {
@@ -120,7 +120,7 @@ impl AesgcmkwJweAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be enc: {}", val),
             }
-            if !jwk.is_for_key_operation("decrypt") {
+            if !jwk.is_for_key_operation(&KeyOperation::Decrypt) {
                 bail!("A parameter key_ops must contains decrypt.");
             }
             match jwk.algorithm() {
@@ -266,6 +266,12 @@ impl JweEncrypter for AesgcmkwJweEncrypter {
     }
 }
 
+impl Drop for AesgcmkwJweEncrypter {
+    fn drop(&mut self) {
+        util::zeroize(&mut self.private_key);
+    }
+}
+
 impl Deref for AesgcmkwJweEncrypter {
     type Target = dyn JweEncrypter;
 
@@ -347,6 +353,12 @@ impl JweDecrypter for AesgcmkwJweDecrypter {
     }
 }
 
+impl Drop for AesgcmkwJweDecrypter {
+    fn drop(&mut self) {
+        util::zeroize(&mut self.private_key);
+    }
+}
+
 impl Deref for AesgcmkwJweDecrypter {
     type Target = dyn JweDecrypter;
 
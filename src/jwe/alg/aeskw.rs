@@ -5,8 +5,10 @@ use std::ops::Deref;
 use anyhow::bail;
 use openssl::aes::{self, AesKey};
 
+use crate::jwe::alg::key_wrap;
 use crate::jwe::{JweAlgorithm, JweContentEncryption, JweDecrypter, JweEncrypter, JweHeader};
-use crate::jwk::Jwk;
+use crate::jwk::{Jwk, KeyOperation};
+use crate::util::{self, Zeroizing};
 use crate::{JoseError, Value};
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
@@ -55,7 +57,7 @@ impl AeskwJweAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be enc: {}", val),
             }
-            if !jwk.is_for_key_operation("wrapKey") {
+            if !jwk.is_for_key_operation(&KeyOperation::WrapKey) {
                 bail!("A parameter key_ops must contains wrapKey.");
             }
             match jwk.algorithm() {
@@ -119,7 +121,7 @@ impl AeskwJweAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be enc: {}", val),
             }
-            if !jwk.is_for_key_operation("unwrapKey") {
+            if !jwk.is_for_key_operation(&KeyOperation::UnwrapKey) {
                 bail!("A parameter key_ops must contains unwrapKey.");
             }
             match jwk.algorithm() {
@@ -215,6 +217,10 @@ impl JweEncrypter for AeskwJweEncrypter {
         }
     }
 
+    fn is_supported_key_len(&self, key_len: usize) -> bool {
+        key_wrap::is_wrappable_key_len(key_len)
+    }
+
     fn compute_content_encryption_key(
         &self,
         _cencryption: &dyn JweContentEncryption,
@@ -256,6 +262,12 @@ impl JweEncrypter for AeskwJweEncrypter {
     }
 }
 
+impl Drop for AeskwJweEncrypter {
+    fn drop(&mut self) {
+        util::zeroize(&mut self.private_key);
+    }
+}
+
 impl Deref for AeskwJweEncrypter {
     type Target = dyn JweEncrypter;
 
@@ -296,7 +308,7 @@ impl JweDecrypter for AeskwJweDecrypter {
     fn decrypt(
         &self,
         encrypted_key: Option<&[u8]>,
-        _cencryption: &dyn JweContentEncryption,
+        cencryption: &dyn JweContentEncryption,
         _header: &JweHeader,
     ) -> Result<Cow<[u8]>, JoseError> {
         (|| -> anyhow::Result<Cow<[u8]>> {
@@ -304,23 +316,24 @@ impl JweDecrypter for AeskwJweDecrypter {
                 Some(val) => val,
                 None => bail!("A encrypted_key is required."),
             };
+            key_wrap::check_wrapped_key_len(encrypted_key, cencryption)?;
 
             let aes = match AesKey::new_decrypt(&self.private_key) {
                 Ok(val) => val,
                 Err(_) => bail!("Failed to set decrypt key."),
             };
 
-            let mut key = vec![0; encrypted_key.len() - 8];
+            let mut key = Zeroizing::new(vec![0; cencryption.key_len()]);
             match aes::unwrap_key(&aes, None, &mut key, encrypted_key) {
                 Ok(val) => {
-                    if val < key.len() {
-                        key.truncate(val);
+                    if val != key.len() {
+                        bail!("The unwrapped key must be {} bytes: {}", key.len(), val);
                     }
                 }
                 Err(_) => bail!("Failed to unwrap key."),
-            };
+            }
 
-            Ok(Cow::Owned(key))
+            Ok(Cow::Owned(key.to_vec()))
         })()
         .map_err(|err| JoseError::InvalidJweFormat(err))
     }
@@ -330,6 +343,12 @@ impl JweDecrypter for AeskwJweDecrypter {
     }
 }
 
+impl Drop for AeskwJweDecrypter {
+    fn drop(&mut self) {
+        util::zeroize(&mut self.private_key);
+    }
+}
+
 impl Deref for AeskwJweDecrypter {
     type Target = dyn JweDecrypter;
 
@@ -346,9 +365,11 @@ mod tests {
 
     use super::AeskwJweAlgorithm;
     use crate::jwe::enc::aescbc_hmac::AescbcHmacJweEncryption;
+    use crate::jwe::enc::aesgcm::AesgcmJweEncryption;
     use crate::jwe::JweHeader;
     use crate::jwk::Jwk;
     use crate::util;
+    use crate::JoseError;
 
     #[test]
     fn encrypt_and_decrypt_aes() -> Result<()> {
@@ -385,4 +406,110 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn decrypt_aes_with_invalid_encrypted_key_len() -> Result<()> {
+        let enc = AescbcHmacJweEncryption::A128cbcHs256;
+        let alg = AeskwJweAlgorithm::A128kw;
+        let key = util::random_bytes(alg.key_len());
+        let src_key = util::random_bytes(enc.key_len());
+
+        let encrypter = alg.encrypter_from_bytes(&key)?;
+        let decrypter = alg.decrypter_from_bytes(&key)?;
+
+        let mut header = JweHeader::new();
+        header.set_content_encryption(enc.name());
+        let mut out_header = header.clone();
+        let encrypted_key = encrypter
+            .encrypt(&src_key, &header, &mut out_header)?
+            .unwrap();
+        assert_eq!(encrypted_key.len(), enc.key_len() + 8);
+
+        let mut extended = encrypted_key.clone();
+        extended.extend_from_slice(&[0; 8]);
+        for invalid in &[
+            &encrypted_key[0..0],
+            &encrypted_key[0..7],
+            &encrypted_key[0..16],
+            &encrypted_key[0..(encrypted_key.len() - 8)],
+            &encrypted_key[0..(encrypted_key.len() - 1)],
+            &extended,
+        ] {
+            let result = decrypter.decrypt(Some(invalid), &enc, &out_header);
+            assert!(matches!(result, Err(JoseError::InvalidJweFormat(_))));
+        }
+
+        let dst_key = decrypter.decrypt(Some(&encrypted_key), &enc, &out_header)?;
+        assert_eq!(&src_key as &[u8], &dst_key as &[u8]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn wrap_and_unwrap_rfc3394_vectors() -> Result<()> {
+        let header = JweHeader::new();
+        let kek = from_hex("000102030405060708090A0B0C0D0E0F101112131415161718191A1B1C1D1E1F");
+        let key = from_hex("00112233445566778899AABBCCDDEEFF000102030405060708090A0B0C0D0E0F");
+
+        // RFC 3394 section 4.1 - 4.6
+        for (alg, enc, expected) in &[
+            (
+                AeskwJweAlgorithm::A128kw,
+                AesgcmJweEncryption::A128gcm,
+                "1FA68B0A8112B447AEF34BD8FB5A7B829D3E862371D2CFE5",
+            ),
+            (
+                AeskwJweAlgorithm::A192kw,
+                AesgcmJweEncryption::A128gcm,
+                "96778B25AE6CA435F92B5B97C050AED2468AB8A17AD84E5D",
+            ),
+            (
+                AeskwJweAlgorithm::A256kw,
+                AesgcmJweEncryption::A128gcm,
+                "64E8C3F9CE0F5BA263E9777905818A2A93C8191E7D6E8AE7",
+            ),
+            (
+                AeskwJweAlgorithm::A192kw,
+                AesgcmJweEncryption::A192gcm,
+                "031D33264E15D33268F24EC260743EDCE1C6C7DDEE725A936BA814915C6762D2",
+            ),
+            (
+                AeskwJweAlgorithm::A256kw,
+                AesgcmJweEncryption::A192gcm,
+                "A8F9BC1612C68B3FF6E6F4FBE30E71E4769C8B80A32CB8958CD5D17D6B254DA1",
+            ),
+            (
+                AeskwJweAlgorithm::A256kw,
+                AesgcmJweEncryption::A256gcm,
+                "28C9F404C4B810F4CBCCB35CFB87F8263F5786E2D80ED326CBC7F0E71A99F43BFB988B9B7A02DD21",
+            ),
+        ] {
+            let kek = &kek[0..alg.key_len()];
+            let key = &key[0..enc.key_len()];
+            let expected = from_hex(expected);
+
+            let encrypter = alg.encrypter_from_bytes(kek)?;
+            let mut out_header = header.clone();
+            let encrypted_key = encrypter.encrypt(key, &header, &mut out_header)?;
+            assert_eq!(encrypted_key, Some(expected.clone()));
+
+            let decrypter = alg.decrypter_from_bytes(kek)?;
+            let dst_key = decrypter.decrypt(Some(&expected), enc, &header)?;
+            assert_eq!(key, &dst_key as &[u8]);
+
+            // The integrity check of the unwrapped IV must fail.
+            let mut tampered = expected.clone();
+            tampered[0] ^= 1;
+            assert!(decrypter.decrypt(Some(&tampered), enc, &header).is_err());
+        }
+
+        Ok(())
+    }
+
+    fn from_hex(input: &str) -> Vec<u8> {
+        (0..input.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&input[i..(i + 2)], 16).unwrap())
+            .collect()
+    }
 }
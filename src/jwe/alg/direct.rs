@@ -5,7 +5,8 @@ use std::ops::Deref;
 use anyhow::bail;
 
 use crate::jwe::{JweAlgorithm, JweContentEncryption, JweDecrypter, JweEncrypter, JweHeader};
-use crate::jwk::Jwk;
+use crate::jwk::{Jwk, KeyOperation};
+use crate::util;
 use crate::{JoseError, Value};
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
@@ -39,7 +40,7 @@ impl DirectJweAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be enc: {}", val),
             }
-            if !jwk.is_for_key_operation("encrypt") {
+            if !jwk.is_for_key_operation(&KeyOperation::Encrypt) {
                 bail!("A parameter key_ops must contains encrypt.");
             }
             match jwk.algorithm() {
@@ -88,7 +89,7 @@ impl DirectJweAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be enc: {}", val),
             }
-            if !jwk.is_for_key_operation("decrypt") {
+            if !jwk.is_for_key_operation(&KeyOperation::Decrypt) {
                 bail!("A parameter key_ops must contains decrypt.");
             }
             match jwk.algorithm() {
@@ -115,6 +116,24 @@ impl DirectJweAlgorithm {
     }
 }
 
+impl DirectJweAlgorithm {
+    fn check_key_len(
+        &self,
+        cencryption: &dyn JweContentEncryption,
+        key: &[u8],
+    ) -> anyhow::Result<()> {
+        if key.len() != cencryption.key_len() {
+            bail!(
+                "The key length for {} must be {} bytes but the key is {} bytes.",
+                cencryption.name(),
+                cencryption.key_len(),
+                key.len()
+            );
+        }
+        Ok(())
+    }
+}
+
 impl JweAlgorithm for DirectJweAlgorithm {
     fn name(&self) -> &str {
         match self {
@@ -177,15 +196,8 @@ impl JweEncrypter for DirectJweEncrypter {
         _header: &mut JweHeader,
     ) -> Result<Option<Cow<[u8]>>, JoseError> {
         (|| -> anyhow::Result<Option<Cow<[u8]>>> {
-            let actual_len = self.cencryption_key.len();
-            if cencryption.key_len() != actual_len {
-                bail!(
-                    "The key size is expected to be {}: {}",
-                    cencryption.key_len(),
-                    actual_len
-                );
-            }
-
+            self.algorithm
+                .check_key_len(cencryption, &self.cencryption_key)?;
             Ok(Some(Cow::Borrowed(&self.cencryption_key)))
         })()
         .map_err(|err| JoseError::InvalidKeyFormat(err))
@@ -205,6 +217,12 @@ impl JweEncrypter for DirectJweEncrypter {
     }
 }
 
+impl Drop for DirectJweEncrypter {
+    fn drop(&mut self) {
+        util::zeroize(&mut self.cencryption_key);
+    }
+}
+
 impl Deref for DirectJweEncrypter {
     type Target = dyn JweEncrypter;
 
@@ -245,7 +263,7 @@ impl JweDecrypter for DirectJweDecrypter {
     fn decrypt(
         &self,
         encrypted_key: Option<&[u8]>,
-        _cencryption: &dyn JweContentEncryption,
+        cencryption: &dyn JweContentEncryption,
         _header: &JweHeader,
     ) -> Result<Cow<[u8]>, JoseError> {
         (|| -> anyhow::Result<Cow<[u8]>> {
@@ -253,6 +271,8 @@ impl JweDecrypter for DirectJweDecrypter {
                 bail!("The encrypted_key must not exist.");
             }
 
+            self.algorithm
+                .check_key_len(cencryption, &self.cencryption_key)?;
             Ok(Cow::Borrowed(&self.cencryption_key))
         })()
         .map_err(|err| JoseError::InvalidJweFormat(err))
@@ -263,6 +283,12 @@ impl JweDecrypter for DirectJweDecrypter {
     }
 }
 
+impl Drop for DirectJweDecrypter {
+    fn drop(&mut self) {
+        util::zeroize(&mut self.cencryption_key);
+    }
+}
+
 impl Deref for DirectJweDecrypter {
     type Target = dyn JweDecrypter;
 
@@ -278,8 +304,10 @@ mod tests {
 
     use super::DirectJweAlgorithm;
     use crate::jwe::enc::aescbc_hmac::AescbcHmacJweEncryption;
+    use crate::jwe::enc::aesgcm::AesgcmJweEncryption;
     use crate::jwe::JweHeader;
     use crate::jwk::Jwk;
+    use crate::util;
 
     #[test]
     fn encrypt_and_decrypt_direct() -> Result<()> {
@@ -314,4 +342,40 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn direct_key_len_mismatch() -> Result<()> {
+        let enc = AesgcmJweEncryption::A256gcm;
+        let alg = DirectJweAlgorithm::Dir;
+        let key = util::random_bytes(16);
+
+        let header = JweHeader::new();
+        let mut out_header = header.clone();
+
+        let encrypter = alg.encrypter_from_bytes(&key)?;
+        let err = encrypter
+            .compute_content_encryption_key(&enc, &header, &mut out_header)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("A256GCM must be 32 bytes but the key is 16 bytes"));
+
+        let decrypter = alg.decrypter_from_bytes(&key)?;
+        let err = decrypter.decrypt(None, &enc, &header).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("A256GCM must be 32 bytes but the key is 16 bytes"));
+
+        let decrypter = alg.decrypter_from_bytes(util::random_bytes(32))?;
+        assert!(decrypter.decrypt(None, &enc, &header).is_ok());
+        assert!(decrypter.decrypt(Some(&key), &enc, &header).is_err());
+
+        let mut jwk = Jwk::new("oct");
+        jwk.set_key_use("sig");
+        jwk.set_parameter("k", Some(json!("MDEyMzQ1Njc4OUFCQ0RFRg")))?;
+        assert!(alg.encrypter_from_jwk(&jwk).is_err());
+        assert!(alg.decrypter_from_jwk(&jwk).is_err());
+
+        Ok(())
+    }
 }
@@ -8,18 +8,16 @@ use openssl::derive::Deriver;
 use openssl::hash::{Hasher, MessageDigest};
 use openssl::pkey::{PKey, Private, Public};
 
+use crate::jwe::alg::key_wrap;
 use crate::jwe::{JweAlgorithm, JweContentEncryption, JweDecrypter, JweEncrypter, JweHeader};
 use crate::jwk::alg::{
     ec::{EcCurve, EcKeyPair},
     ecx::{EcxCurve, EcxKeyPair},
 };
-use crate::jwk::Jwk;
+use crate::jwk::{Jwk, JwkCurve, KeyOperation};
 use crate::util;
 use crate::util::der::{DerReader, DerType};
-use crate::util::oid::{
-    OID_ID_EC_PUBLIC_KEY, OID_PRIME256V1, OID_SECP256K1, OID_SECP384R1, OID_SECP521R1, OID_X25519,
-    OID_X448,
-};
+use crate::util::oid::{OID_ID_EC_PUBLIC_KEY, OID_X25519, OID_X448};
 use crate::{JoseError, JoseHeader, Map, Value};
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
@@ -197,7 +195,7 @@ impl EcdhEsJweAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be enc: {}", val),
             }
-            if !jwk.is_for_key_operation("deriveKey") {
+            if !jwk.is_for_key_operation(&KeyOperation::DeriveKey) {
                 bail!("A parameter key_ops must contains deriveKey.");
             }
             match jwk.algorithm() {
@@ -205,16 +203,9 @@ impl EcdhEsJweAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter alg must be {} but {}", self.name(), val),
             }
-            let (public_key, key_type) = match jwk.parameter("crv") {
-                Some(Value::String(val)) => match key_type {
-                    "EC" => {
-                        let curve = match val.as_str() {
-                            "P-256" => EcCurve::P256,
-                            "P-384" => EcCurve::P384,
-                            "P-521" => EcCurve::P521,
-                            "secp256k1" => EcCurve::Secp256k1,
-                            val => bail!("EC key doesn't support the curve algorithm: {}", val),
-                        };
+            let (public_key, key_type) = match jwk.curve() {
+                Some(curve) => match (key_type, curve) {
+                    ("EC", JwkCurve::Ec(curve)) => {
                         let x = match jwk.parameter("x") {
                             Some(Value::String(val)) => {
                                 base64::decode_config(val, base64::URL_SAFE_NO_PAD)?
@@ -230,21 +221,15 @@ impl EcdhEsJweAlgorithm {
                             None => bail!("A parameter y is required."),
                         };
 
-                        let mut vec = Vec::with_capacity(1 + x.len() + y.len());
-                        vec.push(0x04);
-                        vec.extend_from_slice(&x);
-                        vec.extend_from_slice(&y);
-
-                        let pkcs8 = EcKeyPair::to_pkcs8(&vec, true, curve);
-                        let public_key = PKey::public_key_from_der(&pkcs8)?;
+                        let ec_key = EcKeyPair::public_key_from_components(curve, &x, &y)?;
+                        let public_key = PKey::from_ec_key(ec_key)?;
 
                         (public_key, EcdhEsKeyType::Ec(curve))
                     }
-                    "OKP" => {
-                        let curve = match val.as_str() {
-                            "X25519" => EcxCurve::X25519,
-                            "X448" => EcxCurve::X448,
-                            val => bail!("OKP key doesn't support the curve algorithm: {}", val),
+                    ("OKP", JwkCurve::Okp(val)) => {
+                        let curve = match val.ecx_curve() {
+                            Some(curve) => curve,
+                            None => bail!("OKP key doesn't support the curve algorithm: {}", val),
                         };
                         let x = match jwk.parameter("x") {
                             Some(Value::String(val)) => {
@@ -259,9 +244,12 @@ impl EcdhEsJweAlgorithm {
 
                         (public_key, EcdhEsKeyType::Ecx(curve))
                     }
-                    _ => unreachable!(),
+                    (key_type, curve) => bail!(
+                        "{} key doesn't support the curve algorithm: {}",
+                        key_type,
+                        curve
+                    ),
                 },
-                Some(_) => bail!("A parameter crv must be a string."),
                 None => bail!("A parameter crv is required."),
             };
             let key_id = jwk.key_id().map(|val| val.to_string());
@@ -363,7 +351,7 @@ impl EcdhEsJweAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be enc: {}", val),
             }
-            if !jwk.is_for_key_operation("deriveKey") {
+            if !jwk.is_for_key_operation(&KeyOperation::DeriveKey) {
                 bail!("A parameter key_ops must contains deriveKey.");
             }
             match jwk.algorithm() {
@@ -371,49 +359,30 @@ impl EcdhEsJweAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter alg must be {} but {}", self.name(), val),
             }
-            let (private_key, key_type) = match jwk.parameter("crv") {
-                Some(Value::String(val)) => match key_type {
-                    "EC" => {
-                        let curve = match val.as_str() {
-                            "P-256" => EcCurve::P256,
-                            "P-384" => EcCurve::P384,
-                            "P-521" => EcCurve::P521,
-                            "secp256k1" => EcCurve::Secp256k1,
-                            val => bail!("EC key doesn't support the curve algorithm: {}", val),
-                        };
-                        match jwk.curve() {
-                            Some(val) if val == curve.name() => {}
-                            Some(val) => {
-                                bail!("A parameter crv must be {} but {}", self.name(), val)
-                            }
-                            None => bail!("A parameter crv is required."),
-                        }
+            let (private_key, key_type) = match jwk.curve() {
+                Some(curve) => match (key_type, curve) {
+                    ("EC", JwkCurve::Ec(curve)) => {
                         let key_pair = EcKeyPair::from_jwk(&jwk)?;
                         let private_key = key_pair.into_private_key();
 
                         (private_key, EcdhEsKeyType::Ec(curve))
                     }
-                    "OKP" => {
-                        let curve = match val.as_str() {
-                            "X25519" => EcxCurve::X25519,
-                            "X448" => EcxCurve::X448,
-                            val => bail!("OKP key doesn't support the curve algorithm: {}", val),
+                    ("OKP", JwkCurve::Okp(val)) => {
+                        let curve = match val.ecx_curve() {
+                            Some(curve) => curve,
+                            None => bail!("OKP key doesn't support the curve algorithm: {}", val),
                         };
-                        match jwk.curve() {
-                            Some(val) if val == curve.name() => {}
-                            Some(val) => {
-                                bail!("A parameter crv must be {} but {}", self.name(), val)
-                            }
-                            None => bail!("A parameter crv is required."),
-                        }
                         let key_pair = EcxKeyPair::from_jwk(&jwk)?;
                         let private_key = key_pair.into_private_key();
 
                         (private_key, EcdhEsKeyType::Ecx(curve))
                     }
-                    _ => unreachable!(),
+                    (key_type, curve) => bail!(
+                        "{} key doesn't support the curve algorithm: {}",
+                        key_type,
+                        curve
+                    ),
                 },
-                Some(_) => bail!("A parameter crv must be a string."),
                 None => bail!("A parameter crv is required."),
             };
             let key_id = jwk.key_id().map(|val| val.to_string());
@@ -486,10 +455,7 @@ impl EcdhEsJweAlgorithm {
 
                 key_type = match reader.next() {
                     Ok(Some(DerType::ObjectIdentifier)) => match reader.to_object_identifier() {
-                        Ok(val) if val == *OID_PRIME256V1 => EcdhEsKeyType::Ec(EcCurve::P256),
-                        Ok(val) if val == *OID_SECP384R1 => EcdhEsKeyType::Ec(EcCurve::P384),
-                        Ok(val) if val == *OID_SECP521R1 => EcdhEsKeyType::Ec(EcCurve::P521),
-                        Ok(val) if val == *OID_SECP256K1 => EcdhEsKeyType::Ec(EcCurve::Secp256k1),
+                        Ok(val) => EcdhEsKeyType::Ec(EcCurve::from_oid(&val)?),
                         _ => return None,
                     },
                     _ => return None,
@@ -500,6 +466,20 @@ impl EcdhEsJweAlgorithm {
         Some(key_type)
     }
 
+    fn derive(private_key: &PKey<Private>, public_key: &PKey<Public>) -> anyhow::Result<Vec<u8>> {
+        let mut deriver = Deriver::new(private_key)?;
+        deriver.set_peer(public_key)?;
+        let derived_key = deriver.derive_to_vec()?;
+
+        // RFC 8037 Section 3.2: An all-zero shared secret means that the peer key
+        // is a low order point.
+        if derived_key.iter().all(|val| *val == 0) {
+            bail!("The shared secret must not be all zero.");
+        }
+
+        Ok(derived_key)
+    }
+
     fn concat_kdf(
         &self,
         alg: &str,
@@ -704,9 +684,7 @@ impl EcdhEsJweEncrypter {
 
             header.set_claim("epk", Some(Value::Object(map)))?;
 
-            let mut deriver = Deriver::new(&private_key)?;
-            deriver.set_peer(&self.public_key)?;
-            let derived_key = deriver.derive_to_vec()?;
+            let derived_key = EcdhEsJweAlgorithm::derive(&private_key, &self.public_key)?;
 
             let shared_key = self.algorithm.concat_kdf(
                 alg,
@@ -737,6 +715,13 @@ impl JweEncrypter for EcdhEsJweEncrypter {
         }
     }
 
+    fn is_supported_key_len(&self, key_len: usize) -> bool {
+        match self.algorithm {
+            EcdhEsJweAlgorithm::EcdhEs => true,
+            _ => key_wrap::is_wrappable_key_len(key_len),
+        }
+    }
+
     fn compute_content_encryption_key(
         &self,
         cencryption: &dyn JweContentEncryption,
@@ -909,9 +894,9 @@ impl JweDecrypter for EcdhEsJweDecrypter {
                                     base64::decode_config(val, base64::URL_SAFE_NO_PAD)?
                                 }
                                 Some(_) => {
-                                    bail!("The x parameter in epk header claim must be a string.")
+                                    bail!("The y parameter in epk header claim must be a string.")
                                 }
-                                None => bail!("The x parameter in epk header claim is required."),
+                                None => bail!("The y parameter in epk header claim is required."),
                             };
 
                             let mut vec = Vec::with_capacity(1 + x.len() + y.len());
@@ -919,8 +904,9 @@ impl JweDecrypter for EcdhEsJweDecrypter {
                             vec.extend_from_slice(&x);
                             vec.extend_from_slice(&y);
 
-                            let pkcs8 = EcKeyPair::to_pkcs8(&vec, true, *curve);
-                            PKey::public_key_from_der(&pkcs8)?
+                            // Reject a point that is not on the curve (invalid curve attack).
+                            let ec_key = EcKeyPair::public_key_from_sec1(&vec, *curve)?;
+                            PKey::from_ec_key(ec_key)?
                         }
                         EcdhEsKeyType::Ecx(curve) => {
                             let x = match map.get("x") {
@@ -942,9 +928,7 @@ impl JweDecrypter for EcdhEsJweDecrypter {
                 None => bail!("This algorithm must have epk header claim."),
             };
 
-            let mut deriver = Deriver::new(&self.private_key)?;
-            deriver.set_peer(&public_key)?;
-            let derived_key = deriver.derive_to_vec()?;
+            let derived_key = EcdhEsJweAlgorithm::derive(&self.private_key, &public_key)?;
 
             // concat KDF
             if let EcdhEsJweAlgorithm::EcdhEs = self.algorithm {
@@ -974,16 +958,17 @@ impl JweDecrypter for EcdhEsJweDecrypter {
                     Some(val) => val,
                     None => unreachable!(),
                 };
+                key_wrap::check_wrapped_key_len(encrypted_key, cencryption)?;
 
-                let mut key = vec![0; encrypted_key.len() - 8];
-                match aes::unwrap_key(&aes, None, &mut key, &encrypted_key) {
-                    Ok(len) => {
-                        if len < key.len() {
-                            key.truncate(len);
+                let mut key = vec![0; cencryption.key_len()];
+                match aes::unwrap_key(&aes, None, &mut key, encrypted_key) {
+                    Ok(val) => {
+                        if val != key.len() {
+                            bail!("The unwrapped key must be {} bytes: {}", key.len(), val);
                         }
                     }
                     Err(_) => bail!("Failed to unwrap key."),
-                };
+                }
 
                 Ok(Cow::Owned(key))
             }
@@ -1007,6 +992,7 @@ impl Deref for EcdhEsJweDecrypter {
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
+    use serde_json::json;
     use std::borrow::Cow;
     use std::fs;
     use std::path::PathBuf;
@@ -1018,6 +1004,7 @@ mod tests {
     use crate::jwk::alg::{ec::EcCurve, ecx::EcxCurve};
     use crate::jwk::Jwk;
     use crate::util;
+    use crate::JoseError;
 
     #[test]
     fn encrypt_and_decrypt_ecdh_es_with_pkcs8_der() -> Result<()> {
@@ -1037,22 +1024,14 @@ mod tests {
                 EcdhEsKeyType::Ecx(EcxCurve::X25519),
                 EcdhEsKeyType::Ecx(EcxCurve::X448),
             ] {
-                let private_key = load_file(match key {
-                    EcdhEsKeyType::Ec(EcCurve::P256) => "der/EC_P-256_pkcs8_private.der",
-                    EcdhEsKeyType::Ec(EcCurve::P384) => "der/EC_P-384_pkcs8_private.der",
-                    EcdhEsKeyType::Ec(EcCurve::P521) => "der/EC_P-521_pkcs8_private.der",
-                    EcdhEsKeyType::Ec(EcCurve::Secp256k1) => "der/EC_secp256k1_pkcs8_private.der",
-                    EcdhEsKeyType::Ecx(EcxCurve::X25519) => "der/X25519_pkcs8_private.der",
-                    EcdhEsKeyType::Ecx(EcxCurve::X448) => "der/X448_pkcs8_private.der",
+                let private_key = load_file(&match key {
+                    EcdhEsKeyType::Ec(curve) => format!("der/EC_{}_pkcs8_private.der", curve),
+                    EcdhEsKeyType::Ecx(curve) => format!("der/{}_pkcs8_private.der", curve),
                 })?;
 
-                let public_key = load_file(match key {
-                    EcdhEsKeyType::Ec(EcCurve::P256) => "der/EC_P-256_spki_public.der",
-                    EcdhEsKeyType::Ec(EcCurve::P384) => "der/EC_P-384_spki_public.der",
-                    EcdhEsKeyType::Ec(EcCurve::P521) => "der/EC_P-521_spki_public.der",
-                    EcdhEsKeyType::Ec(EcCurve::Secp256k1) => "der/EC_secp256k1_spki_public.der",
-                    EcdhEsKeyType::Ecx(EcxCurve::X25519) => "der/X25519_spki_public.der",
-                    EcdhEsKeyType::Ecx(EcxCurve::X448) => "der/X448_spki_public.der",
+                let public_key = load_file(&match key {
+                    EcdhEsKeyType::Ec(curve) => format!("der/EC_{}_spki_public.der", curve),
+                    EcdhEsKeyType::Ecx(curve) => format!("der/{}_spki_public.der", curve),
                 })?;
 
                 let mut header = JweHeader::new();
@@ -1099,22 +1078,14 @@ mod tests {
                 EcdhEsKeyType::Ecx(EcxCurve::X25519),
                 EcdhEsKeyType::Ecx(EcxCurve::X448),
             ] {
-                let private_key = load_file(match key {
-                    EcdhEsKeyType::Ec(EcCurve::P256) => "pem/EC_P-256_private.pem",
-                    EcdhEsKeyType::Ec(EcCurve::P384) => "pem/EC_P-384_private.pem",
-                    EcdhEsKeyType::Ec(EcCurve::P521) => "pem/EC_P-521_private.pem",
-                    EcdhEsKeyType::Ec(EcCurve::Secp256k1) => "pem/EC_secp256k1_private.pem",
-                    EcdhEsKeyType::Ecx(EcxCurve::X25519) => "pem/X25519_private.pem",
-                    EcdhEsKeyType::Ecx(EcxCurve::X448) => "pem/X448_private.pem",
+                let private_key = load_file(&match key {
+                    EcdhEsKeyType::Ec(curve) => format!("pem/EC_{}_private.pem", curve),
+                    EcdhEsKeyType::Ecx(curve) => format!("pem/{}_private.pem", curve),
                 })?;
 
-                let public_key = load_file(match key {
-                    EcdhEsKeyType::Ec(EcCurve::P256) => "pem/EC_P-256_public.pem",
-                    EcdhEsKeyType::Ec(EcCurve::P384) => "pem/EC_P-384_public.pem",
-                    EcdhEsKeyType::Ec(EcCurve::P521) => "pem/EC_P-521_public.pem",
-                    EcdhEsKeyType::Ec(EcCurve::Secp256k1) => "pem/EC_secp256k1_public.pem",
-                    EcdhEsKeyType::Ecx(EcxCurve::X25519) => "pem/X25519_public.pem",
-                    EcdhEsKeyType::Ecx(EcxCurve::X448) => "pem/X448_public.pem",
+                let public_key = load_file(&match key {
+                    EcdhEsKeyType::Ec(curve) => format!("pem/EC_{}_public.pem", curve),
+                    EcdhEsKeyType::Ecx(curve) => format!("pem/{}_public.pem", curve),
                 })?;
 
                 let mut header = JweHeader::new();
@@ -1161,24 +1132,14 @@ mod tests {
                 EcdhEsKeyType::Ecx(EcxCurve::X25519),
                 EcdhEsKeyType::Ecx(EcxCurve::X448),
             ] {
-                let private_key = load_file(match key {
-                    EcdhEsKeyType::Ec(EcCurve::P256) => "pem/EC_P-256_traditional_private.pem",
-                    EcdhEsKeyType::Ec(EcCurve::P384) => "pem/EC_P-384_traditional_private.pem",
-                    EcdhEsKeyType::Ec(EcCurve::P521) => "pem/EC_P-521_traditional_private.pem",
-                    EcdhEsKeyType::Ec(EcCurve::Secp256k1) => {
-                        "pem/EC_secp256k1_traditional_private.pem"
-                    }
-                    EcdhEsKeyType::Ecx(EcxCurve::X25519) => "pem/X25519_traditional_private.pem",
-                    EcdhEsKeyType::Ecx(EcxCurve::X448) => "pem/X448_traditional_private.pem",
+                let private_key = load_file(&match key {
+                    EcdhEsKeyType::Ec(curve) => format!("pem/EC_{}_traditional_private.pem", curve),
+                    EcdhEsKeyType::Ecx(curve) => format!("pem/{}_traditional_private.pem", curve),
                 })?;
 
-                let public_key = load_file(match key {
-                    EcdhEsKeyType::Ec(EcCurve::P256) => "pem/EC_P-256_public.pem",
-                    EcdhEsKeyType::Ec(EcCurve::P384) => "pem/EC_P-384_public.pem",
-                    EcdhEsKeyType::Ec(EcCurve::P521) => "pem/EC_P-521_public.pem",
-                    EcdhEsKeyType::Ec(EcCurve::Secp256k1) => "pem/EC_secp256k1_public.pem",
-                    EcdhEsKeyType::Ecx(EcxCurve::X25519) => "pem/X25519_public.pem",
-                    EcdhEsKeyType::Ecx(EcxCurve::X448) => "pem/X448_public.pem",
+                let public_key = load_file(&match key {
+                    EcdhEsKeyType::Ec(curve) => format!("pem/EC_{}_public.pem", curve),
+                    EcdhEsKeyType::Ecx(curve) => format!("pem/{}_public.pem", curve),
                 })?;
 
                 let mut header = JweHeader::new();
@@ -1225,22 +1186,14 @@ mod tests {
                 EcdhEsKeyType::Ecx(EcxCurve::X25519),
                 EcdhEsKeyType::Ecx(EcxCurve::X448),
             ] {
-                let private_key = load_file(match key {
-                    EcdhEsKeyType::Ec(EcCurve::P256) => "jwk/EC_P-256_private.jwk",
-                    EcdhEsKeyType::Ec(EcCurve::P384) => "jwk/EC_P-384_private.jwk",
-                    EcdhEsKeyType::Ec(EcCurve::P521) => "jwk/EC_P-521_private.jwk",
-                    EcdhEsKeyType::Ec(EcCurve::Secp256k1) => "jwk/EC_secp256k1_private.jwk",
-                    EcdhEsKeyType::Ecx(EcxCurve::X25519) => "jwk/OKP_X25519_private.jwk",
-                    EcdhEsKeyType::Ecx(EcxCurve::X448) => "jwk/OKP_X448_private.jwk",
+                let private_key = load_file(&match key {
+                    EcdhEsKeyType::Ec(curve) => format!("jwk/EC_{}_private.jwk", curve),
+                    EcdhEsKeyType::Ecx(curve) => format!("jwk/OKP_{}_private.jwk", curve),
                 })?;
 
-                let public_key = load_file(match key {
-                    EcdhEsKeyType::Ec(EcCurve::P256) => "jwk/EC_P-256_public.jwk",
-                    EcdhEsKeyType::Ec(EcCurve::P384) => "jwk/EC_P-384_public.jwk",
-                    EcdhEsKeyType::Ec(EcCurve::P521) => "jwk/EC_P-521_public.jwk",
-                    EcdhEsKeyType::Ec(EcCurve::Secp256k1) => "jwk/EC_secp256k1_public.jwk",
-                    EcdhEsKeyType::Ecx(EcxCurve::X25519) => "jwk/OKP_X25519_public.jwk",
-                    EcdhEsKeyType::Ecx(EcxCurve::X448) => "jwk/OKP_X448_public.jwk",
+                let public_key = load_file(&match key {
+                    EcdhEsKeyType::Ec(curve) => format!("jwk/EC_{}_public.jwk", curve),
+                    EcdhEsKeyType::Ecx(curve) => format!("jwk/OKP_{}_public.jwk", curve),
                 })?;
 
                 let mut header = JweHeader::new();
@@ -1271,6 +1224,192 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn decrypt_ecdh_es_rfc7518_appendix_c() -> Result<()> {
+        let enc = AesgcmJweEncryption::A128gcm;
+        let alg = EcdhEsJweAlgorithm::EcdhEs;
+
+        let private_key = Jwk::from_bytes(
+            br#"{"kty":"EC","crv":"P-256",
+                "x":"weNJy2HscCSM6AEDTDg04biOvhFhyyWvOHQfeF_PxMQ",
+                "y":"e8lnCO-AlStT-NJVX-crhB7QRYhiix03illJOVAOyck",
+                "d":"VEmDZpDXXK8p8N0Cndsxs924q6nS1RXFASRl6BfUqdw"}"#,
+        )?;
+        let decrypter = alg.decrypter_from_jwk(&private_key)?;
+
+        let mut header = JweHeader::new();
+        header.set_algorithm(alg.name());
+        header.set_content_encryption(enc.name());
+        header.set_claim(
+            "epk",
+            Some(json!({
+                "kty": "EC",
+                "crv": "P-256",
+                "x": "gI0GAILBdu7T53akrFmMyGcsF3n5dO7MmwNBHKW5SV0",
+                "y": "SLW_xSffzlPWrHEVI30DHM_4egVwt3NQqeUD7nMFpps",
+            })),
+        )?;
+        header.set_claim("apu", Some(json!("QWxpY2U")))?;
+        header.set_claim("apv", Some(json!("Qm9i")))?;
+
+        let key = decrypter.decrypt(None, &enc, &header)?;
+        assert_eq!(
+            base64::encode_config(&key, base64::URL_SAFE_NO_PAD),
+            "VqqN6vgjbSBcIijNcacQGg"
+        );
+
+        // A point that is not on the curve must be rejected.
+        header.set_claim(
+            "epk",
+            Some(json!({
+                "kty": "EC",
+                "crv": "P-256",
+                "x": "gI0GAILBdu7T53akrFmMyGcsF3n5dO7MmwNBHKW5SV0",
+                "y": "SLW_xSffzlPWrHEVI30DHM_4egVwt3NQqeUD7nMFppo",
+            })),
+        )?;
+        assert!(decrypter.decrypt(None, &enc, &header).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn encrypt_and_decrypt_ecdh_es_with_generated_ecx() -> Result<()> {
+        let enc = AesgcmJweEncryption::A256gcm;
+
+        for alg in &[EcdhEsJweAlgorithm::EcdhEs, EcdhEsJweAlgorithm::EcdhEsA256kw] {
+            for curve in &[EcxCurve::X25519, EcxCurve::X448] {
+                let key_pair = alg.generate_ecx_key_pair(*curve)?;
+
+                let mut header = JweHeader::new();
+                header.set_content_encryption(enc.name());
+
+                let encrypter = alg.encrypter_from_jwk(&key_pair.to_jwk_public_key())?;
+                let mut out_header = header.clone();
+                let src_key = match encrypter.compute_content_encryption_key(
+                    &enc,
+                    &header,
+                    &mut out_header,
+                )? {
+                    Some(val) => val,
+                    None => Cow::Owned(util::random_bytes(enc.key_len())),
+                };
+                let encrypted_key = encrypter.encrypt(&src_key, &header, &mut out_header)?;
+
+                let epk = out_header.claim("epk").unwrap();
+                assert_eq!(epk["kty"], "OKP");
+                assert_eq!(epk["crv"], curve.name());
+
+                let decrypter = alg.decrypter_from_jwk(&key_pair.to_jwk_private_key())?;
+                let dst_key = decrypter.decrypt(encrypted_key.as_deref(), &enc, &out_header)?;
+                assert_eq!(&src_key, &dst_key);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn encrypt_and_decrypt_ecdh_es_with_agreement_info() -> Result<()> {
+        let enc = AesgcmJweEncryption::A128gcm;
+        let alg = EcdhEsJweAlgorithm::EcdhEs;
+        let key_pair = alg.generate_ec_key_pair(EcCurve::P256)?;
+        let encrypter = alg.encrypter_from_jwk(&key_pair.to_jwk_public_key())?;
+        let decrypter = alg.decrypter_from_jwk(&key_pair.to_jwk_private_key())?;
+
+        let mut header = JweHeader::new();
+        header.set_content_encryption(enc.name());
+        header.set_agreement_partyuinfo(b"client-id");
+        header.set_agreement_partyvinfo(b"server-id");
+
+        let mut out_header = header.clone();
+        let src_key = encrypter
+            .compute_content_encryption_key(&enc, &header, &mut out_header)?
+            .unwrap();
+        assert_eq!(
+            out_header.agreement_partyuinfo(),
+            Some(b"client-id".to_vec())
+        );
+        let dst_key = decrypter.decrypt(None, &enc, &out_header)?;
+        assert_eq!(&src_key, &dst_key);
+
+        // The apu and apv header claims are bound into the key derivation.
+        let mut tampered = out_header.clone();
+        tampered.set_agreement_partyvinfo(b"other-id");
+        let dst_key = decrypter.decrypt(None, &enc, &tampered)?;
+        assert_ne!(&src_key, &dst_key);
+
+        // Absent apu and apv are the same as empty ones.
+        let mut header = JweHeader::new();
+        header.set_content_encryption(enc.name());
+        let mut out_header = header.clone();
+        let src_key = encrypter
+            .compute_content_encryption_key(&enc, &header, &mut out_header)?
+            .unwrap();
+        out_header.set_agreement_partyuinfo(b"");
+        out_header.set_agreement_partyvinfo(b"");
+        let dst_key = decrypter.decrypt(None, &enc, &out_header)?;
+        assert_eq!(&src_key, &dst_key);
+
+        let mut header = JweHeader::new();
+        assert!(header.set_claim("apu", Some(json!("not base64!"))).is_err());
+        assert!(header.set_claim("apv", Some(json!(1))).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn encrypter_from_invalid_jwk_point() -> Result<()> {
+        let alg = EcdhEsJweAlgorithm::EcdhEs;
+
+        let jwk = Jwk::from_bytes(&load_file("jwk/EC_P-256_public_short_x.jwk")?)?;
+        match alg.encrypter_from_jwk(&jwk) {
+            Err(JoseError::InvalidKeyFormat(err)) => {
+                assert!(err.to_string().contains("coordinate length"))
+            }
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+
+        let jwk = Jwk::from_bytes(&load_file("jwk/EC_P-256_public_off_curve.jwk")?)?;
+        match alg.encrypter_from_jwk(&jwk) {
+            Err(JoseError::InvalidKeyFormat(err)) => {
+                assert!(err.to_string().contains("not on the curve"))
+            }
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn decrypt_ecdh_es_with_low_order_ecx_point() -> Result<()> {
+        let enc = AesgcmJweEncryption::A256gcm;
+        let alg = EcdhEsJweAlgorithm::EcdhEs;
+
+        for curve in &[EcxCurve::X25519, EcxCurve::X448] {
+            let key_pair = alg.generate_ecx_key_pair(*curve)?;
+            let decrypter = alg.decrypter_from_jwk(&key_pair.to_jwk_private_key())?;
+
+            let key_len = match curve {
+                EcxCurve::X25519 => 32,
+                EcxCurve::X448 => 56,
+            };
+            let x = base64::encode_config(vec![0; key_len], base64::URL_SAFE_NO_PAD);
+
+            let mut header = JweHeader::new();
+            header.set_algorithm(alg.name());
+            header.set_content_encryption(enc.name());
+            header.set_claim(
+                "epk",
+                Some(json!({ "kty": "OKP", "crv": curve.name(), "x": x })),
+            )?;
+
+            assert!(decrypter.decrypt(None, &enc, &header).is_err());
+        }
+
+        Ok(())
+    }
+
     fn load_file(path: &str) -> Result<Vec<u8>> {
         let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         pb.push("data");
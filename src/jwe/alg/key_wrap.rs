@@ -0,0 +1,34 @@
+use anyhow::bail;
+
+use crate::jwe::JweContentEncryption;
+
+/// Test whether a content encryption key of the length can be wrapped by AES Key Wrap.
+#[allow(clippy::manual_is_multiple_of)]
+pub(crate) fn is_wrappable_key_len(key_len: usize) -> bool {
+    // AES key wrap requires two or more 64-bit blocks.
+    key_len >= 16 && key_len % 8 == 0
+}
+
+/// Check that an encrypted_key has the length of a content encryption key
+/// wrapped by AES Key Wrap (RFC 3394).
+#[allow(clippy::manual_is_multiple_of)]
+pub(crate) fn check_wrapped_key_len(
+    encrypted_key: &[u8],
+    cencryption: &dyn JweContentEncryption,
+) -> anyhow::Result<()> {
+    let expected_len = cencryption.key_len() + 8;
+    if encrypted_key.len() < 24 || encrypted_key.len() % 8 != 0 {
+        bail!(
+            "The encrypted_key must be a multiple of 8 bytes and 24 bytes or more: {}",
+            encrypted_key.len()
+        );
+    } else if encrypted_key.len() != expected_len {
+        bail!(
+            "The encrypted_key must be {} bytes for {}: {}",
+            expected_len,
+            cencryption.name(),
+            encrypted_key.len()
+        );
+    }
+    Ok(())
+}
@@ -3,13 +3,15 @@ use std::convert::TryFrom;
 use std::fmt::Display;
 use std::ops::Deref;
 
-use anyhow::bail;
+use anyhow::{anyhow, bail};
 use openssl::aes::{self, AesKey};
 use openssl::pkcs5;
+use unicode_normalization::UnicodeNormalization;
 
+use crate::jwe::alg::key_wrap;
 use crate::jwe::{JweAlgorithm, JweContentEncryption, JweDecrypter, JweEncrypter, JweHeader};
-use crate::jwk::Jwk;
-use crate::util::{self, HashAlgorithm};
+use crate::jwk::{Jwk, KeyOperation};
+use crate::util::{self, HashAlgorithm, Zeroizing};
 use crate::{JoseError, JoseHeader, Number, Value};
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
@@ -38,7 +40,7 @@ impl Pbes2HmacAeskwJweAlgorithm {
                 algorithm: self.clone(),
                 private_key,
                 salt_len: 8,
-                iter_count: 1000,
+                iter_count: 600_000,
                 key_id: None,
             })
         })()
@@ -56,7 +58,7 @@ impl Pbes2HmacAeskwJweAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be enc: {}", val),
             }
-            if !jwk.is_for_key_operation("deriveKey") {
+            if !jwk.is_for_key_operation(&KeyOperation::DeriveKey) {
                 bail!("A parameter key_ops must contains deriveKey.");
             }
             match jwk.algorithm() {
@@ -80,13 +82,29 @@ impl Pbes2HmacAeskwJweAlgorithm {
                 algorithm: self.clone(),
                 private_key: k,
                 salt_len: 8,
-                iter_count: 1000,
+                iter_count: 600_000,
                 key_id,
             })
         })()
         .map_err(|err| JoseError::InvalidKeyFormat(err))
     }
 
+    /// Return a encrypter from a password.
+    ///
+    /// The password is normalized to Unicode Normalization Form C (NFC) and then encoded
+    /// as UTF-8, so composed and decomposed forms of the same password derive the same key.
+    /// No other mapping (e.g. case folding or SASLprep) is applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - a password
+    pub fn encrypter_from_password(
+        &self,
+        password: &str,
+    ) -> Result<Pbes2HmacAeskwJweEncrypter, JoseError> {
+        self.encrypter_from_bytes(&*Self::normalize_password(password))
+    }
+
     pub fn decrypter_from_bytes(
         &self,
         input: impl AsRef<[u8]>,
@@ -101,6 +119,8 @@ impl Pbes2HmacAeskwJweAlgorithm {
             Ok(Pbes2HmacAeskwJweDecrypter {
                 algorithm: self.clone(),
                 private_key,
+                max_salt_len: 256,
+                max_iter_count: 1_000_000,
                 key_id: None,
             })
         })()
@@ -118,7 +138,7 @@ impl Pbes2HmacAeskwJweAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be enc: {}", val),
             }
-            if !jwk.is_for_key_operation("deriveKey") {
+            if !jwk.is_for_key_operation(&KeyOperation::DeriveKey) {
                 bail!("A parameter key_ops must contains deriveKey.");
             }
             match jwk.algorithm() {
@@ -142,12 +162,32 @@ impl Pbes2HmacAeskwJweAlgorithm {
             Ok(Pbes2HmacAeskwJweDecrypter {
                 algorithm: self.clone(),
                 private_key: k,
+                max_salt_len: 256,
+                max_iter_count: 1_000_000,
                 key_id,
             })
         })()
         .map_err(|err| JoseError::InvalidKeyFormat(err))
     }
 
+    /// Return a decrypter from a password.
+    ///
+    /// The password is normalized in the same way as `encrypter_from_password`.
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - a password
+    pub fn decrypter_from_password(
+        &self,
+        password: &str,
+    ) -> Result<Pbes2HmacAeskwJweDecrypter, JoseError> {
+        self.decrypter_from_bytes(&*Self::normalize_password(password))
+    }
+
+    fn normalize_password(password: &str) -> Zeroizing<Vec<u8>> {
+        Zeroizing::new(password.nfc().collect::<String>().into_bytes())
+    }
+
     fn hash_algorithm(&self) -> HashAlgorithm {
         match self {
             Self::Pbes2Hs256A128kw => HashAlgorithm::Sha256,
@@ -238,6 +278,10 @@ impl JweEncrypter for Pbes2HmacAeskwJweEncrypter {
         }
     }
 
+    fn is_supported_key_len(&self, key_len: usize) -> bool {
+        key_wrap::is_wrappable_key_len(key_len)
+    }
+
     fn compute_content_encryption_key(
         &self,
         _cencryption: &dyn JweContentEncryption,
@@ -289,7 +333,7 @@ impl JweEncrypter for Pbes2HmacAeskwJweEncrypter {
             salt.extend_from_slice(&p2s);
 
             let md = self.algorithm.hash_algorithm().message_digest();
-            let mut derived_key = vec![0; self.algorithm.derived_key_len()];
+            let mut derived_key = Zeroizing::new(vec![0; self.algorithm.derived_key_len()]);
             pkcs5::pbkdf2_hmac(&self.private_key, &salt, p2c, md, &mut derived_key)?;
 
             let aes = match AesKey::new_encrypt(&derived_key) {
@@ -317,6 +361,12 @@ impl JweEncrypter for Pbes2HmacAeskwJweEncrypter {
     }
 }
 
+impl Drop for Pbes2HmacAeskwJweEncrypter {
+    fn drop(&mut self) {
+        util::zeroize(&mut self.private_key);
+    }
+}
+
 impl Deref for Pbes2HmacAeskwJweEncrypter {
     type Target = dyn JweEncrypter;
 
@@ -329,10 +379,46 @@ impl Deref for Pbes2HmacAeskwJweEncrypter {
 pub struct Pbes2HmacAeskwJweDecrypter {
     algorithm: Pbes2HmacAeskwJweAlgorithm,
     private_key: Vec<u8>,
+    max_salt_len: usize,
+    max_iter_count: usize,
     key_id: Option<String>,
 }
 
 impl Pbes2HmacAeskwJweDecrypter {
+    /// Return the maximum length of the decoded p2s header claim. The default is 256.
+    pub fn max_salt_len(&self) -> usize {
+        self.max_salt_len
+    }
+
+    /// Set the maximum length of the decoded p2s header claim.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_salt_len` - the maximum salt length in bytes
+    pub fn set_max_salt_len(&mut self, max_salt_len: usize) {
+        if max_salt_len < 8 {
+            panic!("max_salt_len must be 8 or more: {}", max_salt_len);
+        }
+        self.max_salt_len = max_salt_len;
+    }
+
+    /// Return the maximum value of the p2c header claim. The default is 1,000,000.
+    pub fn max_iter_count(&self) -> usize {
+        self.max_iter_count
+    }
+
+    /// Set the maximum value of the p2c header claim.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_iter_count` - the maximum iteration count
+    pub fn set_max_iter_count(&mut self, max_iter_count: usize) {
+        if max_iter_count < 1000 {
+            panic!("max_iter_count must be 1000 or more: {}", max_iter_count);
+        }
+        self.max_iter_count = max_iter_count;
+    }
+
     pub fn set_key_id(&mut self, value: impl Into<String>) {
         self.key_id = Some(value.into());
     }
@@ -357,7 +443,7 @@ impl JweDecrypter for Pbes2HmacAeskwJweDecrypter {
     fn decrypt(
         &self,
         encrypted_key: Option<&[u8]>,
-        _cencryption: &dyn JweContentEncryption,
+        cencryption: &dyn JweContentEncryption,
         header: &JweHeader,
     ) -> Result<Cow<[u8]>, JoseError> {
         (|| -> anyhow::Result<Cow<[u8]>> {
@@ -365,6 +451,7 @@ impl JweDecrypter for Pbes2HmacAeskwJweDecrypter {
                 Some(val) => val,
                 None => bail!("A encrypted_key value is required."),
             };
+            key_wrap::check_wrapped_key_len(encrypted_key, cencryption)?;
 
             let p2s = match header.claim("p2s") {
                 Some(Value::String(val)) => {
@@ -382,17 +469,32 @@ impl JweDecrypter for Pbes2HmacAeskwJweDecrypter {
                     Some(val) => usize::try_from(val)?,
                     None => bail!("Overflow u64 value: {}", val),
                 },
-                Some(_) => bail!("The p2s header claim must be string."),
+                Some(_) => bail!("The p2c header claim must be a number."),
                 None => bail!("The p2c header claim is required."),
             };
 
+            if p2s.len() > self.max_salt_len {
+                return Err(anyhow!(JoseError::Pbes2LimitExceeded(anyhow!(
+                    "The decoded value of p2s header claim must be {} or less: {}",
+                    self.max_salt_len,
+                    p2s.len()
+                ))));
+            }
+            if p2c > self.max_iter_count {
+                return Err(anyhow!(JoseError::Pbes2LimitExceeded(anyhow!(
+                    "The p2c header claim must be {} or less: {}",
+                    self.max_iter_count,
+                    p2c
+                ))));
+            }
+
             let mut salt = Vec::with_capacity(self.algorithm().name().len() + 1 + p2s.len());
             salt.extend_from_slice(self.algorithm().name().as_bytes());
             salt.push(0);
             salt.extend_from_slice(&p2s);
 
             let md = self.algorithm.hash_algorithm().message_digest();
-            let mut derived_key = vec![0; self.algorithm.derived_key_len()];
+            let mut derived_key = Zeroizing::new(vec![0; self.algorithm.derived_key_len()]);
             pkcs5::pbkdf2_hmac(&self.private_key, &salt, p2c, md, &mut derived_key)?;
 
             let aes = match AesKey::new_decrypt(&derived_key) {
@@ -400,19 +502,22 @@ impl JweDecrypter for Pbes2HmacAeskwJweDecrypter {
                 Err(_) => bail!("Failed to set a decryption key."),
             };
 
-            let mut key = vec![0; encrypted_key.len() - 8];
-            match aes::unwrap_key(&aes, None, &mut key, &encrypted_key) {
+            let mut key = Zeroizing::new(vec![0; cencryption.key_len()]);
+            match aes::unwrap_key(&aes, None, &mut key, encrypted_key) {
                 Ok(val) => {
-                    if val < key.len() {
-                        key.truncate(val);
+                    if val != key.len() {
+                        bail!("The unwrapped key must be {} bytes: {}", key.len(), val);
                     }
                 }
                 Err(_) => bail!("Failed to unwrap a key."),
             }
 
-            Ok(Cow::Owned(key))
+            Ok(Cow::Owned(key.to_vec()))
         })()
-        .map_err(|err| JoseError::InvalidJweFormat(err))
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidJweFormat(err),
+        })
     }
 
     fn box_clone(&self) -> Box<dyn JweDecrypter> {
@@ -420,6 +525,12 @@ impl JweDecrypter for Pbes2HmacAeskwJweDecrypter {
     }
 }
 
+impl Drop for Pbes2HmacAeskwJweDecrypter {
+    fn drop(&mut self) {
+        util::zeroize(&mut self.private_key);
+    }
+}
+
 impl Deref for Pbes2HmacAeskwJweDecrypter {
     type Target = dyn JweDecrypter;
 
@@ -439,6 +550,7 @@ mod tests {
     use crate::jwe::JweHeader;
     use crate::jwk::Jwk;
     use crate::util;
+    use crate::JoseError;
 
     #[test]
     fn encrypt_and_decrypt_pbes2_hmac() -> Result<()> {
@@ -476,4 +588,110 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn decrypt_pbes2_hmac_with_limits() -> Result<()> {
+        let enc = AescbcHmacJweEncryption::A128cbcHs256;
+        let alg = Pbes2HmacAeskwJweAlgorithm::Pbes2Hs256A128kw;
+        let key = util::random_bytes(8);
+        let src_key = util::random_bytes(enc.key_len());
+
+        let mut encrypter = alg.encrypter_from_bytes(&key)?;
+        encrypter.set_salt_len(16);
+        encrypter.set_iter_count(2000);
+
+        let mut decrypter = alg.decrypter_from_bytes(&key)?;
+        assert_eq!(decrypter.max_salt_len(), 256);
+        assert_eq!(decrypter.max_iter_count(), 1_000_000);
+        decrypter.set_max_salt_len(8);
+        decrypter.set_max_iter_count(1000);
+
+        let mut header = JweHeader::new();
+        header.set_content_encryption(enc.name());
+        let mut out_header = header.clone();
+        let encrypted_key = encrypter.encrypt(&src_key, &header, &mut out_header)?;
+
+        let result = decrypter.decrypt(encrypted_key.as_deref(), &enc, &out_header);
+        assert!(matches!(result, Err(JoseError::Pbes2LimitExceeded(_))));
+
+        decrypter.set_max_salt_len(16);
+        let result = decrypter.decrypt(encrypted_key.as_deref(), &enc, &out_header);
+        assert!(matches!(result, Err(JoseError::Pbes2LimitExceeded(_))));
+
+        decrypter.set_max_iter_count(2000);
+        let dst_key = decrypter.decrypt(encrypted_key.as_deref(), &enc, &out_header)?;
+        assert_eq!(&src_key as &[u8], &dst_key as &[u8]);
+
+        // A huge p2c must be rejected before the key derivation starts.
+        out_header.set_claim("p2c", Some(json!(1u64 << 31)))?;
+        let result = decrypter.decrypt(encrypted_key.as_deref(), &enc, &out_header);
+        assert!(matches!(result, Err(JoseError::Pbes2LimitExceeded(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn decrypt_pbes2_hmac_with_invalid_encrypted_key_len() -> Result<()> {
+        let enc = AescbcHmacJweEncryption::A128cbcHs256;
+        let alg = Pbes2HmacAeskwJweAlgorithm::Pbes2Hs256A128kw;
+        let key = util::random_bytes(8);
+        let src_key = util::random_bytes(enc.key_len());
+
+        let mut encrypter = alg.encrypter_from_bytes(&key)?;
+        encrypter.set_iter_count(1000);
+        let decrypter = alg.decrypter_from_bytes(&key)?;
+
+        let mut header = JweHeader::new();
+        header.set_content_encryption(enc.name());
+        let mut out_header = header.clone();
+        let encrypted_key = encrypter
+            .encrypt(&src_key, &header, &mut out_header)?
+            .unwrap();
+
+        let mut extended = encrypted_key.clone();
+        extended.extend_from_slice(&[0; 8]);
+        for invalid in &[
+            &encrypted_key[0..0],
+            &encrypted_key[0..16],
+            &encrypted_key[0..(encrypted_key.len() - 8)],
+            &encrypted_key[0..(encrypted_key.len() - 1)],
+            &extended,
+        ] {
+            let result = decrypter.decrypt(Some(invalid), &enc, &out_header);
+            assert!(matches!(result, Err(JoseError::InvalidJweFormat(_))));
+        }
+
+        let dst_key = decrypter.decrypt(Some(&encrypted_key), &enc, &out_header)?;
+        assert_eq!(&src_key as &[u8], &dst_key as &[u8]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn encrypt_and_decrypt_pbes2_hmac_with_password() -> Result<()> {
+        let enc = AescbcHmacJweEncryption::A128cbcHs256;
+        let alg = Pbes2HmacAeskwJweAlgorithm::Pbes2Hs256A128kw;
+        let composed = "caf\u{e9} pass";
+        let decomposed = "cafe\u{301} pass";
+        assert_ne!(composed, decomposed);
+
+        let mut header = JweHeader::new();
+        header.set_content_encryption(enc.name());
+        let src_key = util::random_bytes(enc.key_len());
+
+        let encrypter = alg.encrypter_from_password(composed)?;
+        let mut out_header = header.clone();
+        let encrypted_key = encrypter.encrypt(&src_key, &header, &mut out_header)?;
+
+        let decrypter = alg.decrypter_from_password(decomposed)?;
+        let dst_key = decrypter.decrypt(encrypted_key.as_deref(), &enc, &out_header)?;
+        assert_eq!(&src_key as &[u8], &dst_key as &[u8]);
+
+        let decrypter = alg.decrypter_from_bytes(decomposed.as_bytes())?;
+        assert!(decrypter
+            .decrypt(encrypted_key.as_deref(), &enc, &out_header)
+            .is_err());
+
+        Ok(())
+    }
 }
@@ -8,7 +8,7 @@ use openssl::pkey::{PKey, Private, Public};
 use openssl::rsa::Padding;
 
 use crate::jwe::{JweAlgorithm, JweContentEncryption, JweDecrypter, JweEncrypter, JweHeader};
-use crate::jwk::{alg::rsa::RsaKeyPair, Jwk};
+use crate::jwk::{alg::rsa::RsaKeyPair, Jwk, KeyOperation};
 use crate::util;
 use crate::util::der::{DerBuilder, DerType};
 use crate::{JoseError, Value};
@@ -16,6 +16,12 @@ use crate::{JoseError, Value};
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum RsaesJweAlgorithm {
     /// RSAES-PKCS1-v1_5
+    ///
+    /// RSA1_5 is vulnerable to padding oracle attacks (Bleichenbacher) and should
+    /// only be used to interoperate with legacy systems. It is only available from
+    /// the `*_unsafe_rsa15` methods. On a padding error, their decrypters continue
+    /// with a random content encryption key as recommended by RFC 7516 Section 11.5,
+    /// so that the failure is only reported by the content decryption.
     #[deprecated(note = "This algorithm is no longer recommended.")]
     Rsa1_5,
     /// RSAES OAEP using default parameters
@@ -97,9 +103,197 @@ impl RsaesJweAlgorithm {
         })
     }
 
+    /// Return an encrypter from a DER encoded public key.
+    ///
+    /// RSA1_5 is rejected. Use `encrypter_from_der_unsafe_rsa15` for it.
+    ///
+    /// # Arguments
+    /// * `input` - A public key that is a DER encoded SubjectPublicKeyInfo or PKCS#1 RSAPublicKey.
     pub fn encrypter_from_der(
         &self,
         input: impl AsRef<[u8]>,
+    ) -> Result<RsaesJweEncrypter, JoseError> {
+        self.check_not_rsa1_5("encrypter_from_der")?;
+        self.encrypter_from_der_unchecked(input.as_ref())
+    }
+
+    /// Return an encrypter from a public key of PEM format.
+    ///
+    /// RSA1_5 is rejected. Use `encrypter_from_pem_unsafe_rsa15` for it.
+    ///
+    /// # Arguments
+    /// * `input` - A public key of common or traditional PEM format.
+    pub fn encrypter_from_pem(
+        &self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<RsaesJweEncrypter, JoseError> {
+        self.check_not_rsa1_5("encrypter_from_pem")?;
+        self.encrypter_from_pem_unchecked(input.as_ref())
+    }
+
+    /// Return an encrypter from a public key that is formatted by a JWK of RSA type.
+    ///
+    /// RSA1_5 is rejected. Use `encrypter_from_jwk_unsafe_rsa15` for it.
+    ///
+    /// # Arguments
+    /// * `jwk` - A public key that is formatted by a JWK of RSA type.
+    pub fn encrypter_from_jwk(&self, jwk: &Jwk) -> Result<RsaesJweEncrypter, JoseError> {
+        self.check_not_rsa1_5("encrypter_from_jwk")?;
+        self.encrypter_from_jwk_unchecked(jwk)
+    }
+
+    /// Return a decrypter from a DER encoded private key.
+    ///
+    /// RSA1_5 is rejected. Use `decrypter_from_der_unsafe_rsa15` for it.
+    ///
+    /// # Arguments
+    /// * `input` - A private key that is a DER encoded PKCS#8 PrivateKeyInfo or PKCS#1 RSAPrivateKey.
+    pub fn decrypter_from_der(
+        &self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<RsaesJweDecrypter, JoseError> {
+        self.check_not_rsa1_5("decrypter_from_der")?;
+        self.decrypter_from_der_unchecked(input.as_ref())
+    }
+
+    /// Return a decrypter from a private key of PEM format.
+    ///
+    /// RSA1_5 is rejected. Use `decrypter_from_pem_unsafe_rsa15` for it.
+    ///
+    /// # Arguments
+    /// * `input` - A private key of common or traditional PEM format.
+    pub fn decrypter_from_pem(
+        &self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<RsaesJweDecrypter, JoseError> {
+        self.check_not_rsa1_5("decrypter_from_pem")?;
+        self.decrypter_from_pem_unchecked(input.as_ref())
+    }
+
+    /// Return a decrypter from a private key that is formatted by a JWK of RSA type.
+    ///
+    /// RSA1_5 is rejected. Use `decrypter_from_jwk_unsafe_rsa15` for it.
+    ///
+    /// # Arguments
+    /// * `jwk` - A private key that is formatted by a JWK of RSA type.
+    pub fn decrypter_from_jwk(&self, jwk: &Jwk) -> Result<RsaesJweDecrypter, JoseError> {
+        self.check_not_rsa1_5("decrypter_from_jwk")?;
+        self.decrypter_from_jwk_unchecked(jwk)
+    }
+
+    /// Return an encrypter of RSA1_5 (RSAES-PKCS1-v1_5).
+    ///
+    /// See `RsaesJweAlgorithm::Rsa1_5` for the risks.
+    ///
+    /// # Arguments
+    /// * `input` - A public key that is a DER encoded SubjectPublicKeyInfo or PKCS#1 RSAPublicKey.
+    pub fn encrypter_from_der_unsafe_rsa15(
+        &self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<RsaesJweEncrypter, JoseError> {
+        self.check_rsa1_5("encrypter_from_der_unsafe_rsa15")?;
+        self.encrypter_from_der_unchecked(input.as_ref())
+    }
+
+    /// Return an encrypter of RSA1_5 (RSAES-PKCS1-v1_5).
+    ///
+    /// See `RsaesJweAlgorithm::Rsa1_5` for the risks.
+    ///
+    /// # Arguments
+    /// * `input` - A public key of common or traditional PEM format.
+    pub fn encrypter_from_pem_unsafe_rsa15(
+        &self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<RsaesJweEncrypter, JoseError> {
+        self.check_rsa1_5("encrypter_from_pem_unsafe_rsa15")?;
+        self.encrypter_from_pem_unchecked(input.as_ref())
+    }
+
+    /// Return an encrypter of RSA1_5 (RSAES-PKCS1-v1_5).
+    ///
+    /// See `RsaesJweAlgorithm::Rsa1_5` for the risks.
+    ///
+    /// # Arguments
+    /// * `jwk` - A public key that is formatted by a JWK of RSA type.
+    pub fn encrypter_from_jwk_unsafe_rsa15(
+        &self,
+        jwk: &Jwk,
+    ) -> Result<RsaesJweEncrypter, JoseError> {
+        self.check_rsa1_5("encrypter_from_jwk_unsafe_rsa15")?;
+        self.encrypter_from_jwk_unchecked(jwk)
+    }
+
+    /// Return a decrypter of RSA1_5 (RSAES-PKCS1-v1_5).
+    ///
+    /// See `RsaesJweAlgorithm::Rsa1_5` for the risks.
+    ///
+    /// # Arguments
+    /// * `input` - A private key that is a DER encoded PKCS#8 PrivateKeyInfo or PKCS#1 RSAPrivateKey.
+    pub fn decrypter_from_der_unsafe_rsa15(
+        &self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<RsaesJweDecrypter, JoseError> {
+        self.check_rsa1_5("decrypter_from_der_unsafe_rsa15")?;
+        self.decrypter_from_der_unchecked(input.as_ref())
+    }
+
+    /// Return a decrypter of RSA1_5 (RSAES-PKCS1-v1_5).
+    ///
+    /// See `RsaesJweAlgorithm::Rsa1_5` for the risks.
+    ///
+    /// # Arguments
+    /// * `input` - A private key of common or traditional PEM format.
+    pub fn decrypter_from_pem_unsafe_rsa15(
+        &self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<RsaesJweDecrypter, JoseError> {
+        self.check_rsa1_5("decrypter_from_pem_unsafe_rsa15")?;
+        self.decrypter_from_pem_unchecked(input.as_ref())
+    }
+
+    /// Return a decrypter of RSA1_5 (RSAES-PKCS1-v1_5).
+    ///
+    /// See `RsaesJweAlgorithm::Rsa1_5` for the risks.
+    ///
+    /// # Arguments
+    /// * `jwk` - A private key that is formatted by a JWK of RSA type.
+    pub fn decrypter_from_jwk_unsafe_rsa15(
+        &self,
+        jwk: &Jwk,
+    ) -> Result<RsaesJweDecrypter, JoseError> {
+        self.check_rsa1_5("decrypter_from_jwk_unsafe_rsa15")?;
+        self.decrypter_from_jwk_unchecked(jwk)
+    }
+
+    #[allow(deprecated)]
+    fn is_rsa1_5(&self) -> bool {
+        *self == Self::Rsa1_5
+    }
+
+    fn check_rsa1_5(&self, method: &str) -> Result<(), JoseError> {
+        if !self.is_rsa1_5() {
+            return Err(JoseError::InvalidKeyFormat(anyhow::anyhow!(
+                "{} is only for RSA1_5: {}",
+                method,
+                self.name()
+            )));
+        }
+        Ok(())
+    }
+
+    fn check_not_rsa1_5(&self, method: &str) -> Result<(), JoseError> {
+        if self.is_rsa1_5() {
+            return Err(JoseError::InvalidKeyFormat(anyhow::anyhow!(
+                "RSA1_5 is disabled because of the padding oracle attacks. Use {}_unsafe_rsa15 to enable it explicitly.",
+                method
+            )));
+        }
+        Ok(())
+    }
+
+    fn encrypter_from_der_unchecked(
+        &self,
+        input: impl AsRef<[u8]>,
     ) -> Result<RsaesJweEncrypter, JoseError> {
         (|| -> anyhow::Result<RsaesJweEncrypter> {
             let spki_der_vec;
@@ -127,7 +321,7 @@ impl RsaesJweAlgorithm {
         .map_err(|err| JoseError::InvalidKeyFormat(err))
     }
 
-    pub fn encrypter_from_pem(
+    fn encrypter_from_pem_unchecked(
         &self,
         input: impl AsRef<[u8]>,
     ) -> Result<RsaesJweEncrypter, JoseError> {
@@ -160,7 +354,7 @@ impl RsaesJweAlgorithm {
         .map_err(|err| JoseError::InvalidKeyFormat(err))
     }
 
-    pub fn encrypter_from_jwk(&self, jwk: &Jwk) -> Result<RsaesJweEncrypter, JoseError> {
+    fn encrypter_from_jwk_unchecked(&self, jwk: &Jwk) -> Result<RsaesJweEncrypter, JoseError> {
         (|| -> anyhow::Result<RsaesJweEncrypter> {
             match jwk.key_type() {
                 val if val == "RSA" => {}
@@ -171,7 +365,7 @@ impl RsaesJweAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be enc: {}", val),
             }
-            if !jwk.is_for_key_operation("encrypt") {
+            if !jwk.is_for_key_operation(&KeyOperation::Encrypt) {
                 bail!("A parameter key_ops must contains encrypt.");
             }
             match jwk.algorithm() {
@@ -218,7 +412,7 @@ impl RsaesJweAlgorithm {
         .map_err(|err| JoseError::InvalidKeyFormat(err))
     }
 
-    pub fn decrypter_from_der(
+    fn decrypter_from_der_unchecked(
         &self,
         input: impl AsRef<[u8]>,
     ) -> Result<RsaesJweDecrypter, JoseError> {
@@ -230,7 +424,7 @@ impl RsaesJweAlgorithm {
         })
     }
 
-    pub fn decrypter_from_pem(
+    fn decrypter_from_pem_unchecked(
         &self,
         input: impl AsRef<[u8]>,
     ) -> Result<RsaesJweDecrypter, JoseError> {
@@ -242,14 +436,14 @@ impl RsaesJweAlgorithm {
         })
     }
 
-    pub fn decrypter_from_jwk(&self, jwk: &Jwk) -> Result<RsaesJweDecrypter, JoseError> {
+    fn decrypter_from_jwk_unchecked(&self, jwk: &Jwk) -> Result<RsaesJweDecrypter, JoseError> {
         (|| -> anyhow::Result<RsaesJweDecrypter> {
             match jwk.key_use() {
                 Some(val) if val == "enc" => {}
                 None => {}
                 Some(val) => bail!("A parameter use must be enc: {}", val),
             }
-            if !jwk.is_for_key_operation("decrypt") {
+            if !jwk.is_for_key_operation(&KeyOperation::Decrypt) {
                 bail!("A parameter key_ops must contains decrypt.");
             }
             match jwk.algorithm() {
@@ -435,7 +629,7 @@ impl JweDecrypter for RsaesJweDecrypter {
     fn decrypt(
         &self,
         encrypted_key: Option<&[u8]>,
-        _cencryption: &dyn JweContentEncryption,
+        cencryption: &dyn JweContentEncryption,
         _header: &JweHeader,
     ) -> Result<Cow<[u8]>, JoseError> {
         (|| -> anyhow::Result<Cow<[u8]>> {
@@ -445,12 +639,27 @@ impl JweDecrypter for RsaesJweDecrypter {
             };
 
             let rsa = self.private_key.rsa()?;
+            if encrypted_key.len() != rsa.size() as usize {
+                bail!(
+                    "The length of encrypted_key must be {}: {}",
+                    rsa.size(),
+                    encrypted_key.len()
+                );
+            }
+
             let key = match self.algorithm {
                 RsaesJweAlgorithm::Rsa1_5 => {
+                    // RFC 7516 Section 11.5: Not to reveal a padding error,
+                    // a random key is used instead of the decrypted one.
+                    let random_key = util::random_bytes(cencryption.key_len());
                     let mut key = vec![0; rsa.size() as usize];
-                    let len = rsa.private_decrypt(&encrypted_key, &mut key, Padding::PKCS1)?;
-                    key.truncate(len);
-                    key
+                    match rsa.private_decrypt(&encrypted_key, &mut key, Padding::PKCS1) {
+                        Ok(len) if len == random_key.len() => {
+                            key.truncate(len);
+                            key
+                        }
+                        _ => random_key,
+                    }
                 }
                 RsaesJweAlgorithm::RsaOaep => {
                     let mut key = vec![0; rsa.size() as usize];
@@ -496,14 +705,16 @@ impl Deref for RsaesJweDecrypter {
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
+    use openssl::rsa::Rsa;
     use std::fs;
     use std::path::PathBuf;
 
     use super::RsaesJweAlgorithm;
     use crate::jwe::enc::aescbc_hmac::AescbcHmacJweEncryption;
-    use crate::jwe::JweHeader;
+    use crate::jwe::{self, JweHeader};
     use crate::jwk::Jwk;
     use crate::util;
+    use crate::JoseError;
 
     #[test]
     #[allow(deprecated)]
@@ -522,16 +733,28 @@ mod tests {
             RsaesJweAlgorithm::Rsa1_5,
             RsaesJweAlgorithm::RsaOaep,
             RsaesJweAlgorithm::RsaOaep256,
+            RsaesJweAlgorithm::RsaOaep384,
+            RsaesJweAlgorithm::RsaOaep512,
         ] {
             let mut header = JweHeader::new();
             header.set_content_encryption(enc.name());
 
-            let encrypter = alg.encrypter_from_jwk(&public_key)?;
+            let (encrypter, decrypter) = if alg == RsaesJweAlgorithm::Rsa1_5 {
+                (
+                    alg.encrypter_from_jwk_unsafe_rsa15(&public_key)?,
+                    alg.decrypter_from_jwk_unsafe_rsa15(&private_key)?,
+                )
+            } else {
+                (
+                    alg.encrypter_from_jwk(&public_key)?,
+                    alg.decrypter_from_jwk(&private_key)?,
+                )
+            };
+
             let mut out_header = header.clone();
             let src_key = util::random_bytes(enc.key_len());
             let encrypted_key = encrypter.encrypt(&src_key, &header, &mut out_header)?;
 
-            let decrypter = alg.decrypter_from_jwk(&private_key)?;
             let dst_key = decrypter.decrypt(encrypted_key.as_deref(), &enc, &out_header)?;
 
             assert_eq!(&src_key as &[u8], &dst_key as &[u8]);
@@ -540,6 +763,162 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn decrypt_rsaes_oaep_rfc7516_a1() -> Result<()> {
+        let private_key = load_file("jwk/RSA_rfc7516_A1_private.jwk")?;
+        let private_key = Jwk::from_bytes(&private_key)?;
+
+        let input = concat!(
+            "eyJhbGciOiJSU0EtT0FFUCIsImVuYyI6IkEyNTZHQ00ifQ.",
+            "OKOawDo13gRp2ojaHV7LFpZcgV7T6DVZKTyKOMTYUmKoTCVJRgckCL9kiMT03JGe",
+            "ipsEdY3mx_etLbbWSrFr05kLzcSr4qKAq7YN7e9jwQRb23nfa6c9d-StnImGyFDb",
+            "Sv04uVuxIp5Zms1gNxKKK2Da14B8S4rzVRltdYwam_lDp5XnZAYpQdb76FdIKLaV",
+            "mqgfwX7XWRxv2322i-vDxRfqNzo_tETKzpVLzfiwQyeyPGLBIO56YJ7eObdv0je8",
+            "1860ppamavo35UgoRdbYaBcoh9QcfylQr66oc6vFWXRcZ_ZT2LawVCWTIy3brGPi",
+            "6UklfCpIMfIjf7iGdXKHzg.",
+            "48V1_ALb6US04U3b.",
+            "5eym8TW_c8SuK0ltJ3rpYIzOeDQz7TALvtu6UG9oMo4vpzs9tX_EFShS8iB7j6ji",
+            "SdiwkIr3ajwQzaBtQD_A.",
+            "XFBoMYUZodetZdvTiFvSkQ"
+        );
+
+        let decrypter = RsaesJweAlgorithm::RsaOaep.decrypter_from_jwk(&private_key)?;
+        let (payload, header) = jwe::deserialize_compact(input, &decrypter)?;
+
+        assert_eq!(header.algorithm(), Some("RSA-OAEP"));
+        assert_eq!(header.content_encryption(), Some("A256GCM"));
+        assert_eq!(
+            payload,
+            b"The true sign of intelligence is not knowledge but imagination.".to_vec()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn rsaes_invalid_keys() -> Result<()> {
+        let private_key = load_file("jwk/RSA_private.jwk")?;
+        let mut private_key = Jwk::from_bytes(&private_key)?;
+        private_key.set_key_use("sig");
+
+        let public_key = load_file("jwk/RSA_public.jwk")?;
+        let mut public_key = Jwk::from_bytes(&public_key)?;
+        public_key.set_key_use("sig");
+
+        let small_key = Rsa::generate(1024)?;
+        let small_private_key = small_key.private_key_to_der()?;
+        let small_public_key = small_key.public_key_to_der()?;
+
+        for alg in &[
+            RsaesJweAlgorithm::Rsa1_5,
+            RsaesJweAlgorithm::RsaOaep,
+            RsaesJweAlgorithm::RsaOaep256,
+        ] {
+            assert!(alg.encrypter_from_jwk(&public_key).is_err());
+            assert!(alg.decrypter_from_jwk(&private_key).is_err());
+            assert!(alg.encrypter_from_der(&small_public_key).is_err());
+            assert!(alg.decrypter_from_der(&small_private_key).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn rsaes_rsa1_5_requires_opt_in() -> Result<()> {
+        let enc = AescbcHmacJweEncryption::A128cbcHs256;
+        let alg = RsaesJweAlgorithm::Rsa1_5;
+
+        let private_key = load_file("pem/RSA_2048bit_private.pem")?;
+        let public_key = load_file("pem/RSA_2048bit_public.pem")?;
+
+        assert!(alg.encrypter_from_pem(&public_key).is_err());
+        assert!(alg.decrypter_from_pem(&private_key).is_err());
+        assert!(RsaesJweAlgorithm::RsaOaep
+            .decrypter_from_pem_unsafe_rsa15(&private_key)
+            .is_err());
+
+        let encrypter = alg.encrypter_from_pem_unsafe_rsa15(&public_key)?;
+        let decrypter = alg.decrypter_from_pem_unsafe_rsa15(&private_key)?;
+
+        let header = JweHeader::new();
+        let mut out_header = header.clone();
+        let src_key = util::random_bytes(enc.key_len());
+        let encrypted_key = encrypter
+            .encrypt(&src_key, &header, &mut out_header)?
+            .unwrap();
+        let dst_key = decrypter.decrypt(Some(&encrypted_key), &enc, &out_header)?;
+        assert_eq!(&src_key as &[u8], &dst_key as &[u8]);
+
+        // A padding error or a wrong key length results in a random key instead of an error.
+        let mut broken_key = encrypted_key.clone();
+        broken_key[0] ^= 0xFF;
+        let dst_key = decrypter.decrypt(Some(&broken_key), &enc, &out_header)?;
+        assert_eq!(dst_key.len(), enc.key_len());
+        assert_ne!(&src_key as &[u8], &dst_key as &[u8]);
+
+        let short_key = encrypter
+            .encrypt(&src_key[1..], &header, &mut out_header)?
+            .unwrap();
+        let dst_key = decrypter.decrypt(Some(&short_key), &enc, &out_header)?;
+        assert_eq!(dst_key.len(), enc.key_len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn rsaes_oaep_invalid_input() -> Result<()> {
+        let enc = AescbcHmacJweEncryption::A128cbcHs256;
+
+        let private_key = load_file("jwk/RSA_private.jwk")?;
+        let private_key = Jwk::from_bytes(&private_key)?;
+        let public_key = load_file("jwk/RSA_public.jwk")?;
+        let public_key = Jwk::from_bytes(&public_key)?;
+
+        for alg in &[
+            RsaesJweAlgorithm::RsaOaep,
+            RsaesJweAlgorithm::RsaOaep256,
+            RsaesJweAlgorithm::RsaOaep384,
+            RsaesJweAlgorithm::RsaOaep512,
+        ] {
+            let header = JweHeader::new();
+            let mut out_header = header.clone();
+            let src_key = util::random_bytes(enc.key_len());
+            let encrypter = alg.encrypter_from_jwk(&public_key)?;
+            let encrypted_key = encrypter
+                .encrypt(&src_key, &header, &mut out_header)?
+                .unwrap();
+
+            let decrypter = alg.decrypter_from_jwk(&private_key)?;
+            for len in &[0, encrypted_key.len() - 1] {
+                let result = decrypter.decrypt(Some(&encrypted_key[..*len]), &enc, &out_header);
+                assert!(matches!(result, Err(JoseError::InvalidJweFormat(_))));
+            }
+            let mut long_key = encrypted_key.clone();
+            long_key.push(0);
+            let result = decrypter.decrypt(Some(&long_key), &enc, &out_header);
+            assert!(matches!(result, Err(JoseError::InvalidJweFormat(_))));
+
+            for other in &[
+                RsaesJweAlgorithm::RsaOaep,
+                RsaesJweAlgorithm::RsaOaep256,
+                RsaesJweAlgorithm::RsaOaep384,
+                RsaesJweAlgorithm::RsaOaep512,
+            ] {
+                let mut private_key = private_key.clone();
+                private_key.set_algorithm(other.name());
+                assert_eq!(alg.decrypter_from_jwk(&private_key).is_ok(), alg == other);
+
+                let mut public_key = public_key.clone();
+                public_key.set_algorithm(other.name());
+                assert_eq!(alg.encrypter_from_jwk(&public_key).is_ok(), alg == other);
+            }
+        }
+
+        Ok(())
+    }
+
     fn load_file(path: &str) -> Result<Vec<u8>> {
         let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         pb.push("data");
@@ -1,12 +1,22 @@
+#[cfg(feature = "enc-cbc")]
 pub mod aescbc_hmac;
+#[cfg(feature = "enc-gcm")]
 pub mod aesgcm;
 
+#[cfg(feature = "enc-cbc")]
 use crate::jwe::enc::aescbc_hmac::AescbcHmacJweEncryption;
+#[cfg(feature = "enc-cbc")]
 pub use AescbcHmacJweEncryption::A128cbcHs256 as A128CBC_HS256;
+#[cfg(feature = "enc-cbc")]
 pub use AescbcHmacJweEncryption::A192cbcHs384 as A192CBC_HS384;
+#[cfg(feature = "enc-cbc")]
 pub use AescbcHmacJweEncryption::A256cbcHs512 as A256CBC_HS512;
 
+#[cfg(feature = "enc-gcm")]
 use crate::jwe::enc::aesgcm::AesgcmJweEncryption;
+#[cfg(feature = "enc-gcm")]
 pub use AesgcmJweEncryption::A128gcm as A128GCM;
+#[cfg(feature = "enc-gcm")]
 pub use AesgcmJweEncryption::A192gcm as A192GCM;
+#[cfg(feature = "enc-gcm")]
 pub use AesgcmJweEncryption::A256gcm as A256GCM;
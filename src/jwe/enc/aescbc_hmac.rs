@@ -29,6 +29,14 @@ impl AescbcHmacJweEncryption {
         }
     }
 
+    fn mac_len(&self) -> usize {
+        match self {
+            Self::A128cbcHs256 => 16,
+            Self::A192cbcHs384 => 24,
+            Self::A256cbcHs512 => 32,
+        }
+    }
+
     fn calcurate_tag(
         &self,
         aad: &[u8],
@@ -36,11 +44,12 @@ impl AescbcHmacJweEncryption {
         ciphertext: &[u8],
         mac_key: &[u8],
     ) -> Result<Vec<u8>, JoseError> {
-        let (message_digest, tlen) = match self {
-            Self::A128cbcHs256 => (MessageDigest::sha256(), 16),
-            Self::A192cbcHs384 => (MessageDigest::sha384(), 24),
-            Self::A256cbcHs512 => (MessageDigest::sha512(), 32),
+        let message_digest = match self {
+            Self::A128cbcHs256 => MessageDigest::sha256(),
+            Self::A192cbcHs384 => MessageDigest::sha384(),
+            Self::A256cbcHs512 => MessageDigest::sha512(),
         };
+        let tlen = self.mac_len();
 
         let pkey = (|| -> anyhow::Result<PKey<Private>> {
             let pkey = PKey::hmac(mac_key)?;
@@ -80,8 +89,8 @@ impl JweContentEncryption for AescbcHmacJweEncryption {
     fn key_len(&self) -> usize {
         match self {
             Self::A128cbcHs256 => 16 + 16,
-            Self::A192cbcHs384 => 16 + 24,
-            Self::A256cbcHs512 => 16 + 32,
+            Self::A192cbcHs384 => 24 + 24,
+            Self::A256cbcHs512 => 32 + 32,
         }
     }
 
@@ -89,6 +98,10 @@ impl JweContentEncryption for AescbcHmacJweEncryption {
         16
     }
 
+    fn tag_len(&self) -> Option<usize> {
+        Some(self.mac_len())
+    }
+
     fn encrypt(
         &self,
         key: &[u8],
@@ -106,8 +119,8 @@ impl JweContentEncryption for AescbcHmacJweEncryption {
                 );
             }
 
-            let mac_key = &key[0..16];
-            let enc_key = &key[16..];
+            // The first half is the MAC key and the second half is the encryption key.
+            let (mac_key, enc_key) = key.split_at(expected_len / 2);
 
             let cipher = self.cipher();
             let encrypted_message = symm::encrypt(cipher, enc_key, iv, message)?;
@@ -138,8 +151,8 @@ impl JweContentEncryption for AescbcHmacJweEncryption {
                 );
             }
 
-            let mac_key = &key[0..16];
-            let enc_key = &key[16..];
+            // The first half is the MAC key and the second half is the encryption key.
+            let (mac_key, enc_key) = key.split_at(expected_len / 2);
 
             let cipher = self.cipher();
             let message = symm::decrypt(cipher, enc_key, iv, encrypted_message)?;
@@ -218,4 +231,44 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn encrypt_aes_cbc_hmac_rfc7518_appendix_b1() -> Result<()> {
+        let enc = AescbcHmacJweEncryption::A128cbcHs256;
+        assert_eq!(enc.key_len(), 32);
+        assert_eq!(AescbcHmacJweEncryption::A192cbcHs384.key_len(), 48);
+        assert_eq!(AescbcHmacJweEncryption::A256cbcHs512.key_len(), 64);
+
+        let key = from_hex("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f");
+        let message = b"A cipher system must not be required to be secret, \
+            and it must be able to fall into the hands of the enemy without inconvenience";
+        let iv = from_hex("1af38c2dc2b96ffdd86694092341bc04");
+        let aad = b"The second principle of Auguste Kerckhoffs";
+
+        let (encrypted_message, tag) = enc.encrypt(&key, Some(&iv), message, aad)?;
+        assert_eq!(
+            encrypted_message,
+            from_hex(concat!(
+                "c80edfa32ddf39d5ef00c0b468834279a2e46a1b8049f792f76bfe54b903a9c9",
+                "a94ac9b47ad2655c5f10f9aef71427e2fc6f9b3f399a221489f16362c7032336",
+                "09d45ac69864e3321cf82935ac4096c86e133314c54019e8ca7980dfa4b9cf1b",
+                "384c486f3a54c51078158ee5d79de59fbd34d848b3d69550a67646344427ade5",
+                "4b8851ffb598f7f80074b9473c82e2db"
+            ))
+        );
+        assert_eq!(tag, Some(from_hex("652c3fa36b0a7c5b3219fab3a30bc1c4")));
+
+        let decrypted_message =
+            enc.decrypt(&key, Some(&iv), &encrypted_message, aad, tag.as_deref())?;
+        assert_eq!(&message[..], &decrypted_message[..]);
+
+        Ok(())
+    }
+
+    fn from_hex(input: &str) -> Vec<u8> {
+        (0..input.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&input[i..(i + 2)], 16).unwrap())
+            .collect()
+    }
 }
@@ -1,12 +1,19 @@
+use std::collections::VecDeque;
 use std::fmt::Display;
 use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 
-use anyhow::bail;
+use anyhow::{anyhow, bail};
 use openssl::symm::{self, Cipher};
 
 use crate::jwe::JweContentEncryption;
+use crate::util;
 use crate::JoseError;
 
+/// The number of recently used initialization vectors kept to detect a reuse.
+const USED_IV_HISTORY_LEN: usize = 1024;
+
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum AesgcmJweEncryption {
     /// AES GCM using 128-bit key
@@ -48,6 +55,10 @@ impl JweContentEncryption for AesgcmJweEncryption {
         12
     }
 
+    fn tag_len(&self) -> Option<usize> {
+        Some(16)
+    }
+
     fn encrypt(
         &self,
         key: &[u8],
@@ -108,6 +119,188 @@ impl JweContentEncryption for AesgcmJweEncryption {
     }
 }
 
+/// Represent a strategy to generate initialization vectors of AES GCM.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum IvGenerator {
+    /// Random 96-bit initialization vectors.
+    Random,
+    /// A 32-bit fixed prefix followed by a 64-bit big-endian counter.
+    ///
+    /// The counter is never wrapped around, so the encryption fails with
+    /// `JoseError::IvExhausted` when it is exhausted.
+    Counter { prefix: [u8; 4] },
+}
+
+/// Represent a AES GCM content encryption that generates initialization vectors by itself.
+///
+/// The clones of a instance share the counter and the history of used initialization vectors,
+/// so they never generate a same initialization vector.
+#[derive(Debug, Clone)]
+pub struct AesgcmJweContentEncryption {
+    encryption: AesgcmJweEncryption,
+    iv_generator: IvGenerator,
+    counter: Arc<AtomicU64>,
+    used_ivs: Option<Arc<Mutex<VecDeque<Vec<u8>>>>>,
+}
+
+impl AesgcmJweContentEncryption {
+    /// Return a new instance that generates random initialization vectors.
+    ///
+    /// # Arguments
+    ///
+    /// * `encryption` - A AES GCM content encryption.
+    pub fn new(encryption: AesgcmJweEncryption) -> Self {
+        Self {
+            encryption,
+            iv_generator: IvGenerator::Random,
+            counter: Arc::new(AtomicU64::new(0)),
+            used_ivs: None,
+        }
+    }
+
+    /// Return the AES GCM content encryption.
+    pub fn encryption(&self) -> AesgcmJweEncryption {
+        self.encryption
+    }
+
+    /// Return the strategy to generate initialization vectors.
+    pub fn iv_generator(&self) -> IvGenerator {
+        self.iv_generator
+    }
+
+    /// Set the strategy to generate initialization vectors.
+    ///
+    /// # Arguments
+    ///
+    /// * `iv_generator` - A strategy to generate initialization vectors.
+    pub fn set_iv_generator(&mut self, iv_generator: IvGenerator) {
+        self.iv_generator = iv_generator;
+    }
+
+    /// Test whether a reuse of initialization vectors is checked.
+    pub fn is_iv_reuse_check(&self) -> bool {
+        self.used_ivs.is_some()
+    }
+
+    /// Set whether a reuse of initialization vectors is checked.
+    ///
+    /// When it is enabled, the recently used initialization vectors are tracked and
+    /// a debug assertion fails if one of them is used again. It is intended for tests.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - true if a reuse of initialization vectors is checked.
+    pub fn set_iv_reuse_check(&mut self, enabled: bool) {
+        self.used_ivs = if enabled {
+            Some(Arc::new(Mutex::new(VecDeque::with_capacity(
+                USED_IV_HISTORY_LEN,
+            ))))
+        } else {
+            None
+        };
+    }
+
+    fn check_iv_reuse(&self, iv: &[u8]) {
+        if let Some(used_ivs) = &self.used_ivs {
+            let mut used_ivs = match used_ivs.lock() {
+                Ok(val) => val,
+                Err(err) => err.into_inner(),
+            };
+            debug_assert!(
+                !used_ivs.iter().any(|val| val == iv),
+                "An initialization vector is reused: {}",
+                base64::encode_config(iv, base64::URL_SAFE_NO_PAD)
+            );
+            if used_ivs.len() >= USED_IV_HISTORY_LEN {
+                used_ivs.pop_front();
+            }
+            used_ivs.push_back(iv.to_vec());
+        }
+    }
+}
+
+impl JweContentEncryption for AesgcmJweContentEncryption {
+    fn name(&self) -> &str {
+        self.encryption.name()
+    }
+
+    fn key_len(&self) -> usize {
+        self.encryption.key_len()
+    }
+
+    fn iv_len(&self) -> usize {
+        self.encryption.iv_len()
+    }
+
+    fn tag_len(&self) -> Option<usize> {
+        self.encryption.tag_len()
+    }
+
+    fn generate_iv(&self) -> Result<Vec<u8>, JoseError> {
+        match self.iv_generator {
+            IvGenerator::Random => Ok(util::random_bytes(self.iv_len())),
+            IvGenerator::Counter { prefix } => {
+                let count = self
+                    .counter
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |val| val.checked_add(1))
+                    .map_err(|_| {
+                        JoseError::IvExhausted(anyhow!(
+                            "The initialization vector counter is exhausted."
+                        ))
+                    })?;
+
+                let mut iv = Vec::with_capacity(self.iv_len());
+                iv.extend_from_slice(&prefix);
+                iv.extend_from_slice(&count.to_be_bytes());
+                Ok(iv)
+            }
+        }
+    }
+
+    fn encrypt(
+        &self,
+        key: &[u8],
+        iv: Option<&[u8]>,
+        message: &[u8],
+        aad: &[u8],
+    ) -> Result<(Vec<u8>, Option<Vec<u8>>), JoseError> {
+        if let Some(val) = iv {
+            self.check_iv_reuse(val);
+        }
+        self.encryption.encrypt(key, iv, message, aad)
+    }
+
+    fn decrypt(
+        &self,
+        key: &[u8],
+        iv: Option<&[u8]>,
+        encrypted_message: &[u8],
+        aad: &[u8],
+        tag: Option<&[u8]>,
+    ) -> Result<Vec<u8>, JoseError> {
+        self.encryption
+            .decrypt(key, iv, encrypted_message, aad, tag)
+    }
+
+    fn box_clone(&self) -> Box<dyn JweContentEncryption> {
+        Box::new(self.clone())
+    }
+}
+
+impl Display for AesgcmJweContentEncryption {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        fmt.write_str(self.name())
+    }
+}
+
+impl Deref for AesgcmJweContentEncryption {
+    type Target = dyn JweContentEncryption;
+
+    fn deref(&self) -> &Self::Target {
+        self
+    }
+}
+
 impl Display for AesgcmJweEncryption {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         fmt.write_str(self.name())
@@ -126,8 +319,9 @@ impl Deref for AesgcmJweEncryption {
 mod tests {
     use anyhow::Result;
 
-    use super::AesgcmJweEncryption;
+    use super::{AesgcmJweContentEncryption, AesgcmJweEncryption, IvGenerator};
     use crate::util;
+    use crate::JoseError;
 
     #[test]
     fn encrypt_and_decrypt_aes_gcm() -> Result<()> {
@@ -156,4 +350,58 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn generate_iv_with_counter() -> Result<()> {
+        let message = b"abcde12345";
+        let aad = b"test";
+
+        let mut enc = AesgcmJweContentEncryption::new(AesgcmJweEncryption::A256gcm);
+        assert_eq!(enc.iv_generator(), IvGenerator::Random);
+        assert_eq!(enc.generate_iv()?.len(), 12);
+
+        enc.set_iv_generator(IvGenerator::Counter {
+            prefix: [1, 2, 3, 4],
+        });
+        assert_eq!(enc.generate_iv()?, vec![1, 2, 3, 4, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(enc.generate_iv()?, vec![1, 2, 3, 4, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+        // A clone shares the counter.
+        let cloned = enc.clone();
+        assert_eq!(
+            cloned.generate_iv()?,
+            vec![1, 2, 3, 4, 0, 0, 0, 0, 0, 0, 0, 2]
+        );
+        assert_eq!(enc.generate_iv()?, vec![1, 2, 3, 4, 0, 0, 0, 0, 0, 0, 0, 3]);
+
+        let key = util::random_bytes(enc.key_len());
+        let iv = enc.generate_iv()?;
+        let (encrypted_message, tag) = enc.encrypt(&key, Some(&iv), message, aad)?;
+        let decrypted_message =
+            enc.decrypt(&key, Some(&iv), &encrypted_message, aad, tag.as_deref())?;
+        assert_eq!(&message[..], &decrypted_message[..]);
+
+        // The counter must not wrap around.
+        enc.counter
+            .store(u64::MAX, std::sync::atomic::Ordering::SeqCst);
+        assert!(matches!(enc.generate_iv(), Err(JoseError::IvExhausted(_))));
+        assert!(matches!(enc.generate_iv(), Err(JoseError::IvExhausted(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "An initialization vector is reused")]
+    fn encrypt_aes_gcm_with_reused_iv() {
+        let mut enc = AesgcmJweContentEncryption::new(AesgcmJweEncryption::A128gcm);
+        enc.set_iv_reuse_check(true);
+
+        let key = util::random_bytes(enc.key_len());
+        let iv = [0; 12];
+        enc.encrypt(&key, Some(&iv), b"abcde12345", b"test")
+            .unwrap();
+        enc.encrypt(&key, Some(&iv), b"abcde12345", b"test")
+            .unwrap();
+    }
 }
@@ -34,6 +34,17 @@ pub trait JweEncrypter: Debug + Send + Sync {
     /// The default value is a value of kid parameter in JWK.
     fn key_id(&self) -> Option<&str>;
 
+    /// Test whether the encrypter can wrap or derive a content encryption key of the length.
+    ///
+    /// The default implementation accepts any length.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_len` - The length of the content encryption key in bytes.
+    fn is_supported_key_len(&self, _key_len: usize) -> bool {
+        true
+    }
+
     /// Compute a content encryption key.
     ///
     /// # Arguments
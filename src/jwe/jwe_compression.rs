@@ -2,6 +2,8 @@ use std::cmp::Eq;
 use std::fmt::Debug;
 use std::io;
 
+use thiserror::Error;
+
 /// Represent a algorithm of JWE zip header claim.
 pub trait JweCompression: Debug + Send + Sync {
     /// Return the "zip" (compression algorithm) header parameter value of JWE.
@@ -11,9 +13,56 @@ pub trait JweCompression: Debug + Send + Sync {
 
     fn decompress(&self, message: &[u8]) -> Result<Vec<u8>, io::Error>;
 
+    /// Decompress the message without producing more than `limit` bytes.
+    ///
+    /// If the decompressed data exceeds the limit, an `io::Error` that has
+    /// `DecompressedSizeLimitError` as its inner error must be returned.
+    /// The default implementation checks the length after decompressing,
+    /// so implementations should override it to abort as soon as the limit is crossed.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - a compressed message
+    /// * `limit` - the maximum length of the decompressed data
+    fn decompress_with_limit(&self, message: &[u8], limit: usize) -> Result<Vec<u8>, io::Error> {
+        let vec = self.decompress(message)?;
+        if vec.len() > limit {
+            return Err(DecompressedSizeLimitError(limit).into());
+        }
+        Ok(vec)
+    }
+
     fn box_clone(&self) -> Box<dyn JweCompression>;
 }
 
+/// Represent that the decompressed data exceeds the limit of
+/// `JweCompression::decompress_with_limit`.
+///
+/// It is returned as the inner error of a `io::Error`.
+#[derive(Error, Debug, Eq, PartialEq, Copy, Clone)]
+#[error("The decompressed data exceeds {0} bytes.")]
+pub struct DecompressedSizeLimitError(pub usize);
+
+impl DecompressedSizeLimitError {
+    /// Return true if the error is caused by the decompressed size limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `err` - an error that is returned by `JweCompression::decompress_with_limit`
+    pub fn is_cause_of(err: &io::Error) -> bool {
+        match err.get_ref() {
+            Some(val) => val.is::<Self>(),
+            None => false,
+        }
+    }
+}
+
+impl From<DecompressedSizeLimitError> for io::Error {
+    fn from(err: DecompressedSizeLimitError) -> Self {
+        io::Error::other(err)
+    }
+}
+
 impl PartialEq for Box<dyn JweCompression> {
     fn eq(&self, other: &Self) -> bool {
         self == other
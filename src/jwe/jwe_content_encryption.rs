@@ -1,17 +1,49 @@
 use std::cmp::Eq;
 use std::fmt::Debug;
 
+use crate::util;
 use crate::JoseError;
 
 /// Represent a algorithm of JWE enc header claim.
+///
+/// It can be implemented outside this crate to use a content encryption that isn't
+/// registered in this crate. The implementation is made available by
+/// `JweContext::register_content_encryption`.
 pub trait JweContentEncryption: Debug + Send + Sync {
     /// Return the "enc" (encryption) header parameter value of JWE.
     fn name(&self) -> &str;
 
+    /// Return the length of the content encryption key in bytes.
     fn key_len(&self) -> usize;
 
+    /// Return the length of the initialization vector in bytes.
+    ///
+    /// If it is 0, no initialization vector is used.
     fn iv_len(&self) -> usize;
 
+    /// Return the length of the authentication tag in bytes if it is fixed.
+    ///
+    /// If it is None, the length of the tag segment is only limited by
+    /// `JweContext::max_tag_len`. The default implementation returns None.
+    fn tag_len(&self) -> Option<usize> {
+        None
+    }
+
+    /// Return a new initialization vector for a encryption.
+    ///
+    /// The default implementation returns random bytes of iv_len.
+    fn generate_iv(&self) -> Result<Vec<u8>, JoseError> {
+        Ok(util::random_bytes(self.iv_len()))
+    }
+
+    /// Return a encrypted message and a authentication tag.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The content encryption key that has key_len bytes.
+    /// * `iv` - The initialization vector that has iv_len bytes.
+    /// * `message` - The message to be encrypted.
+    /// * `aad` - The additional authenticated data.
     fn encrypt(
         &self,
         key: &[u8],
@@ -20,6 +52,17 @@ pub trait JweContentEncryption: Debug + Send + Sync {
         aad: &[u8],
     ) -> Result<(Vec<u8>, Option<Vec<u8>>), JoseError>;
 
+    /// Return a decrypted message.
+    ///
+    /// The authentication tag must be verified.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The content encryption key that has key_len bytes.
+    /// * `iv` - The initialization vector.
+    /// * `encrypted_message` - The encrypted message.
+    /// * `aad` - The additional authenticated data.
+    /// * `tag` - The authentication tag.
     fn decrypt(
         &self,
         key: &[u8],
@@ -29,6 +72,7 @@ pub trait JweContentEncryption: Debug + Send + Sync {
         tag: Option<&[u8]>,
     ) -> Result<Vec<u8>, JoseError>;
 
+    /// Return a boxed clone of this instance.
     fn box_clone(&self) -> Box<dyn JweContentEncryption>;
 }
 
@@ -1,22 +1,43 @@
-use std::borrow::Cow;
 use std::cmp::Eq;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Debug;
 
-use anyhow::bail;
+use anyhow::{anyhow, bail};
 
-use crate::jwe::enc::{A128CBC_HS256, A128GCM, A192CBC_HS384, A192GCM, A256CBC_HS512, A256GCM};
+#[cfg(feature = "enc-cbc")]
+use crate::jwe::enc::{A128CBC_HS256, A192CBC_HS384, A256CBC_HS512};
+#[cfg(feature = "enc-gcm")]
+use crate::jwe::enc::{A128GCM, A192GCM, A256GCM};
 use crate::jwe::zip::Def;
 use crate::jwe::{
-    JweCompression, JweContentEncryption, JweDecrypter, JweEncrypter, JweHeader, JweHeaderSet,
+    DecompressedSizeLimitError, Dir, JweCompression, JweContentEncryption, JweDecrypter,
+    JweDeserializedJson, JweEncrypter, JweHeader, JweHeaderSet,
 };
-use crate::util;
+#[cfg(feature = "jwe-aeskw")]
+use crate::jwe::{A128GCMKW, A128KW, A192GCMKW, A192KW, A256GCMKW, A256KW};
+#[cfg(feature = "jwe-ecdh")]
+use crate::jwe::{ECDH_ES, ECDH_ES_A128KW, ECDH_ES_A192KW, ECDH_ES_A256KW};
+#[cfg(feature = "jwe-pbes2")]
+use crate::jwe::{PBES2_HS256_A128KW, PBES2_HS384_A192KW, PBES2_HS512_A256KW};
+#[cfg(feature = "jwe-rsa")]
+use crate::jwe::{RSA_OAEP, RSA_OAEP_256, RSA_OAEP_384, RSA_OAEP_512};
+use crate::jwk::{Jwk, JwkSet};
+use crate::util::{self, Zeroizing};
 use crate::{JoseError, JoseHeader, Map, Value};
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct JweContext {
     acceptable_criticals: BTreeSet<String>,
+    acceptable_algorithms: Option<BTreeSet<String>>,
+    acceptable_content_encryptions: Option<BTreeSet<String>>,
     compressions: BTreeMap<String, Box<dyn JweCompression>>,
+    max_decompressed_len: usize,
+    max_header_len: usize,
+    max_encrypted_key_len: usize,
+    max_iv_len: usize,
+    max_ciphertext_len: usize,
+    max_tag_len: usize,
+    duplicate_keys_rejected: bool,
     content_encryptions: BTreeMap<String, Box<dyn JweContentEncryption>>,
 }
 
@@ -24,6 +45,8 @@ impl JweContext {
     pub fn new() -> Self {
         Self {
             acceptable_criticals: BTreeSet::new(),
+            acceptable_algorithms: None,
+            acceptable_content_encryptions: None,
             compressions: {
                 let compressions: Vec<Box<dyn JweCompression>> = vec![Box::new(Def)];
 
@@ -33,15 +56,28 @@ impl JweContext {
                 }
                 map
             },
+            max_decompressed_len: 16 * 1024 * 1024,
+            max_header_len: 8 * 1024,
+            max_encrypted_key_len: 2 * 1024,
+            max_iv_len: 256,
+            max_ciphertext_len: 16 * 1024 * 1024,
+            max_tag_len: 256,
+            duplicate_keys_rejected: false,
             content_encryptions: {
-                let content_encryptions: Vec<Box<dyn JweContentEncryption>> = vec![
-                    Box::new(A128CBC_HS256),
-                    Box::new(A192CBC_HS384),
-                    Box::new(A256CBC_HS512),
-                    Box::new(A128GCM),
-                    Box::new(A192GCM),
-                    Box::new(A256GCM),
-                ];
+                #[allow(unused_mut)]
+                let mut content_encryptions: Vec<Box<dyn JweContentEncryption>> = Vec::new();
+                #[cfg(feature = "enc-cbc")]
+                {
+                    content_encryptions.push(Box::new(A128CBC_HS256));
+                    content_encryptions.push(Box::new(A192CBC_HS384));
+                    content_encryptions.push(Box::new(A256CBC_HS512));
+                }
+                #[cfg(feature = "enc-gcm")]
+                {
+                    content_encryptions.push(Box::new(A128GCM));
+                    content_encryptions.push(Box::new(A192GCM));
+                    content_encryptions.push(Box::new(A256GCM));
+                }
 
                 let mut map = BTreeMap::new();
                 for content_encryption in content_encryptions {
@@ -79,6 +115,78 @@ impl JweContext {
         self.acceptable_criticals.remove(name);
     }
 
+    /// Test a alg header claim value is acceptable when deserializing.
+    /// Until a name is added, every algorithm is acceptable.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - a alg header claim value
+    pub fn is_acceptable_algorithm(&self, name: &str) -> bool {
+        match &self.acceptable_algorithms {
+            Some(val) => val.contains(name),
+            None => true,
+        }
+    }
+
+    /// Add a acceptable alg header claim value. Once a name is added,
+    /// only the added algorithms are accepted.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - a acceptable alg header claim value
+    pub fn add_acceptable_algorithm(&mut self, name: &str) {
+        self.acceptable_algorithms
+            .get_or_insert_with(BTreeSet::new)
+            .insert(name.to_string());
+    }
+
+    /// Remove a acceptable alg header claim value.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - a acceptable alg header claim value
+    pub fn remove_acceptable_algorithm(&mut self, name: &str) {
+        if let Some(val) = &mut self.acceptable_algorithms {
+            val.remove(name);
+        }
+    }
+
+    /// Test a enc header claim value is acceptable when deserializing.
+    /// Until a name is added, every content encryption is acceptable.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - a enc header claim value
+    pub fn is_acceptable_content_encryption(&self, name: &str) -> bool {
+        match &self.acceptable_content_encryptions {
+            Some(val) => val.contains(name),
+            None => true,
+        }
+    }
+
+    /// Add a acceptable enc header claim value. Once a name is added,
+    /// only the added content encryptions are accepted.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - a acceptable enc header claim value
+    pub fn add_acceptable_content_encryption(&mut self, name: &str) {
+        self.acceptable_content_encryptions
+            .get_or_insert_with(BTreeSet::new)
+            .insert(name.to_string());
+    }
+
+    /// Remove a acceptable enc header claim value.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - a acceptable enc header claim value
+    pub fn remove_acceptable_content_encryption(&mut self, name: &str) {
+        if let Some(val) = &mut self.acceptable_content_encryptions {
+            val.remove(name);
+        }
+    }
+
     /// Get a compression algorithm for zip header claim value.
     ///
     /// # Arguments
@@ -110,6 +218,132 @@ impl JweContext {
         self.compressions.remove(name);
     }
 
+    pub(crate) fn compression_names(&self) -> Vec<String> {
+        self.compressions.keys().cloned().collect()
+    }
+
+    /// Return the maximum length of a decompressed payload.
+    pub fn max_decompressed_len(&self) -> usize {
+        self.max_decompressed_len
+    }
+
+    /// Set the maximum length of a decompressed payload. The default is 16 MiB.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - the maximum length in bytes
+    pub fn set_max_decompressed_len(&mut self, len: usize) {
+        self.max_decompressed_len = len;
+    }
+
+    /// Return the maximum decoded length of the header segment in compact serialization.
+    pub fn max_header_len(&self) -> usize {
+        self.max_header_len
+    }
+
+    /// Set the maximum decoded length of the header segment in compact serialization.
+    /// The default is 8 KiB.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - the maximum length in bytes
+    pub fn set_max_header_len(&mut self, len: usize) {
+        self.max_header_len = len;
+    }
+
+    /// Return the maximum decoded length of the encrypted key segment in compact serialization.
+    pub fn max_encrypted_key_len(&self) -> usize {
+        self.max_encrypted_key_len
+    }
+
+    /// Set the maximum decoded length of the encrypted key segment in compact serialization.
+    /// The default is 2 KiB.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - the maximum length in bytes
+    pub fn set_max_encrypted_key_len(&mut self, len: usize) {
+        self.max_encrypted_key_len = len;
+    }
+
+    /// Return the maximum decoded length of the iv segment in compact serialization.
+    pub fn max_iv_len(&self) -> usize {
+        self.max_iv_len
+    }
+
+    /// Set the maximum decoded length of the iv segment in compact serialization.
+    /// The default is 256 bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - the maximum length in bytes
+    pub fn set_max_iv_len(&mut self, len: usize) {
+        self.max_iv_len = len;
+    }
+
+    /// Return the maximum decoded length of the ciphertext segment in compact serialization.
+    pub fn max_ciphertext_len(&self) -> usize {
+        self.max_ciphertext_len
+    }
+
+    /// Set the maximum decoded length of the ciphertext segment in compact serialization.
+    /// The default is 16 MiB.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - the maximum length in bytes
+    pub fn set_max_ciphertext_len(&mut self, len: usize) {
+        self.max_ciphertext_len = len;
+    }
+
+    /// Return the maximum decoded length of the tag segment in compact serialization.
+    pub fn max_tag_len(&self) -> usize {
+        self.max_tag_len
+    }
+
+    /// Set the maximum decoded length of the tag segment in compact serialization.
+    /// The default is 256 bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - the maximum length in bytes
+    pub fn set_max_tag_len(&mut self, len: usize) {
+        self.max_tag_len = len;
+    }
+
+    /// Test whether a header that has duplicated member names is rejected when deserializing.
+    pub fn is_duplicate_keys_rejected(&self) -> bool {
+        self.duplicate_keys_rejected
+    }
+
+    /// Set whether a header that has duplicated member names is rejected when deserializing.
+    ///
+    /// It also applies to nested objects, and costs an additional scan of the JSON text.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - true if duplicated member names are rejected.
+    pub fn set_duplicate_keys_rejected(&mut self, value: bool) {
+        self.duplicate_keys_rejected = value;
+    }
+
+    fn decompress(
+        &self,
+        compression: &dyn JweCompression,
+        content: &[u8],
+    ) -> Result<Vec<u8>, JoseError> {
+        match compression.decompress_with_limit(content, self.max_decompressed_len) {
+            Ok(val) => Ok(val),
+            Err(err) if DecompressedSizeLimitError::is_cause_of(&err) => {
+                Err(JoseError::DecompressedSizeExceeded(anyhow!(
+                    "The decompressed payload exceeds {} bytes.",
+                    self.max_decompressed_len
+                )))
+            }
+            Err(err) => Err(JoseError::InvalidJweFormat(err.into())),
+        }
+    }
+
     /// Get a content encryption algorithm for enc header claim value.
     ///
     /// # Arguments
@@ -132,6 +366,31 @@ impl JweContext {
             .insert(content_encryption.name().to_string(), content_encryption);
     }
 
+    /// Register a custom content encryption algorithm for enc header claim name.
+    ///
+    /// It is used for both serialization and deserialization. The name must not be
+    /// one of the content encryptions registered in RFC 7518.
+    ///
+    /// # Arguments
+    ///
+    /// * `content_encryption` - a custom content encryption algorithm
+    pub fn register_content_encryption(
+        &mut self,
+        content_encryption: Box<dyn JweContentEncryption>,
+    ) -> Result<(), JoseError> {
+        match content_encryption.name() {
+            "A128CBC-HS256" | "A192CBC-HS384" | "A256CBC-HS512" | "A128GCM" | "A192GCM"
+            | "A256GCM" => Err(JoseError::UnsupportedEncryptionAlgorithm(anyhow!(
+                "The content encryption name collides with a built-in one: {}",
+                content_encryption.name()
+            ))),
+            _ => {
+                self.add_content_encryption(content_encryption);
+                Ok(())
+            }
+        }
+    }
+
     /// Remove a content encryption algorithm for enc header claim name.
     ///
     /// # Arguments
@@ -141,6 +400,10 @@ impl JweContext {
         self.content_encryptions.remove(name);
     }
 
+    pub(crate) fn content_encryption_names(&self) -> Vec<String> {
+        self.content_encryptions.keys().cloned().collect()
+    }
+
     /// Return a representation of the data that is formatted by compact serialization.
     ///
     /// # Arguments
@@ -179,6 +442,13 @@ impl JweContext {
                 None => bail!("A encrypter is not found."),
             };
 
+            if let Some(alg) = header.algorithm() {
+                let expected_alg = encrypter.algorithm().name();
+                if alg != expected_alg {
+                    bail!("The JWE alg header claim is not {}: {}", expected_alg, alg);
+                }
+            }
+
             let cencryption = match header.content_encryption() {
                 Some(enc) => match self.get_content_encryption(enc) {
                     Some(val) => val,
@@ -198,13 +468,14 @@ impl JweContext {
             let mut out_header = header.clone();
 
             let key_len = cencryption.key_len();
+            Self::check_key_len(encrypter, cencryption)?;
             let key = match encrypter.compute_content_encryption_key(
                 cencryption,
                 &header,
                 &mut out_header,
             )? {
-                Some(val) => val,
-                None => Cow::Owned(util::random_bytes(key_len)),
+                Some(val) => Zeroizing::new(val.into_owned()),
+                None => Zeroizing::new(util::random_bytes(key_len)),
             };
 
             let encrypted_key = encrypter.encrypt(&key, &header, &mut out_header)?;
@@ -229,7 +500,7 @@ impl JweContext {
 
             let iv_vec;
             let iv = if cencryption.iv_len() > 0 {
-                iv_vec = util::random_bytes(cencryption.iv_len());
+                iv_vec = cencryption.generate_iv()?;
                 Some(iv_vec.as_slice())
             } else {
                 None
@@ -238,18 +509,19 @@ impl JweContext {
             let (ciphertext, tag) =
                 cencryption.encrypt(&key, iv, content, header_b64.as_bytes())?;
 
-            let mut capacity = 4;
-            capacity += header_b64.len();
-            if let Some(val) = &encrypted_key {
-                capacity += util::ceiling(val.len() * 4, 3);
-            }
-            if let Some(val) = iv {
-                capacity += util::ceiling(val.len() * 4, 3);
-            }
-            capacity += util::ceiling(ciphertext.len() * 4, 3);
-            if let Some(val) = &tag {
-                capacity += util::ceiling(val.len() * 4, 3);
-            }
+            let capacity = match [
+                Some(header_b64.len()),
+                util::base64_len(encrypted_key.as_ref().map_or(0, |val| val.len())),
+                util::base64_len(iv.map_or(0, |val| val.len())),
+                util::base64_len(ciphertext.len()),
+                util::base64_len(tag.as_ref().map_or(0, |val| val.len())),
+            ]
+            .iter()
+            .try_fold(4usize, |acc, val| acc.checked_add((*val)?))
+            {
+                Some(val) => val,
+                None => bail!("The JWE is too large to be serialized."),
+            };
 
             let mut message = String::with_capacity(capacity);
             message.push_str(&header_b64);
@@ -356,20 +628,33 @@ impl JweContext {
             let mut encrypter_list = Vec::new();
 
             let mut selected_cencryption: Option<&dyn JweContentEncryption> = None;
-            let mut selected_key: Option<Cow<[u8]>> = None;
+            let mut selected_key: Option<Zeroizing<Vec<u8>>> = None;
             for (i, recipient_header) in recipient_headers.iter().enumerate() {
                 let mut merged_map = merged_map.clone();
 
                 if let Some(val) = recipient_header {
                     for (key, value) in val.claims_set() {
+                        match key.as_str() {
+                            "enc" | "zip" => bail!(
+                                "The JWE {} header claim must not be in a per-recipient header.",
+                                key
+                            ),
+                            _ => {}
+                        }
                         if merged_map.contains_key(key) {
-                            bail!("Duplicate key exists: {}", key);
+                            bail!(
+                                "The JWE {} header claim of a recipient conflicts with the shared header.",
+                                key
+                            );
                         }
                         merged_map.insert(key.clone(), value.clone());
                     }
                 }
 
                 let merged = JweHeader::from_map(merged_map)?;
+                if compression.is_none() && merged.claim("zip").is_some() {
+                    bail!("A zip header claim must be in the protected header.");
+                }
 
                 let cencryption = match merged.claim("enc") {
                     Some(Value::String(enc)) => {
@@ -403,17 +688,18 @@ impl JweContext {
                     None => JweHeader::new(),
                 };
 
+                Self::check_key_len(encrypter, cencryption)?;
                 if let Some(key) = encrypter.compute_content_encryption_key(
                     cencryption,
                     &merged,
                     &mut recipient_header,
                 )? {
                     if let Some(selected_key) = &selected_key {
-                        if key.as_ref() != selected_key.as_ref() {
+                        if key.as_ref() != selected_key.as_slice() {
                             bail!("A content encryption key must be only one.");
                         }
                     } else {
-                        selected_key = Some(key);
+                        selected_key = Some(Zeroizing::new(key.into_owned()));
                     }
                 };
 
@@ -441,13 +727,13 @@ impl JweContext {
                 None => bail!("A enc header claim is required."),
             };
 
-            let key = match &selected_key {
-                Some(val) => Cow::Borrowed(val.as_ref()),
-                None => Cow::Owned(util::random_bytes(cencryption.key_len())),
+            let key = match selected_key {
+                Some(val) => val,
+                None => Zeroizing::new(util::random_bytes(cencryption.key_len())),
             };
 
             let iv = if cencryption.iv_len() > 0 {
-                Some(util::random_bytes(cencryption.iv_len()))
+                Some(cencryption.generate_iv()?)
             } else {
                 None
             };
@@ -485,8 +771,8 @@ impl JweContext {
             if let Some(val) = &protected_b64 {
                 full_aad.push_str(&val);
             }
-            full_aad.push_str(".");
             if let Some(val) = &aad_b64 {
+                full_aad.push_str(".");
                 full_aad.push_str(&val);
             }
 
@@ -506,7 +792,7 @@ impl JweContext {
             if let Some(val) = protected_b64 {
                 json.push_str("{\"protected\":\"");
                 json.push_str(&val);
-                json.push_str("\"");
+                json.push('"');
                 writed = true;
             }
 
@@ -535,19 +821,21 @@ impl JweContext {
                 let encrypted_key = encrypter.encrypt(&key, &merged, &mut header)?;
 
                 let mut writed = false;
-                if header.len() > 0 {
+                if !header.is_empty() {
                     let header_json = serde_json::to_string(header.claims_set())?;
                     json.push_str("{\"header\":");
                     json.push_str(&header_json);
                     writed = true;
                 }
 
-                json.push_str(if writed { "," } else { "{" });
-                json.push_str("\"encrypted_key\":\"");
                 if let Some(val) = encrypted_key {
+                    json.push_str(if writed { "," } else { "{" });
+                    json.push_str("\"encrypted_key\":\"");
                     base64::encode_config_buf(&val, base64::URL_SAFE_NO_PAD, &mut json);
+                    json.push('"');
+                    writed = true;
                 }
-                json.push_str("\"}");
+                json.push_str(if writed { "}" } else { "{}" });
             }
             json.push_str("]");
 
@@ -652,13 +940,19 @@ impl JweContext {
             if let Some(val) = recipient_header {
                 for (key, value) in val.claims_set() {
                     if merged_map.contains_key(key) {
-                        bail!("Duplicate key exists: {}", key);
+                        bail!(
+                            "The JWE {} header claim of a recipient conflicts with the shared header.",
+                            key
+                        );
                     }
                     merged_map.insert(key.clone(), value.clone());
                 }
             }
 
             let merged = JweHeader::from_map(merged_map)?;
+            if compression.is_none() && merged.claim("zip").is_some() {
+                bail!("A zip header claim must be in the protected header.");
+            }
 
             let cencryption = match merged.claim("enc") {
                 Some(Value::String(enc)) => match self.get_content_encryption(enc) {
@@ -679,13 +973,14 @@ impl JweContext {
                 None => JweHeader::new(),
             };
 
+            Self::check_key_len(encrypter, cencryption)?;
             let key = match encrypter.compute_content_encryption_key(
                 cencryption,
                 &merged,
                 &mut protected,
             )? {
-                Some(val) => val,
-                None => Cow::Owned(util::random_bytes(cencryption.key_len())),
+                Some(val) => Zeroizing::new(val.into_owned()),
+                None => Zeroizing::new(util::random_bytes(cencryption.key_len())),
             };
 
             let encrypted_key = encrypter.encrypt(&key, &merged, &mut protected)?;
@@ -706,13 +1001,13 @@ impl JweContext {
 
             let iv_vec;
             let iv = if cencryption.iv_len() > 0 {
-                iv_vec = util::random_bytes(cencryption.iv_len());
+                iv_vec = cencryption.generate_iv()?;
                 Some(iv_vec.as_slice())
             } else {
                 None
             };
 
-            let protected_b64 = if protected.len() > 0 {
+            let protected_b64 = if !protected.is_empty() {
                 let protected_json = serde_json::to_vec(protected.claims_set())?;
                 let protected_b64 = base64::encode_config(protected_json, base64::URL_SAFE_NO_PAD);
                 Some(protected_b64)
@@ -738,8 +1033,8 @@ impl JweContext {
             if let Some(val) = &protected_b64 {
                 full_aad.push_str(&val);
             }
-            full_aad.push_str(".");
             if let Some(val) = &aad_b64 {
+                full_aad.push_str(".");
                 full_aad.push_str(&val);
             }
 
@@ -758,7 +1053,7 @@ impl JweContext {
             if let Some(val) = protected_b64 {
                 json.push_str("{\"protected\":\"");
                 json.push_str(&val);
-                json.push_str("\"");
+                json.push('"');
                 writed = true;
             }
 
@@ -780,22 +1075,28 @@ impl JweContext {
                     json.push_str(if writed { "," } else { "{" });
                     json.push_str("\"header\":");
                     json.push_str(&header);
+                    writed = true;
                 }
             }
 
-            json.push_str(",\"encrypted_key\":\"");
             if let Some(val) = encrypted_key {
+                json.push_str(if writed { "," } else { "{" });
+                json.push_str("\"encrypted_key\":\"");
                 base64::encode_config_buf(&val, base64::URL_SAFE_NO_PAD, &mut json);
+                json.push('"');
+                writed = true;
             }
-            json.push_str("\"");
 
             if let Some(val) = aad_b64 {
-                json.push_str(",\"aad\":\"");
+                json.push_str(if writed { "," } else { "{" });
+                json.push_str("\"aad\":\"");
                 json.push_str(&val);
-                json.push_str("\"");
+                json.push('"');
+                writed = true;
             }
 
-            json.push_str(",\"iv\":\"");
+            json.push_str(if writed { "," } else { "{" });
+            json.push_str("\"iv\":\"");
             if let Some(val) = iv {
                 base64::encode_config_buf(&val, base64::URL_SAFE_NO_PAD, &mut json);
             }
@@ -844,6 +1145,18 @@ impl JweContext {
         input: impl AsRef<[u8]>,
         selector: F,
     ) -> Result<(Vec<u8>, JweHeader), JoseError>
+    where
+        F: Fn(&JweHeader) -> Result<Option<&'a dyn JweDecrypter>, JoseError>,
+    {
+        self.deserialize_compact_internal(input, true, selector)
+    }
+
+    fn deserialize_compact_internal<'a, F>(
+        &self,
+        input: impl AsRef<[u8]>,
+        key_id_checked: bool,
+        selector: F,
+    ) -> Result<(Vec<u8>, JweHeader), JoseError>
     where
         F: Fn(&JweHeader) -> Result<Option<&'a dyn JweDecrypter>, JoseError>,
     {
@@ -857,46 +1170,95 @@ impl JweContext {
                 .collect();
             if indexies.len() != 4 {
                 bail!(
-                    "The compact serialization form of JWE must be five parts separated by colon."
+                    "The compact serialization form of JWE must be five parts separated by period."
                 );
             }
 
             let header_b64 = &input[0..indexies[0]];
-
             let encrypted_key_b64 = &input[(indexies[0] + 1)..(indexies[1])];
+            let iv_b64 = &input[(indexies[1] + 1)..(indexies[2])];
+            let ciphertext_b64 = &input[(indexies[2] + 1)..(indexies[3])];
+            let tag_b64 = &input[(indexies[3] + 1)..];
+
+            for (name, segment, max_len) in &[
+                ("header", header_b64, self.max_header_len),
+                (
+                    "encrypted key",
+                    encrypted_key_b64,
+                    self.max_encrypted_key_len,
+                ),
+                ("iv", iv_b64, self.max_iv_len),
+                ("ciphertext", ciphertext_b64, self.max_ciphertext_len),
+                ("tag", tag_b64, self.max_tag_len),
+            ] {
+                if segment.len() > util::base64_len(*max_len).unwrap_or(usize::MAX) {
+                    bail!(
+                        "The JWE {} segment must be {} bytes or less.",
+                        name,
+                        max_len
+                    );
+                }
+            }
+
+            let decode = |name: &str, segment: &[u8]| -> anyhow::Result<Vec<u8>> {
+                match base64::decode_config(segment, base64::URL_SAFE_NO_PAD) {
+                    Ok(val) => Ok(val),
+                    Err(_) => bail!("The JWE {} segment must be a base64url string.", name),
+                }
+            };
+
+            let header = decode("header", header_b64)?;
+
             let encrypted_key_vec;
             let encrypted_key = if encrypted_key_b64.len() > 0 {
-                encrypted_key_vec =
-                    base64::decode_config(encrypted_key_b64, base64::URL_SAFE_NO_PAD)?;
+                encrypted_key_vec = decode("encrypted key", encrypted_key_b64)?;
                 Some(encrypted_key_vec.as_slice())
             } else {
                 None
             };
 
-            let iv_b64 = &input[(indexies[1] + 1)..(indexies[2])];
             let iv_vec;
             let iv = if iv_b64.len() > 0 {
-                iv_vec = base64::decode_config(iv_b64, base64::URL_SAFE_NO_PAD)?;
+                iv_vec = decode("iv", iv_b64)?;
                 Some(iv_vec.as_slice())
             } else {
                 None
             };
 
-            let ciphertext_b64 = &input[(indexies[2] + 1)..(indexies[3])];
-            let ciphertext = base64::decode_config(ciphertext_b64, base64::URL_SAFE_NO_PAD)?;
+            let ciphertext = decode("ciphertext", ciphertext_b64)?;
 
-            let tag_b64 = &input[(indexies[3] + 1)..];
             let tag_vec;
             let tag = if tag_b64.len() > 0 {
-                tag_vec = base64::decode_config(tag_b64, base64::URL_SAFE_NO_PAD)?;
+                tag_vec = decode("tag", tag_b64)?;
                 Some(tag_vec.as_slice())
             } else {
                 None
             };
 
-            let header = base64::decode_config(header_b64, base64::URL_SAFE_NO_PAD)?;
-            let merged: Map<String, Value> = serde_json::from_slice(&header)?;
+            let merged = util::parse_json_object(&header, self.duplicate_keys_rejected)?;
+            self.check_critical(&merged)?;
             let merged = JweHeader::from_map(merged)?;
+            merged.check_typed_claims()?;
+
+            match merged.claim("alg") {
+                Some(Value::String(val)) => {
+                    if !self.is_acceptable_algorithm(val) {
+                        bail!("The JWE alg header claim is not acceptable: {}", val);
+                    }
+                }
+                Some(_) => bail!("A alg header claim must be a string."),
+                None => bail!("The JWE alg header claim is required."),
+            }
+
+            match merged.claim("enc") {
+                Some(Value::String(val)) => {
+                    if !self.is_acceptable_content_encryption(val) {
+                        bail!("The JWE enc header claim is not acceptable: {}", val);
+                    }
+                }
+                Some(_) => bail!("A enc header claim must be a string."),
+                None => bail!("A enc header claim is required."),
+            }
 
             let decrypter = match selector(&merged)? {
                 Some(val) => val,
@@ -912,12 +1274,34 @@ impl JweContext {
                 None => bail!("A enc header claim is required."),
             };
 
+            let iv_len = iv.map_or(0, |val| val.len());
+            if iv_len != cencryption.iv_len() {
+                bail!(
+                    "The JWE iv segment must be {} bytes for {}: {}",
+                    cencryption.iv_len(),
+                    cencryption.name(),
+                    iv_len
+                );
+            }
+
+            if let Some(expected_tag_len) = cencryption.tag_len() {
+                let tag_len = tag.map_or(0, |val| val.len());
+                if tag_len != expected_tag_len {
+                    bail!(
+                        "The JWE tag segment must be {} bytes for {}: {}",
+                        expected_tag_len,
+                        cencryption.name(),
+                        tag_len
+                    );
+                }
+            }
+
             let compression = match merged.claim("zip") {
                 Some(Value::String(val)) => match self.get_compression(val) {
                     Some(val2) => Some(val2),
                     None => bail!("A compression algorithm is not registered: {}", val),
                 },
-                Some(_) => bail!("A enc header claim must be a string."),
+                Some(_) => bail!("A zip header claim must be a string."),
                 None => None,
             };
 
@@ -932,16 +1316,24 @@ impl JweContext {
                 None => bail!("The JWE alg header claim is required."),
             }
 
-            match decrypter.key_id() {
-                Some(expected) => match merged.key_id() {
-                    Some(actual) if expected == actual => {}
-                    Some(actual) => bail!("The JWE kid header claim is mismatched: {}", actual),
-                    None => bail!("The JWE kid header claim is required."),
-                },
-                None => {}
+            if key_id_checked {
+                match decrypter.key_id() {
+                    Some(expected) => match merged.key_id() {
+                        Some(actual) if expected == actual => {}
+                        Some(actual) => {
+                            bail!("The JWE kid header claim is mismatched: {}", actual)
+                        }
+                        None => bail!("The JWE kid header claim is required."),
+                    },
+                    None => {}
+                }
             }
 
-            let key = decrypter.decrypt(encrypted_key, cencryption, &merged)?;
+            let key = Zeroizing::new(
+                decrypter
+                    .decrypt(encrypted_key, cencryption, &merged)?
+                    .into_owned(),
+            );
             if key.len() != cencryption.key_len() {
                 bail!(
                     "The key size is expected to be {}: {}",
@@ -952,7 +1344,7 @@ impl JweContext {
 
             let content = cencryption.decrypt(&key, iv, &ciphertext, header_b64, tag)?;
             let content = match compression {
-                Some(val) => val.decompress(&content)?,
+                Some(val) => self.decompress(val, &content)?,
                 None => content,
             };
 
@@ -964,7 +1356,131 @@ impl JweContext {
         })
     }
 
-    /// Deserialize the input that is formatted by flattened json serialization.
+    /// Deserialize the input that is formatted by compact serialization with a private key in a JWK set.
+    ///
+    /// The candidate keys are the keys that match the kid header claim, or all keys when it is absent.
+    /// A key is skipped when its alg parameter differs from the alg header claim, or when its use
+    /// and key_ops parameters do not allow decryption. The failure of each candidate is not reported
+    /// to avoid leaking which stage of the decryption failed.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input data.
+    /// * `jwk_set` - a JWK set.
+    pub fn deserialize_compact_with_jwk_set(
+        &self,
+        input: impl AsRef<[u8]>,
+        jwk_set: &JwkSet,
+    ) -> Result<(Vec<u8>, JweHeader), JoseError> {
+        let input = input.as_ref();
+
+        let header = (|| -> anyhow::Result<JweHeader> {
+            let header_b64 = match input.iter().position(|b| *b == b'.') {
+                Some(val) => &input[0..val],
+                None => bail!(
+                    "The compact serialization form of JWE must be five parts separated by period."
+                ),
+            };
+            let header = base64::decode_config(header_b64, base64::URL_SAFE_NO_PAD)?;
+            let header = util::parse_json_object(&header, self.duplicate_keys_rejected)?;
+            Ok(JweHeader::from_map(header)?)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidJweFormat(err),
+        })?;
+
+        let alg = match header.algorithm() {
+            Some(val) => val,
+            None => {
+                return Err(JoseError::InvalidJweFormat(anyhow!(
+                    "The JWE alg header claim is required."
+                )))
+            }
+        };
+
+        let candidates = match header.key_id() {
+            Some(val) => jwk_set.get(val),
+            None => jwk_set.keys().collect(),
+        };
+
+        let mut tried = 0;
+        for jwk in candidates {
+            if let Some(val) = jwk.algorithm() {
+                if val != alg {
+                    continue;
+                }
+            }
+
+            let decrypter = match Self::decrypter_from_jwk(alg, jwk) {
+                Some(val) => val,
+                None => continue,
+            };
+
+            // The candidates are already selected by the kid header claim, and a key
+            // that has a key ID is also a candidate when the header claim is absent.
+            tried += 1;
+            if let Ok(val) =
+                self.deserialize_compact_internal(input, false, |_| Ok(Some(&*decrypter)))
+            {
+                return Ok(val);
+            }
+        }
+
+        Err(JoseError::InvalidJweFormat(if tried == 0 {
+            anyhow!("A key for the JWE is not found in the JWK set: {}", alg)
+        } else {
+            anyhow!(
+                "The JWE cannot be decrypted by {} key(s) in the JWK set.",
+                tried
+            )
+        }))
+    }
+
+    fn decrypter_from_jwk(alg: &str, jwk: &Jwk) -> Option<Box<dyn JweDecrypter>> {
+        if alg == Dir.name() {
+            return Some(Box::new(Dir.decrypter_from_jwk(jwk).ok()?));
+        }
+
+        #[cfg(feature = "jwe-aeskw")]
+        for val in &[A128KW, A192KW, A256KW] {
+            if alg == val.name() {
+                return Some(Box::new(val.decrypter_from_jwk(jwk).ok()?));
+            }
+        }
+
+        #[cfg(feature = "jwe-aeskw")]
+        for val in &[A128GCMKW, A192GCMKW, A256GCMKW] {
+            if alg == val.name() {
+                return Some(Box::new(val.decrypter_from_jwk(jwk).ok()?));
+            }
+        }
+
+        #[cfg(feature = "jwe-ecdh")]
+        for val in &[ECDH_ES, ECDH_ES_A128KW, ECDH_ES_A192KW, ECDH_ES_A256KW] {
+            if alg == val.name() {
+                return Some(Box::new(val.decrypter_from_jwk(jwk).ok()?));
+            }
+        }
+
+        #[cfg(feature = "jwe-pbes2")]
+        for val in &[PBES2_HS256_A128KW, PBES2_HS384_A192KW, PBES2_HS512_A256KW] {
+            if alg == val.name() {
+                return Some(Box::new(val.decrypter_from_jwk(jwk).ok()?));
+            }
+        }
+
+        #[cfg(feature = "jwe-rsa")]
+        for val in &[RSA_OAEP, RSA_OAEP_256, RSA_OAEP_384, RSA_OAEP_512] {
+            if alg == val.name() {
+                return Some(Box::new(val.decrypter_from_jwk(jwk).ok()?));
+            }
+        }
+
+        None
+    }
+
+    /// Deserialize the input that is formatted by flattened or general json serialization.
     ///
     /// # Arguments
     ///
@@ -976,35 +1492,53 @@ impl JweContext {
         decrypter: &'a dyn JweDecrypter,
     ) -> Result<(Vec<u8>, JweHeader), JoseError> {
         self.deserialize_json_with_selector(input, |header| {
-            match header.algorithm() {
-                Some(val) => {
-                    let expected_alg = decrypter.algorithm().name();
-                    if val != expected_alg {
-                        return Ok(None);
-                    }
-                }
-                _ => return Ok(None),
-            }
+            Ok(Self::select_decrypter(header, decrypter))
+        })
+    }
 
-            match decrypter.key_id() {
-                Some(expected) => match header.key_id() {
-                    Some(actual) if expected == actual => {}
-                    _ => return Ok(None),
-                },
-                None => {}
-            }
+    /// Deserialize the input that is formatted by flattened or general json serialization.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input data.
+    /// * `selector` - a function for selecting the decrypting algorithm.
+    pub fn deserialize_json_with_selector<'a, F>(
+        &self,
+        input: impl AsRef<[u8]>,
+        selector: F,
+    ) -> Result<(Vec<u8>, JweHeader), JoseError>
+    where
+        F: Fn(&JweHeader) -> Result<Option<&'a dyn JweDecrypter>, JoseError>,
+    {
+        self.deserialize_json_internal(input, false, selector)
+            .map(|val| val.into_parts())
+    }
 
-            Ok(Some(decrypter))
+    /// Deserialize the input that is formatted by flattened json serialization.
+    /// The general json serialization is rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input data.
+    /// * `decrypter` - The JWE decrypter.
+    pub fn deserialize_flattened_json(
+        &self,
+        input: impl AsRef<[u8]>,
+        decrypter: &dyn JweDecrypter,
+    ) -> Result<(Vec<u8>, JweHeader), JoseError> {
+        self.deserialize_flattened_json_with_selector(input, |header| {
+            Ok(Self::select_decrypter(header, decrypter))
         })
     }
 
     /// Deserialize the input that is formatted by flattened json serialization.
+    /// The general json serialization is rejected.
     ///
     /// # Arguments
     ///
     /// * `input` - The input data.
     /// * `selector` - a function for selecting the decrypting algorithm.
-    pub fn deserialize_json_with_selector<'a, F>(
+    pub fn deserialize_flattened_json_with_selector<'a, F>(
         &self,
         input: impl AsRef<[u8]>,
         selector: F,
@@ -1012,9 +1546,135 @@ impl JweContext {
     where
         F: Fn(&JweHeader) -> Result<Option<&'a dyn JweDecrypter>, JoseError>,
     {
-        (|| -> anyhow::Result<(Vec<u8>, JweHeader)> {
+        self.deserialize_json_internal(input, true, selector)
+            .map(|val| val.into_parts())
+    }
+
+    /// Deserialize the input that is formatted by general or flattened json serialization,
+    /// and return the information of the recipient that was used to decrypt it.
+    ///
+    /// The recipients are tried in order and the first one that the decrypter can unwrap
+    /// the content encryption key for wins. The remaining recipients are not attempted.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input data.
+    /// * `decrypter` - The JWE decrypter.
+    pub fn deserialize_general_json(
+        &self,
+        input: impl AsRef<[u8]>,
+        decrypter: &dyn JweDecrypter,
+    ) -> Result<JweDeserializedJson, JoseError> {
+        self.deserialize_general_json_with_selector(input, |header| {
+            Ok(Self::select_decrypter(header, decrypter))
+        })
+    }
+
+    /// Deserialize the input that is formatted by general or flattened json serialization,
+    /// and return the information of the recipient that was used to decrypt it.
+    ///
+    /// The recipients are tried in order and the first one that the selected decrypter can
+    /// unwrap the content encryption key for wins. The remaining recipients are not attempted.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input data.
+    /// * `selector` - a function for selecting the decrypting algorithm.
+    pub fn deserialize_general_json_with_selector<'a, F>(
+        &self,
+        input: impl AsRef<[u8]>,
+        selector: F,
+    ) -> Result<JweDeserializedJson, JoseError>
+    where
+        F: Fn(&JweHeader) -> Result<Option<&'a dyn JweDecrypter>, JoseError>,
+    {
+        self.deserialize_json_internal(input, false, selector)
+    }
+
+    fn check_key_len(
+        encrypter: &dyn JweEncrypter,
+        cencryption: &dyn JweContentEncryption,
+    ) -> anyhow::Result<()> {
+        let key_len = cencryption.key_len();
+        if !encrypter.is_supported_key_len(key_len) {
+            bail!(
+                "{} cannot wrap a {}-byte CEK required by {}.",
+                encrypter.algorithm().name(),
+                key_len,
+                cencryption.name()
+            );
+        }
+        Ok(())
+    }
+
+    fn select_decrypter<'a>(
+        header: &JweHeader,
+        decrypter: &'a dyn JweDecrypter,
+    ) -> Option<&'a dyn JweDecrypter> {
+        match header.algorithm() {
+            Some(val) => {
+                let expected_alg = decrypter.algorithm().name();
+                if val != expected_alg {
+                    return None;
+                }
+            }
+            _ => return None,
+        }
+
+        match decrypter.key_id() {
+            Some(expected) => match header.key_id() {
+                Some(actual) if expected == actual => {}
+                _ => return None,
+            },
+            None => {}
+        }
+
+        Some(decrypter)
+    }
+
+    fn check_critical(&self, protected: &Map<String, Value>) -> anyhow::Result<()> {
+        match protected.get("crit") {
+            Some(Value::Array(vals)) => {
+                if vals.is_empty() {
+                    bail!("The JWE crit header claim must not be empty.");
+                }
+                for val in vals {
+                    match val {
+                        Value::String(name) => match name.as_str() {
+                            "alg" | "enc" | "zip" | "jku" | "jwk" | "kid" | "x5u" | "x5c"
+                            | "x5t" | "x5t#S256" | "typ" | "cty" | "crit" | "epk" | "apu"
+                            | "apv" | "iv" | "tag" | "p2s" | "p2c" => bail!(
+                                "The JWE crit header claim must not contain a standard header claim name: {}",
+                                name
+                            ),
+                            _ => {
+                                if !self.is_acceptable_critical(name) {
+                                    bail!("The critical name '{}' is not supported.", name);
+                                }
+                            }
+                        },
+                        _ => bail!("The JWE crit header claim must be a array of string."),
+                    }
+                }
+            }
+            Some(_) => bail!("The JWE crit header claim must be a array."),
+            None => {}
+        }
+        Ok(())
+    }
+
+    fn deserialize_json_internal<'a, F>(
+        &self,
+        input: impl AsRef<[u8]>,
+        flattened: bool,
+        selector: F,
+    ) -> Result<JweDeserializedJson, JoseError>
+    where
+        F: Fn(&JweHeader) -> Result<Option<&'a dyn JweDecrypter>, JoseError>,
+    {
+        (|| -> anyhow::Result<JweDeserializedJson> {
             let input = input.as_ref();
-            let mut map: Map<String, Value> = serde_json::from_slice(input)?;
+            let mut map = util::parse_json_object(input, self.duplicate_keys_rejected)?;
 
             let (protected, protected_b64) = match map.remove("protected") {
                 Some(Value::String(val)) => {
@@ -1022,7 +1682,7 @@ impl JweContext {
                         bail!("The protected field must be empty.");
                     }
                     let vec = base64::decode_config(&val, base64::URL_SAFE_NO_PAD)?;
-                    let json: Map<String, Value> = serde_json::from_slice(&vec)?;
+                    let json = util::parse_json_object(&vec, self.duplicate_keys_rejected)?;
                     (Some(json), Some(val))
                 }
                 Some(_) => bail!("The protected field must be a string."),
@@ -1086,6 +1746,9 @@ impl JweContext {
             };
 
             let recipients = match map.remove("recipients") {
+                Some(_) if flattened => {
+                    bail!("The recipients field must not exist in flattened json serialization.")
+                }
                 Some(Value::Array(vals)) => {
                     if vals.len() == 0 {
                         bail!("The recipients field must be empty.");
@@ -1108,7 +1771,32 @@ impl JweContext {
                 }
             };
 
-            for mut recipient in recipients {
+            if let Some(val) = &protected {
+                self.check_critical(val)?;
+            }
+            if let Some(val) = &unprotected {
+                if val.contains_key("crit") {
+                    bail!("The JWE crit header claim must be in the protected header.");
+                }
+            }
+
+            // All recipients must share the same content encryption.
+            let mut recipient_enc = None;
+            for recipient in &recipients {
+                if let Some(Value::Object(header)) = recipient.get("header") {
+                    if let Some(enc) = header.get("enc") {
+                        match recipient_enc {
+                            Some(val) if val != enc => bail!(
+                                "The JWE enc header claim must be same for all recipients."
+                            ),
+                            _ => recipient_enc = Some(enc),
+                        }
+                    }
+                }
+            }
+
+            let mut last_error = None;
+            for (recipient_index, mut recipient) in recipients.into_iter().enumerate() {
                 let header = recipient.remove("header");
 
                 let encrypted_key_vec;
@@ -1124,16 +1812,26 @@ impl JweContext {
                     None => None,
                 };
 
-                let mut merged = match header {
-                    Some(Value::Object(val)) => val,
+                let recipient_header = match header {
+                    Some(Value::Object(val)) => Some(val),
                     Some(_) => bail!("The protected field must be a object."),
+                    None => None,
+                };
+                let mut merged = match &recipient_header {
+                    Some(val) => val.clone(),
                     None => Map::new(),
                 };
+                if merged.contains_key("crit") {
+                    bail!("The JWE crit header claim must be in the protected header.");
+                }
 
                 if let Some(val) = &unprotected {
                     for (key, value) in val {
                         if merged.contains_key(key) {
-                            bail!("A duplicate key exists: {}", key);
+                            bail!(
+                                "The JWE {} header claim of a recipient conflicts with the shared unprotected header.",
+                                key
+                            );
                         } else {
                             merged.insert(key.clone(), value.clone());
                         }
@@ -1143,7 +1841,10 @@ impl JweContext {
                 if let Some(val) = &protected {
                     for (key, value) in val {
                         if merged.contains_key(key) {
-                            bail!("A duplicate key exists: {}", key);
+                            bail!(
+                                "The JWE {} header claim conflicts with the protected header.",
+                                key
+                            );
                         } else {
                             merged.insert(key.clone(), value.clone());
                         }
@@ -1151,6 +1852,23 @@ impl JweContext {
                 }
 
                 let merged = JweHeader::from_map(merged)?;
+                merged.check_typed_claims()?;
+
+                match merged.claim("enc") {
+                    Some(Value::String(val)) => {
+                        if !self.is_acceptable_content_encryption(val) {
+                            bail!("The JWE enc header claim is not acceptable: {}", val);
+                        }
+                    }
+                    Some(_) => bail!("A enc header claim must be string."),
+                    None => bail!("A enc header claim is required."),
+                }
+
+                // A recipient using an unacceptable algorithm is for someone else.
+                match merged.algorithm() {
+                    Some(val) if self.is_acceptable_algorithm(val) => {}
+                    _ => continue,
+                }
 
                 let decrypter = match selector(&merged)? {
                     Some(val) => val,
@@ -1166,14 +1884,18 @@ impl JweContext {
                     None => bail!("A enc header claim is required."),
                 };
 
-                let compression = match merged.claim("zip") {
+                // The zip header claim is honored only in the protected header.
+                let compression = match protected.as_ref().and_then(|val| val.get("zip")) {
                     Some(Value::String(val)) => match self.get_compression(val) {
                         Some(val2) => Some(val2),
                         None => bail!("A compression algorithm is not registered: {}", val),
                     },
-                    Some(_) => bail!("A enc header claim must be string."),
+                    Some(_) => bail!("A zip header claim must be a string."),
                     None => None,
                 };
+                if compression.is_none() && merged.claim("zip").is_some() {
+                    bail!("A zip header claim must be in the protected header.");
+                }
 
                 match merged.algorithm() {
                     Some(val) => {
@@ -1194,16 +1916,23 @@ impl JweContext {
                     None => {}
                 }
 
-                let mut full_aad = match protected_b64 {
-                    Some(val) => val,
+                let mut full_aad = match &protected_b64 {
+                    Some(val) => val.clone(),
                     None => String::new(),
                 };
-                full_aad.push_str(".");
-                if let Some(val) = aad_b64 {
-                    full_aad.push_str(&val);
+                if let Some(val) = &aad_b64 {
+                    full_aad.push_str(".");
+                    full_aad.push_str(val);
                 }
 
-                let key = decrypter.decrypt(encrypted_key, cencryption, &merged)?;
+                // The first recipient that can be unwrapped wins.
+                let key = match decrypter.decrypt(encrypted_key, cencryption, &merged) {
+                    Ok(val) => Zeroizing::new(val.into_owned()),
+                    Err(err) => {
+                        last_error = Some(err);
+                        continue;
+                    }
+                };
                 if key.len() != cencryption.key_len() {
                     bail!(
                         "The key size is expected to be {}: {}",
@@ -1215,13 +1944,26 @@ impl JweContext {
                 let content =
                     cencryption.decrypt(&key, iv, &ciphertext, full_aad.as_bytes(), tag)?;
                 let content = match compression {
-                    Some(val) => val.decompress(&content)?,
+                    Some(val) => self.decompress(val, &content)?,
                     None => content,
                 };
 
-                return Ok((content, merged));
+                let recipient_header = match recipient_header {
+                    Some(val) => Some(JweHeader::from_map(val)?),
+                    None => None,
+                };
+
+                return Ok(JweDeserializedJson::new(
+                    content,
+                    merged,
+                    recipient_index,
+                    recipient_header,
+                ));
             }
 
+            if let Some(err) = last_error {
+                return Err(err.into());
+            }
             bail!("A recipient that matched the header claims is not found.");
         })()
         .map_err(|err| match err.downcast::<JoseError>() {
@@ -0,0 +1,55 @@
+use crate::jwe::JweHeader;
+
+/// Represent the result of deserializing a JWE formatted by json serialization.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct JweDeserializedJson {
+    payload: Vec<u8>,
+    header: JweHeader,
+    recipient_index: usize,
+    recipient_header: Option<JweHeader>,
+}
+
+impl JweDeserializedJson {
+    pub(crate) fn new(
+        payload: Vec<u8>,
+        header: JweHeader,
+        recipient_index: usize,
+        recipient_header: Option<JweHeader>,
+    ) -> Self {
+        Self {
+            payload,
+            header,
+            recipient_index,
+            recipient_header,
+        }
+    }
+
+    /// Return the decrypted payload.
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Return the header claims merged from the protected, shared unprotected
+    /// and per-recipient unprotected headers.
+    pub fn header(&self) -> &JweHeader {
+        &self.header
+    }
+
+    /// Return the index of the recipient that was used to decrypt the JWE.
+    ///
+    /// It is always 0 for flattened json serialization.
+    pub fn recipient_index(&self) -> usize {
+        self.recipient_index
+    }
+
+    /// Return the per-recipient unprotected header claims of the recipient
+    /// that was used to decrypt the JWE.
+    pub fn recipient_header(&self) -> Option<&JweHeader> {
+        self.recipient_header.as_ref()
+    }
+
+    /// Return the decrypted payload and the merged header claims.
+    pub fn into_parts(self) -> (Vec<u8>, JweHeader) {
+        (self.payload, self.header)
+    }
+}
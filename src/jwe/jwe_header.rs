@@ -3,11 +3,11 @@ use std::convert::Into;
 use std::fmt::{Debug, Display};
 use std::ops::Deref;
 
-use anyhow::bail;
+use anyhow::{anyhow, bail};
 
 use crate::jwk::Jwk;
-use crate::util;
-use crate::{JoseError, JoseHeader, Map, Value};
+use crate::util::{self, HashAlgorithm};
+use crate::{ConflictPolicy, JoseError, JoseHeader, Map, Value};
 
 /// Represent JWE header claims
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -27,11 +27,19 @@ impl JweHeader {
     ///
     /// * `value` - The json style header claims
     pub fn from_bytes(value: &[u8]) -> Result<Self, JoseError> {
-        let claims = (|| -> anyhow::Result<Map<String, Value>> {
-            let claims: Map<String, Value> = serde_json::from_slice(value)?;
-            Ok(claims)
-        })()
-        .map_err(|err| JoseError::InvalidJson(err))?;
+        let claims = util::parse_json_object(value, false).map_err(JoseError::InvalidJson)?;
+
+        let header = Self::from_map(claims)?;
+        Ok(header)
+    }
+
+    /// Return a new header instance from json style header that has no duplicated member names.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The json style header claims
+    pub fn from_bytes_strict(value: &[u8]) -> Result<Self, JoseError> {
+        let claims = util::parse_json_object(value, true).map_err(JoseError::InvalidJson)?;
 
         let header = Self::from_map(claims)?;
         Ok(header)
@@ -135,13 +143,52 @@ impl JweHeader {
     }
 
     /// Return the value for JWK header claim (jwk).
+    ///
+    /// None is also returned if the value is malformed.
     pub fn jwk(&self) -> Option<Jwk> {
+        self.try_jwk().unwrap_or(None)
+    }
+
+    /// Return the value for JWK header claim (jwk).
+    ///
+    /// An error is returned if the value is present but malformed.
+    pub fn try_jwk(&self) -> Result<Option<Jwk>, JoseError> {
         match self.claims.get("jwk") {
-            Some(Value::Object(vals)) => match Jwk::from_map(vals.clone()) {
-                Ok(val) => Some(val),
-                Err(_) => None,
-            },
-            _ => None,
+            Some(Value::Object(vals)) => Ok(Some(Jwk::from_map(vals.clone())?)),
+            Some(_) => Err(JoseError::InvalidJweFormat(anyhow!(
+                "The JWE jwk header claim must be a JSON object."
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    /// Set a value for ephemeral public key header claim (epk).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - a ephemeral public key
+    pub fn set_ephemeral_public_key(&mut self, value: Jwk) -> Result<(), JoseError> {
+        let value: Map<String, Value> = value.into();
+        self.set_claim("epk", Some(Value::Object(value)))
+    }
+
+    /// Return the value for ephemeral public key header claim (epk).
+    ///
+    /// None is also returned if the value is malformed.
+    pub fn ephemeral_public_key(&self) -> Option<Jwk> {
+        self.try_ephemeral_public_key().unwrap_or(None)
+    }
+
+    /// Return the value for ephemeral public key header claim (epk).
+    ///
+    /// An error is returned if the value is present but malformed.
+    pub fn try_ephemeral_public_key(&self) -> Result<Option<Jwk>, JoseError> {
+        match self.claims.get("epk") {
+            Some(Value::Object(vals)) => Ok(Some(Jwk::from_map(vals.clone())?)),
+            Some(_) => Err(JoseError::InvalidJweFormat(anyhow!(
+                "The JWE epk header claim must be a JSON object."
+            ))),
+            None => Ok(None),
         }
     }
 
@@ -172,34 +219,50 @@ impl JweHeader {
         let key = "x5c";
         let mut vec = Vec::with_capacity(values.len());
         for val in values {
+            // RFC 7515 section 4.1.6 uses the standard base64 encoding for x5c.
             vec.push(Value::String(base64::encode_config(
                 val.as_ref(),
-                base64::URL_SAFE_NO_PAD,
+                base64::STANDARD,
             )));
         }
         self.claims.insert(key.to_string(), Value::Array(vec));
     }
 
     /// Return values for a X.509 certificate chain header claim (x5c).
+    ///
+    /// None is also returned if the value is malformed.
     pub fn x509_certificate_chain(&self) -> Option<Vec<Vec<u8>>> {
-        match self.claims.get("x5c") {
-            Some(Value::Array(vals)) => {
-                let mut vec = Vec::with_capacity(vals.len());
-                for val in vals {
-                    match val {
-                        Value::String(val2) => {
-                            match base64::decode_config(val2, base64::URL_SAFE_NO_PAD) {
-                                Ok(val3) => vec.push(val3.clone()),
-                                Err(_) => return None,
-                            }
-                        }
-                        _ => return None,
-                    }
+        self.try_x509_certificate_chain().unwrap_or(None)
+    }
+
+    /// Return values for a X.509 certificate chain header claim (x5c).
+    ///
+    /// An error is returned if the value is present but malformed.
+    pub fn try_x509_certificate_chain(&self) -> Result<Option<Vec<Vec<u8>>>, JoseError> {
+        (|| -> anyhow::Result<Option<Vec<Vec<u8>>>> {
+            let vals = match self.claims.get("x5c") {
+                Some(Value::Array(vals)) => vals,
+                Some(_) => bail!("The JWE x5c header claim must be a array."),
+                None => return Ok(None),
+            };
+
+            let mut vec = Vec::with_capacity(vals.len());
+            for val in vals {
+                let val = match val {
+                    Value::String(val) => val,
+                    _ => bail!("An element of the JWE x5c header claim must be a string."),
+                };
+                // RFC 7515 uses the standard base64 encoding for x5c.
+                match base64::decode_config(val, base64::URL_SAFE_NO_PAD)
+                    .or_else(|_| base64::decode_config(val, base64::STANDARD))
+                {
+                    Ok(val2) => vec.push(val2),
+                    Err(err) => bail!("The JWE x5c header claim must be a base64 string: {}", err),
                 }
-                Some(vec)
             }
-            _ => None,
-        }
+            Ok(Some(vec))
+        })()
+        .map_err(JoseError::InvalidJweFormat)
     }
 
     /// Set a value for X.509 certificate SHA-1 thumbprint header claim (x5t).
@@ -214,14 +277,17 @@ impl JweHeader {
     }
 
     /// Return the value for X.509 certificate SHA-1 thumbprint header claim (x5t).
+    ///
+    /// None is also returned if the value is malformed.
     pub fn x509_certificate_sha1_thumbprint(&self) -> Option<Vec<u8>> {
-        match self.claims.get("x5t") {
-            Some(Value::String(val)) => match base64::decode_config(val, base64::URL_SAFE_NO_PAD) {
-                Ok(val2) => Some(val2),
-                Err(_) => None,
-            },
-            _ => None,
-        }
+        self.try_x509_certificate_sha1_thumbprint().unwrap_or(None)
+    }
+
+    /// Return the value for X.509 certificate SHA-1 thumbprint header claim (x5t).
+    ///
+    /// An error is returned if the value is present but malformed.
+    pub fn try_x509_certificate_sha1_thumbprint(&self) -> Result<Option<Vec<u8>>, JoseError> {
+        self.try_base64_claim("x5t")
     }
 
     /// Set a value for a x509 certificate SHA-256 thumbprint header claim (x5t#S256).
@@ -236,14 +302,84 @@ impl JweHeader {
     }
 
     /// Return the value for X.509 certificate SHA-256 thumbprint header claim (x5t#S256).
+    ///
+    /// None is also returned if the value is malformed.
     pub fn x509_certificate_sha256_thumbprint(&self) -> Option<Vec<u8>> {
-        match self.claims.get("x5t#S256") {
-            Some(Value::String(val)) => match base64::decode_config(val, base64::URL_SAFE_NO_PAD) {
-                Ok(val2) => Some(val2),
-                Err(_) => None,
-            },
-            _ => None,
-        }
+        self.try_x509_certificate_sha256_thumbprint()
+            .unwrap_or(None)
+    }
+
+    /// Return the value for X.509 certificate SHA-256 thumbprint header claim (x5t#S256).
+    ///
+    /// An error is returned if the value is present but malformed.
+    pub fn try_x509_certificate_sha256_thumbprint(&self) -> Result<Option<Vec<u8>>, JoseError> {
+        self.try_base64_claim("x5t#S256")
+    }
+
+    /// Set the SHA-1 thumbprint of a DER encoded X.509 certificate to X.509 certificate
+    /// SHA-1 thumbprint header claim (x5t).
+    ///
+    /// # Arguments
+    ///
+    /// * `cert_der` - A DER encoded X.509 certificate
+    pub fn set_x509_certificate_sha1_thumbprint_from_der(
+        &mut self,
+        cert_der: impl AsRef<[u8]>,
+    ) -> Result<(), JoseError> {
+        let thumbprint = util::x509_thumbprint(cert_der.as_ref(), HashAlgorithm::Sha1)
+            .map_err(JoseError::InvalidJweFormat)?;
+        self.set_x509_certificate_sha1_thumbprint(thumbprint);
+        Ok(())
+    }
+
+    /// Set the SHA-256 thumbprint of a DER encoded X.509 certificate to X.509 certificate
+    /// SHA-256 thumbprint header claim (x5t#S256).
+    ///
+    /// # Arguments
+    ///
+    /// * `cert_der` - A DER encoded X.509 certificate
+    pub fn set_x509_certificate_sha256_thumbprint_from_der(
+        &mut self,
+        cert_der: impl AsRef<[u8]>,
+    ) -> Result<(), JoseError> {
+        let thumbprint = util::x509_thumbprint(cert_der.as_ref(), HashAlgorithm::Sha256)
+            .map_err(JoseError::InvalidJweFormat)?;
+        self.set_x509_certificate_sha256_thumbprint(thumbprint);
+        Ok(())
+    }
+
+    /// Set a DER encoded X.509 certificate to X.509 certificate chain header claim (x5c),
+    /// and its thumbprints to x5t and x5t#S256.
+    ///
+    /// # Arguments
+    ///
+    /// * `cert_der` - A DER encoded X.509 certificate
+    pub fn set_x509_from_certificate(
+        &mut self,
+        cert_der: impl AsRef<[u8]>,
+    ) -> Result<(), JoseError> {
+        let cert_der = cert_der.as_ref();
+        self.set_x509_certificate_sha1_thumbprint_from_der(cert_der)?;
+        self.set_x509_certificate_sha256_thumbprint_from_der(cert_der)?;
+        self.set_x509_certificate_chain(&vec![cert_der]);
+        Ok(())
+    }
+
+    /// Test whether x5t and x5t#S256 header claims are the thumbprints of a DER encoded
+    /// X.509 certificate (e.g. the first certificate of x5c).
+    ///
+    /// It returns false when neither header claim is present, and an error when
+    /// a header claim is malformed.
+    ///
+    /// # Arguments
+    ///
+    /// * `cert_der` - A DER encoded X.509 certificate
+    pub fn x509_thumbprint_matches(&self, cert_der: impl AsRef<[u8]>) -> Result<bool, JoseError> {
+        Ok(util::is_x509_thumbprint_match(
+            cert_der.as_ref(),
+            self.try_x509_certificate_sha1_thumbprint()?,
+            self.try_x509_certificate_sha256_thumbprint()?,
+        ))
     }
 
     /// Set a value for key ID header claim (kid).
@@ -315,20 +451,33 @@ impl JweHeader {
     }
 
     /// Return values for critical header claim (crit).
+    ///
+    /// None is also returned if the value is malformed.
     pub fn critical(&self) -> Option<Vec<&str>> {
-        match self.claims.get("crit") {
-            Some(Value::Array(vals)) => {
-                let mut vec = Vec::with_capacity(vals.len());
-                for val in vals {
-                    match val {
-                        Value::String(val2) => vec.push(val2.as_str()),
-                        _ => return None,
-                    }
+        self.try_critical().unwrap_or(None)
+    }
+
+    /// Return values for critical header claim (crit).
+    ///
+    /// An error is returned if the value is present but malformed.
+    pub fn try_critical(&self) -> Result<Option<Vec<&str>>, JoseError> {
+        (|| -> anyhow::Result<Option<Vec<&str>>> {
+            let vals = match self.claims.get("crit") {
+                Some(Value::Array(vals)) => vals,
+                Some(_) => bail!("The JWE crit header claim must be a array."),
+                None => return Ok(None),
+            };
+
+            let mut vec = Vec::with_capacity(vals.len());
+            for val in vals {
+                match val {
+                    Value::String(val2) => vec.push(val2.as_str()),
+                    _ => bail!("An element of the JWE crit header claim must be a string."),
                 }
-                Some(vec)
             }
-            _ => None,
-        }
+            Ok(Some(vec))
+        })()
+        .map_err(JoseError::InvalidJweFormat)
     }
 
     /// Set a value for url header claim (url).
@@ -361,14 +510,17 @@ impl JweHeader {
     }
 
     /// Return the value for nonce header claim (nonce).
+    ///
+    /// None is also returned if the value is malformed.
     pub fn nonce(&self) -> Option<Vec<u8>> {
-        match self.claims.get("nonce") {
-            Some(Value::String(val)) => match base64::decode_config(val, base64::URL_SAFE_NO_PAD) {
-                Ok(val2) => Some(val2),
-                Err(_) => None,
-            },
-            _ => None,
-        }
+        self.try_nonce().unwrap_or(None)
+    }
+
+    /// Return the value for nonce header claim (nonce).
+    ///
+    /// An error is returned if the value is present but malformed.
+    pub fn try_nonce(&self) -> Result<Option<Vec<u8>>, JoseError> {
+        self.try_base64_claim("nonce")
     }
 
     /// Set a value for a agreement PartyUInfo header claim (apu).
@@ -383,14 +535,17 @@ impl JweHeader {
     }
 
     /// Return the value for agreement PartyUInfo header claim (apu).
+    ///
+    /// None is also returned if the value is malformed.
     pub fn agreement_partyuinfo(&self) -> Option<Vec<u8>> {
-        match self.claims.get("apu") {
-            Some(Value::String(val)) => match base64::decode_config(val, base64::URL_SAFE_NO_PAD) {
-                Ok(val2) => Some(val2),
-                Err(_) => None,
-            },
-            _ => None,
-        }
+        self.try_agreement_partyuinfo().unwrap_or(None)
+    }
+
+    /// Return the value for agreement PartyUInfo header claim (apu).
+    ///
+    /// An error is returned if the value is present but malformed.
+    pub fn try_agreement_partyuinfo(&self) -> Result<Option<Vec<u8>>, JoseError> {
+        self.try_base64_claim("apu")
     }
 
     /// Set a value for a agreement PartyVInfo header claim (apv).
@@ -405,14 +560,17 @@ impl JweHeader {
     }
 
     /// Return the value for agreement PartyVInfo header claim (apv).
+    ///
+    /// None is also returned if the value is malformed.
     pub fn agreement_partyvinfo(&self) -> Option<Vec<u8>> {
-        match self.claims.get("apv") {
-            Some(Value::String(val)) => match base64::decode_config(val, base64::URL_SAFE_NO_PAD) {
-                Ok(val2) => Some(val2),
-                Err(_) => None,
-            },
-            _ => None,
-        }
+        self.try_agreement_partyvinfo().unwrap_or(None)
+    }
+
+    /// Return the value for agreement PartyVInfo header claim (apv).
+    ///
+    /// An error is returned if the value is present but malformed.
+    pub fn try_agreement_partyvinfo(&self) -> Result<Option<Vec<u8>>, JoseError> {
+        self.try_base64_claim("apv")
     }
 
     /// Set a value for issuer header claim (iss).
@@ -477,23 +635,34 @@ impl JweHeader {
     }
 
     /// Return values for audience header claim (aud).
+    ///
+    /// None is also returned if the value is malformed.
     pub fn audience(&self) -> Option<Vec<&str>> {
-        match self.claims.get("aud") {
-            Some(Value::Array(vals)) => {
-                let mut vec = Vec::with_capacity(vals.len());
-                for val in vals {
-                    match val {
-                        Value::String(val2) => {
-                            vec.push(val2.as_str());
+        self.try_audience().unwrap_or(None)
+    }
+
+    /// Return values for audience header claim (aud).
+    ///
+    /// An error is returned if the value is present but malformed.
+    pub fn try_audience(&self) -> Result<Option<Vec<&str>>, JoseError> {
+        (|| -> anyhow::Result<Option<Vec<&str>>> {
+            match self.claims.get("aud") {
+                Some(Value::Array(vals)) => {
+                    let mut vec = Vec::with_capacity(vals.len());
+                    for val in vals {
+                        match val {
+                            Value::String(val2) => vec.push(val2.as_str()),
+                            _ => bail!("An element of the JWE aud header claim must be a string."),
                         }
-                        _ => return None,
                     }
+                    Ok(Some(vec))
                 }
-                Some(vec)
+                Some(Value::String(val)) => Ok(Some(vec![val.as_str()])),
+                Some(_) => bail!("The JWE aud header claim must be a string or array."),
+                None => Ok(None),
             }
-            Some(Value::String(val)) => Some(vec![val]),
-            _ => None,
-        }
+        })()
+        .map_err(JoseError::InvalidJweFormat)
     }
 
     /// Set a value for header claim of a specified key.
@@ -516,6 +685,55 @@ impl JweHeader {
         Ok(())
     }
 
+    /// Remove a header claim of a specified key and return the removed value.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - a key name of header claim
+    pub fn remove_claim(&mut self, key: &str) -> Option<Value> {
+        self.claims.remove(key)
+    }
+
+    /// Merge the claims of other header into this header.
+    ///
+    /// Every claim is checked in the same way as `set_claim`, and this header is unchanged
+    /// if the merge fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - a header whose claims are merged
+    /// * `on_conflict` - how to resolve a claim that is in both headers
+    pub fn merge(
+        &mut self,
+        other: &dyn JoseHeader,
+        on_conflict: ConflictPolicy,
+    ) -> Result<(), JoseError> {
+        let mut claims = self.claims.clone();
+        for (key, value) in other.iter() {
+            match claims.get(key) {
+                Some(existing) if existing == value => {}
+                Some(_) => match on_conflict {
+                    ConflictPolicy::Error => {
+                        return Err(JoseError::InvalidJweFormat(anyhow!(
+                            "The JWE {} header claim conflicts in the merged headers.",
+                            key
+                        )));
+                    }
+                    ConflictPolicy::KeepExisting => {}
+                    ConflictPolicy::Overwrite => {
+                        claims.insert(key.to_string(), value.clone());
+                    }
+                },
+                None => {
+                    claims.insert(key.to_string(), value.clone());
+                }
+            }
+        }
+
+        *self = Self::from_map(claims)?;
+        Ok(())
+    }
+
     /// Return values for header claims set
     pub fn claims_set(&self) -> &Map<String, Value> {
         &self.claims
@@ -526,6 +744,40 @@ impl JweHeader {
         self.claims
     }
 
+    pub(crate) fn check_typed_claims(&self) -> Result<(), JoseError> {
+        self.try_jwk()?;
+        self.try_ephemeral_public_key()?;
+        self.try_x509_certificate_chain()?;
+        self.try_x509_certificate_sha1_thumbprint()?;
+        self.try_x509_certificate_sha256_thumbprint()?;
+        self.try_critical()?;
+        self.try_nonce()?;
+        self.try_agreement_partyuinfo()?;
+        self.try_agreement_partyvinfo()?;
+        self.try_audience()?;
+        Ok(())
+    }
+
+    fn try_base64_claim(&self, key: &str) -> Result<Option<Vec<u8>>, JoseError> {
+        (|| -> anyhow::Result<Option<Vec<u8>>> {
+            match self.claims.get(key) {
+                Some(Value::String(val)) => {
+                    match base64::decode_config(val, base64::URL_SAFE_NO_PAD) {
+                        Ok(val2) => Ok(Some(val2)),
+                        Err(err) => bail!(
+                            "The JWE {} header claim must be a base64 string: {}",
+                            key,
+                            err
+                        ),
+                    }
+                }
+                Some(_) => bail!("The JWE {} header claim must be a string.", key),
+                None => Ok(None),
+            }
+        })()
+        .map_err(JoseError::InvalidJweFormat)
+    }
+
     pub(crate) fn check_claim(key: &str, value: &Value) -> Result<(), JoseError> {
         (|| -> anyhow::Result<()> {
             match key {
@@ -596,6 +848,18 @@ impl JweHeader {
                     Value::Object(vals) => Jwk::check_map(vals)?,
                     _ => bail!("The JWE {} header claim must be a string.", key),
                 },
+                "epk" => match &value {
+                    Value::Object(vals) => {
+                        Jwk::check_map(vals)?;
+                        if vals.contains_key("d") {
+                            bail!(
+                                "The JWE {} header claim must not contain a private key.",
+                                key
+                            );
+                        }
+                    }
+                    _ => bail!("The JWE {} header claim must be a object.", key),
+                },
                 _ => {}
             }
 
@@ -614,6 +878,10 @@ impl JoseHeader for JweHeader {
         self.claims.get(key)
     }
 
+    fn iter(&self) -> Box<dyn Iterator<Item = (&str, &Value)> + '_> {
+        Box::new(self.claims.iter().map(|(key, value)| (key.as_str(), value)))
+    }
+
     fn box_clone(&self) -> Box<dyn JoseHeader> {
         Box::new(self.clone())
     }
@@ -631,6 +899,21 @@ impl Into<Map<String, Value>> for JweHeader {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for JweHeader {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&self.claims, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for JweHeader {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let map: Map<String, Value> = serde::Deserialize::deserialize(deserializer)?;
+        Self::from_map(map).map_err(serde::de::Error::custom)
+    }
+}
+
 impl Display for JweHeader {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         let val = serde_json::to_string(&self.claims).map_err(|_e| std::fmt::Error {})?;
@@ -648,11 +931,130 @@ impl Deref for JweHeader {
 
 #[cfg(test)]
 mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
     use anyhow::Result;
+    use openssl::x509::X509;
     use serde_json::json;
 
     use crate::jwe::JweHeader;
     use crate::jwk::Jwk;
+    use crate::jws::JwsHeader;
+    use crate::{ConflictPolicy, JoseError, JoseHeader};
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_jwe_header_serde() -> Result<()> {
+        let header = JweHeader::from_bytes(br#"{"alg":"PBES2-HS256+A128KW","enc":"A128GCM","p2s":"2WCTcJZ1Rvd_CJuJripQ1w","p2c":4096,"crit":["exp"],"exp":1300819380}"#)?;
+
+        let json = serde_json::to_string(&header)?;
+        assert_eq!(json, header.to_string());
+        assert_eq!(serde_json::from_str::<JweHeader>(&json)?, header);
+
+        let mut cbor = Vec::new();
+        ciborium::ser::into_writer(&header, &mut cbor)?;
+        assert_eq!(
+            ciborium::de::from_reader::<JweHeader, _>(cbor.as_slice())?,
+            header
+        );
+
+        assert!(serde_json::from_str::<JweHeader>(r#"{"enc":["A128GCM"]}"#).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_header_try_getters() -> Result<()> {
+        let header = JweHeader::from_bytes(br#"{"alg":"dir","enc":"A128GCM"}"#)?;
+        assert!(matches!(header.try_ephemeral_public_key(), Ok(None)));
+        assert!(matches!(header.try_agreement_partyuinfo(), Ok(None)));
+
+        let header = JweHeader::from_bytes(
+            br#"{"x5c":["+/+/"],"apu":"a+b/","apv":"AQ","aud":["a","b"],"crit":["exp"]}"#,
+        )?;
+        assert_eq!(
+            header.x509_certificate_chain(),
+            Some(vec![vec![251, 255, 191]])
+        );
+        assert_eq!(header.agreement_partyuinfo(), None);
+        assert!(matches!(
+            header.try_agreement_partyuinfo(),
+            Err(JoseError::InvalidJweFormat(_))
+        ));
+        assert_eq!(header.try_agreement_partyvinfo()?, Some(vec![1]));
+        assert_eq!(header.try_audience()?, Some(vec!["a", "b"]));
+        assert_eq!(header.try_critical()?, Some(vec!["exp"]));
+        assert!(header.check_typed_claims().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_header_x509_from_certificate() -> Result<()> {
+        let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        pb.push("data/pem/RSA_2048bit_private_with_certificate.pem");
+        let der = X509::from_pem(&fs::read(&pb)?)?.to_der()?;
+
+        let mut header = JweHeader::new();
+        header.set_x509_from_certificate(&der)?;
+        assert_eq!(
+            header.claim("x5c"),
+            Some(&json!([base64::encode_config(&der, base64::STANDARD)]))
+        );
+        assert!(header.x509_thumbprint_matches(&der)?);
+
+        header.set_claim("x5t#S256", Some(json!("a+b/")))?;
+        assert!(matches!(
+            header.x509_thumbprint_matches(&der),
+            Err(JoseError::InvalidJweFormat(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwe_header_merge() -> Result<()> {
+        let mut template = JweHeader::new();
+        template.set_algorithm("A128KW");
+        template.set_content_encryption("A128GCM");
+
+        let mut recipient = JweHeader::new();
+        recipient.set_algorithm("A256KW");
+        recipient.set_key_id("kid");
+
+        let mut header = template.clone();
+        assert!(matches!(
+            header.merge(&recipient, ConflictPolicy::Error),
+            Err(JoseError::InvalidJweFormat(_))
+        ));
+        assert_eq!(header, template);
+
+        header.merge(&recipient, ConflictPolicy::KeepExisting)?;
+        assert_eq!(header.algorithm(), Some("A128KW"));
+        assert_eq!(header.key_id(), Some("kid"));
+
+        header.merge(&recipient, ConflictPolicy::Overwrite)?;
+        assert_eq!(header.algorithm(), Some("A256KW"));
+        assert_eq!(header.len(), 3);
+        assert_eq!(
+            header.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+            vec!["alg", "enc", "kid"]
+        );
+
+        let mut raw = JwsHeader::new();
+        raw.set_claim("epk", Some(json!("epk")))?;
+        assert!(header.merge(&raw, ConflictPolicy::Overwrite).is_err());
+        assert!(header.claim("epk").is_none());
+
+        assert_eq!(header.remove_claim("kid"), Some(json!("kid")));
+        assert_eq!(header.remove_claim("kid"), None);
+        header.remove_claim("alg");
+        header.remove_claim("enc");
+        assert!(header.is_empty());
+
+        Ok(())
+    }
 
     #[test]
     fn test_new_jwe_header() -> Result<()> {
@@ -691,6 +1093,7 @@ mod tests {
                 b"x5c1".to_vec(),
             ])
         );
+        assert_eq!(header.claim("x5c"), Some(&json!(["eDVjMA==", "eDVjMQ=="])));
         assert!(
             matches!(header.x509_certificate_sha1_thumbprint(), Some(val) if val == b"x5t".to_vec())
         );
@@ -711,4 +1114,31 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_jwe_header_ephemeral_public_key() -> Result<()> {
+        let key_pair = Jwk::generate_ec_key(crate::jwk::P_256)?;
+        let public_key = key_pair.to_public_key()?;
+
+        let mut header = JweHeader::new();
+        header.set_ephemeral_public_key(public_key.clone())?;
+        assert!(matches!(header.ephemeral_public_key(), Some(val) if val == public_key));
+
+        let json = serde_json::to_vec(header.claims_set())?;
+        let header = JweHeader::from_bytes(&json)?;
+        assert!(matches!(header.ephemeral_public_key(), Some(val) if val == public_key));
+
+        let mut header = JweHeader::new();
+        assert!(header.set_ephemeral_public_key(key_pair.clone()).is_err());
+        assert!(header.ephemeral_public_key().is_none());
+        assert!(header.set_claim("epk", Some(json!("epk"))).is_err());
+        assert!(header
+            .set_claim("epk", Some(json!({ "x": "AAAA" })))
+            .is_err());
+
+        let json = serde_json::to_vec(&json!({ "epk": key_pair.as_ref() }))?;
+        assert!(JweHeader::from_bytes(&json).is_err());
+
+        Ok(())
+    }
 }
@@ -640,6 +640,17 @@ impl JweHeaderSet {
         Ok(())
     }
 
+    /// Remove a header claim of a specified key and return the removed value.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - a key name of header claim
+    pub fn remove_claim(&mut self, key: &str) -> Option<Value> {
+        let protected = self.protected.remove(key);
+        let unprotected = self.unprotected.remove(key);
+        protected.or(unprotected)
+    }
+
     /// Return values for header claims set
     pub fn claims_set(&self, protection: bool) -> &Map<String, Value> {
         if protection {
@@ -671,6 +682,15 @@ impl JoseHeader for JweHeaderSet {
         }
     }
 
+    fn iter(&self) -> Box<dyn Iterator<Item = (&str, &Value)> + '_> {
+        Box::new(
+            self.protected
+                .iter()
+                .chain(self.unprotected.iter())
+                .map(|(key, value)| (key.as_str(), value)),
+        )
+    }
+
     fn box_clone(&self) -> Box<dyn JoseHeader> {
         Box::new(self.clone())
     }
@@ -3,10 +3,10 @@ use std::io::{self, Read, Write};
 use std::ops::Deref;
 
 use flate2::read::DeflateDecoder;
-use flate2::write::DeflateEncoder;
+use flate2::write::{DeflateDecoder as DeflateWriteDecoder, DeflateEncoder};
 use flate2::Compression;
 
-use crate::jwe::JweCompression;
+use crate::jwe::{DecompressedSizeLimitError, JweCompression};
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum DeflateJweCompression {
@@ -35,11 +35,47 @@ impl JweCompression for DeflateJweCompression {
         Ok(vec)
     }
 
+    fn decompress_with_limit(&self, data: &[u8], limit: usize) -> Result<Vec<u8>, io::Error> {
+        let mut decoder = DeflateWriteDecoder::new(BoundedWriter::new(limit));
+        decoder.write_all(data)?;
+        let writer = decoder.finish()?;
+        Ok(writer.buf)
+    }
+
     fn box_clone(&self) -> Box<dyn JweCompression> {
         Box::new(self.clone())
     }
 }
 
+/// A writer that fails as soon as the written data exceeds the limit.
+struct BoundedWriter {
+    buf: Vec<u8>,
+    limit: usize,
+}
+
+impl BoundedWriter {
+    fn new(limit: usize) -> Self {
+        Self {
+            buf: Vec::new(),
+            limit,
+        }
+    }
+}
+
+impl Write for BoundedWriter {
+    fn write(&mut self, data: &[u8]) -> Result<usize, io::Error> {
+        if data.len() > self.limit - self.buf.len() {
+            return Err(DecompressedSizeLimitError(self.limit).into());
+        }
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> Result<(), io::Error> {
+        Ok(())
+    }
+}
+
 impl Display for DeflateJweCompression {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         fmt.write_str(self.name())
@@ -53,3 +89,51 @@ impl Deref for DeflateJweCompression {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::DeflateJweCompression;
+    use crate::jwe::{DecompressedSizeLimitError, JweCompression};
+
+    #[test]
+    fn compress_and_decompress_deflate() -> Result<()> {
+        let zip = DeflateJweCompression::Def;
+        let message = b"test message! test message! test message!";
+
+        let compressed = zip.compress(message)?;
+        assert_eq!(zip.decompress(&compressed)?, message.to_vec());
+        assert_eq!(
+            zip.decompress_with_limit(&compressed, message.len())?,
+            message.to_vec()
+        );
+
+        let err = zip
+            .decompress_with_limit(&compressed, message.len() - 1)
+            .unwrap_err();
+        assert!(DecompressedSizeLimitError::is_cause_of(&err));
+
+        let err = zip.decompress_with_limit(b"invalid", 1024).unwrap_err();
+        assert!(!DecompressedSizeLimitError::is_cause_of(&err));
+
+        Ok(())
+    }
+
+    #[test]
+    fn decompress_deflate_bomb_aborts_early() -> Result<()> {
+        let zip = DeflateJweCompression::Def;
+
+        // 1 MiB of zeros deflates to about 1 KiB. Dropping the tail makes the
+        // stream invalid, so only an early abort can yield the limit error.
+        let mut bomb = zip.compress(&vec![0; 1024 * 1024])?;
+        assert!(bomb.len() * 100 < 1024 * 1024);
+        bomb.truncate(bomb.len() / 2);
+        assert!(zip.decompress(&bomb).is_err());
+
+        let err = zip.decompress_with_limit(&bomb, 16 * 1024).unwrap_err();
+        assert!(DecompressedSizeLimitError::is_cause_of(&err));
+
+        Ok(())
+    }
+}
@@ -1,18 +1,52 @@
 //! JSON Web Key (JWK) support.
+//!
+//! A JWK is constructed from typed parameters by [`JwkBuilder`].
+//!
+//! ```
+//! use josekit::jwk::JwkBuilder;
+//!
+//! let jwk = JwkBuilder::new()
+//!     .kid("key-1")
+//!     .key_use("sig")
+//!     .alg("HS256")
+//!     .oct(b"a secret key that is at least 32 bytes")
+//!     .build()?;
+//! assert_eq!(jwk.key_type(), "oct");
+//! # Ok::<(), josekit::JoseError>(())
+//! ```
 
 pub mod alg;
 
 mod jwk;
+mod jwk_builder;
+mod jwk_curve;
+mod jwk_export_options;
+mod jwk_parse_error;
+mod jwk_selector;
 mod jwk_set;
 mod key_info;
-mod key_pair;
+mod key_operation;
+pub mod key_pair;
+mod oct_key_builder;
+mod oct_key_policy;
 
 pub use crate::jwk::jwk::Jwk;
+pub use crate::jwk::jwk_builder::JwkBuilder;
+pub use crate::jwk::jwk_curve::JwkCurve;
+pub use crate::jwk::jwk_export_options::JwkExportOptions;
+pub use crate::jwk::jwk_parse_error::JwkParseError;
+pub use crate::jwk::jwk_selector::JwkSelector;
 pub use crate::jwk::jwk_set::JwkSet;
 pub use crate::jwk::key_info::KeyAlg;
 pub use crate::jwk::key_info::KeyFormat;
 pub use crate::jwk::key_info::KeyInfo;
+pub use crate::jwk::key_operation::KeyOperation;
 pub use crate::jwk::key_pair::KeyPair;
+pub use crate::jwk::oct_key_builder::OctKeyBuilder;
+pub use crate::jwk::oct_key_policy::OctKeyPolicy;
+
+pub use crate::jwk::alg::ec::EcCurve;
+pub use crate::jwk::alg::okp::OkpCurve;
 
 pub use crate::jwk::alg::ec::EcCurve::Secp256k1;
 pub use crate::jwk::alg::ec::EcCurve::P256 as P_256;
@@ -1,5 +1,6 @@
 pub mod ec;
 pub mod ecx;
 pub mod ed;
+pub mod okp;
 pub mod rsa;
 pub mod rsapss;
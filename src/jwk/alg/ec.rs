@@ -2,35 +2,126 @@ use std::fmt::Display;
 use std::ops::Deref;
 
 use anyhow::bail;
-use openssl::bn::{BigNum, BigNumContext};
-use openssl::ec::{EcGroup, EcKey};
+use openssl::bn::{BigNum, BigNumContext, BigNumContextRef, BigNumRef};
+use openssl::ec::{EcGroup, EcGroupRef, EcKey, EcKeyRef, EcPoint, PointConversionForm};
 use openssl::nid::Nid;
-use openssl::pkey::{PKey, Private};
+use openssl::pkey::{HasPublic, PKey, Private, Public};
 
-use crate::jwk::{Jwk, KeyPair};
+use crate::jwk::{Jwk, JwkCurve, KeyPair};
 use crate::util;
 use crate::util::der::{DerBuilder, DerClass, DerReader, DerType};
 use crate::util::oid::{
     ObjectIdentifier, OID_ID_EC_PUBLIC_KEY, OID_PRIME256V1, OID_SECP256K1, OID_SECP384R1,
     OID_SECP521R1,
 };
+#[cfg(feature = "brainpool")]
+use crate::util::oid::{OID_BRAINPOOL_P256R1, OID_BRAINPOOL_P384R1, OID_BRAINPOOL_P512R1};
 use crate::{JoseError, Value};
 
+/// The byte length of a coordinate of brainpoolP256r1.
+#[cfg(feature = "brainpool")]
+const BP256R1_COORDINATE_SIZE: usize = 32;
+/// The byte length of a coordinate of brainpoolP384r1.
+#[cfg(feature = "brainpool")]
+const BP384R1_COORDINATE_SIZE: usize = 48;
+/// The byte length of a coordinate of brainpoolP512r1.
+#[cfg(feature = "brainpool")]
+const BP512R1_COORDINATE_SIZE: usize = 64;
+
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[non_exhaustive]
 pub enum EcCurve {
     P256,
     P384,
     P521,
     Secp256k1,
+    /// brainpoolP256r1
+    #[cfg(feature = "brainpool")]
+    Bp256r1,
+    /// brainpoolP384r1
+    #[cfg(feature = "brainpool")]
+    Bp384r1,
+    /// brainpoolP512r1
+    #[cfg(feature = "brainpool")]
+    Bp512r1,
 }
 
 impl EcCurve {
+    /// Return a curve from the name of the crv parameter.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A curve name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "P-256" => Some(Self::P256),
+            "P-384" => Some(Self::P384),
+            "P-521" => Some(Self::P521),
+            "secp256k1" => Some(Self::Secp256k1),
+            #[cfg(feature = "brainpool")]
+            "BP256R1" => Some(Self::Bp256r1),
+            #[cfg(feature = "brainpool")]
+            "BP384R1" => Some(Self::Bp384r1),
+            #[cfg(feature = "brainpool")]
+            "BP512R1" => Some(Self::Bp512r1),
+            _ => None,
+        }
+    }
+
+    /// Return a curve from the OID of the named curve.
+    ///
+    /// # Arguments
+    ///
+    /// * `oid` - A OID of the named curve
+    pub(crate) fn from_oid(oid: &ObjectIdentifier) -> Option<Self> {
+        match oid {
+            val if *val == *OID_PRIME256V1 => Some(Self::P256),
+            val if *val == *OID_SECP384R1 => Some(Self::P384),
+            val if *val == *OID_SECP521R1 => Some(Self::P521),
+            val if *val == *OID_SECP256K1 => Some(Self::Secp256k1),
+            #[cfg(feature = "brainpool")]
+            val if *val == *OID_BRAINPOOL_P256R1 => Some(Self::Bp256r1),
+            #[cfg(feature = "brainpool")]
+            val if *val == *OID_BRAINPOOL_P384R1 => Some(Self::Bp384r1),
+            #[cfg(feature = "brainpool")]
+            val if *val == *OID_BRAINPOOL_P512R1 => Some(Self::Bp512r1),
+            _ => None,
+        }
+    }
+
+    /// Return a curve from the openssl NID of the curve.
+    ///
+    /// # Arguments
+    ///
+    /// * `nid` - A NID of the curve
+    pub(crate) fn from_nid(nid: Nid) -> Option<Self> {
+        match nid {
+            Nid::X9_62_PRIME256V1 => Some(Self::P256),
+            Nid::SECP384R1 => Some(Self::P384),
+            Nid::SECP521R1 => Some(Self::P521),
+            Nid::SECP256K1 => Some(Self::Secp256k1),
+            #[cfg(feature = "brainpool")]
+            Nid::BRAINPOOL_P256R1 => Some(Self::Bp256r1),
+            #[cfg(feature = "brainpool")]
+            Nid::BRAINPOOL_P384R1 => Some(Self::Bp384r1),
+            #[cfg(feature = "brainpool")]
+            Nid::BRAINPOOL_P512R1 => Some(Self::Bp512r1),
+            _ => None,
+        }
+    }
+
     pub fn name(&self) -> &str {
         match self {
             Self::P256 => "P-256",
             Self::P384 => "P-384",
             Self::P521 => "P-521",
             Self::Secp256k1 => "secp256k1",
+            #[cfg(feature = "brainpool")]
+            Self::Bp256r1 => "BP256R1",
+            #[cfg(feature = "brainpool")]
+            Self::Bp384r1 => "BP384R1",
+            #[cfg(feature = "brainpool")]
+            Self::Bp512r1 => "BP512R1",
         }
     }
 
@@ -40,23 +131,63 @@ impl EcCurve {
             Self::P384 => &OID_SECP384R1,
             Self::P521 => &OID_SECP521R1,
             Self::Secp256k1 => &OID_SECP256K1,
+            #[cfg(feature = "brainpool")]
+            Self::Bp256r1 => &OID_BRAINPOOL_P256R1,
+            #[cfg(feature = "brainpool")]
+            Self::Bp384r1 => &OID_BRAINPOOL_P384R1,
+            #[cfg(feature = "brainpool")]
+            Self::Bp512r1 => &OID_BRAINPOOL_P512R1,
         }
     }
 
-    fn nid(&self) -> Nid {
+    /// Return the openssl NID of the curve.
+    pub fn nid(&self) -> Nid {
         match self {
             Self::P256 => Nid::X9_62_PRIME256V1,
             Self::P384 => Nid::SECP384R1,
             Self::P521 => Nid::SECP521R1,
             Self::Secp256k1 => Nid::SECP256K1,
+            #[cfg(feature = "brainpool")]
+            Self::Bp256r1 => Nid::BRAINPOOL_P256R1,
+            #[cfg(feature = "brainpool")]
+            Self::Bp384r1 => Nid::BRAINPOOL_P384R1,
+            #[cfg(feature = "brainpool")]
+            Self::Bp512r1 => Nid::BRAINPOOL_P512R1,
+        }
+    }
+
+    #[cfg_attr(not(feature = "jws-ecdsa"), allow(dead_code))]
+    pub(crate) fn is_openssl_supported(&self) -> bool {
+        EcGroup::from_curve_name(self.nid()).is_ok()
+    }
+
+    /// Return the bit length of the field of the curve.
+    pub fn key_bits(&self) -> u32 {
+        match self {
+            Self::P256 | Self::Secp256k1 => 256,
+            Self::P384 => 384,
+            Self::P521 => 521,
+            #[cfg(feature = "brainpool")]
+            Self::Bp256r1 => 256,
+            #[cfg(feature = "brainpool")]
+            Self::Bp384r1 => 384,
+            #[cfg(feature = "brainpool")]
+            Self::Bp512r1 => 512,
         }
     }
 
-    fn coordinate_size(&self) -> usize {
+    /// Return the byte length of a coordinate (x, y) and the private key (d).
+    pub fn coordinate_size(&self) -> usize {
         match self {
             Self::P256 | Self::Secp256k1 => 32,
             Self::P384 => 48,
             Self::P521 => 66,
+            #[cfg(feature = "brainpool")]
+            Self::Bp256r1 => BP256R1_COORDINATE_SIZE,
+            #[cfg(feature = "brainpool")]
+            Self::Bp384r1 => BP384R1_COORDINATE_SIZE,
+            #[cfg(feature = "brainpool")]
+            Self::Bp512r1 => BP512R1_COORDINATE_SIZE,
         }
     }
 }
@@ -95,6 +226,10 @@ impl EcKeyPair {
         }
     }
 
+    #[cfg_attr(
+        not(any(feature = "jws-ecdsa", feature = "jwe-ecdh")),
+        allow(dead_code)
+    )]
     pub(crate) fn into_private_key(self) -> PKey<Private> {
         self.private_key
     }
@@ -156,6 +291,44 @@ impl EcKeyPair {
         })
     }
 
+    /// Create a EC key pair from a DER encoded PKCS#8 EncryptedPrivateKeyInfo.
+    ///
+    /// # Arguments
+    /// * `input` - A DER encoded PKCS#8 EncryptedPrivateKeyInfo.
+    /// * `passphrase` - A passphrase that must not be empty.
+    pub fn from_encrypted_der(
+        input: impl AsRef<[u8]>,
+        passphrase: impl AsRef<[u8]>,
+    ) -> Result<Self, JoseError> {
+        let der = util::decrypt_pkcs8(input.as_ref(), passphrase.as_ref())
+            .map_err(JoseError::InvalidKeyFormat)?;
+        Self::from_der(&der, None)
+    }
+
+    /// Create a EC key pair from a PKCS#8 EncryptedPrivateKeyInfo
+    /// that surrounded by "-----BEGIN/END ENCRYPTED PRIVATE KEY----".
+    ///
+    /// # Arguments
+    /// * `input` - A private key of encrypted PEM format.
+    /// * `passphrase` - A passphrase that must not be empty.
+    pub fn from_encrypted_pem(
+        input: impl AsRef<[u8]>,
+        passphrase: impl AsRef<[u8]>,
+    ) -> Result<Self, JoseError> {
+        (|| -> anyhow::Result<Self> {
+            let (alg, data) = util::parse_pem(input.as_ref())?;
+            match alg.as_str() {
+                "ENCRYPTED PRIVATE KEY" => {}
+                alg => bail!("Inappropriate algorithm: {}", alg),
+            }
+            Ok(Self::from_encrypted_der(&data, passphrase)?)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidKeyFormat(err),
+        })
+    }
+
     /// Return a signer from a private key that is formatted by a JWK of EC type.
     ///
     /// # Arguments
@@ -167,15 +340,9 @@ impl EcKeyPair {
                 val if val == "EC" => {}
                 val => bail!("A parameter kty must be EC: {}", val),
             }
-            let curve = match jwk.parameter("crv") {
-                Some(Value::String(val)) => match val.as_str() {
-                    "P-256" => EcCurve::P256,
-                    "P-384" => EcCurve::P384,
-                    "P-521" => EcCurve::P521,
-                    "secp256k1" => EcCurve::Secp256k1,
-                    _ => bail!("A Unknown curve: {}", val),
-                },
-                Some(_) => bail!("A parameter crv must be a string."),
+            let curve = match jwk.curve() {
+                Some(JwkCurve::Ec(val)) => val,
+                Some(val) => bail!("A Unknown curve: {}", val),
                 None => bail!("A parameter crv is required."),
             };
             let d = match jwk.parameter("d") {
@@ -246,6 +413,39 @@ impl EcKeyPair {
         .map_err(|err| JoseError::InvalidKeyFormat(err))
     }
 
+    /// Create a EC key pair from raw affine coordinates and a private scalar.
+    ///
+    /// A key pair always has a private key. Use [`EcKeyPair::jwk_from_components`]
+    /// to import a key whose private scalar is optional.
+    ///
+    /// # Arguments
+    ///
+    /// * `curve` - EC curve
+    /// * `x` - A big-endian x coordinate that is zero-padded to the coordinate size.
+    /// * `y` - A big-endian y coordinate that is zero-padded to the coordinate size.
+    /// * `d` - A big-endian private scalar that is zero-padded to the coordinate size.
+    pub fn from_components(
+        curve: EcCurve,
+        x: &[u8],
+        y: &[u8],
+        d: &[u8],
+    ) -> Result<Self, JoseError> {
+        (|| -> anyhow::Result<Self> {
+            let ec_key = Self::private_key_from_components(curve, x, y, d)?;
+
+            Ok(EcKeyPair {
+                private_key: PKey::from_ec_key(ec_key)?,
+                curve,
+                algorithm: None,
+                key_id: None,
+            })
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidKeyFormat(err),
+        })
+    }
+
     /// Create a Ec key pair from a private key of common or traditinal PEM format.
     ///
     /// Common PEM format is a DER and base64 encoded PKCS#8 PrivateKeyInfo
@@ -313,11 +513,37 @@ impl EcKeyPair {
         ec_key.private_key_to_der().unwrap()
     }
 
+    /// Return a DER encoded PKCS#8 PrivateKeyInfo.
+    ///
+    /// `to_der_private_key` returns a SEC1 ECPrivateKey instead.
+    pub fn to_pkcs8_der_private_key(&self) -> Vec<u8> {
+        Self::to_pkcs8(&self.to_raw_private_key(), false, self.curve)
+    }
+
     pub fn to_traditional_pem_private_key(&self) -> Vec<u8> {
         let ec_key = self.private_key.ec_key().unwrap();
         ec_key.private_key_to_pem().unwrap()
     }
 
+    /// Return a raw private key scalar that is zero-padded to the coordinate size of the curve.
+    ///
+    /// The scalar is the secret itself, so it must be handled with the same care
+    /// as the DER encoded private key.
+    pub fn to_raw_private_scalar(&self) -> Vec<u8> {
+        let ec_key = self.private_key.ec_key().unwrap();
+        util::num_to_vec(ec_key.private_key(), self.curve.coordinate_size())
+    }
+
+    /// Return a public key as a SEC1 uncompressed point (0x04 || X || Y).
+    pub fn to_sec1_public_key(&self) -> Vec<u8> {
+        let ec_key = self.private_key.ec_key().unwrap();
+        let mut ctx = BigNumContext::new().unwrap();
+        ec_key
+            .public_key()
+            .to_bytes(ec_key.group(), PointConversionForm::UNCOMPRESSED, &mut ctx)
+            .unwrap()
+    }
+
     fn to_jwk(&self, private: bool, public: bool) -> Jwk {
         let ec_key = self.private_key.ec_key().unwrap();
 
@@ -338,24 +564,213 @@ impl EcKeyPair {
             jwk.set_parameter("d", Some(Value::String(d))).unwrap();
         }
         if public {
-            let public_key = ec_key.public_key();
-            let mut x = BigNum::new().unwrap();
-            let mut y = BigNum::new().unwrap();
-            let mut ctx = BigNumContext::new().unwrap();
-            public_key
-                .affine_coordinates_gfp(ec_key.group(), &mut x, &mut y, &mut ctx)
-                .unwrap();
+            Self::set_public_coordinates(&mut jwk, &ec_key, self.curve);
+        }
+        jwk
+    }
 
-            let x = util::num_to_vec(&x, self.curve.coordinate_size());
-            let x = base64::encode_config(&x, base64::URL_SAFE_NO_PAD);
+    fn set_public_coordinates<T: HasPublic>(jwk: &mut Jwk, ec_key: &EcKeyRef<T>, curve: EcCurve) {
+        let public_key = ec_key.public_key();
+        let mut x = BigNum::new().unwrap();
+        let mut y = BigNum::new().unwrap();
+        let mut ctx = BigNumContext::new().unwrap();
+        public_key
+            .affine_coordinates_gfp(ec_key.group(), &mut x, &mut y, &mut ctx)
+            .unwrap();
+
+        let x = util::num_to_vec(&x, curve.coordinate_size());
+        let x = base64::encode_config(&x, base64::URL_SAFE_NO_PAD);
 
-            let y = util::num_to_vec(&y, self.curve.coordinate_size());
-            let y = base64::encode_config(&y, base64::URL_SAFE_NO_PAD);
+        let y = util::num_to_vec(&y, curve.coordinate_size());
+        let y = base64::encode_config(&y, base64::URL_SAFE_NO_PAD);
+
+        jwk.set_parameter("x", Some(Value::String(x))).unwrap();
+        jwk.set_parameter("y", Some(Value::String(y))).unwrap();
+    }
+
+    /// Return a public key that is formatted by a JWK of EC type from a SEC1 encoded point.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - A SEC1 encoded point that is uncompressed (0x04) or compressed (0x02 or 0x03).
+    /// * `curve` - EC curve
+    pub fn public_jwk_from_sec1(point: &[u8], curve: EcCurve) -> Result<Jwk, JoseError> {
+        let ec_key =
+            Self::public_key_from_sec1(point, curve).map_err(JoseError::InvalidKeyFormat)?;
+
+        let mut jwk = Jwk::new("EC");
+        jwk.set_parameter("crv", Some(Value::String(curve.to_string())))
+            .unwrap();
+        Self::set_public_coordinates(&mut jwk, &ec_key, curve);
+        Ok(jwk)
+    }
 
-            jwk.set_parameter("x", Some(Value::String(x))).unwrap();
-            jwk.set_parameter("y", Some(Value::String(y))).unwrap();
+    /// Return a key that is formatted by a JWK of EC type from raw affine coordinates
+    /// and an optional private scalar.
+    ///
+    /// The JWK is a key pair when the private scalar is given, otherwise a public key.
+    ///
+    /// # Arguments
+    ///
+    /// * `curve` - EC curve
+    /// * `x` - A big-endian x coordinate that is zero-padded to the coordinate size.
+    /// * `y` - A big-endian y coordinate that is zero-padded to the coordinate size.
+    /// * `d` - A big-endian private scalar that is zero-padded to the coordinate size.
+    pub fn jwk_from_components(
+        curve: EcCurve,
+        x: &[u8],
+        y: &[u8],
+        d: Option<&[u8]>,
+    ) -> Result<Jwk, JoseError> {
+        if let Some(d) = d {
+            return Ok(Self::from_components(curve, x, y, d)?.to_jwk_key_pair());
         }
-        jwk
+
+        let ec_key =
+            Self::public_key_from_components(curve, x, y).map_err(JoseError::InvalidKeyFormat)?;
+
+        let mut jwk = Jwk::new("EC");
+        jwk.set_parameter("crv", Some(Value::String(curve.to_string())))
+            .unwrap();
+        Self::set_public_coordinates(&mut jwk, &ec_key, curve);
+        Ok(jwk)
+    }
+
+    /// Return a private key from the affine coordinates and the private scalar
+    /// after checking that the point (x, y) is equal to d * G.
+    pub(crate) fn private_key_from_components(
+        curve: EcCurve,
+        x: &[u8],
+        y: &[u8],
+        d: &[u8],
+    ) -> anyhow::Result<EcKey<Private>> {
+        let public_key = Self::public_key_from_components(curve, x, y)?;
+        if d.len() != curve.coordinate_size() {
+            bail!(
+                "The private key (d) length must be {} for {}: {}",
+                curve.coordinate_size(),
+                curve,
+                d.len()
+            );
+        }
+
+        let group = public_key.group();
+        let mut ctx = BigNumContext::new()?;
+        let mut order = BigNum::new()?;
+        group.order(&mut order, &mut ctx)?;
+        let d = BigNum::from_slice(d)?;
+        if d.num_bits() == 0 || d >= order {
+            bail!("The private key (d) is out of range for {}.", curve);
+        }
+
+        let mut expected = EcPoint::new(group)?;
+        expected.mul_generator2(group, &d, &mut ctx)?;
+        if !expected.eq(group, public_key.public_key(), &mut ctx)? {
+            bail!("The public key (x, y) does not correspond to the private key (d).");
+        }
+
+        let ec_key = EcKey::from_private_components(group, &d, public_key.public_key())?;
+        ec_key.check_key()?;
+        Ok(ec_key)
+    }
+
+    pub(crate) fn public_key_from_components(
+        curve: EcCurve,
+        x: &[u8],
+        y: &[u8],
+    ) -> anyhow::Result<EcKey<Public>> {
+        Self::check_coordinate_len(curve, x, y)?;
+
+        let group = EcGroup::from_curve_name(curve.nid())?;
+        let mut ctx = BigNumContext::new()?;
+        let x = BigNum::from_slice(x)?;
+        let y = BigNum::from_slice(y)?;
+        if !Self::is_on_curve(&group, &x, &y, &mut ctx)? {
+            bail!("The point (x, y) is not on the curve: {}", curve);
+        }
+
+        let ec_key = EcKey::from_public_key_affine_coordinates(&group, &x, &y)?;
+        ec_key.check_key()?;
+        Ok(ec_key)
+    }
+
+    /// Check that the affine coordinates are zero-padded to the coordinate size of the curve
+    /// as required by RFC 7518.
+    pub(crate) fn check_coordinate_len(curve: EcCurve, x: &[u8], y: &[u8]) -> anyhow::Result<()> {
+        for (name, val) in &[("x", x), ("y", y)] {
+            if val.len() != curve.coordinate_size() {
+                bail!(
+                    "The {} coordinate length must be {} for {}: {}",
+                    name,
+                    curve.coordinate_size(),
+                    curve,
+                    val.len()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Test whether the affine coordinates are in the field and satisfy the curve equation
+    /// y^2 = x^3 + ax + b (mod p).
+    fn is_on_curve(
+        group: &EcGroupRef,
+        x: &BigNumRef,
+        y: &BigNumRef,
+        ctx: &mut BigNumContextRef,
+    ) -> anyhow::Result<bool> {
+        let mut p = BigNum::new()?;
+        let mut a = BigNum::new()?;
+        let mut b = BigNum::new()?;
+        group.components_gfp(&mut p, &mut a, &mut b, ctx)?;
+        if x >= &p || y >= &p {
+            return Ok(false);
+        }
+
+        let mut lhs = BigNum::new()?;
+        lhs.mod_sqr(y, &p, ctx)?;
+
+        let mut x2 = BigNum::new()?;
+        x2.mod_sqr(x, &p, ctx)?;
+        let mut x2_a = BigNum::new()?;
+        x2_a.mod_add(&x2, &a, &p, ctx)?;
+        let mut x3_ax = BigNum::new()?;
+        x3_ax.mod_mul(&x2_a, x, &p, ctx)?;
+        let mut rhs = BigNum::new()?;
+        rhs.mod_add(&x3_ax, &b, &p, ctx)?;
+
+        Ok(lhs == rhs)
+    }
+
+    pub(crate) fn public_key_from_sec1(
+        point: &[u8],
+        curve: EcCurve,
+    ) -> anyhow::Result<EcKey<Public>> {
+        let expected_len = match point.first() {
+            Some(0x04) => 1 + curve.coordinate_size() * 2,
+            Some(0x02) | Some(0x03) => 1 + curve.coordinate_size(),
+            Some(val) => bail!("The SEC1 point format is unsupported: {:#04x}", val),
+            None => bail!("The SEC1 point must not be empty."),
+        };
+        if point.len() != expected_len {
+            bail!(
+                "The SEC1 point length must be {} for {}: {}",
+                expected_len,
+                curve,
+                point.len()
+            );
+        }
+
+        let group = EcGroup::from_curve_name(curve.nid())?;
+        let mut ctx = BigNumContext::new()?;
+        let ec_point = EcPoint::from_bytes(&group, point, &mut ctx)?;
+        if !ec_point.is_on_curve(&group, &mut ctx)? {
+            bail!("The SEC1 point is not on the curve: {}", curve);
+        }
+        let ec_key = EcKey::from_public_key(&group, &ec_point)?;
+        ec_key.check_key()?;
+
+        Ok(ec_key)
     }
 
     pub(crate) fn detect_pkcs8(input: impl AsRef<[u8]>, is_public: bool) -> Option<EcCurve> {
@@ -399,10 +814,7 @@ impl EcKeyPair {
 
                 curve = match reader.next() {
                     Ok(Some(DerType::ObjectIdentifier)) => match reader.to_object_identifier() {
-                        Ok(val) if val == *OID_PRIME256V1 => EcCurve::P256,
-                        Ok(val) if val == *OID_SECP384R1 => EcCurve::P384,
-                        Ok(val) if val == *OID_SECP521R1 => EcCurve::P521,
-                        Ok(val) if val == *OID_SECP256K1 => EcCurve::Secp256k1,
+                        Ok(val) => EcCurve::from_oid(&val)?,
                         _ => return None,
                     },
                     _ => return None,
@@ -448,10 +860,7 @@ impl EcKeyPair {
                 // NamedCurve
                 curve = match reader.next() {
                     Ok(Some(DerType::ObjectIdentifier)) => match reader.to_object_identifier() {
-                        Ok(val) if val == *OID_PRIME256V1 => EcCurve::P256,
-                        Ok(val) if val == *OID_SECP384R1 => EcCurve::P384,
-                        Ok(val) if val == *OID_SECP521R1 => EcCurve::P521,
-                        Ok(val) if val == *OID_SECP256K1 => EcCurve::Secp256k1,
+                        Ok(val) => EcCurve::from_oid(&val)?,
                         _ => return None,
                     },
                     _ => return None,
@@ -497,6 +906,10 @@ impl KeyPair for EcKeyPair {
         }
     }
 
+    fn set_algorithm(&mut self, value: Option<&str>) {
+        EcKeyPair::set_algorithm(self, value);
+    }
+
     fn key_id(&self) -> Option<&str> {
         match &self.key_id {
             Some(val) => Some(val.as_str()),
@@ -532,6 +945,10 @@ impl KeyPair for EcKeyPair {
         self.to_jwk(true, true)
     }
 
+    fn as_ec(&self) -> Option<&EcKeyPair> {
+        Some(self)
+    }
+
     fn box_clone(&self) -> Box<dyn KeyPair> {
         Box::new(self.clone())
     }
@@ -549,7 +966,13 @@ impl Deref for EcKeyPair {
 mod tests {
     use anyhow::Result;
 
+    use openssl::bn::BigNumContext;
+    use openssl::ec::{EcKey, PointConversionForm};
+    use openssl::pkey::PKey;
+
     use super::{EcCurve, EcKeyPair};
+    use crate::jwk::{Jwk, KeyPair};
+    use crate::{JoseError, Value};
 
     #[test]
     fn test_ec_jwt() -> Result<()> {
@@ -575,4 +998,181 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_ec_raw_private_scalar() -> Result<()> {
+        for curve in &[
+            EcCurve::P256,
+            EcCurve::P384,
+            EcCurve::P521,
+            EcCurve::Secp256k1,
+        ] {
+            let key_pair_1 = EcKeyPair::generate(*curve)?;
+            let scalar = key_pair_1.to_raw_private_scalar();
+            assert_eq!(scalar.len(), curve.coordinate_size());
+
+            let mut jwk = Jwk::new("EC");
+            jwk.set_parameter("crv", Some(Value::String(curve.name().to_string())))?;
+            jwk.set_parameter(
+                "d",
+                Some(Value::String(base64::encode_config(
+                    &scalar,
+                    base64::URL_SAFE_NO_PAD,
+                ))),
+            )?;
+
+            let key_pair_2 = EcKeyPair::from_jwk(&jwk)?;
+            assert_eq!(key_pair_2.to_raw_private_scalar(), scalar);
+            assert_eq!(
+                key_pair_1.to_jwk_private_key().parameter("d"),
+                jwk.parameter("d")
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ec_sec1_public_key() -> Result<()> {
+        for curve in &[
+            EcCurve::P256,
+            EcCurve::P384,
+            EcCurve::P521,
+            EcCurve::Secp256k1,
+        ] {
+            let key_pair = EcKeyPair::generate(*curve)?;
+            let sec1 = key_pair.to_sec1_public_key();
+
+            assert_eq!(sec1.len(), 1 + curve.coordinate_size() * 2);
+            assert_eq!(sec1[0], 0x04);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ec_der_private_key() -> Result<()> {
+        for curve in &[
+            EcCurve::P256,
+            EcCurve::P384,
+            EcCurve::P521,
+            EcCurve::Secp256k1,
+        ] {
+            let key_pair = EcKeyPair::generate(*curve)?;
+
+            let sec1 = key_pair.to_der_private_key();
+            assert!(EcKey::private_key_from_der(&sec1).is_ok());
+            let loaded = EcKeyPair::from_der(&sec1, Some(*curve))?;
+            assert_eq!(loaded.to_jwk_key_pair(), key_pair.to_jwk_key_pair());
+
+            let pkcs8 = key_pair.to_pkcs8_der_private_key();
+            assert!(PKey::private_key_from_pkcs8(&pkcs8).is_ok());
+            let loaded = EcKeyPair::from_der(&pkcs8, Some(*curve))?;
+            assert_eq!(loaded.to_jwk_key_pair(), key_pair.to_jwk_key_pair());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ec_compressed_sec1_to_jwk() -> Result<()> {
+        for curve in &[
+            EcCurve::P256,
+            EcCurve::P384,
+            EcCurve::P521,
+            EcCurve::Secp256k1,
+        ] {
+            let key_pair = EcKeyPair::generate(*curve)?;
+            let ec_key = key_pair.private_key.ec_key()?;
+            let mut ctx = BigNumContext::new()?;
+            let compressed = ec_key.public_key().to_bytes(
+                ec_key.group(),
+                PointConversionForm::COMPRESSED,
+                &mut ctx,
+            )?;
+            assert_eq!(compressed.len(), 1 + curve.coordinate_size());
+
+            let jwk = EcKeyPair::public_jwk_from_sec1(&compressed, *curve)?;
+            assert_eq!(jwk, key_pair.to_jwk_public_key());
+            for name in &["x", "y"] {
+                let val = match jwk.parameter(name) {
+                    Some(Value::String(val)) => {
+                        base64::decode_config(val, base64::URL_SAFE_NO_PAD)?
+                    }
+                    _ => unreachable!(),
+                };
+                assert_eq!(val.len(), curve.coordinate_size());
+            }
+
+            let mut invalid_prefix = compressed.clone();
+            invalid_prefix[0] = 0x01;
+            assert!(EcKeyPair::public_jwk_from_sec1(&invalid_prefix, *curve).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ec_from_components() -> Result<()> {
+        let decode = |jwk: &Jwk, name: &str| -> Vec<u8> {
+            match jwk.parameter(name) {
+                Some(Value::String(val)) => {
+                    base64::decode_config(val, base64::URL_SAFE_NO_PAD).unwrap()
+                }
+                _ => unreachable!(),
+            }
+        };
+
+        for curve in &[
+            EcCurve::P256,
+            EcCurve::P384,
+            EcCurve::P521,
+            EcCurve::Secp256k1,
+        ] {
+            let key_pair_1 = EcKeyPair::generate(*curve)?;
+            let jwk = key_pair_1.to_jwk_key_pair();
+            let (x, y, d) = (decode(&jwk, "x"), decode(&jwk, "y"), decode(&jwk, "d"));
+
+            let key_pair_2 = EcKeyPair::from_components(*curve, &x, &y, &d)?;
+            assert_eq!(
+                key_pair_2.to_jwk_private_key(),
+                key_pair_1.to_jwk_private_key()
+            );
+            assert_eq!(
+                key_pair_2.to_jwk_public_key(),
+                key_pair_1.to_jwk_public_key()
+            );
+            assert_eq!(
+                EcKeyPair::jwk_from_components(*curve, &x, &y, None)?,
+                key_pair_1.to_jwk_public_key()
+            );
+            assert_eq!(
+                EcKeyPair::jwk_from_components(*curve, &x, &y, Some(&d))?,
+                key_pair_1.to_jwk_key_pair()
+            );
+
+            let mut short_x = x.clone();
+            short_x.remove(0);
+            assert!(EcKeyPair::from_components(*curve, &short_x, &y, &d).is_err());
+            assert!(EcKeyPair::from_components(*curve, &x, &y, &d[1..]).is_err());
+
+            let mut off_curve_y = y.clone();
+            let last = off_curve_y.len() - 1;
+            off_curve_y[last] ^= 1;
+            assert!(EcKeyPair::from_components(*curve, &x, &off_curve_y, &d).is_err());
+            assert!(EcKeyPair::jwk_from_components(*curve, &x, &off_curve_y, None).is_err());
+            assert!(EcKeyPair::jwk_from_components(*curve, &x, &off_curve_y, Some(&d)).is_err());
+
+            let other = EcKeyPair::generate(*curve)?.to_jwk_key_pair();
+            match EcKeyPair::from_components(*curve, &x, &y, &decode(&other, "d")) {
+                Err(JoseError::InvalidKeyFormat(err)) => assert_eq!(
+                    err.to_string(),
+                    "The public key (x, y) does not correspond to the private key (d)."
+                ),
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        Ok(())
+    }
 }
@@ -4,7 +4,7 @@ use std::ops::Deref;
 use anyhow::bail;
 use openssl::pkey::{PKey, Private};
 
-use crate::jwk::{Jwk, KeyPair};
+use crate::jwk::{Jwk, JwkCurve, KeyPair};
 use crate::util;
 use crate::util::der::{DerBuilder, DerReader, DerType};
 use crate::util::oid::{ObjectIdentifier, OID_X25519, OID_X448};
@@ -62,6 +62,7 @@ impl EcxKeyPair {
         }
     }
 
+    #[cfg_attr(not(feature = "jwe-ecdh"), allow(dead_code))]
     pub(crate) fn into_private_key(self) -> PKey<Private> {
         self.private_key
     }
@@ -177,13 +178,9 @@ impl EcxKeyPair {
                 val if val == "OKP" => {}
                 val => bail!("A parameter kty must be OKP: {}", val),
             }
-            let curve = match jwk.parameter("crv") {
-                Some(Value::String(val)) => match val.as_str() {
-                    "X25519" => EcxCurve::X25519,
-                    "X448" => EcxCurve::X448,
-                    _ => bail!("A parameter crv is unrecognized: {}", val),
-                },
-                Some(_) => bail!("A parameter crv must be a string."),
+            let curve = match jwk.curve() {
+                Some(JwkCurve::Okp(val)) if val.ecx_curve().is_some() => val.ecx_curve().unwrap(),
+                Some(val) => bail!("A parameter crv is unrecognized: {}", val),
                 None => bail!("A parameter crv is required."),
             };
             let d = match jwk.parameter("d") {
@@ -236,6 +233,12 @@ impl EcxKeyPair {
     fn to_jwk(&self, private: bool, public: bool) -> Jwk {
         let mut jwk = Jwk::new("OKP");
         jwk.set_key_use("enc");
+        if let Some(val) = &self.algorithm {
+            jwk.set_algorithm(val);
+        }
+        if let Some(val) = &self.key_id {
+            jwk.set_key_id(val);
+        }
         jwk.set_parameter("crv", Some(Value::String(self.curve.name().to_string())))
             .unwrap();
 
@@ -418,6 +421,10 @@ impl KeyPair for EcxKeyPair {
         }
     }
 
+    fn set_algorithm(&mut self, value: Option<&str>) {
+        EcxKeyPair::set_algorithm(self, value);
+    }
+
     fn key_id(&self) -> Option<&str> {
         match &self.key_id {
             Some(val) => Some(val.as_str()),
@@ -453,6 +460,10 @@ impl KeyPair for EcxKeyPair {
         self.to_jwk(true, true)
     }
 
+    fn as_ecx(&self) -> Option<&EcxKeyPair> {
+        Some(self)
+    }
+
     fn box_clone(&self) -> Box<dyn KeyPair> {
         Box::new(self.clone())
     }
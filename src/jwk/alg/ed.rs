@@ -4,7 +4,7 @@ use std::ops::Deref;
 use anyhow::bail;
 use openssl::pkey::{PKey, Private};
 
-use crate::jwk::{Jwk, KeyPair};
+use crate::jwk::{Jwk, JwkCurve, KeyPair, OkpCurve};
 use crate::util;
 use crate::util::der::{DerBuilder, DerReader, DerType};
 use crate::util::oid::{ObjectIdentifier, OID_ED25519, OID_ED448};
@@ -62,6 +62,7 @@ impl EdKeyPair {
         }
     }
 
+    #[cfg_attr(not(feature = "jws-eddsa"), allow(dead_code))]
     pub(crate) fn into_private_key(self) -> PKey<Private> {
         self.private_key
     }
@@ -170,13 +171,9 @@ impl EdKeyPair {
                 val if val == "OKP" => {}
                 val => bail!("A parameter kty must be OKP: {}", val),
             }
-            let curve = match jwk.parameter("crv") {
-                Some(Value::String(val)) => match val.as_str() {
-                    "Ed25519" => EdCurve::Ed25519,
-                    "Ed448" => EdCurve::Ed448,
-                    _ => bail!("A parameter crv is unrecognized: {}", val),
-                },
-                Some(_) => bail!("A parameter crv must be a string."),
+            let curve = match jwk.curve() {
+                Some(JwkCurve::Okp(val)) if val.ed_curve().is_some() => val.ed_curve().unwrap(),
+                Some(val) => bail!("A parameter crv is unrecognized: {}", val),
                 None => bail!("A parameter crv is required."),
             };
             let d = match jwk.parameter("d") {
@@ -203,6 +200,90 @@ impl EdKeyPair {
         .map_err(|err| JoseError::InvalidKeyFormat(err))
     }
 
+    /// Create a EdDSA key pair from a DER encoded PKCS#8 EncryptedPrivateKeyInfo.
+    ///
+    /// # Arguments
+    /// * `input` - A DER encoded PKCS#8 EncryptedPrivateKeyInfo.
+    /// * `passphrase` - A passphrase that must not be empty.
+    pub fn from_encrypted_der(
+        input: impl AsRef<[u8]>,
+        passphrase: impl AsRef<[u8]>,
+    ) -> Result<Self, JoseError> {
+        let der = util::decrypt_pkcs8(input.as_ref(), passphrase.as_ref())
+            .map_err(JoseError::InvalidKeyFormat)?;
+        Self::from_der(&der)
+    }
+
+    /// Create a EdDSA key pair from a PKCS#8 EncryptedPrivateKeyInfo
+    /// that surrounded by "-----BEGIN/END ENCRYPTED PRIVATE KEY----".
+    ///
+    /// # Arguments
+    /// * `input` - A private key of encrypted PEM format.
+    /// * `passphrase` - A passphrase that must not be empty.
+    pub fn from_encrypted_pem(
+        input: impl AsRef<[u8]>,
+        passphrase: impl AsRef<[u8]>,
+    ) -> Result<Self, JoseError> {
+        (|| -> anyhow::Result<Self> {
+            let (alg, data) = util::parse_pem(input.as_ref())?;
+            match alg.as_str() {
+                "ENCRYPTED PRIVATE KEY" => {}
+                alg => bail!("Inappropriate algorithm: {}", alg),
+            }
+            Ok(Self::from_encrypted_der(&data, passphrase)?)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidKeyFormat(err),
+        })
+    }
+
+    /// Create a EdDSA key pair from a raw private key seed.
+    ///
+    /// # Arguments
+    /// * `seed` - A raw private key seed (32 bytes for Ed25519, 57 bytes for Ed448).
+    /// * `curve` - EdDSA curve algorithm
+    pub fn from_raw_seed(seed: &[u8], curve: EdCurve) -> Result<Self, JoseError> {
+        (|| -> anyhow::Result<Self> {
+            let seed_len = OkpCurve::from(curve).key_len();
+            if seed.len() != seed_len {
+                bail!(
+                    "The {} seed length must be {}: {}",
+                    curve,
+                    seed_len,
+                    seed.len()
+                );
+            }
+
+            let mut builder = DerBuilder::new();
+            builder.append_octed_string_from_bytes(seed);
+
+            let pkcs8 = Self::to_pkcs8(&builder.build(), false, curve);
+            let private_key = PKey::private_key_from_der(&pkcs8)?;
+
+            Ok(Self {
+                private_key,
+                curve,
+                algorithm: None,
+                key_id: None,
+            })
+        })()
+        .map_err(JoseError::InvalidKeyFormat)
+    }
+
+    /// Return a raw public key (32 bytes for Ed25519, 57 bytes for Ed448).
+    pub fn to_raw_public_key(&self) -> Vec<u8> {
+        self.private_key.raw_public_key().unwrap()
+    }
+
+    /// Return a raw private key seed (32 bytes for Ed25519, 57 bytes for Ed448).
+    ///
+    /// The seed is the secret itself. Anyone who can read it can sign as this key pair,
+    /// so it must be handled with the same care as the PKCS#8 private key.
+    pub fn to_raw_private_key_seed(&self) -> Vec<u8> {
+        self.private_key.raw_private_key().unwrap()
+    }
+
     pub fn to_traditional_pem_private_key(&self) -> Vec<u8> {
         let der = self.private_key.private_key_to_der().unwrap();
         let der = base64::encode_config(&der, base64::STANDARD);
@@ -417,6 +498,10 @@ impl KeyPair for EdKeyPair {
         }
     }
 
+    fn set_algorithm(&mut self, value: Option<&str>) {
+        EdKeyPair::set_algorithm(self, value);
+    }
+
     fn key_id(&self) -> Option<&str> {
         match &self.key_id {
             Some(val) => Some(val.as_str()),
@@ -441,7 +526,8 @@ impl KeyPair for EdKeyPair {
     }
 
     fn to_jwk_private_key(&self) -> Jwk {
-        self.to_jwk(true, false)
+        // RFC 8037 requires the public key "x" in a private key too.
+        self.to_jwk(true, true)
     }
 
     fn to_jwk_public_key(&self) -> Jwk {
@@ -452,6 +538,10 @@ impl KeyPair for EdKeyPair {
         self.to_jwk(true, true)
     }
 
+    fn as_ed(&self) -> Option<&EdKeyPair> {
+        Some(self)
+    }
+
     fn box_clone(&self) -> Box<dyn KeyPair> {
         Box::new(self.clone())
     }
@@ -490,4 +580,25 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_ed_raw_key() -> Result<()> {
+        for (curve, len) in &[(EdCurve::Ed25519, 32), (EdCurve::Ed448, 57)] {
+            let key_pair_1 = EdKeyPair::generate(*curve)?;
+            let seed = key_pair_1.to_raw_private_key_seed();
+            let public_key = key_pair_1.to_raw_public_key();
+            assert_eq!(seed.len(), *len);
+            assert_eq!(public_key.len(), *len);
+
+            let key_pair_2 = EdKeyPair::from_raw_seed(&seed, *curve)?;
+            assert_eq!(key_pair_2.to_raw_private_key_seed(), seed);
+            assert_eq!(key_pair_2.to_raw_public_key(), public_key);
+            assert_eq!(
+                key_pair_1.to_der_private_key(),
+                key_pair_2.to_der_private_key()
+            );
+        }
+
+        Ok(())
+    }
 }
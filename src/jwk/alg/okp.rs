@@ -0,0 +1,133 @@
+use std::fmt::Display;
+
+use openssl::nid::Nid;
+use openssl::pkey::Id;
+
+use crate::jwk::alg::ecx::EcxCurve;
+use crate::jwk::alg::ed::EdCurve;
+
+/// Represent a curve of OKP type JWK defined in RFC 8037.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum OkpCurve {
+    Ed25519,
+    Ed448,
+    X25519,
+    X448,
+}
+
+impl OkpCurve {
+    /// Return a curve from the name of the crv parameter.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A curve name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Ed25519" => Some(Self::Ed25519),
+            "Ed448" => Some(Self::Ed448),
+            "X25519" => Some(Self::X25519),
+            "X448" => Some(Self::X448),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Ed25519 => "Ed25519",
+            Self::Ed448 => "Ed448",
+            Self::X25519 => "X25519",
+            Self::X448 => "X448",
+        }
+    }
+
+    /// Return the byte length of the public key (x) and the private key (d).
+    pub fn key_len(&self) -> usize {
+        match self {
+            Self::Ed25519 => 32,
+            Self::Ed448 => 57,
+            Self::X25519 => 32,
+            Self::X448 => 56,
+        }
+    }
+
+    /// Return the bit length of the field of the curve.
+    pub fn key_bits(&self) -> u32 {
+        match self {
+            Self::Ed25519 | Self::X25519 => 255,
+            Self::Ed448 | Self::X448 => 448,
+        }
+    }
+
+    /// Return the byte length of a signature, or None for a key agreement curve.
+    pub fn signature_len(&self) -> Option<usize> {
+        match self {
+            Self::Ed25519 => Some(64),
+            Self::Ed448 => Some(114),
+            Self::X25519 | Self::X448 => None,
+        }
+    }
+
+    /// Return the openssl NID of the curve.
+    pub fn nid(&self) -> Nid {
+        match self {
+            Self::Ed25519 => Nid::from_raw(Id::ED25519.as_raw()),
+            Self::Ed448 => Nid::from_raw(Id::ED448.as_raw()),
+            Self::X25519 => Nid::from_raw(Id::X25519.as_raw()),
+            Self::X448 => Nid::from_raw(Id::X448.as_raw()),
+        }
+    }
+
+    pub(crate) fn id(&self) -> Id {
+        Id::from_raw(self.nid().as_raw())
+    }
+
+    /// Return the EdDSA curve, or None for a key agreement curve.
+    pub fn ed_curve(&self) -> Option<EdCurve> {
+        match self {
+            Self::Ed25519 => Some(EdCurve::Ed25519),
+            Self::Ed448 => Some(EdCurve::Ed448),
+            Self::X25519 | Self::X448 => None,
+        }
+    }
+
+    /// Return the ECDH curve, or None for a signature curve.
+    pub fn ecx_curve(&self) -> Option<EcxCurve> {
+        match self {
+            Self::X25519 => Some(EcxCurve::X25519),
+            Self::X448 => Some(EcxCurve::X448),
+            Self::Ed25519 | Self::Ed448 => None,
+        }
+    }
+
+    /// Return the default value for key use parameter (use).
+    pub fn key_use(&self) -> &str {
+        match self {
+            Self::Ed25519 | Self::Ed448 => "sig",
+            Self::X25519 | Self::X448 => "enc",
+        }
+    }
+}
+
+impl Display for OkpCurve {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        fmt.write_str(self.name())
+    }
+}
+
+impl From<EdCurve> for OkpCurve {
+    fn from(curve: EdCurve) -> Self {
+        match curve {
+            EdCurve::Ed25519 => Self::Ed25519,
+            EdCurve::Ed448 => Self::Ed448,
+        }
+    }
+}
+
+impl From<EcxCurve> for OkpCurve {
+    fn from(curve: EcxCurve) -> Self {
+        match curve {
+            EcxCurve::X25519 => Self::X25519,
+            EcxCurve::X448 => Self::X448,
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use std::ops::Deref;
 
 use anyhow::bail;
+use openssl::bn::{BigNum, BigNumContext};
 use openssl::pkey::{PKey, Private};
 use openssl::rsa::Rsa;
 
@@ -10,6 +11,9 @@ use crate::util::oid::OID_RSA_ENCRYPTION;
 use crate::util::{self, HashAlgorithm};
 use crate::{JoseError, Value};
 
+/// An additional prime of a multi-prime RSA key as (r, d, t).
+pub(crate) type OtherPrimeInfo = (Vec<u8>, Vec<u8>, Vec<u8>);
+
 #[derive(Debug, Clone)]
 pub struct RsaKeyPair {
     private_key: PKey<Private>,
@@ -23,6 +27,11 @@ impl RsaKeyPair {
         self.key_len
     }
 
+    /// Return the bit length of the modulus.
+    pub fn key_bits(&self) -> u32 {
+        self.private_key.bits()
+    }
+
     pub fn set_algorithm(&mut self, value: Option<&str>) {
         self.algorithm = value.map(|val| val.to_string());
     }
@@ -56,6 +65,7 @@ impl RsaKeyPair {
         }
     }
 
+    #[cfg_attr(not(any(feature = "jws-rsa", feature = "jwe-rsa")), allow(dead_code))]
     pub(crate) fn into_private_key(self) -> PKey<Private> {
         self.private_key
     }
@@ -150,6 +160,44 @@ impl RsaKeyPair {
         .map_err(|err| JoseError::InvalidKeyFormat(err))
     }
 
+    /// Create a RSA key pair from a DER encoded PKCS#8 EncryptedPrivateKeyInfo.
+    ///
+    /// # Arguments
+    /// * `input` - A DER encoded PKCS#8 EncryptedPrivateKeyInfo.
+    /// * `passphrase` - A passphrase that must not be empty.
+    pub fn from_encrypted_der(
+        input: impl AsRef<[u8]>,
+        passphrase: impl AsRef<[u8]>,
+    ) -> Result<Self, JoseError> {
+        let der = util::decrypt_pkcs8(input.as_ref(), passphrase.as_ref())
+            .map_err(JoseError::InvalidKeyFormat)?;
+        Self::from_der(&der)
+    }
+
+    /// Create a RSA key pair from a PKCS#8 EncryptedPrivateKeyInfo
+    /// that surrounded by "-----BEGIN/END ENCRYPTED PRIVATE KEY----".
+    ///
+    /// # Arguments
+    /// * `input` - A private key of encrypted PEM format.
+    /// * `passphrase` - A passphrase that must not be empty.
+    pub fn from_encrypted_pem(
+        input: impl AsRef<[u8]>,
+        passphrase: impl AsRef<[u8]>,
+    ) -> Result<Self, JoseError> {
+        (|| -> anyhow::Result<Self> {
+            let (alg, data) = util::parse_pem(input.as_ref())?;
+            match alg.as_str() {
+                "ENCRYPTED PRIVATE KEY" => {}
+                alg => bail!("Inappropriate algorithm: {}", alg),
+            }
+            Ok(Self::from_encrypted_der(&data, passphrase)?)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidKeyFormat(err),
+        })
+    }
+
     /// Create a RSA key pair from a private key that is formatted by a JWK of RSA type.
     ///
     /// # Arguments
@@ -160,63 +208,8 @@ impl RsaKeyPair {
                 val if val == "RSA" => {}
                 val => bail!("A parameter kty must be RSA: {}", val),
             }
-            let n = match jwk.parameter("n") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter n must be a string."),
-                None => bail!("A parameter n is required."),
-            };
-            let e = match jwk.parameter("e") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter e must be a string."),
-                None => bail!("A parameter e is required."),
-            };
-            let d = match jwk.parameter("d") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter d must be a string."),
-                None => bail!("A parameter d is required."),
-            };
-            let p = match jwk.parameter("p") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter p must be a string."),
-                None => bail!("A parameter p is required."),
-            };
-            let q = match jwk.parameter("q") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter q must be a string."),
-                None => bail!("A parameter q is required."),
-            };
-            let dp = match jwk.parameter("dp") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter dp must be a string."),
-                None => bail!("A parameter dp is required."),
-            };
-            let dq = match jwk.parameter("dq") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter dq must be a string."),
-                None => bail!("A parameter dq is required."),
-            };
-            let qi = match jwk.parameter("qi") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter qi must be a string."),
-                None => bail!("A parameter qi is required."),
-            };
-
-            let mut builder = DerBuilder::new();
-            builder.begin(DerType::Sequence);
-            {
-                builder.append_integer_from_u8(0); // version
-                builder.append_integer_from_be_slice(&n, false); // n
-                builder.append_integer_from_be_slice(&e, false); // e
-                builder.append_integer_from_be_slice(&d, false); // d
-                builder.append_integer_from_be_slice(&p, false); // p
-                builder.append_integer_from_be_slice(&q, false); // q
-                builder.append_integer_from_be_slice(&dp, false); // d mod (p-1)
-                builder.append_integer_from_be_slice(&dq, false); // d mod (q-1)
-                builder.append_integer_from_be_slice(&qi, false); // (inverse of q) mod p
-            }
-            builder.end();
-
-            let pkcs8 = Self::to_pkcs8(&builder.build(), false);
+            let der = Self::private_key_der_from_jwk(jwk)?;
+            let pkcs8 = Self::to_pkcs8(&der, false);
             let private_key = PKey::private_key_from_der(&pkcs8)?;
             let rsa = private_key.rsa()?;
             let key_len = rsa.size();
@@ -233,6 +226,280 @@ impl RsaKeyPair {
         .map_err(|err| JoseError::InvalidKeyFormat(err))
     }
 
+    /// Return a private key in the DER format of RSAPrivateKey (PKCS#1) that is
+    /// built from a JWK of RSA type, including the primes of "oth" when it is a multi-prime key.
+    ///
+    /// # Arguments
+    /// * `jwk` - A private key that is formatted by a JWK of RSA type.
+    pub(crate) fn private_key_der_from_jwk(jwk: &Jwk) -> anyhow::Result<Vec<u8>> {
+        let n = match jwk.parameter("n") {
+            Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
+            Some(_) => bail!("A parameter n must be a string."),
+            None => bail!("A parameter n is required."),
+        };
+        let e = match jwk.parameter("e") {
+            Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
+            Some(_) => bail!("A parameter e must be a string."),
+            None => bail!("A parameter e is required."),
+        };
+        let d = match jwk.parameter("d") {
+            Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
+            Some(_) => bail!("A parameter d must be a string."),
+            None => bail!("A parameter d is required."),
+        };
+        let [p, q, dp, dq, qi] = Self::crt_params_from_jwk(jwk, &n, &e, &d)?;
+        let oth = Self::other_primes_from_jwk(jwk)?;
+        Self::check_primes(&n, &p, &q, &oth)?;
+
+        let mut builder = DerBuilder::new();
+        builder.begin(DerType::Sequence);
+        {
+            // version: two-prime(0) or multi(1)
+            builder.append_integer_from_u8(if oth.is_empty() { 0 } else { 1 });
+            builder.append_integer_from_be_slice(&n, false); // n
+            builder.append_integer_from_be_slice(&e, false); // e
+            builder.append_integer_from_be_slice(&d, false); // d
+            builder.append_integer_from_be_slice(&p, false); // p
+            builder.append_integer_from_be_slice(&q, false); // q
+            builder.append_integer_from_be_slice(&dp, false); // d mod (p-1)
+            builder.append_integer_from_be_slice(&dq, false); // d mod (q-1)
+            builder.append_integer_from_be_slice(&qi, false); // (inverse of q) mod p
+            if !oth.is_empty() {
+                builder.begin(DerType::Sequence); // otherPrimeInfos
+                for (r, d, t) in &oth {
+                    builder.begin(DerType::Sequence);
+                    builder.append_integer_from_be_slice(r, false); // prime
+                    builder.append_integer_from_be_slice(d, false); // exponent
+                    builder.append_integer_from_be_slice(t, false); // coefficient
+                    builder.end();
+                }
+                builder.end();
+            }
+        }
+        builder.end();
+        Ok(builder.build())
+    }
+
+    /// Return the CRT parameters (p, q, dp, dq, qi) of a private key that is
+    /// formatted by a JWK of RSA type.
+    ///
+    /// RFC 7518 allows to omit all of them, and then the primes are recovered
+    /// from n, e and d. The missing exponents and coefficient are computed from the primes.
+    pub(crate) fn crt_params_from_jwk(
+        jwk: &Jwk,
+        n: &[u8],
+        e: &[u8],
+        d: &[u8],
+    ) -> anyhow::Result<[Vec<u8>; 5]> {
+        let optional = |key: &str| -> anyhow::Result<Option<BigNum>> {
+            match jwk.parameter(key) {
+                Some(Value::String(val)) => {
+                    let val = base64::decode_config(val, base64::URL_SAFE_NO_PAD)?;
+                    Ok(Some(BigNum::from_slice(&val)?))
+                }
+                Some(_) => bail!("A parameter {} must be a string.", key),
+                None => Ok(None),
+            }
+        };
+
+        let mut ctx = BigNumContext::new()?;
+        let n = BigNum::from_slice(n)?;
+        let e = BigNum::from_slice(e)?;
+        let d = BigNum::from_slice(d)?;
+
+        // The primes are range-checked before the exponents and the coefficient are derived from them.
+        let p = optional("p")?;
+        let q = optional("q")?;
+        for (name, prime) in &[("p", &p), ("q", &q)] {
+            if let Some(prime) = prime {
+                Self::check_prime_range(&n, prime, name)?;
+            }
+        }
+
+        let (p, q) = match (p, q) {
+            (Some(p), Some(q)) => (p, q),
+            (Some(p), None) => {
+                let q = Self::cofactor(&n, &p, "p", &mut ctx)?;
+                (p, q)
+            }
+            (None, Some(q)) => {
+                let p = Self::cofactor(&n, &q, "q", &mut ctx)?;
+                (p, q)
+            }
+            (None, None) => {
+                if jwk.parameter("oth").is_some() {
+                    bail!("A parameter p and q are required for a multi-prime key.");
+                }
+                Self::factor_modulus(&n, &e, &d, &mut ctx)?
+            }
+        };
+
+        let one = BigNum::from_u32(1)?;
+        let dp = match optional("dp")? {
+            Some(val) => val,
+            None => &d % &(&p - &one),
+        };
+        let dq = match optional("dq")? {
+            Some(val) => val,
+            None => &d % &(&q - &one),
+        };
+        let qi = match optional("qi")? {
+            Some(val) => val,
+            None => {
+                let mut qi = BigNum::new()?;
+                qi.mod_inverse(&q, &p, &mut ctx)?;
+                qi
+            }
+        };
+
+        Ok([
+            p.to_vec(),
+            q.to_vec(),
+            dp.to_vec(),
+            dq.to_vec(),
+            qi.to_vec(),
+        ])
+    }
+
+    /// Check that a prime is greater than 1 and less than the modulus.
+    fn check_prime_range(n: &BigNum, prime: &BigNum, name: &str) -> anyhow::Result<()> {
+        let one = BigNum::from_u32(1)?;
+        if prime <= &one || prime >= n {
+            bail!("A parameter {} must be greater than 1 and less than n.", name);
+        }
+        Ok(())
+    }
+
+    /// Return n / prime after checking that the prime is a divisor of the modulus.
+    fn cofactor(
+        n: &BigNum,
+        prime: &BigNum,
+        name: &str,
+        ctx: &mut BigNumContext,
+    ) -> anyhow::Result<BigNum> {
+        let mut rem = BigNum::new()?;
+        rem.checked_rem(n, prime, ctx)?;
+        if rem.num_bits() != 0 {
+            bail!("A parameter {} must be a divisor of n.", name);
+        }
+
+        let mut cofactor = BigNum::new()?;
+        cofactor.checked_div(n, prime, ctx)?;
+        Ok(cofactor)
+    }
+
+    /// Recover the primes p and q (p > q) from a modulus and its exponents
+    /// by the algorithm of NIST SP 800-56B Appendix C.
+    fn factor_modulus(
+        n: &BigNum,
+        e: &BigNum,
+        d: &BigNum,
+        ctx: &mut BigNumContext,
+    ) -> anyhow::Result<(BigNum, BigNum)> {
+        let one = BigNum::from_u32(1)?;
+        let n_minus_one = n - &one;
+
+        // k = d * e - 1 = 2^s * t where t is odd.
+        let k = &(d * e) - &one;
+        if k.num_bits() == 0 || k.is_bit_set(0) {
+            bail!("The parameters n, e and d are inconsistent.");
+        }
+        let mut t = k;
+        let mut s = 0;
+        while !t.is_bit_set(0) {
+            let mut next = BigNum::new()?;
+            next.rshift1(&t)?;
+            t = next;
+            s += 1;
+        }
+
+        // Bases are tried in order so that the result is deterministic.
+        for g in 2..102u32 {
+            let g = BigNum::from_u32(g)?;
+            let mut x = BigNum::new()?;
+            x.mod_exp(&g, &t, n, ctx)?;
+            for _ in 0..s {
+                if x == one || x == n_minus_one {
+                    break;
+                }
+                let mut y = BigNum::new()?;
+                y.mod_sqr(&x, n, ctx)?;
+                if y == one {
+                    let mut p = BigNum::new()?;
+                    p.gcd(&(&x - &one), n, ctx)?;
+                    if p <= one || &p >= n {
+                        break;
+                    }
+                    let q = n / &p;
+                    return Ok(if p > q { (p, q) } else { (q, p) });
+                }
+                x = y;
+            }
+        }
+
+        bail!("Failed to recover the primes from the parameters n, e and d.");
+    }
+
+    /// Return the additional primes of a multi-prime RSA key that is
+    /// specified by the "oth" parameter as tuples of (r, d, t).
+    pub(crate) fn other_primes_from_jwk(jwk: &Jwk) -> anyhow::Result<Vec<OtherPrimeInfo>> {
+        let oth = match jwk.parameter("oth") {
+            Some(Value::Array(vals)) if !vals.is_empty() => vals,
+            Some(Value::Array(_)) => bail!("A parameter oth must not be empty."),
+            Some(_) => bail!("A parameter oth must be an array."),
+            None => return Ok(Vec::new()),
+        };
+
+        let mut vec = Vec::with_capacity(oth.len());
+        for info in oth {
+            let info = match info {
+                Value::Object(val) => val,
+                _ => bail!("A parameter oth must be an array of objects."),
+            };
+            let decode = |key: &str| -> anyhow::Result<Vec<u8>> {
+                match info.get(key) {
+                    Some(Value::String(val)) => {
+                        Ok(base64::decode_config(val, base64::URL_SAFE_NO_PAD)?)
+                    }
+                    Some(_) => bail!("A member {} of oth must be a string.", key),
+                    None => bail!("A member {} of oth is required.", key),
+                }
+            };
+            vec.push((decode("r")?, decode("d")?, decode("t")?));
+        }
+        Ok(vec)
+    }
+
+    /// Check that the product of all the primes is equal to the modulus.
+    pub(crate) fn check_primes(
+        n: &[u8],
+        p: &[u8],
+        q: &[u8],
+        oth: &[OtherPrimeInfo],
+    ) -> anyhow::Result<()> {
+        let mut ctx = BigNumContext::new()?;
+        let n = BigNum::from_slice(n)?;
+        let p = BigNum::from_slice(p)?;
+        let q = BigNum::from_slice(q)?;
+        let mut product = BigNum::new()?;
+        product.checked_mul(&p, &q, &mut ctx)?;
+        for (r, _, _) in oth {
+            let r = BigNum::from_slice(r)?;
+            let current = product;
+            product = BigNum::new()?;
+            product.checked_mul(&current, &r, &mut ctx)?;
+        }
+
+        if product != n {
+            if oth.is_empty() {
+                bail!("The product of p and q is not equal to n. A multi-prime key requires the parameter oth.");
+            } else {
+                bail!("The product of p, q and the primes of oth is not equal to n.");
+            }
+        }
+        Ok(())
+    }
+
     pub fn to_raw_private_key(&self) -> Vec<u8> {
         let rsa = self.private_key.rsa().unwrap();
         rsa.private_key_to_der().unwrap()
@@ -387,6 +654,10 @@ impl KeyPair for RsaKeyPair {
         }
     }
 
+    fn set_algorithm(&mut self, value: Option<&str>) {
+        RsaKeyPair::set_algorithm(self, value);
+    }
+
     fn key_id(&self) -> Option<&str> {
         match &self.key_id {
             Some(val) => Some(val.as_str()),
@@ -422,6 +693,10 @@ impl KeyPair for RsaKeyPair {
         self.to_jwk(true, true)
     }
 
+    fn as_rsa(&self) -> Option<&RsaKeyPair> {
+        Some(self)
+    }
+
     fn box_clone(&self) -> Box<dyn KeyPair> {
         Box::new(self.clone())
     }
@@ -26,6 +26,11 @@ impl RsaPssKeyPair {
         self.key_len
     }
 
+    /// Return the bit length of the modulus.
+    pub fn key_bits(&self) -> u32 {
+        self.private_key.bits()
+    }
+
     pub fn set_algorithm(&mut self, value: Option<&str>) {
         self.algorithm = value.map(|val| val.to_string());
     }
@@ -63,6 +68,7 @@ impl RsaPssKeyPair {
         }
     }
 
+    #[cfg_attr(not(feature = "jws-rsa"), allow(dead_code))]
     pub(crate) fn into_private_key(self) -> PKey<Private> {
         self.private_key
     }
@@ -292,63 +298,8 @@ impl RsaPssKeyPair {
                 val if val == "RSA" => {}
                 val => bail!("A parameter kty must be RSA: {}", val),
             }
-            let n = match jwk.parameter("n") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter n must be a string."),
-                None => bail!("A parameter n is required."),
-            };
-            let e = match jwk.parameter("e") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter e must be a string."),
-                None => bail!("A parameter e is required."),
-            };
-            let d = match jwk.parameter("d") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter d must be a string."),
-                None => bail!("A parameter d is required."),
-            };
-            let p = match jwk.parameter("p") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter p must be a string."),
-                None => bail!("A parameter p is required."),
-            };
-            let q = match jwk.parameter("q") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter q must be a string."),
-                None => bail!("A parameter q is required."),
-            };
-            let dp = match jwk.parameter("dp") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter dp must be a string."),
-                None => bail!("A parameter dp is required."),
-            };
-            let dq = match jwk.parameter("dq") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter dq must be a string."),
-                None => bail!("A parameter dq is required."),
-            };
-            let qi = match jwk.parameter("qi") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
-                Some(_) => bail!("A parameter qi must be a string."),
-                None => bail!("A parameter qi is required."),
-            };
-
-            let mut builder = DerBuilder::new();
-            builder.begin(DerType::Sequence);
-            {
-                builder.append_integer_from_u8(0); // version
-                builder.append_integer_from_be_slice(&n, false); // n
-                builder.append_integer_from_be_slice(&e, false); // e
-                builder.append_integer_from_be_slice(&d, false); // d
-                builder.append_integer_from_be_slice(&p, false); // p
-                builder.append_integer_from_be_slice(&q, false); // q
-                builder.append_integer_from_be_slice(&dp, false); // d mod (p-1)
-                builder.append_integer_from_be_slice(&dq, false); // d mod (q-1)
-                builder.append_integer_from_be_slice(&qi, false); // (inverse of q) mod p
-            }
-            builder.end();
-
-            let pkcs8 = RsaPssKeyPair::to_pkcs8(&builder.build(), false, hash, mgf1_hash, salt_len);
+            let der = RsaKeyPair::private_key_der_from_jwk(jwk)?;
+            let pkcs8 = RsaPssKeyPair::to_pkcs8(&der, false, hash, mgf1_hash, salt_len);
             let private_key = PKey::private_key_from_der(&pkcs8)?;
             let rsa = private_key.rsa()?;
             let key_len = rsa.size();
@@ -682,6 +633,10 @@ impl KeyPair for RsaPssKeyPair {
         }
     }
 
+    fn set_algorithm(&mut self, value: Option<&str>) {
+        RsaPssKeyPair::set_algorithm(self, value);
+    }
+
     fn key_id(&self) -> Option<&str> {
         match &self.key_id {
             Some(val) => Some(val.as_str()),
@@ -749,6 +704,10 @@ impl KeyPair for RsaPssKeyPair {
         self.to_jwk(true, true)
     }
 
+    fn as_rsa_pss(&self) -> Option<&RsaPssKeyPair> {
+        Some(self)
+    }
+
     fn box_clone(&self) -> Box<dyn KeyPair> {
         Box::new(self.clone())
     }
@@ -1,15 +1,36 @@
+use std::convert::TryFrom;
 use std::fmt::Display;
 use std::io::Read;
 use std::string::ToString;
+use std::time::SystemTime;
 
 use anyhow::bail;
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::ec::PointConversionForm;
+use openssl::hash::{self, MessageDigest};
+use openssl::nid::Nid;
+use openssl::pkey::{HasPublic, Id, PKey, PKeyRef, Private, Public};
+use openssl::rsa::Rsa;
+use openssl::stack::Stack;
+use openssl::x509::store::X509StoreBuilder;
+use openssl::x509::verify::X509VerifyParam;
+use openssl::x509::{X509Ref, X509StoreContext, X509};
 
+use crate::jwe::{self, JweContentEncryption, JweDecrypter, JweEncrypter, JweHeader};
+#[cfg(all(feature = "jwe-pbes2", feature = "enc-gcm"))]
+use crate::jwe::{enc::A256GCM, PBES2_HS512_A256KW};
 use crate::jwk::alg::ec::{EcCurve, EcKeyPair};
 use crate::jwk::alg::ecx::{EcxCurve, EcxKeyPair};
 use crate::jwk::alg::ed::{EdCurve, EdKeyPair};
+use crate::jwk::alg::okp::OkpCurve;
 use crate::jwk::alg::rsa::RsaKeyPair;
+use crate::jwk::alg::rsapss::RsaPssKeyPair;
+use crate::jwk::{JwkCurve, KeyAlg, KeyFormat, KeyInfo, KeyOperation, KeyPair, OctKeyBuilder};
 use crate::util;
-use crate::{JoseError, Map, Value};
+use crate::util::der::{DerReader, DerType};
+use crate::util::oid::OID_ID_EC_PUBLIC_KEY;
+use crate::util::{HashAlgorithm, Zeroizing};
+use crate::{Clock, JoseError, Map, SystemClock, Value};
 
 /// Represents JWK object.
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -57,21 +78,415 @@ impl Jwk {
         })
     }
 
-    /// Generate a new oct type JWK.
+    /// Return a JWK from a PEM encoded private or public key.
+    ///
+    /// The key type is detected from the PEM label and the algorithm identifier.
+    /// A JWK that has both private and public parts is returned for a private key,
+    /// and a JWK that has only public parts is returned for a public key.
     ///
     /// # Arguments
-    /// * `key_len` - A key byte length
-    pub fn generate_oct_key(key_len: u8) -> Result<Self, JoseError> {
-        let k = util::random_bytes(key_len as usize);
+    /// * `input` - A PEM encoded PKCS#8 PrivateKeyInfo, SubjectPublicKeyInfo
+    ///   or traditional (PKCS#1 or SEC1) key.
+    pub fn from_pem(input: impl AsRef<[u8]>) -> Result<Self, JoseError> {
+        (|| -> anyhow::Result<Self> {
+            let input = input.as_ref();
+            let key_info = match KeyInfo::detect(&input) {
+                Some(val) => match val.format() {
+                    KeyFormat::Pem { .. } => val,
+                    _ => bail!("The input is not a PEM encoded key."),
+                },
+                None => bail!("The key type is unrecognized."),
+            };
 
-        let mut jwk = Self::new("oct");
-        jwk.map.insert(
-            "k".to_string(),
-            Value::String(base64::encode_config(&k, base64::URL_SAFE_NO_PAD)),
-        );
+            let jwk = if key_info.is_public_key() {
+                let (_, data) = util::parse_pem(input)?;
+                Self::public_jwk_from_der(&data, &key_info)?
+            } else {
+                match key_info.alg() {
+                    Some(KeyAlg::Rsa) => RsaKeyPair::from_pem(input)?.to_jwk_key_pair(),
+                    Some(KeyAlg::RsaPss {
+                        hash,
+                        mgf1_hash,
+                        salt_len,
+                    }) => {
+                        RsaPssKeyPair::from_pem(input, hash, mgf1_hash, salt_len)?.to_jwk_key_pair()
+                    }
+                    Some(KeyAlg::Ec { curve: Some(curve) }) => {
+                        EcKeyPair::from_pem(input, Some(curve))?.to_jwk_key_pair()
+                    }
+                    Some(KeyAlg::Ed { curve: Some(_) }) => {
+                        EdKeyPair::from_pem(input)?.to_jwk_key_pair()
+                    }
+                    Some(KeyAlg::Ecx { curve: Some(_) }) => {
+                        EcxKeyPair::from_pem(input)?.to_jwk_key_pair()
+                    }
+                    Some(KeyAlg::Ec { curve: None }) => {
+                        let (_, data) = util::parse_pem(input)?;
+                        return Err(Self::unsupported_ec_curve_error(&data));
+                    }
+                    _ => bail!("The key type is unrecognized."),
+                }
+            };
+
+            Ok(jwk)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidKeyFormat(err),
+        })
+    }
+
+    /// Return a JWK from a DER encoded private or public key.
+    ///
+    /// The key type is detected from the algorithm identifier.
+    /// A JWK that has both private and public parts is returned for a private key,
+    /// and a JWK that has only public parts is returned for a public key.
+    ///
+    /// # Arguments
+    /// * `input` - A DER encoded PKCS#8 PrivateKeyInfo, SubjectPublicKeyInfo
+    ///   or raw (PKCS#1 or SEC1) key.
+    pub fn from_der(input: impl AsRef<[u8]>) -> Result<Self, JoseError> {
+        (|| -> anyhow::Result<Self> {
+            let input = input.as_ref();
+            let key_info = match KeyInfo::detect(&input) {
+                Some(val) => match val.format() {
+                    KeyFormat::Der { .. } => val,
+                    _ => bail!("The input is not a DER encoded key."),
+                },
+                None => bail!("The key type is unrecognized."),
+            };
+
+            let jwk = if key_info.is_public_key() {
+                Self::public_jwk_from_der(input, &key_info)?
+            } else {
+                match key_info.alg() {
+                    Some(KeyAlg::Rsa) => RsaKeyPair::from_der(input)?.to_jwk_key_pair(),
+                    Some(KeyAlg::RsaPss {
+                        hash,
+                        mgf1_hash,
+                        salt_len,
+                    }) => {
+                        RsaPssKeyPair::from_der(input, hash, mgf1_hash, salt_len)?.to_jwk_key_pair()
+                    }
+                    Some(KeyAlg::Ec { curve: Some(curve) }) => {
+                        EcKeyPair::from_der(input, Some(curve))?.to_jwk_key_pair()
+                    }
+                    Some(KeyAlg::Ed { curve: Some(_) }) => {
+                        EdKeyPair::from_der(input)?.to_jwk_key_pair()
+                    }
+                    Some(KeyAlg::Ecx { curve: Some(_) }) => {
+                        EcxKeyPair::from_der(input)?.to_jwk_key_pair()
+                    }
+                    Some(KeyAlg::Ec { curve: None }) => {
+                        return Err(Self::unsupported_ec_curve_error(input));
+                    }
+                    _ => bail!("The key type is unrecognized."),
+                }
+            };
+
+            Ok(jwk)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidKeyFormat(err),
+        })
+    }
+
+    /// Return a public key JWK from a PEM encoded X.509 certificate.
+    ///
+    /// See [`Jwk::from_x509_certificate_der`] for the parameters that are set.
+    /// The certificate is not validated.
+    ///
+    /// # Arguments
+    /// * `input` - A X.509 certificate that is surrounded by "-----BEGIN/END CERTIFICATE----".
+    pub fn from_x509_certificate_pem(input: impl AsRef<[u8]>) -> Result<Self, JoseError> {
+        let cert = X509::from_pem(input.as_ref())
+            .map_err(|err| JoseError::InvalidKeyFormat(err.into()))?;
+        Self::from_x509_certificate(&cert)
+    }
+
+    /// Return a public key JWK from a DER encoded X.509 certificate.
+    ///
+    /// The x5c parameter is set to the certificate, and the x5t and x5t#S256 parameters
+    /// are set to the SHA-1 and SHA-256 fingerprints of the certificate.
+    /// The alg parameter is set when the signature algorithm of the certificate can be used
+    /// with the public key of the certificate. The certificate is not validated.
+    ///
+    /// # Arguments
+    /// * `input` - A DER encoded X.509 certificate.
+    pub fn from_x509_certificate_der(input: impl AsRef<[u8]>) -> Result<Self, JoseError> {
+        let cert = X509::from_der(input.as_ref())
+            .map_err(|err| JoseError::InvalidKeyFormat(err.into()))?;
+        Self::from_x509_certificate(&cert)
+    }
+
+    /// Return a public key JWK from a OpenSSH public key line of authorized_keys format.
+    ///
+    /// The supported key types are ssh-rsa, ecdsa-sha2-nistp256, ecdsa-sha2-nistp384,
+    /// ecdsa-sha2-nistp521 and ssh-ed25519. The options before the key type are ignored,
+    /// and the comment after the key is set to the kid parameter if it is present.
+    ///
+    /// # Arguments
+    /// * `line` - A line like "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAA... user@host".
+    pub fn from_ssh_public_key(line: &str) -> Result<Self, JoseError> {
+        (|| -> anyhow::Result<Self> {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let index = match fields.iter().position(|val| {
+                val.starts_with("ssh-") || val.starts_with("ecdsa-") || val.starts_with("sk-")
+            }) {
+                Some(val) => val,
+                None => bail!("The SSH key type is not found."),
+            };
+            let key_type = fields[index];
+            let blob = match fields.get(index + 1) {
+                Some(val) => base64::decode_config(val, base64::STANDARD)?,
+                None => bail!("The SSH key data is not found."),
+            };
+
+            let mut input = blob.as_slice();
+            let blob_key_type = Self::read_ssh_string(&mut input)?;
+            if blob_key_type != key_type.as_bytes() {
+                bail!(
+                    "The SSH key type is mismatched: {} and {}",
+                    key_type,
+                    String::from_utf8_lossy(blob_key_type)
+                );
+            }
+
+            let mut jwk = match key_type {
+                "ssh-rsa" => {
+                    let e = Self::read_ssh_mpint(&mut input)?;
+                    let n = Self::read_ssh_mpint(&mut input)?;
+                    if e.is_empty() || n.is_empty() {
+                        bail!("The RSA key components must be positive.");
+                    }
+
+                    let mut jwk = Jwk::new("RSA");
+                    let n = base64::encode_config(n, base64::URL_SAFE_NO_PAD);
+                    jwk.map.insert("n".to_string(), Value::String(n));
+                    let e = base64::encode_config(e, base64::URL_SAFE_NO_PAD);
+                    jwk.map.insert("e".to_string(), Value::String(e));
+                    jwk
+                }
+                "ecdsa-sha2-nistp256" | "ecdsa-sha2-nistp384" | "ecdsa-sha2-nistp521" => {
+                    let curve_name = &key_type["ecdsa-sha2-".len()..];
+                    let curve = match curve_name {
+                        "nistp256" => EcCurve::P256,
+                        "nistp384" => EcCurve::P384,
+                        _ => EcCurve::P521,
+                    };
+                    let blob_curve_name = Self::read_ssh_string(&mut input)?;
+                    if blob_curve_name != curve_name.as_bytes() {
+                        bail!(
+                            "The SSH curve is mismatched: {} and {}",
+                            curve_name,
+                            String::from_utf8_lossy(blob_curve_name)
+                        );
+                    }
+                    let point = Self::read_ssh_string(&mut input)?;
+                    EcKeyPair::public_jwk_from_sec1(point, curve)?
+                }
+                "ssh-ed25519" => {
+                    let x = Self::read_ssh_string(&mut input)?;
+                    if x.len() != OkpCurve::Ed25519.key_len() {
+                        bail!(
+                            "The public key length must be {} for {}: {}",
+                            OkpCurve::Ed25519.key_len(),
+                            OkpCurve::Ed25519,
+                            x.len()
+                        );
+                    }
+                    Self::okp_public_jwk(OkpCurve::Ed25519.name(), "sig", x)
+                }
+                val => bail!("The SSH key type is unsupported: {}", val),
+            };
+            if !input.is_empty() {
+                bail!("The SSH key data has trailing bytes.");
+            }
+
+            let comment = fields[(index + 2)..].join(" ");
+            if !comment.is_empty() {
+                jwk.set_key_id(comment);
+            }
+            Ok(jwk)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidKeyFormat(err),
+        })
+    }
+
+    /// Return a public key JWK from an openssl public key.
+    ///
+    /// The key type is detected from the key id, and RSA, EC, Ed25519, Ed448, X25519 and X448
+    /// are supported. The parameters are read from the key components without DER encoding.
+    ///
+    /// # Arguments
+    /// * `pkey` - An openssl public key
+    pub fn from_public_pkey(pkey: &PKey<Public>) -> Result<Self, JoseError> {
+        Self::public_jwk_from_pkey(pkey).map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidKeyFormat(err),
+        })
+    }
+
+    /// Return a JWK that has both private and public parts from an openssl private key.
+    ///
+    /// The key type is detected from the key id, and RSA, EC, Ed25519, Ed448, X25519 and X448
+    /// are supported. The parameters are read from the key components without DER encoding.
+    ///
+    /// # Arguments
+    /// * `pkey` - An openssl private key
+    pub fn from_private_pkey(pkey: &PKey<Private>) -> Result<Self, JoseError> {
+        (|| -> anyhow::Result<Self> {
+            let mut jwk = Self::public_jwk_from_pkey(pkey)?;
+            let d = match pkey.id() {
+                Id::RSA => {
+                    let rsa = pkey.rsa()?;
+                    let params = [
+                        ("p", rsa.p()),
+                        ("q", rsa.q()),
+                        ("dp", rsa.dmp1()),
+                        ("dq", rsa.dmq1()),
+                        ("qi", rsa.iqmp()),
+                    ];
+                    for (key, value) in params.iter() {
+                        if let Some(val) = value {
+                            let val = base64::encode_config(val.to_vec(), base64::URL_SAFE_NO_PAD);
+                            jwk.map.insert(key.to_string(), Value::String(val));
+                        }
+                    }
+                    rsa.d().to_vec()
+                }
+                Id::EC => {
+                    let curve = jwk.ec_curve()?;
+                    let ec_key = pkey.ec_key()?;
+                    util::num_to_vec(ec_key.private_key(), curve.coordinate_size())
+                }
+                _ => pkey.raw_private_key()?,
+            };
+            let d = base64::encode_config(d, base64::URL_SAFE_NO_PAD);
+            jwk.map.insert("d".to_string(), Value::String(d));
+            Ok(jwk)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidKeyFormat(err),
+        })
+    }
+
+    fn public_jwk_from_pkey<T: HasPublic>(pkey: &PKeyRef<T>) -> anyhow::Result<Self> {
+        let jwk = match pkey.id() {
+            Id::RSA => {
+                let rsa = pkey.rsa()?;
+
+                let mut jwk = Jwk::new("RSA");
+                let n = base64::encode_config(rsa.n().to_vec(), base64::URL_SAFE_NO_PAD);
+                jwk.map.insert("n".to_string(), Value::String(n));
+                let e = base64::encode_config(rsa.e().to_vec(), base64::URL_SAFE_NO_PAD);
+                jwk.map.insert("e".to_string(), Value::String(e));
+                jwk
+            }
+            Id::EC => {
+                let ec_key = pkey.ec_key()?;
+                let nid = ec_key.group().curve_name();
+                let curve = match nid.and_then(EcCurve::from_nid) {
+                    Some(val) => val,
+                    None => bail!(
+                        "The EC curve is unsupported: {}",
+                        nid.and_then(|val| val.short_name().ok())
+                            .unwrap_or("unknown")
+                    ),
+                };
+                let mut ctx = BigNumContext::new()?;
+                let point = ec_key.public_key().to_bytes(
+                    ec_key.group(),
+                    PointConversionForm::UNCOMPRESSED,
+                    &mut ctx,
+                )?;
+                EcKeyPair::public_jwk_from_sec1(&point, curve)?
+            }
+            Id::ED25519 | Id::ED448 | Id::X25519 | Id::X448 => {
+                let curve = [
+                    OkpCurve::Ed25519,
+                    OkpCurve::Ed448,
+                    OkpCurve::X25519,
+                    OkpCurve::X448,
+                ]
+                .iter()
+                .find(|curve| curve.id() == pkey.id())
+                .unwrap();
+                Self::okp_public_jwk(curve.name(), curve.key_use(), &pkey.raw_public_key()?)
+            }
+            id => bail!(
+                "The key id is unsupported: {} ({})",
+                id.as_raw(),
+                Nid::from_raw(id.as_raw()).short_name().unwrap_or("unknown")
+            ),
+        };
         Ok(jwk)
     }
 
+    fn from_x509_certificate(cert: &X509Ref) -> Result<Self, JoseError> {
+        (|| -> anyhow::Result<Self> {
+            let spki = cert.public_key()?.public_key_to_der()?;
+            let mut jwk = Self::from_der(&spki)?;
+
+            let sig_nid = cert.signature_algorithm().object().nid();
+            let alg = match (jwk.key_type(), jwk.curve()) {
+                ("RSA", _) => match sig_nid {
+                    Nid::SHA256WITHRSAENCRYPTION => Some("RS256"),
+                    Nid::SHA384WITHRSAENCRYPTION => Some("RS384"),
+                    Nid::SHA512WITHRSAENCRYPTION => Some("RS512"),
+                    _ => None,
+                },
+                (_, Some(JwkCurve::Ec(curve))) => match (curve, sig_nid) {
+                    (EcCurve::P256, Nid::ECDSA_WITH_SHA256) => Some("ES256"),
+                    (EcCurve::P384, Nid::ECDSA_WITH_SHA384) => Some("ES384"),
+                    (EcCurve::P521, Nid::ECDSA_WITH_SHA512) => Some("ES512"),
+                    (EcCurve::Secp256k1, Nid::ECDSA_WITH_SHA256) => Some("ES256K"),
+                    #[cfg(feature = "brainpool")]
+                    (EcCurve::Bp256r1, Nid::ECDSA_WITH_SHA256) => Some("ESB256"),
+                    #[cfg(feature = "brainpool")]
+                    (EcCurve::Bp384r1, Nid::ECDSA_WITH_SHA384) => Some("ESB384"),
+                    #[cfg(feature = "brainpool")]
+                    (EcCurve::Bp512r1, Nid::ECDSA_WITH_SHA512) => Some("ESB512"),
+                    _ => None,
+                },
+                (_, Some(JwkCurve::Okp(curve)))
+                    if curve.signature_len().is_some() && sig_nid == curve.nid() =>
+                {
+                    Some("EdDSA")
+                }
+                _ => None,
+            };
+            if let Some(val) = alg {
+                jwk.set_algorithm(val);
+            }
+
+            let der = cert.to_der()?;
+            jwk.set_x509_certificate_chain(&[der]);
+            jwk.set_x509_certificate_sha1_thumbprint(cert.digest(MessageDigest::sha1())?);
+            jwk.set_x509_certificate_sha256_thumbprint(cert.digest(MessageDigest::sha256())?);
+
+            Ok(jwk)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidKeyFormat(err),
+        })
+    }
+
+    /// Generate a new oct type JWK.
+    ///
+    /// Use [`OctKeyBuilder`] to set the use, alg or kid parameters at generation time.
+    ///
+    /// # Arguments
+    /// * `bits` - A key bit length that is a multiple of 8 and at least 112.
+    pub fn generate_oct_key(bits: u32) -> Result<Self, JoseError> {
+        OctKeyBuilder::new(bits).generate()
+    }
+
     /// Generate a new RSA type JWK.
     ///
     /// # Arguments
@@ -83,6 +498,8 @@ impl Jwk {
 
     /// Generate a new EC type JWK.
     ///
+    /// The x, y and d parameters are zero-padded to the coordinate size of the curve.
+    ///
     /// # Arguments
     /// * `curve` - A EC curve algorithm
     pub fn generate_ec_key(curve: EcCurve) -> Result<Self, JoseError> {
@@ -108,197 +525,712 @@ impl Jwk {
         Ok(key_pair.to_jwk_key_pair())
     }
 
-    /// Generate private key from private key.
+    /// Generate a new OKP type JWK.
+    ///
+    /// The key use parameter (use) is set to "sig" for Ed25519 and Ed448, and "enc" for X25519
+    /// and X448. It can be omitted by `set_parameter("use", None)`.
+    ///
+    /// # Arguments
+    /// * `curve` - A OKP curve algorithm
+    pub fn generate_okp_key(curve: OkpCurve) -> Result<Self, JoseError> {
+        let jwk = match curve {
+            OkpCurve::Ed25519 => EdKeyPair::generate(EdCurve::Ed25519)?.to_jwk_key_pair(),
+            OkpCurve::Ed448 => EdKeyPair::generate(EdCurve::Ed448)?.to_jwk_key_pair(),
+            OkpCurve::X25519 => EcxKeyPair::generate(EcxCurve::X25519)?.to_jwk_key_pair(),
+            OkpCurve::X448 => EcxKeyPair::generate(EcxCurve::X448)?.to_jwk_key_pair(),
+        };
+        Ok(jwk)
+    }
+
+    /// Return a public key that has only the public parameters of this key.
+    ///
+    /// The metadata parameters (kid, use, alg, x5u, x5c, x5t and x5t#S256) are retained,
+    /// and the key operations parameter (key_ops) is mapped to the public counterparts
+    /// of the operations (e.g. sign to verify, decrypt to encrypt, unwrapKey to wrapKey).
+    /// An operation that has no public counterpart is dropped, so the public key is
+    /// never less restricted than this key.
     pub fn to_public_key(&self) -> Result<Self, JoseError> {
         (|| -> anyhow::Result<Jwk> {
-            let jwk = match self.key_type() {
+            let names: &[&str] = match self.key_type() {
                 "oct" => bail!("The key type 'oct' doesn't have public key."),
-                "RSA" => {
-                    let mut jwk = Jwk::new("RSA");
-                    match self.map.get("use") {
-                        Some(Value::String(val)) => {
-                            jwk.map
-                                .insert("use".to_string(), Value::String(val.clone()));
-                        }
-                        _ => {}
-                    }
-                    match self.map.get("e") {
-                        Some(Value::String(val)) => {
-                            jwk.map.insert("e".to_string(), Value::String(val.clone()));
-                        }
-                        Some(_) => bail!("The parameter 'x' must be a string."),
-                        None => bail!("The key type 'RSA' must have parameter 'e'."),
-                    }
-                    match self.map.get("n") {
-                        Some(Value::String(val)) => {
-                            jwk.map.insert("n".to_string(), Value::String(val.clone()));
-                        }
-                        Some(_) => bail!("The parameter 'x' must be a string."),
-                        None => bail!("The key type 'RSA' must have parameter 'n'."),
-                    }
-                    jwk
+                "RSA" => &["n", "e"],
+                "EC" => &["crv", "x", "y"],
+                "OKP" => &["crv", "x"],
+                val => bail!("Unknown key type: {}", val),
+            };
+
+            let mut jwk = Jwk::new(self.key_type());
+            for key in &["kid", "use", "alg", "x5u", "x5c", "x5t", "x5t#S256"] {
+                if let Some(val) = self.map.get(*key) {
+                    jwk.map.insert(key.to_string(), val.clone());
                 }
-                "EC" => {
-                    let mut jwk = Jwk::new("EC");
-                    match self.map.get("use") {
-                        Some(Value::String(val)) => {
-                            jwk.map
-                                .insert("use".to_string(), Value::String(val.clone()));
-                        }
-                        _ => {}
-                    }
-                    match self.map.get("crv") {
-                        Some(Value::String(val)) => match val.as_str() {
-                            "P-256" | "P-384" | "P-521" | "secp256k1" => {
-                                jwk.map
-                                    .insert("crv".to_string(), Value::String(val.clone()));
-                            }
-                            val => bail!("Unknown curve: {}", val),
-                        },
-                        Some(_) => bail!("The parameter 'crv' must be a string."),
-                        None => bail!("The key type 'EC' must have parameter 'crv'."),
-                    }
-                    match self.map.get("x") {
-                        Some(Value::String(val)) => {
-                            jwk.map.insert("x".to_string(), Value::String(val.clone()));
-                        }
-                        Some(_) => bail!("The parameter 'x' must be a string."),
-                        None => bail!("The key type 'EC' must have parameter 'x'."),
-                    }
-                    match self.map.get("y") {
-                        Some(Value::String(val)) => {
-                            jwk.map.insert("y".to_string(), Value::String(val.clone()));
-                        }
-                        Some(_) => bail!("The parameter 'x' must be a string."),
-                        None => bail!("The key type 'EC' must have parameter 'y'."),
+            }
+            if let Some(vals) = self.key_operations() {
+                let mut public_vals = Vec::with_capacity(vals.len());
+                for val in vals {
+                    let public_val = match val {
+                        KeyOperation::Sign | KeyOperation::Verify => KeyOperation::Verify,
+                        KeyOperation::Decrypt | KeyOperation::Encrypt => KeyOperation::Encrypt,
+                        KeyOperation::UnwrapKey | KeyOperation::WrapKey => KeyOperation::WrapKey,
+                        KeyOperation::DeriveKey => KeyOperation::DeriveKey,
+                        KeyOperation::DeriveBits => KeyOperation::DeriveBits,
+                        KeyOperation::Other(_) => continue,
+                    };
+                    if !public_vals.contains(&public_val) {
+                        public_vals.push(public_val);
                     }
-                    jwk
                 }
-                "OKP" => {
-                    let mut jwk = Jwk::new("OKP");
-                    match self.map.get("use") {
-                        Some(Value::String(val)) => {
-                            jwk.map
-                                .insert("use".to_string(), Value::String(val.clone()));
-                        }
-                        _ => {}
-                    }
-                    match self.map.get("crv") {
-                        Some(Value::String(val)) => match val.as_str() {
-                            "Ed25519" | "Ed448" | "X25519" | "X448" => {
-                                jwk.map
-                                    .insert("crv".to_string(), Value::String(val.clone()));
+                // An empty list keeps the key from being used for any operation.
+                jwk.set_key_operations(&public_vals);
+            }
+
+            for name in names {
+                match self.map.get(*name) {
+                    Some(Value::String(val)) => {
+                        if *name == "crv" {
+                            match JwkCurve::from_name(val) {
+                                JwkCurve::Ec(_) if self.key_type() == "EC" => {}
+                                JwkCurve::Okp(_) if self.key_type() == "OKP" => {}
+                                _ => bail!("Unknown curve: {}", val),
                             }
-                            val => bail!("Unknown curve: {}", val),
-                        },
-                        Some(_) => bail!("The parameter 'crv' must be a string."),
-                        None => bail!("The key type 'EC' must have parameter 'crv'."),
-                    }
-                    match self.map.get("x") {
-                        Some(Value::String(val)) => {
-                            jwk.map.insert("x".to_string(), Value::String(val.clone()));
                         }
-                        Some(_) => bail!("The parameter 'x' must be a string."),
-                        None => bail!("The key type 'OKP' must have parameter 'x'."),
+                        jwk.map.insert(name.to_string(), Value::String(val.clone()));
                     }
-                    jwk
+                    Some(_) => bail!("The parameter '{}' must be a string.", name),
+                    None => bail!(
+                        "The key type '{}' must have parameter '{}'.",
+                        self.key_type(),
+                        name
+                    ),
                 }
-                val => bail!("Unknown key type: {}", val),
-            };
+            }
+
             Ok(jwk)
         })()
         .map_err(|err| JoseError::InvalidJwkFormat(err))
     }
 
-    /// Set a value for a key type parameter (kty).
+    /// Test whether this key has the private parameter that is required to use it as a private
+    /// key: d for RSA, EC and OKP, and k for oct.
+    pub fn is_private(&self) -> bool {
+        match self.key_type() {
+            "oct" => self.map.contains_key("k"),
+            _ => self.map.contains_key("d"),
+        }
+    }
+
+    /// Test whether this key has any private parameter.
     ///
-    /// # Arguments
-    /// * `value` - A key type
-    pub fn set_key_type(&mut self, value: impl Into<String>) {
-        let value: String = value.into();
-        self.map.insert("kty".to_string(), Value::String(value));
+    /// An oct key is always private. The CRT parameters (p, q, dp, dq, qi and oth) of RSA
+    /// are private parameters even without d.
+    pub fn has_private_parameters(&self) -> bool {
+        self.key_type() == "oct" || !self.private_parameter_names().is_empty()
     }
 
-    /// Return a value for a key type parameter (kty).
-    pub fn key_type(&self) -> &str {
-        match self.map.get("kty") {
-            Some(Value::String(val)) => val,
-            _ => unreachable!("The JWS kty parameter is required."),
+    /// Return an error that lists the private parameters when this key has any of them.
+    pub fn ensure_public(&self) -> Result<(), JoseError> {
+        let names = match self.key_type() {
+            "oct" => vec!["k"],
+            _ => self.private_parameter_names(),
+        };
+        if names.is_empty() {
+            return Ok(());
         }
+        Err(JoseError::InvalidJwkFormat(anyhow::anyhow!(
+            "The {} key has private parameters: {}",
+            self.key_type(),
+            names.join(", ")
+        )))
     }
 
-    /// Set a value for a key use parameter (use).
-    ///
-    /// # Arguments
-    /// * `value` - A key use
-    pub fn set_key_use(&mut self, value: impl Into<String>) {
-        let value: String = value.into();
-        self.map.insert("use".to_string(), Value::String(value));
+    fn private_parameter_names(&self) -> Vec<&'static str> {
+        let names: &[&'static str] = match self.key_type() {
+            "RSA" => &["d", "p", "q", "dp", "dq", "qi", "oth"],
+            "EC" | "OKP" => &["d"],
+            "oct" => &["k"],
+            _ => &["d", "p", "q", "dp", "dq", "qi", "oth", "k"],
+        };
+        names
+            .iter()
+            .filter(|name| self.map.contains_key(**name))
+            .copied()
+            .collect()
     }
 
-    /// Return a value for a key use parameter (use).
-    pub fn key_use(&self) -> Option<&str> {
-        match self.map.get("use") {
-            Some(Value::String(val)) => Some(val),
-            None => None,
-            _ => unreachable!(),
-        }
+    /// Return a JSON text of this key for hashing or comparing.
+    ///
+    /// The members are in lexicographic order of the names and there is no insignificant
+    /// whitespace, like the RFC 7638 thumbprint but over all members. The nested objects
+    /// are ordered in the same way, and the array elements are kept in order.
+    pub fn to_canonical_string(&self) -> String {
+        util::to_canonical_json(&self.map)
     }
 
-    /// Set values for a key operations parameter (key_ops).
+    /// Test whether this key has the same canonical JSON text as the other key.
     ///
     /// # Arguments
-    /// * `values` - key operations
-    pub fn set_key_operations(&mut self, values: Vec<impl Into<String>>) {
-        let mut vec = Vec::with_capacity(values.len());
-        for val in values {
-            let val: String = val.into();
-            vec.push(Value::String(val.clone()));
-        }
-        self.map.insert("key_ops".to_string(), Value::Array(vec));
+    /// * `other` - A key to compare with
+    pub fn equals_canonically(&self, other: &Jwk) -> bool {
+        self.to_canonical_string() == other.to_canonical_string()
     }
 
-    /// Return values for a key operations parameter (key_ops).
-    pub fn key_operations(&self) -> Option<Vec<&str>> {
-        match self.map.get("key_ops") {
-            Some(Value::Array(vals)) => {
-                let mut vec = Vec::with_capacity(vals.len());
-                for val in vals {
-                    match val {
-                        Value::String(val2) => vec.push(val2.as_str()),
-                        _ => return None,
-                    }
+    /// Return the JWK thumbprint defined in RFC 7638.
+    ///
+    /// # Arguments
+    /// * `hash` - A hash algorithm for the thumbprint
+    pub fn thumbprint(&self, hash: HashAlgorithm) -> Result<Vec<u8>, JoseError> {
+        (|| -> anyhow::Result<Vec<u8>> {
+            let names: &[&str] = match self.key_type() {
+                "oct" => &["k", "kty"],
+                "RSA" => &["e", "kty", "n"],
+                "EC" => &["crv", "kty", "x", "y"],
+                "OKP" => &["crv", "kty", "x"],
+                val => bail!("Unknown key type: {}", val),
+            };
+
+            let mut json = String::from("{");
+            for (i, name) in names.iter().enumerate() {
+                let val = match self.map.get(*name) {
+                    Some(val @ Value::String(_)) => val,
+                    Some(_) => bail!("The parameter '{}' must be a string.", name),
+                    None => bail!(
+                        "The key type '{}' must have parameter '{}'.",
+                        self.key_type(),
+                        name
+                    ),
+                };
+                if i > 0 {
+                    json.push(',');
                 }
-                Some(vec)
+                json.push_str(&serde_json::to_string(name)?);
+                json.push(':');
+                json.push_str(&serde_json::to_string(val)?);
             }
-            _ => None,
-        }
+            json.push('}');
+
+            let digest = hash::hash(hash.message_digest(), json.as_bytes())?;
+            Ok(digest.to_vec())
+        })()
+        .map_err(JoseError::InvalidJwkFormat)
     }
 
-    pub fn is_for_key_operation(&self, key_operation: &str) -> bool {
-        match self.map.get("key_ops") {
-            Some(Value::Array(vals)) => vals.iter().any(|val| match val {
-                Value::String(val2) if val2 == key_operation => true,
-                _ => false,
-            }),
-            Some(_) => false,
-            None => true,
-        }
+    /// Set the base64url-encoded JWK thumbprint defined in RFC 7638 as the key ID parameter (kid).
+    ///
+    /// It must be called again after the key parameters are changed to keep the kid in sync.
+    /// The kid is left unchanged when a required member of the thumbprint is missing.
+    ///
+    /// # Arguments
+    /// * `hash` - A hash algorithm for the thumbprint
+    pub fn set_key_id_from_thumbprint(&mut self, hash: HashAlgorithm) -> Result<(), JoseError> {
+        let thumbprint = self.thumbprint(hash)?;
+        self.set_key_id(base64::encode_config(&thumbprint, base64::URL_SAFE_NO_PAD));
+        Ok(())
     }
 
-    /// Set a value for a algorithm parameter (alg).
+    /// Return this key with the base64url-encoded JWK thumbprint as the key ID parameter (kid).
+    ///
+    /// It is intended to be chained to the key generation functions, for example
+    /// `Jwk::generate_ec_key(EcCurve::P256)?.with_thumbprint_kid(HashAlgorithm::Sha256)?`.
     ///
     /// # Arguments
-    /// * `value` - A algorithm
-    pub fn set_algorithm(&mut self, value: impl Into<String>) {
-        let value: String = value.into();
-        self.map.insert("alg".to_string(), Value::String(value));
+    /// * `hash` - A hash algorithm for the thumbprint
+    pub fn with_thumbprint_kid(mut self, hash: HashAlgorithm) -> Result<Self, JoseError> {
+        self.set_key_id_from_thumbprint(hash)?;
+        Ok(self)
     }
 
-    /// Return a value for a algorithm parameter (alg).
-    pub fn algorithm(&self) -> Option<&str> {
-        match self.map.get("alg") {
-            Some(Value::String(val)) => Some(val),
+    /// Validate that the key parameters are consistent with each other.
+    ///
+    /// For RSA, it checks that n is the product of the primes, d * e ≡ 1 mod lcm(p - 1, q - 1)
+    /// and the CRT parameters dp, dq and qi (and those of oth). For EC, it checks that the point
+    /// (x, y) is on the curve and is equal to d * G when d is present. For OKP, it checks that
+    /// x is equal to the public key that is derived from d when d is present.
+    pub fn validate_consistency(&self) -> Result<(), JoseError> {
+        (|| -> anyhow::Result<()> {
+            match self.key_type() {
+                "oct" => {}
+                "RSA" => self.validate_rsa_consistency()?,
+                "EC" => {
+                    let curve = self.ec_curve()?;
+                    let x = self.decode_parameter("x")?;
+                    let y = self.decode_parameter("y")?;
+                    match self.decode_optional_parameter("d")? {
+                        Some(d) => {
+                            EcKeyPair::private_key_from_components(curve, &x, &y, &d)?;
+                        }
+                        None => {
+                            EcKeyPair::public_key_from_components(curve, &x, &y)?;
+                        }
+                    }
+                }
+                "OKP" => {
+                    let curve = self.okp_curve()?;
+                    let x = self.decode_parameter("x")?;
+                    if x.len() != curve.key_len() {
+                        bail!(
+                            "The public key (x) length must be {} for {}: {}",
+                            curve.key_len(),
+                            curve,
+                            x.len()
+                        );
+                    }
+                    if let Some(d) = self.decode_optional_parameter("d")? {
+                        if d.len() != curve.key_len() {
+                            bail!(
+                                "The private key (d) length must be {} for {}: {}",
+                                curve.key_len(),
+                                curve,
+                                d.len()
+                            );
+                        }
+                        let private_key = PKey::private_key_from_raw_bytes(&d, curve.id())?;
+                        if private_key.raw_public_key()? != x {
+                            bail!("The public key (x) does not correspond to the private key (d).");
+                        }
+                    }
+                }
+                val => bail!("The key type is unsupported: {}", val),
+            }
+            Ok(())
+        })()
+        .map_err(JoseError::InvalidJwkFormat)
+    }
+
+    fn validate_rsa_consistency(&self) -> anyhow::Result<()> {
+        let n = self.decode_parameter("n")?;
+        let e = self.decode_parameter("e")?;
+        let d = match self.decode_optional_parameter("d")? {
+            Some(val) => val,
+            None => return Ok(()),
+        };
+
+        let [p, q, dp, dq, qi] = RsaKeyPair::crt_params_from_jwk(self, &n, &e, &d)?;
+        let oth = RsaKeyPair::other_primes_from_jwk(self)?;
+        RsaKeyPair::check_primes(&n, &p, &q, &oth)?;
+
+        let mut ctx = BigNumContext::new()?;
+        let one = BigNum::from_u32(1)?;
+        let e = BigNum::from_slice(&e)?;
+        let d = BigNum::from_slice(&d)?;
+
+        let p = BigNum::from_slice(&p)?;
+        let q = BigNum::from_slice(&q)?;
+        let qi = BigNum::from_slice(&qi)?;
+
+        // The primes with their CRT exponents and coefficients in the order of RFC 8017.
+        let mut primes = vec![
+            (p.to_owned()?, BigNum::from_slice(&dp)?, None),
+            (q.to_owned()?, BigNum::from_slice(&dq)?, None),
+        ];
+        for (r, d, t) in &oth {
+            primes.push((
+                BigNum::from_slice(r)?,
+                BigNum::from_slice(d)?,
+                Some(BigNum::from_slice(t)?),
+            ));
+        }
+
+        let mut lambda = BigNum::from_u32(1)?;
+        for (prime, _, _) in &primes {
+            if prime <= &one {
+                bail!("The RSA primes must be greater than 1.");
+            }
+            let prime_minus_one = prime - &one;
+            let mut gcd = BigNum::new()?;
+            gcd.gcd(&lambda, &prime_minus_one, &mut ctx)?;
+            lambda = &(&lambda * &prime_minus_one) / &gcd;
+        }
+
+        let mut val = BigNum::new()?;
+        val.mod_mul(&d, &e, &lambda, &mut ctx)?;
+        if val != one {
+            bail!("The private exponent (d) is not the inverse of the public exponent (e).");
+        }
+
+        // qi = q^-1 mod p
+        val.mod_mul(&qi, &q, &p, &mut ctx)?;
+        if val != one {
+            bail!("The CRT coefficient (qi) is inconsistent with the primes.");
+        }
+
+        let mut product = BigNum::from_u32(1)?;
+        for (i, (prime, exponent, coefficient)) in primes.iter().enumerate() {
+            if &(&d % &(prime - &one)) != exponent {
+                bail!(
+                    "The CRT exponent of the prime #{} is inconsistent with d.",
+                    i + 1
+                );
+            }
+            // t = (r_1 * ... * r_(i-1))^-1 mod r_i
+            if let Some(coefficient) = coefficient {
+                val.mod_mul(coefficient, &product, prime, &mut ctx)?;
+                if val != one {
+                    bail!(
+                        "The CRT coefficient of the prime #{} is inconsistent with the primes.",
+                        i + 1
+                    );
+                }
+            }
+            product = &product * prime;
+        }
+
+        Ok(())
+    }
+
+    fn ec_curve(&self) -> anyhow::Result<EcCurve> {
+        match self.curve() {
+            Some(JwkCurve::Ec(curve)) => Ok(curve),
+            Some(val) => bail!("The JWK crv parameter is unsupported: {}", val),
+            None => bail!("The JWK crv parameter is required."),
+        }
+    }
+
+    fn okp_curve(&self) -> anyhow::Result<OkpCurve> {
+        match self.curve() {
+            Some(JwkCurve::Okp(curve)) => Ok(curve),
+            Some(val) => bail!("The JWK crv parameter is unsupported: {}", val),
+            None => bail!("The JWK crv parameter is required."),
+        }
+    }
+
+    fn decode_parameter(&self, key: &str) -> anyhow::Result<Vec<u8>> {
+        match self.decode_optional_parameter(key)? {
+            Some(val) => Ok(val),
+            None => bail!("The JWK {} parameter is required.", key),
+        }
+    }
+
+    fn decode_optional_parameter(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        match self.map.get(key) {
+            Some(Value::String(val)) => {
+                Ok(Some(base64::decode_config(val, base64::URL_SAFE_NO_PAD)?))
+            }
+            Some(_) => bail!("The JWK {} parameter must be a string.", key),
+            None => Ok(None),
+        }
+    }
+
+    /// Return a JWE compact serialization that has this key as the plaintext.
+    ///
+    /// The content type header parameter (cty) is set to "jwk+json". The content encryption
+    /// must be registered in the default JWE context.
+    ///
+    /// # Arguments
+    /// * `encrypter` - A JWE encrypter
+    /// * `enc` - A content encryption algorithm
+    pub fn encrypt_to_jwe(
+        &self,
+        encrypter: &dyn JweEncrypter,
+        enc: &dyn JweContentEncryption,
+    ) -> Result<String, JoseError> {
+        let mut header = JweHeader::new();
+        header.set_content_encryption(enc.name());
+        header.set_content_type("jwk+json");
+
+        let payload = Zeroizing::new(self.to_string().into_bytes());
+        jwe::serialize_compact(&payload, &header, encrypter)
+    }
+
+    /// Return a JWK from a JWE compact serialization that is made by [`Jwk::encrypt_to_jwe`].
+    ///
+    /// The content type header parameter (cty) must be "jwk+json" when it is present.
+    ///
+    /// # Arguments
+    /// * `input` - A JWE compact serialization
+    /// * `decrypter` - A JWE decrypter
+    pub fn decrypt_from_jwe(input: &str, decrypter: &dyn JweDecrypter) -> Result<Self, JoseError> {
+        let (payload, header) = jwe::deserialize_compact(input, decrypter)?;
+        let payload = Zeroizing::new(payload);
+        (|| -> anyhow::Result<Self> {
+            match header.content_type() {
+                Some(val)
+                    if val.eq_ignore_ascii_case("jwk+json")
+                        || val.eq_ignore_ascii_case("application/jwk+json") => {}
+                None => {}
+                Some(val) => bail!("The JWE cty header claim must be jwk+json: {}", val),
+            }
+            let map: Map<String, Value> = serde_json::from_slice(&payload)?;
+            Ok(Self::from_map(map)?)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidJwkFormat(err),
+        })
+    }
+
+    /// Return a JWE compact serialization that has this key as the plaintext and is encrypted
+    /// by a password with PBES2-HS512+A256KW and A256GCM.
+    ///
+    /// The password is normalized to Unicode NFC before the key derivation.
+    ///
+    /// # Arguments
+    /// * `password` - A password that must not be empty
+    #[cfg(all(feature = "jwe-pbes2", feature = "enc-gcm"))]
+    pub fn encrypt_with_password(&self, password: &str) -> Result<String, JoseError> {
+        let encrypter = PBES2_HS512_A256KW.encrypter_from_password(password)?;
+        self.encrypt_to_jwe(&encrypter, &A256GCM)
+    }
+
+    /// Return a JWK from a JWE compact serialization that is made by
+    /// [`Jwk::encrypt_with_password`].
+    ///
+    /// The password is normalized to Unicode NFC before the key derivation.
+    ///
+    /// # Arguments
+    /// * `input` - A JWE compact serialization
+    /// * `password` - A password that must not be empty
+    #[cfg(all(feature = "jwe-pbes2", feature = "enc-gcm"))]
+    pub fn decrypt_with_password(input: &str, password: &str) -> Result<Self, JoseError> {
+        let decrypter = PBES2_HS512_A256KW.decrypter_from_password(password)?;
+        Self::decrypt_from_jwe(input, &decrypter)
+    }
+
+    /// Return a DER encoded PKCS#8 PrivateKeyInfo from this private key.
+    pub fn to_der_private_key(&self) -> Result<Vec<u8>, JoseError> {
+        let key_pair = self.to_key_pair()?;
+        match key_pair.as_ec() {
+            Some(val) => Ok(val.to_pkcs8_der_private_key()),
+            None => Ok(key_pair.to_der_private_key()),
+        }
+    }
+
+    /// Return a PEM encoded PKCS#8 PrivateKeyInfo from this private key.
+    pub fn to_pem_private_key(&self) -> Result<Vec<u8>, JoseError> {
+        Ok(self.to_key_pair()?.to_pem_private_key())
+    }
+
+    /// Return a DER encoded SubjectPublicKeyInfo from this public or private key.
+    pub fn to_der_public_key(&self) -> Result<Vec<u8>, JoseError> {
+        let public_key = self.to_public_pkey()?;
+        public_key
+            .public_key_to_der()
+            .map_err(|err| JoseError::InvalidKeyFormat(err.into()))
+    }
+
+    /// Return a PEM encoded SubjectPublicKeyInfo from this public or private key.
+    pub fn to_pem_public_key(&self) -> Result<Vec<u8>, JoseError> {
+        let public_key = self.to_public_pkey()?;
+        public_key
+            .public_key_to_pem()
+            .map_err(|err| JoseError::InvalidKeyFormat(err.into()))
+    }
+
+    /// Return an openssl private key from this private key.
+    ///
+    /// The key is built from the key components without DER encoding,
+    /// except for a RSA key that has the other primes info parameter (oth).
+    pub fn to_private_pkey(&self) -> Result<PKey<Private>, JoseError> {
+        (|| -> anyhow::Result<PKey<Private>> {
+            let private_key = match self.key_type() {
+                "oct" => bail!("The key type 'oct' doesn't have private key."),
+                "RSA" if self.map.contains_key("oth") => {
+                    RsaKeyPair::from_jwk(self)?.into_private_key()
+                }
+                "RSA" => {
+                    let n = self.decode_parameter("n")?;
+                    let e = self.decode_parameter("e")?;
+                    let d = self.decode_parameter("d")?;
+                    let [p, q, dp, dq, qi] = RsaKeyPair::crt_params_from_jwk(self, &n, &e, &d)?;
+                    RsaKeyPair::check_primes(&n, &p, &q, &[])?;
+                    let rsa = Rsa::from_private_components(
+                        BigNum::from_slice(&n)?,
+                        BigNum::from_slice(&e)?,
+                        BigNum::from_slice(&d)?,
+                        BigNum::from_slice(&p)?,
+                        BigNum::from_slice(&q)?,
+                        BigNum::from_slice(&dp)?,
+                        BigNum::from_slice(&dq)?,
+                        BigNum::from_slice(&qi)?,
+                    )?;
+                    PKey::from_rsa(rsa)?
+                }
+                "EC" => {
+                    let curve = self.ec_curve()?;
+                    let x = self.decode_parameter("x")?;
+                    let y = self.decode_parameter("y")?;
+                    let d = self.decode_parameter("d")?;
+                    let ec_key = EcKeyPair::private_key_from_components(curve, &x, &y, &d)?;
+                    PKey::from_ec_key(ec_key)?
+                }
+                "OKP" => {
+                    let id = self.okp_curve()?.id();
+                    PKey::private_key_from_raw_bytes(&self.decode_parameter("d")?, id)?
+                }
+                val => bail!("The key type is unsupported: {}", val),
+            };
+            Ok(private_key)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidKeyFormat(err),
+        })
+    }
+
+    fn to_key_pair(&self) -> Result<Box<dyn KeyPair>, JoseError> {
+        (|| -> anyhow::Result<Box<dyn KeyPair>> {
+            if let "oct" = self.key_type() {
+                bail!("The key type 'oct' cannot be exported as a PEM or DER key.");
+            }
+            if !self.map.contains_key("d") {
+                bail!("The JWK doesn't have private key parameters.");
+            }
+
+            let key_pair: Box<dyn KeyPair> = match self.key_type() {
+                "RSA" => Box::new(RsaKeyPair::from_jwk(self)?),
+                "EC" => Box::new(EcKeyPair::from_jwk(self)?),
+                "OKP" => match self.okp_curve()?.ed_curve() {
+                    Some(_) => Box::new(EdKeyPair::from_jwk(self)?),
+                    None => Box::new(EcxKeyPair::from_jwk(self)?),
+                },
+                val => bail!("The key type is unsupported: {}", val),
+            };
+            Ok(key_pair)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidKeyFormat(err),
+        })
+    }
+
+    /// Return an openssl public key from this public or private key.
+    ///
+    /// The key is built from the key components without DER encoding.
+    pub fn to_public_pkey(&self) -> Result<PKey<Public>, JoseError> {
+        (|| -> anyhow::Result<PKey<Public>> {
+            let decode = |key: &str| -> anyhow::Result<Vec<u8>> {
+                match self.map.get(key) {
+                    Some(Value::String(val)) => {
+                        Ok(base64::decode_config(val, base64::URL_SAFE_NO_PAD)?)
+                    }
+                    Some(_) => bail!("The JWK {} parameter must be a string.", key),
+                    None => bail!("The JWK {} parameter is required.", key),
+                }
+            };
+
+            let public_key = match self.key_type() {
+                "oct" => bail!("The key type 'oct' doesn't have public key."),
+                "RSA" => {
+                    let n = BigNum::from_slice(&decode("n")?)?;
+                    let e = BigNum::from_slice(&decode("e")?)?;
+                    PKey::from_rsa(Rsa::from_public_components(n, e)?)?
+                }
+                "EC" => {
+                    let curve = self.ec_curve()?;
+                    let mut point = vec![0x04];
+                    point.extend_from_slice(&decode("x")?);
+                    point.extend_from_slice(&decode("y")?);
+                    PKey::from_ec_key(EcKeyPair::public_key_from_sec1(&point, curve)?)?
+                }
+                "OKP" => {
+                    let id = self.okp_curve()?.id();
+                    PKey::public_key_from_raw_bytes(&decode("x")?, id)?
+                }
+                val => bail!("The key type is unsupported: {}", val),
+            };
+            Ok(public_key)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidKeyFormat(err),
+        })
+    }
+
+    /// Set a value for a key type parameter (kty).
+    ///
+    /// # Arguments
+    /// * `value` - A key type
+    pub fn set_key_type(&mut self, value: impl Into<String>) {
+        let value: String = value.into();
+        self.map.insert("kty".to_string(), Value::String(value));
+    }
+
+    /// Return a value for a key type parameter (kty).
+    pub fn key_type(&self) -> &str {
+        match self.map.get("kty") {
+            Some(Value::String(val)) => val,
+            _ => unreachable!("The JWS kty parameter is required."),
+        }
+    }
+
+    /// Set a value for a key use parameter (use).
+    ///
+    /// # Arguments
+    /// * `value` - A key use
+    pub fn set_key_use(&mut self, value: impl Into<String>) {
+        let value: String = value.into();
+        self.map.insert("use".to_string(), Value::String(value));
+    }
+
+    /// Return a value for a key use parameter (use).
+    pub fn key_use(&self) -> Option<&str> {
+        match self.map.get("use") {
+            Some(Value::String(val)) => Some(val),
+            None => None,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Set values for a key operations parameter (key_ops).
+    ///
+    /// # Arguments
+    /// * `values` - key operations
+    pub fn set_key_operations(&mut self, values: &[KeyOperation]) {
+        let mut vec = Vec::with_capacity(values.len());
+        for val in values {
+            vec.push(Value::String(val.name().to_string()));
+        }
+        self.map.insert("key_ops".to_string(), Value::Array(vec));
+    }
+
+    /// Return values for a key operations parameter (key_ops).
+    ///
+    /// A value that is not registered in RFC 7517 is returned as `KeyOperation::Other`.
+    pub fn key_operations(&self) -> Option<Vec<KeyOperation>> {
+        match self.map.get("key_ops") {
+            Some(Value::Array(vals)) => {
+                let mut vec = Vec::with_capacity(vals.len());
+                for val in vals {
+                    match val {
+                        Value::String(val2) => vec.push(KeyOperation::from(val2.as_str())),
+                        _ => return None,
+                    }
+                }
+                Some(vec)
+            }
+            _ => None,
+        }
+    }
+
+    /// Test whether the key can be used for the operation.
+    ///
+    /// It returns true when the key operations parameter (key_ops) is absent.
+    ///
+    /// # Arguments
+    /// * `key_operation` - A key operation
+    pub fn is_for_key_operation(&self, key_operation: &KeyOperation) -> bool {
+        match self.map.get("key_ops") {
+            Some(Value::Array(vals)) => vals.iter().any(|val| match val {
+                Value::String(val2) => val2 == key_operation.name(),
+                _ => false,
+            }),
+            Some(_) => false,
+            None => true,
+        }
+    }
+
+    /// Set a value for a algorithm parameter (alg).
+    ///
+    /// # Arguments
+    /// * `value` - A algorithm
+    pub fn set_algorithm(&mut self, value: impl Into<String>) {
+        let value: String = value.into();
+        self.map.insert("alg".to_string(), Value::String(value));
+    }
+
+    /// Return a value for a algorithm parameter (alg).
+    pub fn algorithm(&self) -> Option<&str> {
+        match self.map.get("alg") {
+            Some(Value::String(val)) => Some(val),
             None => None,
             _ => unreachable!(),
         }
@@ -340,6 +1272,24 @@ impl Jwk {
         }
     }
 
+    /// Set a value for a extractable parameter (ext) of WebCrypto.
+    ///
+    /// # Arguments
+    /// * `value` - true if the key is extractable
+    pub fn set_extractable(&mut self, value: bool) {
+        self.map.insert("ext".to_string(), Value::Bool(value));
+    }
+
+    /// Return a value for a extractable parameter (ext) of WebCrypto.
+    ///
+    /// It returns None when the parameter is not a boolean.
+    pub fn extractable(&self) -> Option<bool> {
+        match self.map.get("ext") {
+            Some(Value::Bool(val)) => Some(*val),
+            _ => None,
+        }
+    }
+
     /// Set a value for a x509 certificate SHA-1 thumbprint parameter (x5t).
     ///
     /// # Arguments
@@ -384,22 +1334,96 @@ impl Jwk {
         }
     }
 
+    /// Set the SHA-1 thumbprint of a DER encoded X.509 certificate to X.509 certificate
+    /// SHA-1 thumbprint parameter (x5t).
+    ///
+    /// # Arguments
+    /// * `cert_der` - A DER encoded X.509 certificate
+    pub fn set_x509_certificate_sha1_thumbprint_from_der(
+        &mut self,
+        cert_der: impl AsRef<[u8]>,
+    ) -> Result<(), JoseError> {
+        let thumbprint = util::x509_thumbprint(cert_der.as_ref(), HashAlgorithm::Sha1)
+            .map_err(JoseError::InvalidKeyFormat)?;
+        self.set_x509_certificate_sha1_thumbprint(thumbprint);
+        Ok(())
+    }
+
+    /// Set the SHA-256 thumbprint of a DER encoded X.509 certificate to X.509 certificate
+    /// SHA-256 thumbprint parameter (x5t#S256).
+    ///
+    /// # Arguments
+    /// * `cert_der` - A DER encoded X.509 certificate
+    pub fn set_x509_certificate_sha256_thumbprint_from_der(
+        &mut self,
+        cert_der: impl AsRef<[u8]>,
+    ) -> Result<(), JoseError> {
+        let thumbprint = util::x509_thumbprint(cert_der.as_ref(), HashAlgorithm::Sha256)
+            .map_err(JoseError::InvalidKeyFormat)?;
+        self.set_x509_certificate_sha256_thumbprint(thumbprint);
+        Ok(())
+    }
+
+    /// Set a DER encoded X.509 certificate to X.509 certificate chain parameter (x5c),
+    /// and its thumbprints to x5t and x5t#S256.
+    ///
+    /// # Arguments
+    /// * `cert_der` - A DER encoded X.509 certificate
+    pub fn set_x509_from_certificate(
+        &mut self,
+        cert_der: impl AsRef<[u8]>,
+    ) -> Result<(), JoseError> {
+        let cert_der = cert_der.as_ref();
+        self.set_x509_certificate_sha1_thumbprint_from_der(cert_der)?;
+        self.set_x509_certificate_sha256_thumbprint_from_der(cert_der)?;
+        self.set_x509_certificate_chain(&[cert_der]);
+        Ok(())
+    }
+
+    /// Test whether x5t and x5t#S256 parameters are the thumbprints of a DER encoded
+    /// X.509 certificate (e.g. the first certificate of x5c).
+    ///
+    /// It returns false when neither parameter is present, and an error when
+    /// a parameter is malformed.
+    ///
+    /// # Arguments
+    /// * `cert_der` - A DER encoded X.509 certificate
+    pub fn x509_thumbprint_matches(&self, cert_der: impl AsRef<[u8]>) -> Result<bool, JoseError> {
+        let thumbprint = |key: &str| -> anyhow::Result<Option<Vec<u8>>> {
+            match self.map.get(key) {
+                Some(Value::String(val)) => {
+                    Ok(Some(base64::decode_config(val, base64::URL_SAFE_NO_PAD)?))
+                }
+                Some(_) => bail!("A parameter {} must be a string.", key),
+                None => Ok(None),
+            }
+        };
+
+        (|| -> anyhow::Result<bool> {
+            Ok(util::is_x509_thumbprint_match(
+                cert_der.as_ref(),
+                thumbprint("x5t")?,
+                thumbprint("x5t#S256")?,
+            ))
+        })()
+        .map_err(JoseError::InvalidJwkFormat)
+    }
+
     /// Set values for a X.509 certificate chain parameter (x5c).
     ///
+    /// Each certificate is encoded by standard base64 with padding as defined in RFC 7517.
+    ///
     /// # Arguments
-    /// * `values` - X.509 certificate chain
-    pub fn set_x509_certificate_chain(&mut self, values: &Vec<impl AsRef<[u8]>>) {
+    /// * `values` - DER encoded X.509 certificates that start with the leaf certificate.
+    pub fn set_x509_certificate_chain(&mut self, values: &[impl AsRef<[u8]>]) {
         let mut vec = Vec::with_capacity(values.len());
         for val in values {
-            vec.push(Value::String(base64::encode_config(
-                &val,
-                base64::URL_SAFE_NO_PAD,
-            )));
+            vec.push(Value::String(base64::encode_config(&val, base64::STANDARD)));
         }
         self.map.insert("x5c".to_string(), Value::Array(vec));
     }
 
-    /// Return values for a X.509 certificate chain parameter (x5c).
+    /// Return DER encoded X.509 certificates of a X.509 certificate chain parameter (x5c).
     pub fn x509_certificate_chain(&self) -> Option<Vec<Vec<u8>>> {
         match self.map.get("x5c") {
             Some(Value::Array(vals)) => {
@@ -407,7 +1431,7 @@ impl Jwk {
                 for val in vals {
                     match val {
                         Value::String(val2) => {
-                            match base64::decode_config(val2, base64::URL_SAFE_NO_PAD) {
+                            match base64::decode_config(val2, base64::STANDARD) {
                                 Ok(val3) => vec.push(val3),
                                 Err(_) => return None,
                             }
@@ -421,7 +1445,91 @@ impl Jwk {
         }
     }
 
-    /// Set a value for a curve parameter (crv).
+    /// Verify the X.509 certificate chain parameter (x5c) and its leaf certificate.
+    ///
+    /// The chain must be validated by the trust anchors, and the public key of the leaf
+    /// certificate must match the public key parameters of this JWK. The validity periods
+    /// of the certificates are checked at the current system time.
+    ///
+    /// # Arguments
+    /// * `trust_anchors` - DER encoded X.509 certificates that are trusted.
+    pub fn verify_x509_chain(&self, trust_anchors: &[impl AsRef<[u8]>]) -> Result<(), JoseError> {
+        self.verify_x509_chain_with_clock(trust_anchors, &SystemClock)
+    }
+
+    /// Verify the X.509 certificate chain parameter (x5c) and its leaf certificate
+    /// at the current time of a clock instead of the system time.
+    ///
+    /// # Arguments
+    /// * `trust_anchors` - DER encoded X.509 certificates that are trusted.
+    /// * `clock` - A clock that provides the verification time.
+    pub fn verify_x509_chain_with_clock(
+        &self,
+        trust_anchors: &[impl AsRef<[u8]>],
+        clock: &dyn Clock,
+    ) -> Result<(), JoseError> {
+        (|| -> anyhow::Result<()> {
+            let chain = match self.map.get("x5c") {
+                Some(_) => match self.x509_certificate_chain() {
+                    Some(val) if !val.is_empty() => val,
+                    Some(_) => bail!("The JWK x5c parameter must not be empty."),
+                    None => bail!("The JWK x5c parameter must be a array of base64 string."),
+                },
+                None => bail!("The JWK x5c parameter is required."),
+            };
+
+            let leaf = X509::from_der(&chain[0])?;
+            let mut intermediates = Stack::new()?;
+            for der in &chain[1..] {
+                intermediates.push(X509::from_der(der)?)?;
+            }
+
+            let time = match clock.now().duration_since(SystemTime::UNIX_EPOCH) {
+                Ok(val) => val.as_secs(),
+                Err(_) => bail!("The verification time must be after the UNIX epoch."),
+            };
+            let mut param = X509VerifyParam::new()?;
+            param.set_time(TryFrom::try_from(time)?);
+
+            let mut store = X509StoreBuilder::new()?;
+            for der in trust_anchors {
+                store.add_cert(X509::from_der(der.as_ref())?)?;
+            }
+            store.set_param(&param)?;
+            let store = store.build();
+
+            let mut context = X509StoreContext::new()?;
+            let (is_valid, error) = context.init(&store, &leaf, &intermediates, |ctx| {
+                Ok((ctx.verify_cert()?, ctx.error()))
+            })?;
+            if !is_valid {
+                bail!(
+                    "The X.509 certificate chain is invalid: {}",
+                    error.error_string()
+                );
+            }
+
+            let leaf_jwk = Self::from_der(leaf.public_key()?.public_key_to_der()?)?;
+            let names: &[&str] = match leaf_jwk.key_type() {
+                "RSA" => &["kty", "n", "e"],
+                "EC" => &["kty", "crv", "x", "y"],
+                _ => &["kty", "crv", "x"],
+            };
+            for name in names {
+                if leaf_jwk.map.get(*name) != self.map.get(*name) {
+                    bail!("The public key of the leaf certificate does not match the JWK.");
+                }
+            }
+
+            Ok(())
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidKeyFormat(err),
+        })
+    }
+
+    /// Set a value for a curve parameter (crv).
     ///
     /// # Arguments
     /// * `value` - A curve
@@ -431,9 +1539,11 @@ impl Jwk {
     }
 
     /// Return a value for a curve parameter (crv).
-    pub fn curve(&self) -> Option<&str> {
+    ///
+    /// An unsupported curve name is returned as `JwkCurve::Other`.
+    pub fn curve(&self) -> Option<JwkCurve> {
         match self.map.get("crv") {
-            Some(Value::String(val)) => Some(val),
+            Some(Value::String(val)) => Some(JwkCurve::from_name(val)),
             None => None,
             _ => unreachable!(),
         }
@@ -463,6 +1573,9 @@ impl Jwk {
 
     /// Set a value for a parameter of a specified key.
     ///
+    /// The parameters that are required by the key type (kty) cannot be removed: n and e
+    /// for RSA, crv, x and y for EC, crv and x for OKP, and k for oct.
+    ///
     /// # Arguments
     /// * `key` - A key name of a parameter
     /// * `value` - A typed value of a parameter
@@ -473,22 +1586,75 @@ impl Jwk {
                 self.map.insert(key.to_string(), val);
             }
             None => {
-                (|| -> anyhow::Result<()> {
-                    match key {
-                        "kty" => bail!("The JWK {} parameter must be required.", key),
-                        _ => {}
-                    }
-                    Ok(())
-                })()
-                .map_err(|err| JoseError::InvalidJwkFormat(err))?;
-
-                self.map.remove(key);
+                self.remove_parameter(key)?;
             }
         }
 
         Ok(())
     }
 
+    /// Remove a parameter of a specified key and return the removed value.
+    ///
+    /// The parameters that are required by the key type (kty) cannot be removed
+    /// in the same way as `set_parameter`.
+    ///
+    /// # Arguments
+    /// * `key` - A key name of a parameter
+    pub fn remove_parameter(&mut self, key: &str) -> Result<Option<Value>, JoseError> {
+        (|| -> anyhow::Result<()> {
+            let required: &[&str] = match self.map.get("kty") {
+                Some(Value::String(val)) => match val.as_str() {
+                    "RSA" => &["n", "e"],
+                    "EC" => &["crv", "x", "y"],
+                    "OKP" => &["crv", "x"],
+                    "oct" => &["k"],
+                    _ => &[],
+                },
+                _ => &[],
+            };
+            if key == "kty" {
+                bail!("The JWK {} parameter must be required.", key);
+            }
+            if required.contains(&key) {
+                bail!(
+                    "The JWK {} parameter is required for the key type {}.",
+                    key,
+                    self.key_type()
+                );
+            }
+            Ok(())
+        })()
+        .map_err(JoseError::InvalidJwkFormat)?;
+
+        Ok(self.map.shift_remove(key))
+    }
+
+    /// Return the key size in bits.
+    ///
+    /// It is the bit length of the modulus (n) for RSA, the bit length of the field of the curve
+    /// for EC and OKP, and the bit length of the key value (k) for oct. None is returned when
+    /// the parameters are missing or invalid.
+    pub fn key_bits(&self) -> Option<u32> {
+        match self.key_type() {
+            "RSA" => {
+                let n = self.decode_optional_parameter("n").ok()??;
+                let start = n.iter().position(|val| *val != 0)?;
+                let bits = (n.len() - start) * 8 - n[start].leading_zeros() as usize;
+                Some(bits as u32)
+            }
+            "EC" | "OKP" => match self.curve()? {
+                JwkCurve::Ec(curve) => Some(curve.key_bits()),
+                JwkCurve::Okp(curve) => Some(curve.key_bits()),
+                JwkCurve::Other(_) => None,
+            },
+            "oct" => {
+                let k = self.decode_optional_parameter("k").ok()??;
+                Some((k.len() * 8) as u32)
+            }
+            _ => None,
+        }
+    }
+
     /// Return a value for a parameter of a specified key.
     ///
     /// # Arguments
@@ -497,6 +1663,103 @@ impl Jwk {
         self.map.get(key)
     }
 
+    /// Return values for all parameters including unknown ones.
+    pub fn parameters(&self) -> &Map<String, Value> {
+        &self.map
+    }
+
+    fn public_jwk_from_der(input: &[u8], key_info: &KeyInfo) -> anyhow::Result<Self> {
+        let public_key = match key_info.format() {
+            KeyFormat::Der { raw: true } | KeyFormat::Pem { traditional: true } => {
+                PKey::from_rsa(Rsa::public_key_from_der_pkcs1(input)?)?
+            }
+            _ => PKey::<Public>::public_key_from_der(input)?,
+        };
+
+        let jwk = match key_info.alg() {
+            Some(KeyAlg::Rsa) | Some(KeyAlg::RsaPss { .. }) => {
+                let rsa = public_key.rsa()?;
+
+                let mut jwk = Jwk::new("RSA");
+                let n = base64::encode_config(rsa.n().to_vec(), base64::URL_SAFE_NO_PAD);
+                jwk.map.insert("n".to_string(), Value::String(n));
+                let e = base64::encode_config(rsa.e().to_vec(), base64::URL_SAFE_NO_PAD);
+                jwk.map.insert("e".to_string(), Value::String(e));
+                jwk
+            }
+            Some(KeyAlg::Ec { curve: Some(curve) }) => {
+                let ec_key = public_key.ec_key()?;
+                let mut ctx = openssl::bn::BigNumContext::new()?;
+                let point = ec_key.public_key().to_bytes(
+                    ec_key.group(),
+                    PointConversionForm::UNCOMPRESSED,
+                    &mut ctx,
+                )?;
+                EcKeyPair::public_jwk_from_sec1(&point, curve)?
+            }
+            Some(KeyAlg::Ed { curve: Some(curve) }) => {
+                Self::okp_public_jwk(curve.name(), "sig", &public_key.raw_public_key()?)
+            }
+            Some(KeyAlg::Ecx { curve: Some(curve) }) => {
+                Self::okp_public_jwk(curve.name(), "enc", &public_key.raw_public_key()?)
+            }
+            Some(KeyAlg::Ec { curve: None }) => {
+                return Err(Self::unsupported_ec_curve_error(input));
+            }
+            _ => bail!("The key type is unrecognized."),
+        };
+
+        Ok(jwk)
+    }
+
+    fn read_ssh_string<'a>(input: &mut &'a [u8]) -> anyhow::Result<&'a [u8]> {
+        if input.len() < 4 {
+            bail!("The SSH key data is truncated.");
+        }
+        let len = u32::from_be_bytes([input[0], input[1], input[2], input[3]]) as usize;
+        if input.len() - 4 < len {
+            bail!("The SSH key data is truncated.");
+        }
+        let (val, rest) = input[4..].split_at(len);
+        *input = rest;
+        Ok(val)
+    }
+
+    fn read_ssh_mpint<'a>(input: &mut &'a [u8]) -> anyhow::Result<&'a [u8]> {
+        let val = Self::read_ssh_string(input)?;
+        if let Some(first) = val.first() {
+            if first & 0x80 != 0 {
+                bail!("The SSH mpint must not be negative.");
+            }
+        }
+        let start = val.iter().position(|b| *b != 0).unwrap_or(val.len());
+        Ok(&val[start..])
+    }
+
+    fn okp_public_jwk(curve: &str, key_use: &str, x: &[u8]) -> Self {
+        let mut jwk = Jwk::new("OKP");
+        jwk.set_key_use(key_use);
+        jwk.map
+            .insert("crv".to_string(), Value::String(curve.to_string()));
+        let x = base64::encode_config(x, base64::URL_SAFE_NO_PAD);
+        jwk.map.insert("x".to_string(), Value::String(x));
+        jwk
+    }
+
+    fn unsupported_ec_curve_error(input: &[u8]) -> anyhow::Error {
+        let mut reader = DerReader::from_reader(input);
+        while let Ok(Some(der_type)) = reader.next() {
+            if let DerType::ObjectIdentifier = der_type {
+                match reader.to_object_identifier() {
+                    Ok(val) if val == *OID_ID_EC_PUBLIC_KEY => {}
+                    Ok(val) => return anyhow::anyhow!("The EC curve is unsupported: {}", val),
+                    Err(_) => break,
+                }
+            }
+        }
+        anyhow::anyhow!("The EC curve is unrecognized.")
+    }
+
     pub(crate) fn check_map(map: &Map<String, Value>) -> Result<(), JoseError> {
         for (key, value) in map {
             Self::check_parameter(key, value)?;
@@ -506,9 +1769,33 @@ impl Jwk {
             if !map.contains_key("kty") {
                 bail!("The JWK kty parameter is required.");
             }
+            if let Some(Value::Array(vals)) = map.get("key_ops") {
+                let mut key_ops: Vec<KeyOperation> = Vec::with_capacity(vals.len());
+                for val in vals {
+                    if let Value::String(val) = val {
+                        let key_op = KeyOperation::from(val.as_str());
+                        if key_ops.contains(&key_op) {
+                            bail!("The JWK key_ops parameter must not have duplicate values: {}", val);
+                        }
+                        key_ops.push(key_op);
+                    }
+                }
+                if let Some(Value::String(key_use)) = map.get("use") {
+                    for key_op in &key_ops {
+                        match key_op.key_use() {
+                            Some(val) if val != key_use => bail!(
+                                "The JWK key_ops parameter value {} is inconsistent with the use parameter: {}",
+                                key_op,
+                                key_use
+                            ),
+                            _ => {}
+                        }
+                    }
+                }
+            }
             Ok(())
         })()
-        .map_err(|err| JoseError::InvalidJwsFormat(err))
+        .map_err(JoseError::InvalidJwkFormat)
     }
 
     fn check_parameter(key: &str, value: &Value) -> Result<(), JoseError> {
@@ -532,22 +1819,21 @@ impl Jwk {
                     }
                     _ => bail!("The JWK {} parameter must be a array of string.", key),
                 },
-                "x5t" | "x5t#S256" | "k" | "d" | "p" | "q" | "dp" | "dq" | "qi" | "x" | "y" => {
-                    match &value {
-                        Value::String(val) => {
-                            if !util::is_base64_url_safe_nopad(val) {
-                                bail!("The JWK {} parameter must be a base64 string.", key);
-                            }
+                "x5t" | "x5t#S256" | "k" | "n" | "e" | "d" | "p" | "q" | "dp" | "dq" | "qi"
+                | "x" | "y" => match &value {
+                    Value::String(val) => {
+                        if !util::is_base64_url_safe_nopad(val) {
+                            bail!("The JWK {} parameter must be a base64 string.", key);
                         }
-                        _ => bail!("The JWK {} parameter must be a string.", key),
                     }
-                }
+                    _ => bail!("The JWK {} parameter must be a string.", key),
+                },
                 "x5c" => match &value {
                     Value::Array(vals) => {
                         for val in vals {
                             match val {
                                 Value::String(val) => {
-                                    if !util::is_base64_url_safe_nopad(val) {
+                                    if base64::decode_config(val, base64::STANDARD).is_err() {
                                         bail!("The JWK {} parameter must be a base64 string.", key);
                                     }
                                 }
@@ -581,9 +1867,1213 @@ impl Into<Map<String, Value>> for Jwk {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Jwk {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&self.map, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Jwk {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let map: Map<String, Value> = serde::Deserialize::deserialize(deserializer)?;
+        Self::from_map(map).map_err(serde::de::Error::custom)
+    }
+}
+
 impl Display for Jwk {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         let val = serde_json::to_string(&self.map).map_err(|_e| std::fmt::Error {})?;
         fmt.write_str(&val)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use anyhow::Result;
+    use openssl::asn1::Asn1Time;
+    use openssl::dsa::Dsa;
+    use openssl::ec::{EcGroup, EcKey};
+    use openssl::pkey::{Id, Private};
+    use openssl::x509::extension::{BasicConstraints, KeyUsage};
+    use openssl::x509::{X509Builder, X509NameBuilder};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use crate::jwe::{self, JweHeader, ECDH_ES};
+    use crate::jws::{EdDSA, ES256, RS256};
+    use crate::{FixedClock, SystemClock};
+    use openssl::nid::Nid;
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_jwk_from_pem_and_der() -> Result<()> {
+        for (name, kty, crv) in &[
+            ("RSA_2048bit", "RSA", None),
+            ("RSA-PSS_2048bit_SHA-256", "RSA", None),
+            ("EC_P-256", "EC", Some("P-256")),
+            ("EC_P-384", "EC", Some("P-384")),
+            ("EC_P-521", "EC", Some("P-521")),
+            ("EC_secp256k1", "EC", Some("secp256k1")),
+            ("ED25519", "OKP", Some("Ed25519")),
+            ("ED448", "OKP", Some("Ed448")),
+            ("X25519", "OKP", Some("X25519")),
+            ("X448", "OKP", Some("X448")),
+        ] {
+            let private_jwk = Jwk::from_pem(load_file(&format!("pem/{}_private.pem", name))?)?;
+            assert_eq!(private_jwk.key_type(), *kty);
+            assert_eq!(private_jwk.curve().as_ref().map(|val| val.name()), *crv);
+            assert!(matches!(private_jwk.parameter("d"), Some(_)));
+
+            let public_jwk = Jwk::from_pem(load_file(&format!("pem/{}_public.pem", name))?)?;
+            assert_eq!(public_jwk.key_type(), *kty);
+            assert!(matches!(public_jwk.parameter("d"), None));
+            assert_eq!(public_jwk, private_jwk.to_public_key()?);
+
+            let traditional_jwk =
+                Jwk::from_pem(load_file(&format!("pem/{}_traditional_private.pem", name))?)?;
+            assert_eq!(traditional_jwk, private_jwk);
+
+            let der_private_jwk =
+                Jwk::from_der(load_file(&format!("der/{}_pkcs8_private.der", name))?)?;
+            assert_eq!(der_private_jwk, private_jwk);
+
+            let der_public_jwk =
+                Jwk::from_der(load_file(&format!("der/{}_spki_public.der", name))?)?;
+            assert_eq!(der_public_jwk, public_jwk);
+        }
+
+        let private_jwk = Jwk::from_pem(load_file("pem/RSA_2048bit_private.pem")?)?;
+        let public_jwk = private_jwk.to_public_key()?;
+        assert_eq!(
+            Jwk::from_pem(load_file("pem/RSA_2048bit_traditional_public.pem")?)?,
+            public_jwk
+        );
+        assert_eq!(
+            Jwk::from_der(load_file("der/RSA_2048bit_raw_private.der")?)?,
+            private_jwk
+        );
+        assert_eq!(
+            Jwk::from_der(load_file("der/RSA_2048bit_raw_public.der")?)?,
+            public_jwk
+        );
+
+        for name in &["EC_P-256", "EC_P-384", "EC_P-521", "EC_secp256k1"] {
+            let private_jwk = Jwk::from_pem(load_file(&format!("pem/{}_private.pem", name))?)?;
+            assert_eq!(
+                Jwk::from_der(load_file(&format!("der/{}_raw_private.der", name))?)?,
+                private_jwk
+            );
+        }
+
+        assert!(Jwk::from_der(load_file("pem/RSA_2048bit_private.pem")?).is_err());
+        assert!(Jwk::from_pem(load_file("der/RSA_2048bit_pkcs8_private.der")?).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_from_der_with_unsupported_curve() -> Result<()> {
+        let group = EcGroup::from_curve_name(Nid::BRAINPOOL_P256T1)?;
+        let ec_key = EcKey::generate(&group)?;
+        let sec1_der = ec_key.private_key_to_der()?;
+        let private_key = PKey::from_ec_key(ec_key)?;
+        let pkcs8_der = private_key.private_key_to_pkcs8()?;
+        let spki_der = private_key.public_key_to_der()?;
+        let spki_pem = private_key.public_key_to_pem()?;
+
+        for result in vec![
+            Jwk::from_der(&sec1_der),
+            Jwk::from_der(&pkcs8_der),
+            Jwk::from_der(&spki_der),
+            Jwk::from_pem(&spki_pem),
+        ] {
+            match result {
+                Err(JoseError::InvalidKeyFormat(err)) => {
+                    assert_eq!(
+                        err.to_string(),
+                        "The EC curve is unsupported: 1.3.36.3.3.2.8.1.1.8"
+                    );
+                }
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_to_pem_and_der() -> Result<()> {
+        let mut jwks = vec![Jwk::generate_rsa_key(2048)?];
+        for curve in &[
+            EcCurve::P256,
+            EcCurve::P384,
+            EcCurve::P521,
+            EcCurve::Secp256k1,
+        ] {
+            jwks.push(Jwk::generate_ec_key(*curve)?);
+        }
+        for curve in &[EdCurve::Ed25519, EdCurve::Ed448] {
+            jwks.push(Jwk::generate_ed_key(*curve)?);
+        }
+        for curve in &[EcxCurve::X25519, EcxCurve::X448] {
+            jwks.push(Jwk::generate_ecx_key(*curve)?);
+        }
+
+        for jwk in &jwks {
+            let public_jwk = jwk.to_public_key()?;
+
+            let pem_private_key = jwk.to_pem_private_key()?;
+            let der_private_key = jwk.to_der_private_key()?;
+            let (pem_key_pair, der_key_pair): (Box<dyn KeyPair>, Box<dyn KeyPair>) =
+                match (jwk.key_type(), jwk.curve()) {
+                    ("RSA", _) => (
+                        Box::new(RsaKeyPair::from_pem(&pem_private_key)?),
+                        Box::new(RsaKeyPair::from_der(&der_private_key)?),
+                    ),
+                    ("EC", _) => (
+                        Box::new(EcKeyPair::from_pem(&pem_private_key, None)?),
+                        Box::new(EcKeyPair::from_der(&der_private_key, None)?),
+                    ),
+                    (_, Some(JwkCurve::Okp(OkpCurve::Ed25519)))
+                    | (_, Some(JwkCurve::Okp(OkpCurve::Ed448))) => (
+                        Box::new(EdKeyPair::from_pem(&pem_private_key)?),
+                        Box::new(EdKeyPair::from_der(&der_private_key)?),
+                    ),
+                    _ => (
+                        Box::new(EcxKeyPair::from_pem(&pem_private_key)?),
+                        Box::new(EcxKeyPair::from_der(&der_private_key)?),
+                    ),
+                };
+            assert_eq!(&pem_key_pair.to_jwk_key_pair(), jwk);
+            assert_eq!(&der_key_pair.to_jwk_key_pair(), jwk);
+
+            let pem_public_key = jwk.to_pem_public_key()?;
+            assert_eq!(pem_public_key, pem_key_pair.to_pem_public_key());
+            assert_eq!(pem_public_key, public_jwk.to_pem_public_key()?);
+            assert_eq!(Jwk::from_pem(&pem_public_key)?, public_jwk);
+
+            let der_public_key = jwk.to_der_public_key()?;
+            assert_eq!(der_public_key, der_key_pair.to_der_public_key());
+            assert_eq!(der_public_key, public_jwk.to_der_public_key()?);
+            assert_eq!(Jwk::from_der(&der_public_key)?, public_jwk);
+
+            match public_jwk.to_pem_private_key() {
+                Err(JoseError::InvalidKeyFormat(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+            match public_jwk.to_der_private_key() {
+                Err(JoseError::InvalidKeyFormat(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        let oct_jwk = Jwk::generate_oct_key(256)?;
+        assert!(oct_jwk.to_pem_private_key().is_err());
+        assert!(oct_jwk.to_der_private_key().is_err());
+        assert!(oct_jwk.to_pem_public_key().is_err());
+        assert!(oct_jwk.to_der_public_key().is_err());
+
+        Ok(())
+    }
+
+    #[cfg(all(feature = "jwe-aeskw", feature = "enc-gcm"))]
+    #[test]
+    fn test_jwk_encrypt_to_jwe() -> Result<()> {
+        let jwk = Jwk::from_bytes(load_file("jwk/EC_P-256_private.jwk")?)?;
+
+        let key = Jwk::generate_oct_key(256)?;
+        let input =
+            jwk.encrypt_to_jwe(&jwe::A256KW.encrypter_from_jwk(&key)?, &jwe::enc::A256GCM)?;
+        let decrypter = jwe::A256KW.decrypter_from_jwk(&key)?;
+        let (_, header) = jwe::deserialize_compact(&input, &decrypter)?;
+        assert_eq!(header.content_type(), Some("jwk+json"));
+        assert_eq!(header.content_encryption(), Some("A256GCM"));
+        assert_eq!(Jwk::decrypt_from_jwe(&input, &decrypter)?, jwk);
+
+        let other = jwe::A256KW.decrypter_from_jwk(&Jwk::generate_oct_key(256)?)?;
+        assert!(Jwk::decrypt_from_jwe(&input, &other).is_err());
+
+        let mut header = JweHeader::new();
+        header.set_content_encryption("A256GCM");
+        header.set_content_type("JWT");
+        let encrypter = jwe::A256KW.encrypter_from_jwk(&key)?;
+        let input = jwe::serialize_compact(&jwk.to_string().into_bytes(), &header, &encrypter)?;
+        assert!(Jwk::decrypt_from_jwe(&input, &decrypter).is_err());
+
+        header.set_content_type("jwk+json");
+        let input = jwe::serialize_compact(br#"{"kid":"1"}"#, &header, &encrypter)?;
+        match Jwk::decrypt_from_jwe(&input, &decrypter) {
+            Err(JoseError::InvalidJwkFormat(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[cfg(all(feature = "jwe-pbes2", feature = "enc-gcm"))]
+    #[test]
+    fn test_jwk_encrypt_with_password() -> Result<()> {
+        let jwk = Jwk::from_bytes(load_file("jwk/EC_P-256_private.jwk")?)?;
+
+        let input = jwk.encrypt_with_password("correct horse battery staple")?;
+        let (header, _) = input.split_at(input.find('.').unwrap());
+        let header = base64::decode_config(header, base64::URL_SAFE_NO_PAD)?;
+        let header: Map<String, Value> = serde_json::from_slice(&header)?;
+        assert_eq!(header.get("alg"), Some(&Value::from("PBES2-HS512+A256KW")));
+        assert_eq!(header.get("cty"), Some(&Value::from("jwk+json")));
+        assert_eq!(
+            Jwk::decrypt_with_password(&input, "correct horse battery staple")?,
+            jwk
+        );
+        assert!(Jwk::decrypt_with_password(&input, "wrong password").is_err());
+        assert!(jwk.encrypt_with_password("").is_err());
+        assert!(Jwk::decrypt_with_password(&input, "").is_err());
+
+        let input = jwk.encrypt_with_password("caf\u{e9}")?;
+        assert_eq!(Jwk::decrypt_with_password(&input, "cafe\u{301}")?, jwk);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_pkey_conversion() -> Result<()> {
+        for name in &[
+            "RSA_2048bit",
+            "EC_P-256",
+            "EC_P-384",
+            "EC_P-521",
+            "EC_secp256k1",
+            "ED25519",
+            "ED448",
+            "X25519",
+            "X448",
+        ] {
+            let private_pem = load_file(&format!("pem/{}_private.pem", name))?;
+            let public_pem = load_file(&format!("pem/{}_public.pem", name))?;
+
+            let private_key = PKey::private_key_from_pem(&private_pem)?;
+            let jwk = Jwk::from_private_pkey(&private_key)?;
+            assert_eq!(jwk, Jwk::from_pem(&private_pem)?);
+
+            let public_key = PKey::public_key_from_pem(&public_pem)?;
+            let public_jwk = Jwk::from_public_pkey(&public_key)?;
+            assert_eq!(public_jwk, Jwk::from_pem(&public_pem)?);
+            assert_eq!(public_jwk, jwk.to_public_key()?);
+
+            let private_key2 = jwk.to_private_pkey()?;
+            assert!(private_key2.public_eq(&private_key));
+            assert_eq!(Jwk::from_private_pkey(&private_key2)?, jwk);
+            assert!(jwk.to_public_pkey()?.public_eq(&public_key));
+            assert!(public_jwk.to_public_pkey()?.public_eq(&public_key));
+            assert!(public_jwk.to_private_pkey().is_err());
+        }
+
+        // The CRT parameters are recovered when they are omitted.
+        let mut jwk = Jwk::from_pem(load_file("pem/RSA_2048bit_private.pem")?)?;
+        let expected = jwk.to_private_pkey()?;
+        for key in &["p", "q", "dp", "dq", "qi"] {
+            jwk.set_parameter(key, None)?;
+        }
+        let private_key = jwk.to_private_pkey()?;
+        assert_eq!(
+            private_key.private_key_to_der()?,
+            expected.private_key_to_der()?
+        );
+
+        let dsa = PKey::from_dsa(Dsa::generate(2048)?)?;
+        match Jwk::from_private_pkey(&dsa) {
+            Err(JoseError::InvalidKeyFormat(err)) => {
+                assert!(err.to_string().contains(&Id::DSA.as_raw().to_string()));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let dsa = PKey::public_key_from_der(&dsa.public_key_to_der()?)?;
+        assert!(Jwk::from_public_pkey(&dsa).is_err());
+        assert!(Jwk::generate_oct_key(128)?.to_private_pkey().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_to_public_key() -> Result<()> {
+        let mut jwk = Jwk::generate_rsa_key(2048)?;
+        jwk.set_key_id("rsa-1");
+        jwk.set_key_use("sig");
+        jwk.set_algorithm("RS256");
+        jwk.set_key_operations(&[KeyOperation::Sign, KeyOperation::Verify]);
+        jwk.set_x509_url("https://example.com/cert.pem");
+        jwk.set_x509_certificate_sha1_thumbprint(b"0123456789abcdefghij");
+        jwk.set_parameter("ext", Some(Value::Bool(true)))?;
+
+        let public_jwk = jwk.to_public_key()?;
+        let mut keys: Vec<&str> = public_jwk.as_ref().keys().map(|key| key.as_str()).collect();
+        keys.sort_unstable();
+        assert_eq!(
+            keys,
+            vec!["alg", "e", "key_ops", "kid", "kty", "n", "use", "x5t", "x5u"]
+        );
+        assert_eq!(
+            public_jwk.key_operations(),
+            Some(vec![KeyOperation::Verify])
+        );
+        assert_eq!(public_jwk.parameter("n"), jwk.parameter("n"));
+        assert_eq!(public_jwk.parameter("e"), jwk.parameter("e"));
+
+        jwk.set_key_operations(&[KeyOperation::Sign]);
+        assert_eq!(
+            jwk.to_public_key()?.key_operations(),
+            Some(vec![KeyOperation::Verify])
+        );
+        jwk.set_key_operations(&[KeyOperation::Decrypt, KeyOperation::UnwrapKey]);
+        assert_eq!(
+            jwk.to_public_key()?.key_operations(),
+            Some(vec![KeyOperation::Encrypt, KeyOperation::WrapKey])
+        );
+        jwk.set_key_operations(&[KeyOperation::DeriveKey]);
+        assert_eq!(
+            jwk.to_public_key()?.key_operations(),
+            Some(vec![KeyOperation::DeriveKey])
+        );
+        jwk.set_key_operations(&[KeyOperation::Other("private".to_string())]);
+        assert_eq!(jwk.to_public_key()?.key_operations(), Some(vec![]));
+
+        let jwk = Jwk::generate_ec_key(EcCurve::P256)?;
+        let mut keys: Vec<String> = jwk.to_public_key()?.as_ref().keys().cloned().collect();
+        keys.sort_unstable();
+        assert_eq!(keys, vec!["crv", "kty", "x", "y"]);
+
+        let jwk = Jwk::generate_ed_key(EdCurve::Ed25519)?;
+        let mut keys: Vec<String> = jwk.to_public_key()?.as_ref().keys().cloned().collect();
+        keys.sort_unstable();
+        assert_eq!(keys, vec!["crv", "kty", "use", "x"]);
+
+        match Jwk::generate_oct_key(256)?.to_public_key() {
+            Err(JoseError::InvalidJwkFormat(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_generate_ec_key() -> Result<()> {
+        for curve in &[
+            EcCurve::P256,
+            EcCurve::P384,
+            EcCurve::P521,
+            EcCurve::Secp256k1,
+        ] {
+            let coordinate_size = curve.coordinate_size();
+
+            // The top byte of a P-521 coordinate is often zero,
+            // so generate enough keys to cover the short encodings.
+            for _ in 0..32 {
+                let jwk = Jwk::generate_ec_key(*curve)?;
+                assert_eq!(jwk.key_type(), "EC");
+                assert_eq!(jwk.curve(), Some(JwkCurve::Ec(*curve)));
+                for name in &["x", "y", "d"] {
+                    let val = match jwk.parameter(name) {
+                        Some(Value::String(val)) => {
+                            base64::decode_config(val, base64::URL_SAFE_NO_PAD)?
+                        }
+                        _ => unreachable!(),
+                    };
+                    assert_eq!(val.len(), coordinate_size);
+                }
+                EcKeyPair::from_jwk(&jwk)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_thumbprint() -> Result<()> {
+        // RFC 7638 3.1
+        let jwk = Jwk::from_bytes(
+            br#"{"kty":"RSA","n":"0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw","e":"AQAB","alg":"RS256","kid":"2011-04-29"}"#,
+        )?;
+        let thumbprint = jwk.thumbprint(HashAlgorithm::Sha256)?;
+        assert_eq!(
+            base64::encode_config(&thumbprint, base64::URL_SAFE_NO_PAD),
+            "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs"
+        );
+
+        let jwk = Jwk::generate_ec_key(EcCurve::P521)?;
+        assert_eq!(
+            jwk.thumbprint(HashAlgorithm::Sha256)?,
+            jwk.to_public_key()?.thumbprint(HashAlgorithm::Sha256)?
+        );
+
+        assert!(Jwk::new("EC").thumbprint(HashAlgorithm::Sha256).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_has_private_parameters() -> Result<()> {
+        for (name, is_private) in &[
+            ("RSA_private.jwk", true),
+            ("RSA_public.jwk", false),
+            ("EC_P-256_private.jwk", true),
+            ("EC_P-256_public.jwk", false),
+            ("OKP_Ed25519_private.jwk", true),
+            ("OKP_Ed25519_public.jwk", false),
+            ("oct_128bit_private.jwk", true),
+        ] {
+            let jwk = Jwk::from_bytes(load_file(&format!("jwk/{}", name))?)?;
+            assert_eq!(jwk.is_private(), *is_private);
+            assert_eq!(jwk.has_private_parameters(), *is_private);
+            assert_eq!(jwk.ensure_public().is_err(), *is_private);
+            if jwk.key_type() != "oct" {
+                jwk.to_public_key()?.ensure_public()?;
+            }
+        }
+
+        let mut jwk = Jwk::from_bytes(load_file("jwk/RSA_private.jwk")?)?;
+        jwk.set_parameter("d", None)?;
+        jwk.set_parameter("dp", None)?;
+        assert!(!jwk.is_private());
+        assert!(jwk.has_private_parameters());
+        match jwk.ensure_public() {
+            Err(JoseError::InvalidJwkFormat(err)) => {
+                assert_eq!(
+                    err.to_string(),
+                    "The RSA key has private parameters: p, q, dq, qi"
+                );
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let mut jwk = Jwk::new("oct");
+        assert!(!jwk.is_private());
+        assert!(jwk.has_private_parameters());
+        assert!(jwk.ensure_public().is_err());
+        jwk.set_key_value(b"secret");
+        assert!(jwk.is_private());
+
+        let mut jwk = Jwk::new("unknown");
+        assert!(!jwk.has_private_parameters());
+        jwk.ensure_public()?;
+        jwk.set_parameter("d", Some(Value::from("AQAB")))?;
+        assert!(jwk.has_private_parameters());
+        assert!(jwk.ensure_public().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_to_canonical_string() -> Result<()> {
+        let jwk1 = Jwk::from_bytes(
+            br#"{
+                "kty": "RSA", "n": "AQAB", "e": "AQAB", "d": "AQAB",
+                "oth": [{"r": "Aw", "d": "Ag", "t": "AQ"}, {"t": "BQ", "r": "Bw", "d": "Bg"}],
+                "key_ops": ["sign", "verify"],
+                "ext": {"b": 1.5, "a": [true, null, "\u00e9\n"]}
+            }"#,
+        )?;
+        let jwk2 = Jwk::from_bytes(
+            br#"{
+                "ext": {"a": [true, null, "\u00e9\n"], "b": 1.5},
+                "key_ops": ["sign", "verify"],
+                "oth": [{"d": "Ag", "r": "Aw", "t": "AQ"}, {"d": "Bg", "r": "Bw", "t": "BQ"}],
+                "d": "AQAB", "e": "AQAB", "n": "AQAB", "kty": "RSA"
+            }"#,
+        )?;
+        let expected = concat!(
+            r#"{"d":"AQAB","e":"AQAB","ext":{"a":[true,null,"é\n"],"b":1.5},"#,
+            r#""key_ops":["sign","verify"],"kty":"RSA","n":"AQAB","#,
+            r#""oth":[{"d":"Ag","r":"Aw","t":"AQ"},{"d":"Bg","r":"Bw","t":"BQ"}]}"#,
+        );
+        assert_eq!(jwk1.to_canonical_string(), expected);
+        assert_eq!(jwk2.to_canonical_string(), expected);
+        assert!(jwk1.equals_canonically(&jwk2));
+        assert_ne!(jwk1.to_string(), jwk2.to_string());
+
+        let mut jwk3 = jwk2.clone();
+        jwk3.set_parameter("key_ops", Some(Value::from(vec!["verify", "sign"])))?;
+        assert!(!jwk1.equals_canonically(&jwk3));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_set_key_id_from_thumbprint() -> Result<()> {
+        // RFC 7638 3.1
+        let mut jwk = Jwk::from_bytes(
+            br#"{"kty":"RSA","n":"0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw","e":"AQAB","alg":"RS256","kid":"2011-04-29"}"#,
+        )?;
+        jwk.set_key_id_from_thumbprint(HashAlgorithm::Sha256)?;
+        assert_eq!(
+            jwk.key_id(),
+            Some("NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs")
+        );
+
+        // The kid doesn't affect the thumbprint, so it is stable on repeated calls.
+        jwk.set_key_id_from_thumbprint(HashAlgorithm::Sha256)?;
+        assert_eq!(
+            jwk.key_id(),
+            Some("NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs")
+        );
+
+        for curve in &[EcCurve::P256, EcCurve::P384] {
+            let jwk = Jwk::generate_ec_key(*curve)?.with_thumbprint_kid(HashAlgorithm::Sha256)?;
+            let thumbprint = jwk.to_public_key()?.thumbprint(HashAlgorithm::Sha256)?;
+            assert_eq!(
+                jwk.key_id(),
+                Some(base64::encode_config(&thumbprint, base64::URL_SAFE_NO_PAD).as_str())
+            );
+        }
+
+        let mut jwk =
+            Jwk::generate_okp_key(OkpCurve::Ed25519)?.with_thumbprint_kid(HashAlgorithm::Sha256)?;
+        let key_id = jwk.key_id().map(|val| val.to_string());
+        let other = Jwk::generate_okp_key(OkpCurve::Ed25519)?;
+        jwk.set_parameter("x", other.parameter("x").cloned())?;
+        jwk.set_key_id_from_thumbprint(HashAlgorithm::Sha256)?;
+        assert_ne!(jwk.key_id(), key_id.as_deref());
+        assert_eq!(
+            jwk.key_id(),
+            other.with_thumbprint_kid(HashAlgorithm::Sha256)?.key_id()
+        );
+
+        let mut jwk = Jwk::new("EC");
+        jwk.set_key_id("kid");
+        jwk.set_parameter("crv", Some(Value::String("P-256".to_string())))?;
+        assert!(jwk
+            .set_key_id_from_thumbprint(HashAlgorithm::Sha256)
+            .is_err());
+        assert_eq!(jwk.key_id(), Some("kid"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_generate_okp_key() -> Result<()> {
+        for curve in &[
+            OkpCurve::Ed25519,
+            OkpCurve::Ed448,
+            OkpCurve::X25519,
+            OkpCurve::X448,
+        ] {
+            let jwk = Jwk::generate_okp_key(*curve)?;
+            assert_eq!(jwk.key_type(), "OKP");
+            assert_eq!(jwk.curve(), Some(JwkCurve::Okp(*curve)));
+            assert_eq!(jwk.key_use(), Some(curve.key_use()));
+            for name in &["x", "d"] {
+                let val = match jwk.parameter(name) {
+                    Some(Value::String(val)) => {
+                        base64::decode_config(val, base64::URL_SAFE_NO_PAD)?
+                    }
+                    _ => unreachable!(),
+                };
+                assert_eq!(val.len(), curve.key_len());
+            }
+        }
+
+        let mut jwk = Jwk::generate_okp_key(OkpCurve::Ed25519)?;
+        jwk.set_parameter("use", None)?;
+        assert_eq!(jwk.key_use(), None);
+        let signer = EdDSA.signer_from_jwk(&jwk)?;
+        let signature = signer.sign(b"abcde12345")?;
+        let verifier = EdDSA.verifier_from_jwk(&jwk.to_public_key()?)?;
+        verifier.verify(b"abcde12345", &signature)?;
+
+        let jwk = Jwk::generate_okp_key(OkpCurve::X25519)?;
+        let mut header = JweHeader::new();
+        header.set_content_encryption("A128GCM");
+        let encrypter = ECDH_ES.encrypter_from_jwk(&jwk.to_public_key()?)?;
+        let jwe = jwe::serialize_compact(b"abcde12345", &header, &encrypter)?;
+        let decrypter = ECDH_ES.decrypter_from_jwk(&jwk)?;
+        let (payload, _) = jwe::deserialize_compact(&jwe, &decrypter)?;
+        assert_eq!(payload, b"abcde12345");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_set_parameter_required() -> Result<()> {
+        for (name, required) in &[
+            ("RSA_public.jwk", &["kty", "n", "e"][..]),
+            ("EC_P-256_private.jwk", &["kty", "crv", "x", "y"][..]),
+            ("OKP_Ed25519_private.jwk", &["kty", "crv", "x"][..]),
+            ("oct_128bit_private.jwk", &["kty", "k"][..]),
+        ] {
+            let mut jwk = Jwk::from_bytes(load_file(&format!("jwk/{}", name))?)?;
+            let expected = jwk.clone();
+            for key in required.iter() {
+                match jwk.set_parameter(key, None) {
+                    Err(JoseError::InvalidJwkFormat(_)) => {}
+                    other => panic!("unexpected result: {} {:?}", key, other),
+                }
+            }
+            assert_eq!(jwk, expected);
+        }
+
+        let mut jwk = Jwk::from_bytes(load_file("jwk/RSA_private.jwk")?)?;
+        jwk.set_parameter("d", None)?;
+        jwk.set_parameter("kid", None)?;
+        assert_eq!(jwk.parameter("d"), None);
+
+        for key in &["n", "e", "x", "y", "k", "d"] {
+            let mut jwk = Jwk::new("RSA");
+            assert!(jwk.set_parameter(key, Some(Value::from(123))).is_err());
+            assert!(jwk.set_parameter(key, Some(Value::from("AQ*B"))).is_err());
+            jwk.set_parameter(key, Some(Value::from("AQAB")))?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_key_bits() -> Result<()> {
+        use crate::jws::{ES256K, ES384, ES512, PS256};
+
+        for bits in &[2048, 3072, 4096] {
+            let key_pair = RsaKeyPair::generate(*bits)?;
+            assert_eq!(key_pair.key_bits(), *bits);
+
+            let mut jwk = key_pair.to_jwk_public_key();
+            assert_eq!(jwk.key_bits(), Some(*bits));
+
+            let signer = RS256.signer_from_jwk(&key_pair.to_jwk_private_key())?;
+            assert_eq!(signer.key_bits(), *bits);
+            let verifier = RS256.verifier_from_jwk(&jwk)?;
+            assert_eq!(verifier.key_bits(), *bits);
+            let verifier = PS256.verifier_from_jwk(&jwk)?;
+            assert_eq!(verifier.key_bits(), *bits);
+
+            // The leading zero bytes of n are not counted.
+            let mut n = vec![0, 0];
+            n.extend_from_slice(&jwk.decode_parameter("n")?);
+            let n = base64::encode_config(&n, base64::URL_SAFE_NO_PAD);
+            jwk.set_parameter("n", Some(Value::String(n)))?;
+            assert_eq!(jwk.key_bits(), Some(*bits));
+            assert_eq!(RS256.verifier_from_jwk(&jwk)?.key_bits(), *bits);
+        }
+
+        for (alg, curve, bits) in &[
+            (ES256, EcCurve::P256, 256),
+            (ES384, EcCurve::P384, 384),
+            (ES512, EcCurve::P521, 521),
+            (ES256K, EcCurve::Secp256k1, 256),
+        ] {
+            let key_pair = alg.generate_key_pair()?;
+            assert_eq!(key_pair.curve(), *curve);
+            assert_eq!(key_pair.to_jwk_public_key().key_bits(), Some(*bits));
+            let signer = alg.signer_from_jwk(&key_pair.to_jwk_private_key())?;
+            assert_eq!(signer.curve(), *curve);
+            let verifier = alg.verifier_from_jwk(&key_pair.to_jwk_public_key())?;
+            assert_eq!(verifier.curve(), *curve);
+        }
+
+        for (curve, bits) in &[
+            (OkpCurve::Ed25519, 255),
+            (OkpCurve::Ed448, 448),
+            (OkpCurve::X25519, 255),
+            (OkpCurve::X448, 448),
+        ] {
+            assert_eq!(Jwk::generate_okp_key(*curve)?.key_bits(), Some(*bits));
+        }
+
+        assert_eq!(Jwk::generate_oct_key(256)?.key_bits(), Some(256));
+        assert_eq!(Jwk::new("RSA").key_bits(), None);
+        assert_eq!(Jwk::new("unknown").key_bits(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_extension_parameters() -> Result<()> {
+        let input = r#"{
+            "kty": "EC",
+            "crv": "P-256",
+            "x": "MKBCTNIcKUSDii11ySs3526iDZ8AiTo7Tu6KPAqv7D4",
+            "y": "4Etl6SRW2YiLUrN5vfvVHuhp7x8PxltmWWlbbM4IFyM",
+            "ext": true,
+            "x5u": "https://example.com/cert.pem",
+            "okta:meta": {"created": 1600000000, "tags": ["a", {"b": null}], "ratio": 0.5},
+            "status": "ACTIVE",
+            "versions": [1, 2.5, -3, "4"],
+            "kid": "1"
+        }"#;
+        let original = Jwk::from_bytes(input)?;
+        assert_eq!(original.extractable(), Some(true));
+        assert_eq!(original.x509_url(), Some("https://example.com/cert.pem"));
+        assert_eq!(original.parameters().len(), 10);
+
+        let mut jwk = Jwk::from_bytes(original.to_string())?;
+        jwk.set_key_id("2");
+        assert_eq!(jwk.key_id(), Some("2"));
+        for (key, value) in original.parameters() {
+            if key != "kid" {
+                assert_eq!(jwk.parameter(key), Some(value));
+            }
+        }
+        assert_eq!(
+            jwk.remove_parameter("kid")?,
+            Some(Value::String("2".to_string()))
+        );
+
+        let mut expected = original.clone();
+        expected.remove_parameter("kid")?;
+        assert_eq!(jwk.to_canonical_string(), expected.to_canonical_string());
+        assert_eq!(jwk.to_string(), expected.to_string());
+
+        jwk.set_extractable(false);
+        assert_eq!(jwk.extractable(), Some(false));
+        jwk.set_parameter("ext", Some(Value::String("true".to_string())))?;
+        assert_eq!(jwk.extractable(), None);
+        assert!(jwk.remove_parameter("okta:meta")?.is_some());
+        assert_eq!(jwk.remove_parameter("okta:meta")?, None);
+        assert!(matches!(
+            jwk.remove_parameter("crv"),
+            Err(JoseError::InvalidJwkFormat(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_key_operations() -> Result<()> {
+        let mut jwk = Jwk::new("oct");
+        jwk.set_key_operations(&[
+            KeyOperation::Sign,
+            KeyOperation::Verify,
+            KeyOperation::Other("custom".to_string()),
+        ]);
+        assert_eq!(
+            jwk.parameter("key_ops"),
+            Some(&serde_json::json!(["sign", "verify", "custom"]))
+        );
+        assert!(jwk.is_for_key_operation(&KeyOperation::Verify));
+        assert!(!jwk.is_for_key_operation(&KeyOperation::Encrypt));
+
+        let jwk = Jwk::from_bytes(jwk.to_string())?;
+        assert_eq!(
+            jwk.key_operations(),
+            Some(vec![
+                KeyOperation::Sign,
+                KeyOperation::Verify,
+                KeyOperation::Other("custom".to_string())
+            ])
+        );
+        assert_eq!(
+            jwk.to_string(),
+            r#"{"kty":"oct","key_ops":["sign","verify","custom"]}"#
+        );
+
+        assert!(Jwk::new("oct").is_for_key_operation(&KeyOperation::Sign));
+        assert!(
+            Jwk::from_bytes(r#"{"kty":"oct","use":"enc","key_ops":["wrapKey","custom"]}"#).is_ok()
+        );
+        for input in &[
+            r#"{"kty":"oct","use":"sig","key_ops":["sign","encrypt"]}"#,
+            r#"{"kty":"oct","use":"enc","key_ops":["verify"]}"#,
+            r#"{"kty":"oct","key_ops":["sign","sign"]}"#,
+        ] {
+            match Jwk::from_bytes(input) {
+                Err(JoseError::InvalidJwsFormat(_)) | Err(JoseError::InvalidJwkFormat(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_from_ssh_public_key() -> Result<()> {
+        for (name, params) in &[
+            ("RSA_2048bit", &["kty", "n", "e"][..]),
+            ("EC_P-256", &["kty", "crv", "x", "y"][..]),
+            ("EC_P-384", &["kty", "crv", "x", "y"][..]),
+            ("EC_P-521", &["kty", "crv", "x", "y"][..]),
+        ] {
+            let line = String::from_utf8(load_file(&format!("ssh/{}_public.pub", name))?)?;
+            let jwk = Jwk::from_ssh_public_key(&line)?;
+            let expected = Jwk::from_pem(load_file(&format!("ssh/{}_public.pem", name))?)?;
+            for key in params.iter() {
+                assert_eq!(jwk.parameter(key), expected.parameter(key));
+            }
+            assert_eq!(jwk.key_id(), Some(format!("{}@example.com", name).as_str()));
+            assert!(!jwk.is_private());
+        }
+
+        let line = String::from_utf8(load_file("ssh/OKP_Ed25519_public.pub")?)?;
+        let jwk = Jwk::from_ssh_public_key(&line)?;
+        let expected = Jwk::from_bytes(load_file("ssh/OKP_Ed25519_public.jwk")?)?;
+        for key in &["kty", "crv", "x", "kid"] {
+            assert_eq!(jwk.parameter(key), expected.parameter(key));
+        }
+        EdDSA.verifier_from_jwk(&jwk)?;
+
+        // The options are skipped and the comment is optional.
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let jwk = Jwk::from_ssh_public_key(&format!(
+            "no-pty,command=\"true\" {} {}",
+            fields[0], fields[1]
+        ))?;
+        assert_eq!(jwk.key_id(), None);
+        assert_eq!(jwk.parameter("x"), expected.parameter("x"));
+
+        let rsa_line = String::from_utf8(load_file("ssh/RSA_2048bit_public.pub")?)?;
+        let rsa_fields: Vec<&str> = rsa_line.split_whitespace().collect();
+        let blob = base64::decode_config(fields[1], base64::STANDARD)?;
+        let mut trailing = blob.clone();
+        trailing.push(0);
+        for line in &[
+            "".to_string(),
+            "ssh-ed25519".to_string(),
+            "ssh-ed25519 !!!".to_string(),
+            format!("ssh-dss {}", fields[1]),
+            format!("ssh-rsa {}", fields[1]),
+            format!("ecdsa-sha2-nistp256 {}", rsa_fields[1]),
+            format!(
+                "ssh-ed25519 {}",
+                base64::encode_config(&blob[..blob.len() - 1], base64::STANDARD)
+            ),
+            format!(
+                "ssh-ed25519 {}",
+                base64::encode_config(&trailing, base64::STANDARD)
+            ),
+        ] {
+            match Jwk::from_ssh_public_key(line) {
+                Err(JoseError::InvalidKeyFormat(_)) => {}
+                other => panic!("unexpected result: {} {:?}", line, other),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_from_x509_certificate() -> Result<()> {
+        let pem = load_file("pem/RSA_2048bit_private_with_certificate.pem")?;
+        let cert = X509::from_pem(&pem)?;
+        let jwk = Jwk::from_x509_certificate_pem(&pem)?;
+        assert_eq!(jwk.key_type(), "RSA");
+        assert_eq!(jwk.algorithm(), Some("RS256"));
+        assert_eq!(jwk.parameter("d"), None);
+        assert_eq!(jwk.x509_certificate_chain(), Some(vec![cert.to_der()?]));
+        assert_eq!(
+            jwk.x509_certificate_sha1_thumbprint(),
+            Some(cert.digest(MessageDigest::sha1())?.to_vec())
+        );
+        assert_eq!(
+            jwk.x509_certificate_sha256_thumbprint(),
+            Some(cert.digest(MessageDigest::sha256())?.to_vec())
+        );
+        let key_pair = RsaKeyPair::from_pem(&pem)?;
+        assert_eq!(
+            key_pair.to_jwk_public_key().parameter("n"),
+            jwk.parameter("n")
+        );
+        assert_eq!(Jwk::from_x509_certificate_der(cert.to_der()?)?, jwk);
+
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+        let private_key = PKey::from_ec_key(EcKey::generate(&group)?)?;
+        let cert = self_signed_certificate(&private_key, Some(MessageDigest::sha256()), 1)?;
+        let jwk = Jwk::from_x509_certificate_der(&cert)?;
+        assert_eq!(jwk.key_type(), "EC");
+        assert_eq!(jwk.curve(), Some(JwkCurve::Ec(EcCurve::P256)));
+        assert_eq!(jwk.algorithm(), Some("ES256"));
+        assert_eq!(jwk.to_der_public_key()?, private_key.public_key_to_der()?);
+
+        // An expired certificate is converted too.
+        let private_key = PKey::generate_ed25519()?;
+        let cert = self_signed_certificate(&private_key, None, -1)?;
+        let jwk = Jwk::from_x509_certificate_der(&cert)?;
+        assert_eq!(jwk.key_type(), "OKP");
+        assert_eq!(jwk.curve(), Some(JwkCurve::Okp(OkpCurve::Ed25519)));
+        assert_eq!(jwk.algorithm(), Some("EdDSA"));
+        assert_eq!(
+            jwk.parameter("x"),
+            Some(&Value::String(base64::encode_config(
+                private_key.raw_public_key()?,
+                base64::URL_SAFE_NO_PAD
+            )))
+        );
+
+        let private_key = PKey::from_dsa(Dsa::generate(1024)?)?;
+        let cert = self_signed_certificate(&private_key, Some(MessageDigest::sha256()), 1)?;
+        match Jwk::from_x509_certificate_der(&cert) {
+            Err(JoseError::InvalidKeyFormat(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_x509_thumbprint_from_der() -> Result<()> {
+        let pem = load_file("pem/RSA_2048bit_private_with_certificate.pem")?;
+        let cert = X509::from_pem(&pem)?.to_der()?;
+
+        let mut jwk = Jwk::new("RSA");
+        assert!(!jwk.x509_thumbprint_matches(&cert)?);
+        assert!(jwk
+            .set_x509_certificate_sha1_thumbprint_from_der(&pem)
+            .is_err());
+        jwk.set_x509_certificate_sha256_thumbprint_from_der(&cert)?;
+        assert!(jwk.x509_thumbprint_matches(&cert)?);
+
+        jwk.set_x509_from_certificate(&cert)?;
+        let expected = Jwk::from_x509_certificate_der(&cert)?;
+        for key in &["x5c", "x5t", "x5t#S256"] {
+            assert_eq!(jwk.parameter(key), expected.parameter(key));
+        }
+
+        let private_key = PKey::generate_ed25519()?;
+        let other = self_signed_certificate(&private_key, None, 1)?;
+        assert!(!jwk.x509_thumbprint_matches(&other)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_x509_certificate_chain() -> Result<()> {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+        let ca_key = PKey::from_ec_key(EcKey::generate(&group)?)?;
+        let ca_cert = issue_certificate(&ca_key, "josekit ca", None)?;
+        let other_ca_key = PKey::generate_ed25519()?;
+        let other_ca_cert = issue_certificate(&other_ca_key, "josekit other ca", None)?;
+
+        let mut jwk = Jwk::generate_ec_key(EcCurve::P256)?;
+        let leaf_key = PKey::private_key_from_der(&jwk.to_der_private_key()?)?;
+        let leaf_cert = issue_certificate(&leaf_key, "josekit leaf", Some((&ca_key, &ca_cert)))?;
+
+        let chain = vec![leaf_cert.to_der()?, ca_cert.to_der()?];
+        jwk.set_x509_certificate_chain(&chain);
+        match jwk.parameter("x5c") {
+            Some(Value::Array(vals)) => {
+                assert_eq!(
+                    vals[0],
+                    Value::String(base64::encode_config(&chain[0], base64::STANDARD))
+                );
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(jwk.x509_certificate_chain(), Some(chain.clone()));
+        assert_eq!(Jwk::from_bytes(jwk.to_string())?, jwk);
+
+        jwk.verify_x509_chain(&[ca_cert.to_der()?])?;
+        jwk.to_public_key()?
+            .verify_x509_chain_with_clock(&[ca_cert.to_der()?], &SystemClock)?;
+
+        let expired = FixedClock(SystemTime::now() + Duration::from_secs(3 * 24 * 60 * 60));
+        match jwk.verify_x509_chain_with_clock(&[ca_cert.to_der()?], &expired) {
+            Err(JoseError::InvalidKeyFormat(err)) => {
+                assert!(err
+                    .to_string()
+                    .starts_with("The X.509 certificate chain is invalid: "));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match jwk.verify_x509_chain(&[other_ca_cert.to_der()?]) {
+            Err(JoseError::InvalidKeyFormat(err)) => {
+                assert!(err
+                    .to_string()
+                    .starts_with("The X.509 certificate chain is invalid: "));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let mut other_jwk = Jwk::generate_ec_key(EcCurve::P256)?;
+        other_jwk.set_x509_certificate_chain(&chain);
+        match other_jwk.verify_x509_chain(&[ca_cert.to_der()?]) {
+            Err(JoseError::InvalidKeyFormat(err)) => assert_eq!(
+                err.to_string(),
+                "The public key of the leaf certificate does not match the JWK."
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match Jwk::generate_ec_key(EcCurve::P256)?.verify_x509_chain(&[ca_cert.to_der()?]) {
+            Err(JoseError::InvalidKeyFormat(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    fn issue_certificate(
+        subject_key: &PKey<Private>,
+        common_name: &str,
+        issuer: Option<(&PKey<Private>, &X509)>,
+    ) -> Result<X509> {
+        let mut name = X509NameBuilder::new()?;
+        name.append_entry_by_text("CN", common_name)?;
+        let name = name.build();
+
+        let mut builder = X509Builder::new()?;
+        builder.set_version(2)?;
+        let serial_number = BigNum::from_u32(1)?.to_asn1_integer()?;
+        builder.set_serial_number(&serial_number)?;
+        builder.set_subject_name(&name)?;
+        builder.set_pubkey(subject_key)?;
+        let not_before = Asn1Time::days_from_now(0)?;
+        let not_after = Asn1Time::days_from_now(1)?;
+        builder.set_not_before(&not_before)?;
+        builder.set_not_after(&not_after)?;
+        let (issuer_key, digest) = match issuer {
+            Some((issuer_key, issuer_cert)) => {
+                builder.set_issuer_name(issuer_cert.subject_name())?;
+                (issuer_key, MessageDigest::sha256())
+            }
+            None => {
+                builder.set_issuer_name(&name)?;
+                builder.append_extension(BasicConstraints::new().critical().ca().build()?)?;
+                builder.append_extension(KeyUsage::new().key_cert_sign().build()?)?;
+                let digest = match subject_key.id() {
+                    Id::ED25519 | Id::ED448 => MessageDigest::null(),
+                    _ => MessageDigest::sha256(),
+                };
+                (subject_key, digest)
+            }
+        };
+        builder.sign(issuer_key, digest)?;
+        Ok(builder.build())
+    }
+
+    fn self_signed_certificate(
+        private_key: &PKey<Private>,
+        digest: Option<MessageDigest>,
+        days: i32,
+    ) -> Result<Vec<u8>> {
+        let mut name = X509NameBuilder::new()?;
+        name.append_entry_by_text("CN", "josekit test")?;
+        let name = name.build();
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        let (not_before, not_after) = if days > 0 {
+            (now, now + 86400 * days as i64)
+        } else {
+            (now + 86400 * (days as i64 - 1), now + 86400 * days as i64)
+        };
+
+        let mut builder = X509Builder::new()?;
+        builder.set_version(2)?;
+        builder.set_subject_name(&name)?;
+        builder.set_issuer_name(&name)?;
+        builder.set_pubkey(private_key)?;
+        let not_before = Asn1Time::from_unix(not_before)?;
+        let not_after = Asn1Time::from_unix(not_after)?;
+        builder.set_not_before(&not_before)?;
+        builder.set_not_after(&not_after)?;
+        match digest {
+            Some(digest) => builder.sign(private_key, digest)?,
+            None => builder.sign(private_key, MessageDigest::null())?,
+        }
+        Ok(builder.build().to_der()?)
+    }
+
+    #[test]
+    fn test_jwk_validate_consistency() -> Result<()> {
+        for key in &[
+            "jwk/RSA_private.jwk",
+            "jwk/RSA_public.jwk",
+            "jwk/RSA_multi_prime_private.jwk",
+            "jwk/RSA_rfc7516_A1_private.jwk",
+            "jwk/EC_P-256_private.jwk",
+            "jwk/EC_P-384_public.jwk",
+            "jwk/EC_P-521_private.jwk",
+            "jwk/EC_secp256k1_private.jwk",
+            "jwk/OKP_Ed25519_private.jwk",
+            "jwk/OKP_Ed448_private.jwk",
+            "jwk/OKP_X25519_private.jwk",
+            "jwk/OKP_X448_public.jwk",
+            "jwk/oct_256bit_private.jwk",
+        ] {
+            let jwk = Jwk::from_bytes(&load_file(key)?)?;
+            jwk.validate_consistency()?;
+        }
+
+        let mut jwk = Jwk::from_bytes(&load_file("jwk/RSA_private.jwk")?)?;
+        for key in &["p", "q", "dp", "dq", "qi"] {
+            jwk.map.remove(*key);
+        }
+        jwk.validate_consistency()?;
+
+        let rsa = Jwk::from_bytes(&load_file("jwk/RSA_private_corrupted_qi.jwk")?)?;
+        let ec = Jwk::from_bytes(&load_file("jwk/EC_P-256_private_corrupted_d.jwk")?)?;
+        let okp = Jwk::from_bytes(&load_file("jwk/OKP_Ed25519_private_corrupted_x.jwk")?)?;
+        for jwk in &[&rsa, &ec, &okp] {
+            assert!(matches!(
+                jwk.validate_consistency(),
+                Err(JoseError::InvalidJwkFormat(_))
+            ));
+        }
+
+        let mut jwk = Jwk::from_bytes(&load_file("jwk/RSA_private.jwk")?)?;
+        let dp = jwk.parameter("dq").cloned();
+        jwk.set_parameter("dp", dp)?;
+        assert!(jwk.validate_consistency().is_err());
+
+        // The corrupted RSA key is accepted unless it is validated.
+        assert!(RS256.signer_from_jwk(&rsa).is_ok());
+        assert!(RS256.signer_from_jwk_with_validation(&rsa, true).is_err());
+        assert!(ES256.signer_from_jwk_with_validation(&ec, true).is_err());
+        assert!(EdDSA.signer_from_jwk_with_validation(&okp, true).is_err());
+
+        let jwk = Jwk::from_bytes(&load_file("jwk/RSA_private.jwk")?)?;
+        assert!(RS256.signer_from_jwk_with_validation(&jwk, true).is_ok());
+
+        let short_x = Jwk::from_bytes(&load_file("jwk/EC_P-256_public_short_x.jwk")?)?;
+        match short_x.validate_consistency() {
+            Err(JoseError::InvalidJwkFormat(err)) => {
+                assert!(err.to_string().contains("coordinate length"))
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let off_curve = Jwk::from_bytes(&load_file("jwk/EC_P-256_public_off_curve.jwk")?)?;
+        match off_curve.validate_consistency() {
+            Err(JoseError::InvalidJwkFormat(err)) => {
+                assert!(err.to_string().contains("not on the curve"))
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_jwk_serde() -> Result<()> {
+        for key in &[
+            "jwk/RSA_private.jwk",
+            "jwk/EC_P-256_private.jwk",
+            "jwk/OKP_Ed25519_public.jwk",
+            "jwk/oct_256bit_private.jwk",
+        ] {
+            let jwk = Jwk::from_bytes(&load_file(key)?)?;
+
+            let json = serde_json::to_string(&jwk)?;
+            assert_eq!(json, jwk.to_string());
+            assert_eq!(serde_json::from_str::<Jwk>(&json)?, jwk);
+
+            let mut cbor = Vec::new();
+            ciborium::ser::into_writer(&jwk, &mut cbor)?;
+            assert_eq!(ciborium::de::from_reader::<Jwk, _>(cbor.as_slice())?, jwk);
+        }
+
+        assert!(serde_json::from_str::<Jwk>(r#"{"kid":"a"}"#).is_err());
+        assert!(serde_json::from_str::<Jwk>(r#"{"kty":"oct","use":1}"#).is_err());
+
+        Ok(())
+    }
+
+    fn load_file(path: &str) -> Result<Vec<u8>> {
+        let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        pb.push("data");
+        pb.push(path);
+
+        let data = fs::read(&pb)?;
+        Ok(data)
+    }
+}
@@ -0,0 +1,230 @@
+use anyhow::bail;
+
+use crate::jwk::Jwk;
+use crate::{JoseError, Map, Value};
+
+/// Represent a builder for constructing a JWK with typed parameters.
+///
+/// The binary parameters are base64url-encoded by the builder, and the parameters are
+/// validated once when [`JwkBuilder::build`] is called.
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct JwkBuilder {
+    key_type: Option<String>,
+    key_material: Vec<&'static str>,
+    map: Map<String, Value>,
+}
+
+impl JwkBuilder {
+    /// Return a new builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a value for key type parameter (kty).
+    ///
+    /// It can be omitted when the key parameters are set by `rsa_public`, `ec_public`,
+    /// `oct` or `okp`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A key type
+    pub fn kty(mut self, value: impl Into<String>) -> Self {
+        self.key_type = Some(value.into());
+        self
+    }
+
+    /// Set a value for key ID parameter (kid).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A key ID
+    pub fn kid(self, value: impl Into<String>) -> Self {
+        self.string("kid", value)
+    }
+
+    /// Set a value for key use parameter (use).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A key use
+    pub fn key_use(self, value: impl Into<String>) -> Self {
+        self.string("use", value)
+    }
+
+    /// Set a value for algorithm parameter (alg).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A algorithm
+    pub fn alg(self, value: impl Into<String>) -> Self {
+        self.string("alg", value)
+    }
+
+    /// Set the public key parameters of RSA type.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - A big-endian modulus
+    /// * `e` - A big-endian public exponent
+    pub fn rsa_public(mut self, n: impl AsRef<[u8]>, e: impl AsRef<[u8]>) -> Self {
+        self.key_material.push("RSA");
+        self.bytes("n", n).bytes("e", e)
+    }
+
+    /// Set the public key parameters of EC type.
+    ///
+    /// # Arguments
+    ///
+    /// * `curve` - A curve name (crv)
+    /// * `x` - A big-endian x coordinate
+    /// * `y` - A big-endian y coordinate
+    pub fn ec_public(
+        mut self,
+        curve: impl Into<String>,
+        x: impl AsRef<[u8]>,
+        y: impl AsRef<[u8]>,
+    ) -> Self {
+        self.key_material.push("EC");
+        self.string("crv", curve).bytes("x", x).bytes("y", y)
+    }
+
+    /// Set the key value parameter of oct type.
+    ///
+    /// # Arguments
+    ///
+    /// * `k` - A key value
+    pub fn oct(mut self, k: impl AsRef<[u8]>) -> Self {
+        self.key_material.push("oct");
+        self.bytes("k", k)
+    }
+
+    /// Set the public key parameters of OKP type.
+    ///
+    /// # Arguments
+    ///
+    /// * `curve` - A curve name (crv)
+    /// * `x` - A public key
+    pub fn okp(mut self, curve: impl Into<String>, x: impl AsRef<[u8]>) -> Self {
+        self.key_material.push("OKP");
+        self.string("crv", curve).bytes("x", x)
+    }
+
+    /// Set a value for a parameter of a specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A key name of a parameter
+    /// * `value` - A typed value of a parameter
+    pub fn parameter(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.map.insert(key.into(), value);
+        self
+    }
+
+    /// Return a new JWK that has the specified parameters.
+    pub fn build(&self) -> Result<Jwk, JoseError> {
+        (|| -> anyhow::Result<Jwk> {
+            let key_type = match self.key_material.as_slice() {
+                [] => match &self.key_type {
+                    Some(val) => val.clone(),
+                    None => bail!("The JWK kty parameter is required."),
+                },
+                [val] => match &self.key_type {
+                    Some(val2) if val2 != val => bail!(
+                        "The JWK kty parameter {} conflicts with the {} key parameters.",
+                        val2,
+                        val
+                    ),
+                    _ => val.to_string(),
+                },
+                [val, val2, ..] => bail!(
+                    "The {} key parameters conflict with the {} key parameters.",
+                    val2,
+                    val
+                ),
+            };
+            if let Some(Value::String(val)) = self.map.get("kty") {
+                if val != &key_type {
+                    bail!("The JWK kty parameter {} conflicts with {}.", val, key_type);
+                }
+            }
+
+            let mut map = Map::new();
+            map.insert("kty".to_string(), Value::String(key_type));
+            for (key, value) in &self.map {
+                map.insert(key.clone(), value.clone());
+            }
+            Ok(Jwk::from_map(map)?)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidJwkFormat(err),
+        })
+    }
+
+    fn string(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.map
+            .insert(key.to_string(), Value::String(value.into()));
+        self
+    }
+
+    fn bytes(mut self, key: &str, value: impl AsRef<[u8]>) -> Self {
+        let value = base64::encode_config(value, base64::URL_SAFE_NO_PAD);
+        self.map.insert(key.to_string(), Value::String(value));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use anyhow::Result;
+
+    use crate::jwk::{JwkCurve, OkpCurve};
+
+    #[test]
+    fn test_jwk_builder() -> Result<()> {
+        let jwk = JwkBuilder::new()
+            .kid("rsa-1")
+            .key_use("sig")
+            .alg("RS256")
+            .rsa_public(&[0xC5, 0x01], &[0x01, 0x00, 0x01])
+            .parameter("ext", Value::Bool(true))
+            .build()?;
+        assert_eq!(
+            jwk.to_string(),
+            r#"{"kty":"RSA","kid":"rsa-1","use":"sig","alg":"RS256","n":"xQE","e":"AQAB","ext":true}"#
+        );
+
+        let jwk = JwkBuilder::new().okp("Ed25519", &[0u8; 32]).build()?;
+        assert_eq!(jwk.key_type(), "OKP");
+        assert_eq!(jwk.curve(), Some(JwkCurve::Okp(OkpCurve::Ed25519)));
+
+        let jwk = JwkBuilder::new().kty("oct").oct(b"secret").build()?;
+        assert_eq!(jwk.key_value(), Some(b"secret".to_vec()));
+
+        let jwk = JwkBuilder::new().kty("custom").build()?;
+        assert_eq!(jwk.key_type(), "custom");
+
+        for builder in vec![
+            JwkBuilder::new(),
+            JwkBuilder::new()
+                .ec_public("P-256", &[1u8; 32], &[2u8; 32])
+                .rsa_public(&[1u8], &[3u8]),
+            JwkBuilder::new().kty("RSA").oct(b"secret"),
+            JwkBuilder::new()
+                .oct(b"secret")
+                .parameter("kty", Value::String("RSA".to_string())),
+            JwkBuilder::new()
+                .oct(b"secret")
+                .parameter("kid", Value::Bool(true)),
+        ] {
+            match builder.build() {
+                Err(JoseError::InvalidJwkFormat(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        Ok(())
+    }
+}
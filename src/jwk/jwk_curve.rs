@@ -0,0 +1,127 @@
+use std::fmt::Display;
+
+use crate::jwk::alg::ec::EcCurve;
+use crate::jwk::alg::okp::OkpCurve;
+
+/// Represent a value of curve parameter (crv) of JWK.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum JwkCurve {
+    /// A curve of EC type JWK
+    Ec(EcCurve),
+    /// A curve of OKP type JWK
+    Okp(OkpCurve),
+    /// A curve that is not supported by this library
+    Other(String),
+}
+
+impl JwkCurve {
+    /// Return a curve from the name of the crv parameter.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A curve name
+    pub fn from_name(name: &str) -> Self {
+        if let Some(curve) = EcCurve::from_name(name) {
+            Self::Ec(curve)
+        } else if let Some(curve) = OkpCurve::from_name(name) {
+            Self::Okp(curve)
+        } else {
+            Self::Other(name.to_string())
+        }
+    }
+
+    /// Return the name of the crv parameter.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Ec(curve) => curve.name(),
+            Self::Okp(curve) => curve.name(),
+            Self::Other(name) => name,
+        }
+    }
+
+    /// Return the key type (kty) that the curve is used with.
+    pub fn key_type(&self) -> Option<&str> {
+        match self {
+            Self::Ec(_) => Some("EC"),
+            Self::Okp(_) => Some("OKP"),
+            Self::Other(_) => None,
+        }
+    }
+}
+
+impl Display for JwkCurve {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        fmt.write_str(self.name())
+    }
+}
+
+impl From<EcCurve> for JwkCurve {
+    fn from(curve: EcCurve) -> Self {
+        Self::Ec(curve)
+    }
+}
+
+impl From<OkpCurve> for JwkCurve {
+    fn from(curve: OkpCurve) -> Self {
+        Self::Okp(curve)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use openssl::nid::Nid;
+    use openssl::pkey::Id;
+
+    use super::JwkCurve;
+    use crate::jwk::alg::ec::EcCurve;
+    use crate::jwk::alg::okp::OkpCurve;
+
+    #[test]
+    fn test_jwk_curve() {
+        for (curve, coordinate_size, nid) in &[
+            (EcCurve::P256, 32, Nid::X9_62_PRIME256V1),
+            (EcCurve::P384, 48, Nid::SECP384R1),
+            (EcCurve::P521, 66, Nid::SECP521R1),
+            (EcCurve::Secp256k1, 32, Nid::SECP256K1),
+            #[cfg(feature = "brainpool")]
+            (EcCurve::Bp256r1, 32, Nid::BRAINPOOL_P256R1),
+            #[cfg(feature = "brainpool")]
+            (EcCurve::Bp384r1, 48, Nid::BRAINPOOL_P384R1),
+            #[cfg(feature = "brainpool")]
+            (EcCurve::Bp512r1, 64, Nid::BRAINPOOL_P512R1),
+        ] {
+            assert_eq!(curve.coordinate_size(), *coordinate_size);
+            assert_eq!(curve.nid(), *nid);
+            assert_eq!(EcCurve::from_name(curve.name()), Some(*curve));
+
+            let jwk_curve = JwkCurve::from_name(curve.name());
+            assert_eq!(jwk_curve, JwkCurve::Ec(*curve));
+            assert_eq!(jwk_curve.name(), curve.name());
+            assert_eq!(jwk_curve.key_type(), Some("EC"));
+        }
+
+        for (curve, key_len, signature_len, id) in &[
+            (OkpCurve::Ed25519, 32, Some(64), Id::ED25519),
+            (OkpCurve::Ed448, 57, Some(114), Id::ED448),
+            (OkpCurve::X25519, 32, None, Id::X25519),
+            (OkpCurve::X448, 56, None, Id::X448),
+        ] {
+            assert_eq!(curve.key_len(), *key_len);
+            assert_eq!(curve.signature_len(), *signature_len);
+            assert_eq!(curve.nid().as_raw(), id.as_raw());
+            assert_eq!(curve.ed_curve().is_some(), signature_len.is_some());
+            assert_eq!(curve.ecx_curve().is_some(), signature_len.is_none());
+            assert_eq!(OkpCurve::from_name(curve.name()), Some(*curve));
+
+            let jwk_curve = JwkCurve::from_name(curve.name());
+            assert_eq!(jwk_curve, JwkCurve::Okp(*curve));
+            assert_eq!(jwk_curve.to_string(), curve.name());
+            assert_eq!(jwk_curve.key_type(), Some("OKP"));
+        }
+
+        let jwk_curve = JwkCurve::from_name("brainpoolP256r1");
+        assert_eq!(jwk_curve, JwkCurve::Other("brainpoolP256r1".to_string()));
+        assert_eq!(jwk_curve.name(), "brainpoolP256r1");
+        assert_eq!(jwk_curve.key_type(), None);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::jwk::{Jwk, KeyOperation};
+use crate::util::HashAlgorithm;
+use crate::JoseError;
+
+/// Represent options for exporting a key pair as a JWK.
+///
+/// By default, the exported JWK has the algorithm and the key ID of the key pair and the
+/// CRT parameters of a RSA key, and it has no key use and key operations parameters.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct JwkExportOptions {
+    include_algorithm: bool,
+    include_crt_parameters: bool,
+    key_use: Option<String>,
+    key_operations: Option<Vec<KeyOperation>>,
+    key_id: Option<String>,
+    key_id_thumbprint: Option<HashAlgorithm>,
+}
+
+impl JwkExportOptions {
+    /// Return a new options with the default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether the algorithm parameter (alg) of the key pair is included.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - true if the algorithm parameter is included.
+    pub fn set_include_algorithm(&mut self, value: bool) {
+        self.include_algorithm = value;
+    }
+
+    /// Test whether the algorithm parameter (alg) of the key pair is included.
+    pub fn is_include_algorithm(&self) -> bool {
+        self.include_algorithm
+    }
+
+    /// Set whether the CRT parameters (p, q, dp, dq and qi) of a RSA private key are included.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - true if the CRT parameters are included.
+    pub fn set_include_crt_parameters(&mut self, value: bool) {
+        self.include_crt_parameters = value;
+    }
+
+    /// Test whether the CRT parameters (p, q, dp, dq and qi) of a RSA private key are included.
+    pub fn is_include_crt_parameters(&self) -> bool {
+        self.include_crt_parameters
+    }
+
+    /// Set a value for key use parameter (use).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A key use
+    pub fn set_key_use(&mut self, value: impl Into<String>) {
+        self.key_use = Some(value.into());
+    }
+
+    /// Return the value for key use parameter (use).
+    pub fn key_use(&self) -> Option<&str> {
+        self.key_use.as_deref()
+    }
+
+    /// Set values for key operations parameter (key_ops).
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - key operations
+    pub fn set_key_operations(&mut self, values: &[KeyOperation]) {
+        self.key_operations = Some(values.to_vec());
+    }
+
+    /// Return values for key operations parameter (key_ops).
+    pub fn key_operations(&self) -> Option<&[KeyOperation]> {
+        self.key_operations.as_deref()
+    }
+
+    /// Set a value for key ID parameter (kid) instead of the key ID of the key pair.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A key ID
+    pub fn set_key_id(&mut self, value: impl Into<String>) {
+        self.key_id = Some(value.into());
+        self.key_id_thumbprint = None;
+    }
+
+    /// Return the value for key ID parameter (kid).
+    pub fn key_id(&self) -> Option<&str> {
+        self.key_id.as_deref()
+    }
+
+    /// Set a hash algorithm to derive the key ID parameter (kid) from the RFC 7638 thumbprint
+    /// instead of the key ID of the key pair.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - A hash algorithm of the thumbprint
+    pub fn set_key_id_thumbprint(&mut self, hash: HashAlgorithm) {
+        self.key_id_thumbprint = Some(hash);
+        self.key_id = None;
+    }
+
+    /// Return the hash algorithm to derive the key ID parameter (kid) from the thumbprint.
+    pub fn key_id_thumbprint(&self) -> Option<HashAlgorithm> {
+        self.key_id_thumbprint
+    }
+
+    pub(crate) fn apply(&self, jwk: &mut Jwk) -> Result<(), JoseError> {
+        if !self.include_algorithm {
+            jwk.remove_parameter("alg")?;
+        }
+        if !self.include_crt_parameters && jwk.key_type() == "RSA" {
+            for key in &["p", "q", "dp", "dq", "qi", "oth"] {
+                jwk.remove_parameter(key)?;
+            }
+        }
+        if let Some(val) = &self.key_use {
+            jwk.set_key_use(val);
+        }
+        if let Some(vals) = &self.key_operations {
+            jwk.set_key_operations(vals);
+        }
+        if let Some(val) = &self.key_id {
+            jwk.set_key_id(val);
+        } else if let Some(hash) = self.key_id_thumbprint {
+            jwk.set_key_id_from_thumbprint(hash)?;
+        }
+
+        Jwk::check_map(jwk.as_ref())
+    }
+}
+
+impl Default for JwkExportOptions {
+    fn default() -> Self {
+        Self {
+            include_algorithm: true,
+            include_crt_parameters: true,
+            key_use: None,
+            key_operations: None,
+            key_id: None,
+            key_id_thumbprint: None,
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use std::fmt::Display;
+
+use crate::JoseError;
+
+/// Represent a key that is skipped by the lenient parsing of a JWK set.
+#[derive(Debug)]
+pub struct JwkParseError {
+    index: usize,
+    key_id: Option<String>,
+    error: JoseError,
+}
+
+impl JwkParseError {
+    pub(crate) fn new(index: usize, key_id: Option<String>, error: JoseError) -> Self {
+        Self {
+            index,
+            key_id,
+            error,
+        }
+    }
+
+    /// Return the index of the key in the keys parameter.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Return the key ID of the key if it is readable.
+    pub fn key_id(&self) -> Option<&str> {
+        self.key_id.as_deref()
+    }
+
+    /// Return the reason why the key is skipped.
+    pub fn error(&self) -> &JoseError {
+        &self.error
+    }
+}
+
+impl Display for JwkParseError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match &self.key_id {
+            Some(val) => write!(
+                fmt,
+                "The key at index {} (kid: {}) is skipped: {}",
+                self.index, val, self.error
+            ),
+            None => write!(
+                fmt,
+                "The key at index {} is skipped: {}",
+                self.index, self.error
+            ),
+        }
+    }
+}
+
+impl std::error::Error for JwkParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
@@ -0,0 +1,168 @@
+use crate::jwk::Jwk;
+
+/// Represent criteria for selecting keys from a JWK set.
+///
+/// The specified criteria are combined with AND semantics. When a key lacks an optional
+/// parameter that is specified in the criteria, the key matches unless the selector is strict.
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct JwkSelector {
+    key_id: Option<String>,
+    key_type: Option<String>,
+    key_use: Option<String>,
+    algorithm: Option<String>,
+    curve: Option<String>,
+    key_operation: Option<String>,
+    strict: bool,
+}
+
+impl JwkSelector {
+    /// Return a new selector that matches any key.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a value for key ID parameter (kid).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A key ID
+    pub fn set_key_id(&mut self, value: impl Into<String>) {
+        self.key_id = Some(value.into());
+    }
+
+    /// Return the value for key ID parameter (kid).
+    pub fn key_id(&self) -> Option<&str> {
+        self.key_id.as_deref()
+    }
+
+    /// Set a value for key type parameter (kty).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A key type
+    pub fn set_key_type(&mut self, value: impl Into<String>) {
+        self.key_type = Some(value.into());
+    }
+
+    /// Return the value for key type parameter (kty).
+    pub fn key_type(&self) -> Option<&str> {
+        self.key_type.as_deref()
+    }
+
+    /// Set a value for key use parameter (use).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A key use
+    pub fn set_key_use(&mut self, value: impl Into<String>) {
+        self.key_use = Some(value.into());
+    }
+
+    /// Return the value for key use parameter (use).
+    pub fn key_use(&self) -> Option<&str> {
+        self.key_use.as_deref()
+    }
+
+    /// Set a value for algorithm parameter (alg).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A algorithm
+    pub fn set_algorithm(&mut self, value: impl Into<String>) {
+        self.algorithm = Some(value.into());
+    }
+
+    /// Return the value for algorithm parameter (alg).
+    pub fn algorithm(&self) -> Option<&str> {
+        self.algorithm.as_deref()
+    }
+
+    /// Set a value for curve parameter (crv).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A curve name
+    pub fn set_curve(&mut self, value: impl Into<String>) {
+        self.curve = Some(value.into());
+    }
+
+    /// Return the value for curve parameter (crv).
+    pub fn curve(&self) -> Option<&str> {
+        self.curve.as_deref()
+    }
+
+    /// Set a key operation that must be in key operations parameter (key_ops).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A key operation
+    pub fn set_key_operation(&mut self, value: impl Into<String>) {
+        self.key_operation = Some(value.into());
+    }
+
+    /// Return the key operation that must be in key operations parameter (key_ops).
+    pub fn key_operation(&self) -> Option<&str> {
+        self.key_operation.as_deref()
+    }
+
+    /// Set whether a key that lacks a specified parameter is excluded.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - true if a key that lacks a specified parameter is excluded.
+    pub fn set_strict(&mut self, value: bool) {
+        self.strict = value;
+    }
+
+    /// Test whether a key that lacks a specified parameter is excluded.
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Test whether the key matches all of the specified criteria.
+    ///
+    /// # Arguments
+    ///
+    /// * `jwk` - A key
+    pub fn is_match(&self, jwk: &Jwk) -> bool {
+        let is_match = |expected: &Option<String>, actual: Option<&str>| match expected {
+            Some(expected) => match actual {
+                Some(actual) => expected == actual,
+                None => !self.strict,
+            },
+            None => true,
+        };
+
+        if let Some(expected) = &self.key_type {
+            if expected != jwk.key_type() {
+                return false;
+            }
+        }
+
+        let curve = jwk.curve();
+        if !is_match(&self.key_id, jwk.key_id())
+            || !is_match(&self.key_use, jwk.key_use())
+            || !is_match(&self.algorithm, jwk.algorithm())
+            || !is_match(&self.curve, curve.as_ref().map(|val| val.name()))
+        {
+            return false;
+        }
+
+        if let Some(expected) = &self.key_operation {
+            match jwk.key_operations() {
+                Some(vals) => {
+                    if !vals.iter().any(|val| val.name() == expected) {
+                        return false;
+                    }
+                }
+                None => {
+                    if self.strict {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        true
+    }
+}
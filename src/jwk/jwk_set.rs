@@ -1,13 +1,12 @@
-use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::io::Read;
-use std::ops::Bound::Included;
 use std::string::ToString;
 use std::sync::Arc;
 
 use anyhow::bail;
 
-use crate::jwk::Jwk;
+use crate::jwk::{Jwk, JwkParseError, JwkSelector, OctKeyPolicy};
 use crate::{JoseError, Map, Value};
 
 /// Represents JWK set.
@@ -15,37 +14,115 @@ use crate::{JoseError, Map, Value};
 pub struct JwkSet {
     keys: Vec<Arc<Jwk>>,
     params: Map<String, Value>,
-    kid_map: BTreeMap<(String, usize), Arc<Jwk>>,
+    kid_map: HashMap<String, Vec<Arc<Jwk>>>,
 }
 
 impl JwkSet {
     pub fn new() -> Self {
+        let mut params = Map::new();
+        params.insert("keys".to_string(), Value::Array(Vec::new()));
+
         Self {
             keys: Vec::new(),
-            params: Map::new(),
-            kid_map: BTreeMap::new(),
+            params,
+            kid_map: HashMap::new(),
         }
     }
 
     pub fn from_map(map: Map<String, Value>) -> Result<Self, JoseError> {
-        (|| -> anyhow::Result<Self> {
-            let mut kid_map = BTreeMap::new();
-            let keys = match map.get("keys") {
+        let (jwk_set, _) = Self::parse_map(map, false)?;
+        Ok(jwk_set)
+    }
+
+    /// Return a JWK set that skips the invalid keys, and the reasons why they are skipped.
+    ///
+    /// The keys of unknown key types (other than oct, RSA, EC and OKP) are also skipped.
+    /// The keys parameter of the returned set has only the valid keys. It is still an error
+    /// when the document doesn't have a keys parameter that is a array.
+    ///
+    /// # Arguments
+    ///
+    /// * `map` - A JWK set document.
+    pub fn from_map_lenient(
+        map: Map<String, Value>,
+    ) -> Result<(Self, Vec<JwkParseError>), JoseError> {
+        Self::parse_map(map, true)
+    }
+
+    /// Return a JWK set that skips the invalid keys, and the reasons why they are skipped.
+    ///
+    /// See [`JwkSet::from_map_lenient`] for details.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - A JSON byte array of a JWK set document.
+    pub fn from_bytes_lenient(
+        input: impl AsRef<[u8]>,
+    ) -> Result<(Self, Vec<JwkParseError>), JoseError> {
+        (|| -> anyhow::Result<(Self, Vec<JwkParseError>)> {
+            let keys: Map<String, Value> = serde_json::from_slice(input.as_ref())?;
+            Ok(Self::from_map_lenient(keys)?)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidJwkFormat(err),
+        })
+    }
+
+    fn parse_map(
+        mut map: Map<String, Value>,
+        lenient: bool,
+    ) -> Result<(Self, Vec<JwkParseError>), JoseError> {
+        (|| -> anyhow::Result<(Self, Vec<JwkParseError>)> {
+            let mut kid_map: HashMap<String, Vec<Arc<Jwk>>> = HashMap::new();
+            let mut errors = Vec::new();
+            let keys = match map.get_mut("keys") {
                 Some(Value::Array(vals)) => {
                     let mut vec = Vec::new();
-                    for (i, val) in vals.iter().enumerate() {
-                        match val {
-                            Value::Object(val) => {
-                                let jwk = Arc::new(Jwk::from_map(val.clone())?);
-                                if let Some(kid) = jwk.key_id() {
-                                    kid_map.insert((kid.to_string(), i), Arc::clone(&jwk));
-                                }
-                                vec.push(jwk);
-                            }
-                            _ => {
-                                bail!("An element of the JWK set keys parameter must be a object.")
+                    let mut valid = Vec::with_capacity(vals.len());
+                    for (index, val) in vals.iter().enumerate() {
+                        let result = match val {
+                            Value::Object(val) => Jwk::from_map(val.clone()),
+                            _ => Err(JoseError::InvalidJwkFormat(anyhow::anyhow!(
+                                "An element of the JWK set keys parameter must be a object."
+                            ))),
+                        };
+                        // RFC 7517 section 5 recommends to ignore the keys of unknown types.
+                        let result = match result {
+                            Ok(val) if lenient => match val.key_type() {
+                                "oct" | "RSA" | "EC" | "OKP" => Ok(val),
+                                kty => Err(JoseError::InvalidJwkFormat(anyhow::anyhow!(
+                                    "The key type is unsupported: {}",
+                                    kty
+                                ))),
+                            },
+                            result => result,
+                        };
+                        let jwk = match result {
+                            Ok(val) => Arc::new(val),
+                            Err(err) if lenient => {
+                                let key_id = match val.get("kid") {
+                                    Some(Value::String(val)) => Some(val.to_string()),
+                                    _ => None,
+                                };
+                                errors.push(JwkParseError::new(index, key_id, err));
+                                valid.push(false);
+                                continue;
                             }
+                            Err(err) => return Err(err.into()),
+                        };
+                        if let Some(kid) = jwk.key_id() {
+                            kid_map
+                                .entry(kid.to_string())
+                                .or_default()
+                                .push(Arc::clone(&jwk));
                         }
+                        vec.push(jwk);
+                        valid.push(true);
+                    }
+                    if !errors.is_empty() {
+                        let mut valid = valid.into_iter();
+                        vals.retain(|_| valid.next().unwrap_or(false));
                     }
                     vec
                 }
@@ -53,11 +130,12 @@ impl JwkSet {
                 None => bail!("The JWK set must have a keys parameter."),
             };
 
-            Ok(Self {
+            let jwk_set = Self {
                 keys,
                 params: map,
-                kid_map: kid_map,
-            })
+                kid_map,
+            };
+            Ok((jwk_set, errors))
         })()
         .map_err(|err| match err.downcast::<JoseError>() {
             Ok(err) => err,
@@ -87,22 +165,39 @@ impl JwkSet {
         })
     }
 
+    /// Return the keys that have the key ID in the order of the set.
+    ///
+    /// Multiple keys may share a key ID, for example during key rotation.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_id` - A key ID.
     pub fn get(&self, key_id: &str) -> Vec<&Jwk> {
-        let mut vec = Vec::new();
-        for (_, val) in self.kid_map.range((
-            Included((key_id.to_string(), 0)),
-            Included((key_id.to_string(), usize::MAX)),
-        )) {
-            let jwk: &Jwk = &val;
-            vec.push(jwk);
+        match self.kid_map.get(key_id) {
+            Some(vals) => vals.iter().map(|e| e.as_ref()).collect(),
+            None => Vec::new(),
         }
-        vec
     }
 
-    pub fn keys(&self) -> Vec<&Jwk> {
-        self.keys.iter().map(|e| e.as_ref()).collect()
+    /// Return a iterator over all keys in the set.
+    pub fn keys(&self) -> impl Iterator<Item = &Jwk> {
+        self.keys.iter().map(|e| e.as_ref())
+    }
+
+    /// Return the keys that match the criteria in the order of the set.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - The criteria for selecting keys.
+    pub fn select(&self, selector: &JwkSelector) -> Vec<&Jwk> {
+        self.keys().filter(|jwk| selector.is_match(jwk)).collect()
     }
 
+    /// Add a key to the end of the set.
+    ///
+    /// # Arguments
+    ///
+    /// * `jwk` - A key.
     pub fn push_key(&mut self, jwk: Jwk) {
         match self.params.get_mut("keys") {
             Some(Value::Array(keys)) => {
@@ -114,11 +209,18 @@ impl JwkSet {
         let jwk = Arc::new(jwk);
         if let Some(kid) = jwk.key_id() {
             self.kid_map
-                .insert((kid.to_string(), self.keys.len()), Arc::clone(&jwk));
+                .entry(kid.to_string())
+                .or_default()
+                .push(Arc::clone(&jwk));
         }
         self.keys.push(jwk);
     }
 
+    /// Remove the first key that is equal to the key from the set.
+    ///
+    /// # Arguments
+    ///
+    /// * `jwk` - A key.
     pub fn remove_key(&mut self, jwk: &Jwk) {
         let index = self.keys.iter().position(|e| e.as_ref() == jwk);
         if let Some(index) = index {
@@ -128,8 +230,166 @@ impl JwkSet {
                 }
                 _ => unreachable!(),
             }
-            self.keys.remove(index);
+            let removed = self.keys.remove(index);
+
+            if let Some(kid) = removed.key_id() {
+                if let Some(vals) = self.kid_map.get_mut(kid) {
+                    if let Some(i) = vals.iter().position(|e| Arc::ptr_eq(e, &removed)) {
+                        vals.remove(i);
+                    }
+                    if vals.is_empty() {
+                        self.kid_map.remove(kid);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Remove all keys that have the key ID from the set.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_id` - A key ID.
+    pub fn remove_keys_by_kid(&mut self, key_id: &str) {
+        if self.kid_map.remove(key_id).is_none() {
+            return;
+        }
+
+        match self.params.get_mut("keys") {
+            Some(Value::Array(keys)) => {
+                keys.retain(|key| match key {
+                    Value::Object(map) => match map.get("kid") {
+                        Some(Value::String(val)) => val != key_id,
+                        _ => true,
+                    },
+                    _ => true,
+                });
+            }
+            _ => unreachable!(),
+        }
+        self.keys.retain(|e| e.key_id() != Some(key_id));
+    }
+
+    /// Replace the first key that is equal to the old key with the new key in place.
+    ///
+    /// # Arguments
+    ///
+    /// * `old` - A key to be replaced.
+    /// * `new` - A key to replace with.
+    pub fn replace_key(&mut self, old: &Jwk, new: Jwk) {
+        let index = self.keys.iter().position(|e| e.as_ref() == old);
+        if let Some(index) = index {
+            match self.params.get_mut("keys") {
+                Some(Value::Array(keys)) => {
+                    keys[index] = Value::Object(new.as_ref().clone());
+                }
+                _ => unreachable!(),
+            }
+
+            let removed = std::mem::replace(&mut self.keys[index], Arc::new(new));
+            if let Some(kid) = removed.key_id() {
+                if let Some(vals) = self.kid_map.get_mut(kid) {
+                    vals.retain(|e| !Arc::ptr_eq(e, &removed));
+                    if vals.is_empty() {
+                        self.kid_map.remove(kid);
+                    }
+                }
+            }
+
+            // Rebuild the key ID entry to keep the order of the set.
+            let jwk = Arc::clone(&self.keys[index]);
+            if let Some(kid) = jwk.key_id() {
+                let vals = self
+                    .keys
+                    .iter()
+                    .filter(|e| e.key_id() == Some(kid))
+                    .cloned()
+                    .collect();
+                self.kid_map.insert(kid.to_string(), vals);
+            }
+        }
+    }
+
+    /// Return a JWK set that has only the public parameters of the keys for publishing.
+    ///
+    /// The private parameters of RSA, EC and OKP keys are removed. It is an error when
+    /// the set has an oct key, unless `exclude_oct` is true and then the oct keys are
+    /// excluded. It is also an error when a key of an unknown type has private parameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `exclude_oct` - true if oct keys are excluded instead of returning an error.
+    pub fn to_public_set(&self, exclude_oct: bool) -> Result<JwkSet, JoseError> {
+        let policy = if exclude_oct {
+            OctKeyPolicy::Exclude
+        } else {
+            OctKeyPolicy::Reject
+        };
+        self.to_public_set_with_policy(policy, Some)
+    }
+
+    /// Return a JWK set that has only the public parameters of the keys for publishing
+    /// after applying a redaction function to each public key.
+    ///
+    /// It is an error when the set has an oct key. Use `to_public_set_with_policy`
+    /// to exclude them.
+    ///
+    /// # Arguments
+    ///
+    /// * `redact` - A function that returns a modified key, or None to exclude the key.
+    pub fn to_public_set_with(
+        &self,
+        redact: impl FnMut(Jwk) -> Option<Jwk>,
+    ) -> Result<JwkSet, JoseError> {
+        self.to_public_set_with_policy(OctKeyPolicy::default(), redact)
+    }
+
+    /// Return a JWK set that has only the public parameters of the keys for publishing
+    /// with a specified policy for oct keys.
+    ///
+    /// The redaction function is applied to the public keys, and the returned keys are
+    /// checked again not to have private parameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - A policy for oct keys.
+    /// * `redact` - A function that returns a modified key, or None to exclude the key.
+    pub fn to_public_set_with_policy(
+        &self,
+        policy: OctKeyPolicy,
+        mut redact: impl FnMut(Jwk) -> Option<Jwk>,
+    ) -> Result<JwkSet, JoseError> {
+        let mut keys = Vec::with_capacity(self.keys.len());
+        for (index, jwk) in self.keys().enumerate() {
+            let jwk = match jwk.key_type() {
+                "oct" => match policy {
+                    OctKeyPolicy::Exclude => continue,
+                    OctKeyPolicy::Reject => {
+                        return Err(JoseError::InvalidJwkFormat(anyhow::anyhow!(
+                            "The key at index {} is a oct key that cannot be published.",
+                            index
+                        )))
+                    }
+                },
+                "RSA" | "EC" | "OKP" => jwk.to_public_key()?,
+                _ => jwk.clone(),
+            };
+            jwk.ensure_public()?;
+
+            if let Some(jwk) = redact(jwk) {
+                jwk.ensure_public()?;
+                keys.push(Value::Object(jwk.into()));
+            }
         }
+
+        let mut map = self.params.clone();
+        map.insert("keys".to_string(), Value::Array(keys));
+        JwkSet::from_map(map)
+    }
+
+    /// Return a JSON byte array of the canonical JWK set document.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.to_string().into_bytes()
     }
 }
 
@@ -145,6 +405,21 @@ impl Into<Map<String, Value>> for JwkSet {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for JwkSet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&self.params, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for JwkSet {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let map: Map<String, Value> = serde::Deserialize::deserialize(deserializer)?;
+        Self::from_map(map).map_err(serde::de::Error::custom)
+    }
+}
+
 impl Display for JwkSet {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         fmt.write_str("{\"keys\":[")?;
@@ -186,6 +461,339 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_jwk_set_serde() -> Result<()> {
+        let mut file = load_file("jwks/test.jwks")?;
+        let jwks = JwkSet::from_reader(&mut file)?;
+
+        let json = serde_json::to_vec(&jwks)?;
+        let decoded: JwkSet = serde_json::from_slice(&json)?;
+        assert_eq!(decoded, jwks);
+        assert_eq!(decoded.get("1").len(), 1);
+
+        let mut cbor = Vec::new();
+        ciborium::ser::into_writer(&jwks, &mut cbor)?;
+        let decoded: JwkSet = ciborium::de::from_reader(cbor.as_slice())?;
+        assert_eq!(decoded, jwks);
+
+        assert!(serde_json::from_str::<JwkSet>(r#"{"keys":[{"kid":"a"}]}"#).is_err());
+        assert!(serde_json::from_str::<JwkSet>(r#"{}"#).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_set_lookup_by_key_id() -> Result<()> {
+        let mut jwks = JwkSet::new();
+        for i in 0..500 {
+            let mut jwk = Jwk::new("oct");
+            jwk.set_key_id(format!("key-{}", i));
+            jwk.set_parameter("k", Some(Value::String(format!("k{}", i))))?;
+            jwks.push_key(jwk);
+        }
+
+        // A rotated key that shares the key ID must not collapse the previous one.
+        let mut rotated = Jwk::new("oct");
+        rotated.set_key_id("key-7");
+        rotated.set_parameter("k", Some(Value::String("rotated".to_string())))?;
+        jwks.push_key(rotated.clone());
+
+        let mut no_kid = Jwk::new("oct");
+        no_kid.set_parameter("k", Some(Value::String("no-kid".to_string())))?;
+        jwks.push_key(no_kid);
+
+        assert_eq!(jwks.keys().count(), 502);
+        for i in 0..500 {
+            assert_eq!(
+                jwks.get(&format!("key-{}", i)).len(),
+                if i == 7 { 2 } else { 1 }
+            );
+        }
+        let keys = jwks.get("key-7");
+        assert_eq!(
+            keys[0].parameter("k"),
+            Some(&Value::String("k7".to_string()))
+        );
+        assert_eq!(keys[1], &rotated);
+        assert!(jwks.get("unknown").is_empty());
+
+        let parsed = JwkSet::from_bytes(jwks.to_string())?;
+        assert_eq!(parsed.keys().count(), 502);
+        assert_eq!(parsed.get("key-7").len(), 2);
+
+        let first = jwks.get("key-7")[0].clone();
+        jwks.remove_key(&first);
+        assert_eq!(jwks.keys().count(), 501);
+        assert_eq!(jwks.get("key-7"), vec![&rotated]);
+        jwks.remove_key(&rotated);
+        assert!(jwks.get("key-7").is_empty());
+        assert_eq!(jwks.get("key-8").len(), 1);
+
+        match jwks.as_ref().get("keys") {
+            Some(Value::Array(vals)) => assert_eq!(vals.len(), 500),
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_set_to_public_set() -> Result<()> {
+        let jwks = JwkSet::from_bytes(
+            r#"{"keys":[
+                {"kty":"EC","kid":"1","crv":"P-256","x":"AA","y":"AA","d":"AA"},
+                {"kty":"RSA","kid":"2","n":"AA","e":"AQAB","d":"AA","p":"AA","q":"AA"},
+                {"kty":"oct","kid":"3","k":"AA"},
+                {"kty":"OKP","kid":"4","crv":"Ed25519","x":"AA"},
+                {"kty":"unknown","kid":"5","ext":"AA"}
+            ],"ext":true}"#,
+        )?;
+
+        assert!(jwks.to_public_set(false).is_err());
+        assert!(jwks
+            .to_public_set_with_policy(OctKeyPolicy::default(), Some)
+            .is_err());
+
+        let public_jwks = jwks.to_public_set(true)?;
+        let kids: Vec<Option<&str>> = public_jwks.keys().map(|jwk| jwk.key_id()).collect();
+        assert_eq!(kids, vec![Some("1"), Some("2"), Some("4"), Some("5")]);
+        for jwk in public_jwks.keys() {
+            assert!(!jwk.has_private_parameters());
+        }
+        assert_eq!(
+            public_jwks.get("2")[0].parameter("n"),
+            jwks.get("2")[0].parameter("n")
+        );
+        assert_eq!(public_jwks.as_ref().get("ext"), Some(&Value::Bool(true)));
+        assert_eq!(JwkSet::from_bytes(public_jwks.to_vec())?.keys().count(), 4);
+
+        let jwks = JwkSet::from_bytes(r#"{"keys":[{"kty":"unknown","d":"AA"}]}"#)?;
+        assert!(jwks.to_public_set(true).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_set_to_public_set_with() -> Result<()> {
+        let mut jwks = JwkSet::new();
+        for name in &[
+            "RSA_multi_prime_private.jwk",
+            "oct_256bit_private.jwk",
+            "EC_P-256_private.jwk",
+            "OKP_Ed25519_private.jwk",
+            "OKP_X448_private.jwk",
+            "RSA_private.jwk",
+        ] {
+            let mut jwk = Jwk::from_reader(&mut load_file(&format!("jwk/{}", name))?)?;
+            jwk.set_key_id(*name);
+            jwk.set_x509_url("https://internal.example.com/cert.pem");
+            jwks.push_key(jwk);
+        }
+
+        assert!(jwks.to_public_set_with(Some).is_err());
+
+        let public_jwks = jwks.to_public_set(true)?;
+        let kids: Vec<Option<&str>> = public_jwks.keys().map(|jwk| jwk.key_id()).collect();
+        assert_eq!(
+            kids,
+            vec![
+                Some("RSA_multi_prime_private.jwk"),
+                Some("EC_P-256_private.jwk"),
+                Some("OKP_Ed25519_private.jwk"),
+                Some("OKP_X448_private.jwk"),
+                Some("RSA_private.jwk"),
+            ]
+        );
+        for jwk in public_jwks.keys() {
+            for key in &["d", "p", "q", "dp", "dq", "qi", "k", "oth"] {
+                assert_eq!(jwk.parameter(key), None);
+            }
+        }
+        let output = public_jwks.to_string();
+        for key in &[
+            "\"d\"", "\"p\"", "\"q\"", "\"dp\"", "\"dq\"", "\"qi\"", "\"k\"", "\"oth\"",
+        ] {
+            assert!(!output.contains(key));
+        }
+
+        let public_jwks = jwks.to_public_set_with_policy(OctKeyPolicy::Exclude, |mut jwk| {
+            if jwk.key_type() == "OKP" {
+                return None;
+            }
+            jwk.remove_parameter("x5u").ok()?;
+            Some(jwk)
+        })?;
+        assert_eq!(public_jwks.keys().count(), 3);
+        assert_eq!(public_jwks.get("EC_P-256_private.jwk").len(), 1);
+        for jwk in public_jwks.keys() {
+            assert_eq!(jwk.x509_url(), None);
+        }
+
+        // A private parameter that is added by the redaction function is an error.
+        let result = jwks.to_public_set_with_policy(OctKeyPolicy::Exclude, |mut jwk| {
+            jwk.set_parameter("d", Some(Value::String("AA".to_string())))
+                .ok()?;
+            Some(jwk)
+        });
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_set_lenient() -> Result<()> {
+        let input = r#"{"keys":[
+            {"kty":"EC","kid":"1","crv":"P-256","x":"AA","y":"AA"},
+            {"kty":"RSA","kid":"2","n":123,"e":"AQAB"},
+            "key",
+            {"kid":"4","k":"AA"},
+            {"kty":"unknown","kid":"5"},
+            {"kty":"oct","kid":"6","key_ops":"sign","k":"AA"},
+            {"kty":"oct","kid":"7","k":"AA"}
+        ]}"#;
+        assert!(JwkSet::from_bytes(input).is_err());
+
+        let (jwks, errors) = JwkSet::from_bytes_lenient(input)?;
+        let kids: Vec<Option<&str>> = jwks.keys().map(|jwk| jwk.key_id()).collect();
+        assert_eq!(kids, vec![Some("1"), Some("7")]);
+        assert_eq!(jwks.get("5").len(), 0);
+        assert_eq!(jwks.get("2").len(), 0);
+        assert_eq!(jwks.get("7").len(), 1);
+        assert_eq!(JwkSet::from_bytes(jwks.to_vec())?, jwks);
+
+        let errors: Vec<(usize, Option<&str>)> = errors
+            .iter()
+            .map(|err| {
+                let message = format!("The key at index {}", err.index());
+                assert!(err.to_string().starts_with(&message));
+                (err.index(), err.key_id())
+            })
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (1, Some("2")),
+                (2, None),
+                (3, Some("4")),
+                (4, Some("5")),
+                (5, Some("6"))
+            ]
+        );
+
+        let (jwks, errors) = JwkSet::from_bytes_lenient(r#"{"keys":[]}"#)?;
+        assert_eq!(jwks.keys().count(), 0);
+        assert!(errors.is_empty());
+
+        for input in &[r#"[]"#, r#"{}"#, r#"{"keys":{}}"#, r#"{"keys":"#] {
+            match JwkSet::from_bytes_lenient(input) {
+                Err(JoseError::InvalidJwkFormat(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_set_select() -> Result<()> {
+        let jwks = JwkSet::from_bytes(
+            r#"{"keys":[
+                {"kty":"EC","kid":"1","use":"sig","alg":"ES256","crv":"P-256","x":"AA","y":"AA"},
+                {"kty":"EC","kid":"2","use":"enc","crv":"P-256","x":"AA","y":"AA"},
+                {"kty":"EC","kid":"3","crv":"P-384","x":"AA","y":"AA"},
+                {"kty":"RSA","kid":"4","use":"sig","alg":"RS256","n":"AA","e":"AQAB"},
+                {"kty":"oct","kid":"5","key_ops":["sign","verify"],"k":"AA"},
+                {"kty":"oct","k":"AQ"}
+            ]}"#,
+        )?;
+        fn kids<'a>(keys: Vec<&'a Jwk>) -> Vec<Option<&'a str>> {
+            keys.iter().map(|jwk| jwk.key_id()).collect()
+        }
+
+        let selector = JwkSelector::new();
+        assert_eq!(jwks.select(&selector).len(), 6);
+
+        let mut selector = JwkSelector::new();
+        selector.set_key_type("EC");
+        selector.set_key_use("sig");
+        assert_eq!(kids(jwks.select(&selector)), vec![Some("1"), Some("3")]);
+        selector.set_strict(true);
+        assert_eq!(kids(jwks.select(&selector)), vec![Some("1")]);
+
+        let mut selector = JwkSelector::new();
+        selector.set_curve("P-256");
+        selector.set_algorithm("ES256");
+        assert_eq!(
+            kids(jwks.select(&selector)),
+            vec![Some("1"), Some("2"), Some("5"), None]
+        );
+
+        let mut selector = JwkSelector::new();
+        selector.set_key_type("oct");
+        selector.set_key_operation("verify");
+        assert_eq!(jwks.select(&selector).len(), 2);
+        selector.set_strict(true);
+        assert_eq!(kids(jwks.select(&selector)), vec![Some("5")]);
+        selector.set_key_operation("encrypt");
+        assert!(jwks.select(&selector).is_empty());
+
+        let mut selector = JwkSelector::new();
+        selector.set_key_id("4");
+        selector.set_key_type("RSA");
+        selector.set_strict(true);
+        assert_eq!(kids(jwks.select(&selector)), vec![Some("4")]);
+        selector.set_key_type("EC");
+        assert!(jwks.select(&selector).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwk_set_mutation_and_serialization() -> Result<()> {
+        let mut jwks = JwkSet::new();
+        assert_eq!(jwks.to_string(), "{\"keys\":[]}");
+
+        let key1 = Jwk::from_bytes(r#"{"kty":"oct","kid":"a","k":"AQ","x-ext":[1,{"y":null}]}"#)?;
+        let key2 = Jwk::from_bytes(r#"{"kty":"oct","k":"Ag"}"#)?;
+        let key3 = Jwk::from_bytes(r#"{"kty":"oct","kid":"a","k":"Aw"}"#)?;
+        let key4 = Jwk::from_bytes(r#"{"kty":"oct","kid":"b","k":"BA"}"#)?;
+        jwks.push_key(key1.clone());
+        jwks.push_key(key2.clone());
+        jwks.push_key(key3.clone());
+
+        assert_eq!(
+            jwks.to_string(),
+            concat!(
+                r#"{"keys":[{"kty":"oct","kid":"a","k":"AQ","x-ext":[1,{"y":null}]},"#,
+                r#"{"kty":"oct","k":"Ag"},{"kty":"oct","kid":"a","k":"Aw"}]}"#
+            )
+        );
+        assert_eq!(jwks.to_vec(), jwks.to_string().into_bytes());
+        assert_eq!(JwkSet::from_bytes(jwks.to_vec())?, jwks);
+        assert_eq!(jwks.get("a"), vec![&key1, &key3]);
+
+        jwks.replace_key(&key1, key4.clone());
+        assert_eq!(jwks.get("a"), vec![&key3]);
+        assert_eq!(jwks.get("b"), vec![&key4]);
+        assert_eq!(jwks.keys().collect::<Vec<_>>(), vec![&key4, &key2, &key3]);
+
+        jwks.replace_key(&key4, key1.clone());
+        assert_eq!(jwks.get("a"), vec![&key1, &key3]);
+        assert!(jwks.get("b").is_empty());
+
+        jwks.remove_keys_by_kid("a");
+        assert!(jwks.get("a").is_empty());
+        assert_eq!(jwks.keys().collect::<Vec<_>>(), vec![&key2]);
+        assert_eq!(jwks.to_string(), r#"{"keys":[{"kty":"oct","k":"Ag"}]}"#);
+        assert_eq!(JwkSet::from_bytes(jwks.to_vec())?, jwks);
+
+        jwks.remove_key(&key2);
+        assert_eq!(jwks.to_string(), "{\"keys\":[]}");
+
+        Ok(())
+    }
+
     fn load_file(path: &str) -> Result<File> {
         let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         pb.push("data");
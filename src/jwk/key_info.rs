@@ -1,13 +1,12 @@
 use crate::jwk::alg::ec::EcCurve;
 use crate::jwk::alg::ecx::EcxCurve;
 use crate::jwk::alg::ed::EdCurve;
-use crate::jwk::Jwk;
+use crate::jwk::{Jwk, JwkCurve};
 use crate::util;
 use crate::util::der::{DerClass, DerError, DerReader, DerType};
 use crate::util::oid::{
-    OID_ED25519, OID_ED448, OID_ID_EC_PUBLIC_KEY, OID_MGF1, OID_PRIME256V1, OID_RSASSA_PSS,
-    OID_RSA_ENCRYPTION, OID_SECP256K1, OID_SECP384R1, OID_SECP521R1, OID_SHA1, OID_SHA256,
-    OID_SHA384, OID_SHA512, OID_X25519, OID_X448,
+    OID_ED25519, OID_ED448, OID_ID_EC_PUBLIC_KEY, OID_MGF1, OID_RSASSA_PSS, OID_RSA_ENCRYPTION,
+    OID_SHA1, OID_SHA256, OID_SHA384, OID_SHA512, OID_X25519, OID_X448,
 };
 use crate::util::HashAlgorithm;
 
@@ -254,18 +253,7 @@ impl KeyInfo {
                     }
                     "EC" => {
                         let alg = match jwk.curve() {
-                            Some("P-256") => Some(KeyAlg::Ec {
-                                curve: Some(EcCurve::P256),
-                            }),
-                            Some("P-384") => Some(KeyAlg::Ec {
-                                curve: Some(EcCurve::P384),
-                            }),
-                            Some("P-521") => Some(KeyAlg::Ec {
-                                curve: Some(EcCurve::P521),
-                            }),
-                            Some("secp256k1") => Some(KeyAlg::Ec {
-                                curve: Some(EcCurve::Secp256k1),
-                            }),
+                            Some(JwkCurve::Ec(curve)) => Some(KeyAlg::Ec { curve: Some(curve) }),
                             Some(_) => Some(KeyAlg::Ec { curve: None }),
                             None => return None,
                         };
@@ -279,18 +267,12 @@ impl KeyInfo {
                     }
                     "OKP" => {
                         let alg = match jwk.curve() {
-                            Some("Ed25519") => Some(KeyAlg::Ed {
-                                curve: Some(EdCurve::Ed25519),
-                            }),
-                            Some("Ed448") => Some(KeyAlg::Ed {
-                                curve: Some(EdCurve::Ed448),
-                            }),
-                            Some("X25519") => Some(KeyAlg::Ecx {
-                                curve: Some(EcxCurve::X25519),
-                            }),
-                            Some("X448") => Some(KeyAlg::Ecx {
-                                curve: Some(EcxCurve::X448),
-                            }),
+                            Some(JwkCurve::Okp(curve)) => match curve.ed_curve() {
+                                Some(val) => Some(KeyAlg::Ed { curve: Some(val) }),
+                                None => Some(KeyAlg::Ecx {
+                                    curve: curve.ecx_curve(),
+                                }),
+                            },
                             Some(_) => None,
                             None => return None,
                         };
@@ -347,13 +329,7 @@ impl KeyInfo {
                     val if val == *OID_ID_EC_PUBLIC_KEY => {
                         let curve = match reader.next().ok()? {
                             Some(DerType::ObjectIdentifier) => {
-                                match reader.to_object_identifier().ok()? {
-                                    val if val == *OID_PRIME256V1 => Some(EcCurve::P256),
-                                    val if val == *OID_SECP384R1 => Some(EcCurve::P384),
-                                    val if val == *OID_SECP521R1 => Some(EcCurve::P521),
-                                    val if val == *OID_SECP256K1 => Some(EcCurve::Secp256k1),
-                                    _ => None,
-                                }
+                                EcCurve::from_oid(&reader.to_object_identifier().ok()?)
                             }
                             _ => None,
                         };
@@ -425,13 +401,7 @@ impl KeyInfo {
                         val if val == *OID_ID_EC_PUBLIC_KEY => {
                             let curve = match reader.next().ok()? {
                                 Some(DerType::ObjectIdentifier) => {
-                                    match reader.to_object_identifier().ok()? {
-                                        val if val == *OID_PRIME256V1 => Some(EcCurve::P256),
-                                        val if val == *OID_SECP384R1 => Some(EcCurve::P384),
-                                        val if val == *OID_SECP521R1 => Some(EcCurve::P521),
-                                        val if val == *OID_SECP256K1 => Some(EcCurve::Secp256k1),
-                                        _ => None,
-                                    }
+                                    EcCurve::from_oid(&reader.to_object_identifier().ok()?)
                                 }
                                 _ => None,
                             };
@@ -494,13 +464,7 @@ impl KeyInfo {
                         Some(DerType::Other(DerClass::ContextSpecific, 0)) => {
                             match reader.next().ok()? {
                                 Some(DerType::ObjectIdentifier) => {
-                                    match reader.to_object_identifier().ok()? {
-                                        val if val == *OID_PRIME256V1 => Some(EcCurve::P256),
-                                        val if val == *OID_SECP384R1 => Some(EcCurve::P384),
-                                        val if val == *OID_SECP521R1 => Some(EcCurve::P521),
-                                        val if val == *OID_SECP256K1 => Some(EcCurve::Secp256k1),
-                                        _ => None,
-                                    }
+                                    EcCurve::from_oid(&reader.to_object_identifier().ok()?)
                                 }
                                 _ => None,
                             }
@@ -0,0 +1,78 @@
+use std::fmt::Display;
+
+/// Represent a value of key operations parameter (key_ops) defined in RFC 7517.
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub enum KeyOperation {
+    /// Compute digital signature or MAC
+    Sign,
+    /// Verify digital signature or MAC
+    Verify,
+    /// Encrypt content
+    Encrypt,
+    /// Decrypt content and validate decryption, if applicable
+    Decrypt,
+    /// Encrypt key
+    WrapKey,
+    /// Decrypt key and validate decryption, if applicable
+    UnwrapKey,
+    /// Derive key
+    DeriveKey,
+    /// Derive bits not to be used as a key
+    DeriveBits,
+    /// A value that is not registered in RFC 7517
+    Other(String),
+}
+
+impl KeyOperation {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Sign => "sign",
+            Self::Verify => "verify",
+            Self::Encrypt => "encrypt",
+            Self::Decrypt => "decrypt",
+            Self::WrapKey => "wrapKey",
+            Self::UnwrapKey => "unwrapKey",
+            Self::DeriveKey => "deriveKey",
+            Self::DeriveBits => "deriveBits",
+            Self::Other(val) => val,
+        }
+    }
+
+    /// Return the key use (use) that the operation is consistent with.
+    ///
+    /// It returns None for a value that is not registered in RFC 7517.
+    pub fn key_use(&self) -> Option<&str> {
+        match self {
+            Self::Sign | Self::Verify => Some("sig"),
+            Self::Encrypt
+            | Self::Decrypt
+            | Self::WrapKey
+            | Self::UnwrapKey
+            | Self::DeriveKey
+            | Self::DeriveBits => Some("enc"),
+            Self::Other(_) => None,
+        }
+    }
+}
+
+impl Display for KeyOperation {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        fmt.write_str(self.name())
+    }
+}
+
+impl From<&str> for KeyOperation {
+    fn from(value: &str) -> Self {
+        match value {
+            "sign" => Self::Sign,
+            "verify" => Self::Verify,
+            "encrypt" => Self::Encrypt,
+            "decrypt" => Self::Decrypt,
+            "wrapKey" => Self::WrapKey,
+            "unwrapKey" => Self::UnwrapKey,
+            "deriveKey" => Self::DeriveKey,
+            "deriveBits" => Self::DeriveBits,
+            val => Self::Other(val.to_string()),
+        }
+    }
+}
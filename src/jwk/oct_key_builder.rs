@@ -0,0 +1,176 @@
+use anyhow::bail;
+
+use crate::jwk::Jwk;
+use crate::util::{self, HashAlgorithm};
+use crate::{JoseError, Value};
+
+/// Represent a builder for generating a random oct type JWK.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct OctKeyBuilder {
+    bits: u32,
+    key_use: Option<String>,
+    algorithm: Option<String>,
+    thumbprint_hash: Option<HashAlgorithm>,
+}
+
+impl OctKeyBuilder {
+    /// Minimum key bit length that can be generated.
+    pub const MIN_BITS: u32 = 112;
+
+    /// Return a new builder.
+    ///
+    /// # Arguments
+    ///
+    /// * `bits` - A key bit length that is a multiple of 8 and at least 112.
+    pub fn new(bits: u32) -> Self {
+        Self {
+            bits,
+            key_use: None,
+            algorithm: None,
+            thumbprint_hash: None,
+        }
+    }
+
+    /// Return the key bit length.
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// Set a value for key use parameter (use).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A key use
+    pub fn set_key_use(&mut self, value: impl Into<String>) {
+        self.key_use = Some(value.into());
+    }
+
+    /// Return the value for key use parameter (use).
+    pub fn key_use(&self) -> Option<&str> {
+        self.key_use.as_deref()
+    }
+
+    /// Set a value for algorithm parameter (alg).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A algorithm
+    pub fn set_algorithm(&mut self, value: impl Into<String>) {
+        self.algorithm = Some(value.into());
+    }
+
+    /// Return the value for algorithm parameter (alg).
+    pub fn algorithm(&self) -> Option<&str> {
+        self.algorithm.as_deref()
+    }
+
+    /// Set a hash algorithm to derive the key ID parameter (kid) from the JWK thumbprint.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - A hash algorithm for the thumbprint
+    pub fn set_key_id_from_thumbprint(&mut self, hash: HashAlgorithm) {
+        self.thumbprint_hash = Some(hash);
+    }
+
+    /// Return the hash algorithm to derive the key ID parameter (kid) from the JWK thumbprint.
+    pub fn key_id_thumbprint_hash(&self) -> Option<HashAlgorithm> {
+        self.thumbprint_hash
+    }
+
+    /// Generate a new oct type JWK.
+    pub fn generate(&self) -> Result<Jwk, JoseError> {
+        (|| -> anyhow::Result<Jwk> {
+            #[allow(clippy::manual_is_multiple_of)]
+            if self.bits % 8 != 0 {
+                bail!("The key bit length must be a multiple of 8: {}", self.bits);
+            }
+            if self.bits < Self::MIN_BITS {
+                bail!(
+                    "The key bit length must be at least {}: {}",
+                    Self::MIN_BITS,
+                    self.bits
+                );
+            }
+
+            let k = util::random_bytes((self.bits / 8) as usize);
+            let k = base64::encode_config(&k, base64::URL_SAFE_NO_PAD);
+
+            let mut jwk = Jwk::new("oct");
+            jwk.set_parameter("k", Some(Value::String(k)))?;
+            if let Some(val) = &self.key_use {
+                jwk.set_key_use(val);
+            }
+            if let Some(val) = &self.algorithm {
+                jwk.set_algorithm(val);
+            }
+            if let Some(hash) = self.thumbprint_hash {
+                jwk.set_key_id_from_thumbprint(hash)?;
+            }
+            Ok(jwk)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidKeyFormat(err),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use anyhow::Result;
+
+    use crate::jwe::{A128KW, A256KW};
+    use crate::jws::HS256;
+
+    #[test]
+    fn test_generate_oct_key() -> Result<()> {
+        for bits in &[112, 128, 256, 512] {
+            let jwk = Jwk::generate_oct_key(*bits)?;
+            assert_eq!(jwk.key_type(), "oct");
+            assert_eq!(jwk.key_value().unwrap().len(), (*bits / 8) as usize);
+            assert_eq!(jwk.key_id(), None);
+        }
+
+        for bits in &[0, 8, 104, 129, 255] {
+            match Jwk::generate_oct_key(*bits) {
+                Err(JoseError::InvalidKeyFormat(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_oct_key_builder() -> Result<()> {
+        let mut builder = OctKeyBuilder::new(256);
+        builder.set_key_use("sig");
+        builder.set_algorithm("HS256");
+        builder.set_key_id_from_thumbprint(HashAlgorithm::Sha256);
+        let jwk = builder.generate()?;
+        assert_eq!(jwk.key_use(), Some("sig"));
+        assert_eq!(jwk.algorithm(), Some("HS256"));
+        let thumbprint = jwk.thumbprint(HashAlgorithm::Sha256)?;
+        assert_eq!(
+            jwk.key_id(),
+            Some(base64::encode_config(&thumbprint, base64::URL_SAFE_NO_PAD).as_str())
+        );
+        HS256.signer_from_jwk(&jwk)?;
+        HS256.verifier_from_jwk(&jwk)?;
+
+        let mut builder = OctKeyBuilder::new(128);
+        builder.set_key_use("enc");
+        builder.set_algorithm("A128KW");
+        let jwk = builder.generate()?;
+        A128KW.encrypter_from_jwk(&jwk)?;
+        A128KW.decrypter_from_jwk(&jwk)?;
+
+        let jwk = Jwk::generate_oct_key(256)?;
+        A256KW.encrypter_from_jwk(&jwk)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,16 @@
+/// Represent how oct keys are handled when a public JWK set is created.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum OctKeyPolicy {
+    /// The oct keys are excluded from the public JWK set.
+    Exclude,
+    /// An error is returned when the JWK set has an oct key. This is the default.
+    Reject,
+}
+
+// `#[default]` on enum variants needs Rust 1.62, so the impl is written by hand.
+#[allow(clippy::derivable_impls)]
+impl Default for OctKeyPolicy {
+    fn default() -> Self {
+        Self::Reject
+    }
+}
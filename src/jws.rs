@@ -6,8 +6,10 @@ mod jws_context;
 mod jws_header;
 mod jws_header_set;
 
+use anyhow::anyhow;
 use once_cell::sync::Lazy;
 
+use crate::jwk::Jwk;
 use crate::JoseError;
 
 pub use crate::jws::jws_algorithm::JwsAlgorithm;
@@ -17,28 +19,53 @@ pub use crate::jws::jws_context::JwsContext;
 pub use crate::jws::jws_header::JwsHeader;
 pub use crate::jws::jws_header_set::JwsHeaderSet;
 
+#[cfg(feature = "jws-hmac")]
 use crate::jws::alg::hmac::HmacJwsAlgorithm;
+#[cfg(feature = "jws-hmac")]
 pub use HmacJwsAlgorithm::Hs256 as HS256;
+#[cfg(feature = "jws-hmac")]
 pub use HmacJwsAlgorithm::Hs384 as HS384;
+#[cfg(feature = "jws-hmac")]
 pub use HmacJwsAlgorithm::Hs512 as HS512;
 
+#[cfg(feature = "jws-rsa")]
 use crate::jws::alg::rsassa::RsassaJwsAlgorithm;
+#[cfg(feature = "jws-rsa")]
 pub use RsassaJwsAlgorithm::Rs256 as RS256;
+#[cfg(feature = "jws-rsa")]
 pub use RsassaJwsAlgorithm::Rs384 as RS384;
+#[cfg(feature = "jws-rsa")]
 pub use RsassaJwsAlgorithm::Rs512 as RS512;
 
+#[cfg(feature = "jws-rsa")]
 use crate::jws::alg::rsassa_pss::RsassaPssJwsAlgorithm;
+#[cfg(feature = "jws-rsa")]
 pub use RsassaPssJwsAlgorithm::Ps256 as PS256;
+#[cfg(feature = "jws-rsa")]
 pub use RsassaPssJwsAlgorithm::Ps384 as PS384;
+#[cfg(feature = "jws-rsa")]
 pub use RsassaPssJwsAlgorithm::Ps512 as PS512;
 
+#[cfg(feature = "jws-ecdsa")]
 use crate::jws::alg::ecdsa::EcdsaJwsAlgorithm;
+#[cfg(feature = "jws-ecdsa")]
 pub use EcdsaJwsAlgorithm::Es256 as ES256;
+#[cfg(feature = "jws-ecdsa")]
 pub use EcdsaJwsAlgorithm::Es256k as ES256K;
+#[cfg(feature = "jws-ecdsa")]
 pub use EcdsaJwsAlgorithm::Es384 as ES384;
+#[cfg(feature = "jws-ecdsa")]
 pub use EcdsaJwsAlgorithm::Es512 as ES512;
-
+#[cfg(all(feature = "jws-ecdsa", feature = "brainpool"))]
+pub use EcdsaJwsAlgorithm::Esb256 as ESB256;
+#[cfg(all(feature = "jws-ecdsa", feature = "brainpool"))]
+pub use EcdsaJwsAlgorithm::Esb384 as ESB384;
+#[cfg(all(feature = "jws-ecdsa", feature = "brainpool"))]
+pub use EcdsaJwsAlgorithm::Esb512 as ESB512;
+
+#[cfg(feature = "jws-eddsa")]
 use crate::jws::alg::eddsa::EddsaJwsAlgorithm;
+#[cfg(feature = "jws-eddsa")]
 pub use EddsaJwsAlgorithm::Eddsa as EdDSA;
 
 static DEFAULT_CONTEXT: Lazy<JwsContext> = Lazy::new(|| JwsContext::new());
@@ -204,6 +231,56 @@ where
     DEFAULT_CONTEXT.deserialize_json_with_selector(input, selector)
 }
 
+/// Return a verifier of the algorithm named by a JWS alg header claim from a JWK.
+///
+/// # Arguments
+///
+/// * `name` - a JWS algorithm name.
+/// * `jwk` - a JWK of the verifying key.
+#[allow(unused_variables)]
+pub(crate) fn verifier_from_jwk(name: &str, jwk: &Jwk) -> Result<Box<dyn JwsVerifier>, JoseError> {
+    match name {
+        #[cfg(feature = "jws-hmac")]
+        "HS256" => Ok(Box::new(HS256.verifier_from_jwk(jwk)?)),
+        #[cfg(feature = "jws-hmac")]
+        "HS384" => Ok(Box::new(HS384.verifier_from_jwk(jwk)?)),
+        #[cfg(feature = "jws-hmac")]
+        "HS512" => Ok(Box::new(HS512.verifier_from_jwk(jwk)?)),
+        #[cfg(feature = "jws-rsa")]
+        "RS256" => Ok(Box::new(RS256.verifier_from_jwk(jwk)?)),
+        #[cfg(feature = "jws-rsa")]
+        "RS384" => Ok(Box::new(RS384.verifier_from_jwk(jwk)?)),
+        #[cfg(feature = "jws-rsa")]
+        "RS512" => Ok(Box::new(RS512.verifier_from_jwk(jwk)?)),
+        #[cfg(feature = "jws-rsa")]
+        "PS256" => Ok(Box::new(PS256.verifier_from_jwk(jwk)?)),
+        #[cfg(feature = "jws-rsa")]
+        "PS384" => Ok(Box::new(PS384.verifier_from_jwk(jwk)?)),
+        #[cfg(feature = "jws-rsa")]
+        "PS512" => Ok(Box::new(PS512.verifier_from_jwk(jwk)?)),
+        #[cfg(feature = "jws-ecdsa")]
+        "ES256" => Ok(Box::new(ES256.verifier_from_jwk(jwk)?)),
+        #[cfg(feature = "jws-ecdsa")]
+        "ES384" => Ok(Box::new(ES384.verifier_from_jwk(jwk)?)),
+        #[cfg(feature = "jws-ecdsa")]
+        "ES512" => Ok(Box::new(ES512.verifier_from_jwk(jwk)?)),
+        #[cfg(feature = "jws-ecdsa")]
+        "ES256K" => Ok(Box::new(ES256K.verifier_from_jwk(jwk)?)),
+        #[cfg(all(feature = "jws-ecdsa", feature = "brainpool"))]
+        "ESB256" => Ok(Box::new(ESB256.verifier_from_jwk(jwk)?)),
+        #[cfg(all(feature = "jws-ecdsa", feature = "brainpool"))]
+        "ESB384" => Ok(Box::new(ESB384.verifier_from_jwk(jwk)?)),
+        #[cfg(all(feature = "jws-ecdsa", feature = "brainpool"))]
+        "ESB512" => Ok(Box::new(ESB512.verifier_from_jwk(jwk)?)),
+        #[cfg(feature = "jws-eddsa")]
+        "EdDSA" => Ok(Box::new(EdDSA.verifier_from_jwk(jwk)?)),
+        _ => Err(JoseError::UnsupportedSignatureAlgorithm(anyhow!(
+            "The JWS algorithm is not supported: {}",
+            name
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -211,8 +288,8 @@ mod tests {
 
     use anyhow::Result;
 
-    use crate::jws::{self, EdDSA, JwsHeader, JwsHeaderSet, ES256, RS256};
-    use crate::Value;
+    use crate::jws::{self, EdDSA, JwsHeader, JwsHeaderSet, ES256, HS256, RS256};
+    use crate::{JoseError, Value};
 
     #[test]
     fn test_jws_compact_serialization() -> Result<()> {
@@ -237,6 +314,23 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_jws_compact_malformed_header_claim() -> Result<()> {
+        let key = b"0123456789ABCDEF0123456789ABCDEF";
+        let signer = HS256.signer_from_bytes(key)?;
+        let verifier = HS256.verifier_from_bytes(key)?;
+
+        let mut src_header = JwsHeader::new();
+        src_header.set_claim("x5t", Some(Value::String("a+b/".to_string())))?;
+        let jwt = jws::serialize_compact(b"test payload!", &src_header, &signer)?;
+        assert!(matches!(
+            jws::deserialize_compact(&jwt, &verifier),
+            Err(JoseError::InvalidJwsFormat(_))
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn test_jws_json_serialization() -> Result<()> {
         let alg = RS256;
@@ -1,5 +1,10 @@
+#[cfg(feature = "jws-ecdsa")]
 pub mod ecdsa;
+#[cfg(feature = "jws-eddsa")]
 pub mod eddsa;
+#[cfg(feature = "jws-hmac")]
 pub mod hmac;
+#[cfg(feature = "jws-rsa")]
 pub mod rsassa;
+#[cfg(feature = "jws-rsa")]
 pub mod rsassa_pss;
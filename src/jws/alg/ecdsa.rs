@@ -1,20 +1,25 @@
+use std::cmp::Ordering;
 use std::fmt::Display;
 use std::ops::Deref;
 
 use anyhow::bail;
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::ec::EcPoint;
+use openssl::ecdsa::EcdsaSig;
+use openssl::hash::{self, MessageDigest};
 use openssl::pkey::{PKey, Private, Public};
 use openssl::sign::{Signer, Verifier};
 
 use crate::jwk::{
     alg::ec::{EcCurve, EcKeyPair},
-    Jwk,
+    Jwk, JwkCurve, KeyOperation,
 };
 use crate::jws::{JwsAlgorithm, JwsSigner, JwsVerifier};
-use crate::util::der::{DerBuilder, DerReader, DerType};
 use crate::util::{self, HashAlgorithm};
 use crate::{JoseError, Value};
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[non_exhaustive]
 pub enum EcdsaJwsAlgorithm {
     /// ECDSA using P-256 and SHA-256
     Es256,
@@ -24,6 +29,15 @@ pub enum EcdsaJwsAlgorithm {
     Es512,
     /// ECDSA using secp256k1 curve and SHA-256
     Es256k,
+    /// ECDSA using brainpoolP256r1 curve and SHA-256
+    #[cfg(feature = "brainpool")]
+    Esb256,
+    /// ECDSA using brainpoolP384r1 curve and SHA-384
+    #[cfg(feature = "brainpool")]
+    Esb384,
+    /// ECDSA using brainpoolP512r1 curve and SHA-512
+    #[cfg(feature = "brainpool")]
+    Esb512,
 }
 
 impl EcdsaJwsAlgorithm {
@@ -65,11 +79,25 @@ impl EcdsaJwsAlgorithm {
     /// # Arguments
     /// * `input` - A private key that is a DER encoded PKCS#8 PrivateKeyInfo or ECPrivateKey.
     pub fn signer_from_der(&self, input: impl AsRef<[u8]>) -> Result<EcdsaJwsSigner, JoseError> {
+        #[cfg(feature = "k256")]
+        if self.use_k256() {
+            let private_key = k256_backend::signing_key_from_der(input.as_ref())
+                .map_err(JoseError::InvalidKeyFormat)?;
+            return Ok(EcdsaJwsSigner {
+                algorithm: *self,
+                private_key: EcdsaPrivateKey::K256(private_key),
+                key_id: None,
+                deterministic: false,
+            });
+        }
+        self.check_curve_support()?;
+
         let key_pair = self.key_pair_from_der(input.as_ref())?;
         Ok(EcdsaJwsSigner {
             algorithm: self.clone(),
-            private_key: key_pair.into_private_key(),
+            private_key: EcdsaPrivateKey::Openssl(key_pair.into_private_key()),
             key_id: None,
+            deterministic: false,
         })
     }
 
@@ -84,14 +112,45 @@ impl EcdsaJwsAlgorithm {
     /// # Arguments
     /// * `input` - A private key of common or traditinal PEM format.
     pub fn signer_from_pem(&self, input: impl AsRef<[u8]>) -> Result<EcdsaJwsSigner, JoseError> {
+        #[cfg(feature = "k256")]
+        if self.use_k256() {
+            let private_key = k256_backend::signing_key_from_pem(input.as_ref())
+                .map_err(JoseError::InvalidKeyFormat)?;
+            return Ok(EcdsaJwsSigner {
+                algorithm: *self,
+                private_key: EcdsaPrivateKey::K256(private_key),
+                key_id: None,
+                deterministic: false,
+            });
+        }
+        self.check_curve_support()?;
+
         let key_pair = self.key_pair_from_pem(input.as_ref())?;
         Ok(EcdsaJwsSigner {
             algorithm: self.clone(),
-            private_key: key_pair.into_private_key(),
+            private_key: EcdsaPrivateKey::Openssl(key_pair.into_private_key()),
             key_id: None,
+            deterministic: false,
         })
     }
 
+    /// Return a signer from a private key that is formatted by a JWK of EC type
+    /// after optionally validating the consistency of the key parameters.
+    ///
+    /// # Arguments
+    /// * `jwk` - A private key that is formatted by a JWK of EC type.
+    /// * `validate` - true if the key is validated by `Jwk::validate_consistency`.
+    pub fn signer_from_jwk_with_validation(
+        &self,
+        jwk: &Jwk,
+        validate: bool,
+    ) -> Result<EcdsaJwsSigner, JoseError> {
+        if validate {
+            jwk.validate_consistency()?;
+        }
+        self.signer_from_jwk(jwk)
+    }
+
     /// Return a signer from a private key that is formatted by a JWK of EC type.
     ///
     /// # Arguments
@@ -103,7 +162,7 @@ impl EcdsaJwsAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be sig: {}", val),
             }
-            if !jwk.is_for_key_operation("sign") {
+            if !jwk.is_for_key_operation(&KeyOperation::Sign) {
                 bail!("A parameter key_ops must contains sign.");
             }
             match jwk.algorithm() {
@@ -112,19 +171,31 @@ impl EcdsaJwsAlgorithm {
                 None => {}
             }
             match jwk.curve() {
-                Some(val) if val == self.curve().name() => {}
+                Some(JwkCurve::Ec(val)) if val == self.curve() => {}
                 Some(val) => bail!("A parameter crv must be {} but {}", self.name(), val),
                 None => bail!("A parameter crv is required."),
             }
 
+            #[cfg(feature = "k256")]
+            if self.use_k256() {
+                return Ok(EcdsaJwsSigner {
+                    algorithm: *self,
+                    private_key: EcdsaPrivateKey::K256(k256_backend::signing_key_from_jwk(jwk)?),
+                    key_id: jwk.key_id().map(|val| val.to_string()),
+                    deterministic: false,
+                });
+            }
+            self.check_curve_support()?;
+
             let key_pair = EcKeyPair::from_jwk(jwk)?;
             let private_key = key_pair.into_private_key();
             let key_id = jwk.key_id().map(|val| val.to_string());
 
             Ok(EcdsaJwsSigner {
                 algorithm: self.clone(),
-                private_key,
+                private_key: EcdsaPrivateKey::Openssl(private_key),
                 key_id,
+                deterministic: false,
             })
         })()
         .map_err(|err| JoseError::InvalidKeyFormat(err))
@@ -139,6 +210,18 @@ impl EcdsaJwsAlgorithm {
         input: impl AsRef<[u8]>,
     ) -> Result<EcdsaJwsVerifier, JoseError> {
         (|| -> anyhow::Result<EcdsaJwsVerifier> {
+            #[cfg(feature = "k256")]
+            if self.use_k256() {
+                return Ok(EcdsaJwsVerifier {
+                    algorithm: *self,
+                    public_key: EcdsaPublicKey::K256(k256_backend::verifying_key_from_der(
+                        input.as_ref(),
+                    )?),
+                    key_id: None,
+                });
+            }
+            self.check_curve_support()?;
+
             let spki_der = match EcKeyPair::detect_pkcs8(input.as_ref(), true) {
                 Some(curve) if curve == self.curve() => input.as_ref(),
                 Some(curve) => bail!("The curve is mismatched: {}", curve),
@@ -151,7 +234,7 @@ impl EcdsaJwsAlgorithm {
 
             Ok(EcdsaJwsVerifier {
                 algorithm: self.clone(),
-                public_key,
+                public_key: EcdsaPublicKey::Openssl(public_key),
                 key_id: None,
             })
         })()
@@ -170,6 +253,18 @@ impl EcdsaJwsAlgorithm {
         input: impl AsRef<[u8]>,
     ) -> Result<EcdsaJwsVerifier, JoseError> {
         (|| -> anyhow::Result<EcdsaJwsVerifier> {
+            #[cfg(feature = "k256")]
+            if self.use_k256() {
+                return Ok(EcdsaJwsVerifier {
+                    algorithm: *self,
+                    public_key: EcdsaPublicKey::K256(k256_backend::verifying_key_from_pem(
+                        input.as_ref(),
+                    )?),
+                    key_id: None,
+                });
+            }
+            self.check_curve_support()?;
+
             let (alg, data) = util::parse_pem(input.as_ref())?;
 
             let spki = match alg.as_str() {
@@ -186,7 +281,7 @@ impl EcdsaJwsAlgorithm {
 
             Ok(EcdsaJwsVerifier {
                 algorithm: self.clone(),
-                public_key,
+                public_key: EcdsaPublicKey::Openssl(public_key),
                 key_id: None,
             })
         })()
@@ -210,7 +305,7 @@ impl EcdsaJwsAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be sig: {}", val),
             }
-            if !jwk.is_for_key_operation("verify") {
+            if !jwk.is_for_key_operation(&KeyOperation::Verify) {
                 bail!("A parameter key_ops must contains verify.");
             }
             match jwk.algorithm() {
@@ -237,39 +332,116 @@ impl EcdsaJwsAlgorithm {
                 None => bail!("A parameter y is required."),
             };
 
-            let mut vec = Vec::with_capacity(1 + x.len() + y.len());
-            vec.push(0x04);
-            vec.extend_from_slice(&x);
-            vec.extend_from_slice(&y);
+            EcKeyPair::check_coordinate_len(curve, &x, &y)?;
+
+            #[cfg(feature = "k256")]
+            if self.use_k256() {
+                let mut vec = Vec::with_capacity(1 + x.len() + y.len());
+                vec.push(0x04);
+                vec.extend_from_slice(&x);
+                vec.extend_from_slice(&y);
+
+                let public_key = match k256::ecdsa::VerifyingKey::from_sec1_bytes(&vec) {
+                    Ok(val) => val,
+                    Err(_) => bail!("The point (x, y) is not on the curve: {}", curve),
+                };
+                return Ok(EcdsaJwsVerifier {
+                    algorithm: *self,
+                    public_key: EcdsaPublicKey::K256(public_key),
+                    key_id: jwk.key_id().map(|val| val.to_string()),
+                });
+            }
+            self.check_curve_support()?;
 
-            let pkcs8 = EcKeyPair::to_pkcs8(&vec, true, self.curve());
-            let public_key = PKey::public_key_from_der(&pkcs8)?;
+            let ec_key = EcKeyPair::public_key_from_components(curve, &x, &y)?;
+            let public_key = PKey::from_ec_key(ec_key)?;
             let key_id = jwk.key_id().map(|val| val.to_string());
 
             Ok(EcdsaJwsVerifier {
                 algorithm: self.clone(),
-                public_key,
+                public_key: EcdsaPublicKey::Openssl(public_key),
                 key_id,
             })
         })()
         .map_err(|err| JoseError::InvalidKeyFormat(err))
     }
 
+    /// Return a verifier from a public key that is a SEC1 encoded elliptic curve point.
+    ///
+    /// # Arguments
+    /// * `point` - A public key that is a SEC1 uncompressed point (0x04 || X || Y)
+    ///   or compressed point (0x02 or 0x03 || X).
+    pub fn verifier_from_sec1(&self, point: &[u8]) -> Result<EcdsaJwsVerifier, JoseError> {
+        (|| -> anyhow::Result<EcdsaJwsVerifier> {
+            #[cfg(feature = "k256")]
+            if self.use_k256() {
+                return Ok(EcdsaJwsVerifier {
+                    algorithm: *self,
+                    public_key: EcdsaPublicKey::K256(k256::ecdsa::VerifyingKey::from_sec1_bytes(
+                        point,
+                    )?),
+                    key_id: None,
+                });
+            }
+            self.check_curve_support()?;
+
+            let ec_key = EcKeyPair::public_key_from_sec1(point, self.curve())?;
+            let public_key = PKey::from_ec_key(ec_key)?;
+
+            Ok(EcdsaJwsVerifier {
+                algorithm: *self,
+                public_key: EcdsaPublicKey::Openssl(public_key),
+                key_id: None,
+            })
+        })()
+        .map_err(JoseError::InvalidKeyFormat)
+    }
+
+    /// Return true if the algorithm can be used with the current OpenSSL and cargo features.
+    pub(crate) fn is_available(&self) -> bool {
+        cfg!(feature = "k256") && *self == Self::Es256k || self.curve().is_openssl_supported()
+    }
+
+    /// Return true if ES256K must be backed by the k256 crate because OpenSSL lacks secp256k1.
+    #[cfg(feature = "k256")]
+    fn use_k256(&self) -> bool {
+        *self == Self::Es256k && !self.curve().is_openssl_supported()
+    }
+
+    fn check_curve_support(&self) -> Result<(), JoseError> {
+        let curve = self.curve();
+        if !curve.is_openssl_supported() {
+            let hint = match self {
+                Self::Es256k if !cfg!(feature = "k256") => " Enable the k256 feature to use it.",
+                _ => "",
+            };
+            return Err(JoseError::UnsupportedSignatureAlgorithm(anyhow::anyhow!(
+                "The OpenSSL doesn't support the {} curve for {}.{}",
+                curve,
+                self.name(),
+                hint
+            )));
+        }
+        Ok(())
+    }
+
     fn curve(&self) -> EcCurve {
         match self {
             Self::Es256 => EcCurve::P256,
             Self::Es384 => EcCurve::P384,
             Self::Es512 => EcCurve::P521,
             Self::Es256k => EcCurve::Secp256k1,
+            #[cfg(feature = "brainpool")]
+            Self::Esb256 => EcCurve::Bp256r1,
+            #[cfg(feature = "brainpool")]
+            Self::Esb384 => EcCurve::Bp384r1,
+            #[cfg(feature = "brainpool")]
+            Self::Esb512 => EcCurve::Bp512r1,
         }
     }
 
     fn signature_len(&self) -> usize {
-        match self {
-            Self::Es256 | Self::Es256k => 64,
-            Self::Es384 => 96,
-            Self::Es512 => 132,
-        }
+        self.curve().coordinate_size() * 2
     }
 
     fn hash_algorithm(&self) -> HashAlgorithm {
@@ -278,6 +450,12 @@ impl EcdsaJwsAlgorithm {
             Self::Es384 => HashAlgorithm::Sha384,
             Self::Es512 => HashAlgorithm::Sha512,
             Self::Es256k => HashAlgorithm::Sha256,
+            #[cfg(feature = "brainpool")]
+            Self::Esb256 => HashAlgorithm::Sha256,
+            #[cfg(feature = "brainpool")]
+            Self::Esb384 => HashAlgorithm::Sha384,
+            #[cfg(feature = "brainpool")]
+            Self::Esb512 => HashAlgorithm::Sha512,
         }
     }
 }
@@ -289,6 +467,12 @@ impl JwsAlgorithm for EcdsaJwsAlgorithm {
             Self::Es384 => "ES384",
             Self::Es512 => "ES512",
             Self::Es256k => "ES256K",
+            #[cfg(feature = "brainpool")]
+            Self::Esb256 => "ESB256",
+            #[cfg(feature = "brainpool")]
+            Self::Esb384 => "ESB384",
+            #[cfg(feature = "brainpool")]
+            Self::Esb512 => "ESB512",
         }
     }
 
@@ -311,14 +495,34 @@ impl Deref for EcdsaJwsAlgorithm {
     }
 }
 
+#[derive(Debug, Clone)]
+enum EcdsaPrivateKey {
+    Openssl(PKey<Private>),
+    #[cfg(feature = "k256")]
+    K256(k256::ecdsa::SigningKey),
+}
+
+#[derive(Debug, Clone)]
+enum EcdsaPublicKey {
+    Openssl(PKey<Public>),
+    #[cfg(feature = "k256")]
+    K256(k256::ecdsa::VerifyingKey),
+}
+
 #[derive(Debug, Clone)]
 pub struct EcdsaJwsSigner {
     algorithm: EcdsaJwsAlgorithm,
-    private_key: PKey<Private>,
+    private_key: EcdsaPrivateKey,
     key_id: Option<String>,
+    deterministic: bool,
 }
 
 impl EcdsaJwsSigner {
+    /// Return the curve of the key.
+    pub fn curve(&self) -> EcCurve {
+        self.algorithm.curve()
+    }
+
     pub fn set_key_id(&mut self, value: impl Into<String>) {
         self.key_id = Some(value.into());
     }
@@ -326,6 +530,112 @@ impl EcdsaJwsSigner {
     pub fn remove_key_id(&mut self) {
         self.key_id = None;
     }
+
+    /// Set whether the nonce is derived deterministically from the message and the private key
+    /// as specified in RFC 6979 instead of the random number generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - true if the signature must be deterministic.
+    pub fn set_deterministic(&mut self, value: bool) {
+        self.deterministic = value;
+    }
+
+    /// Return whether the nonce is derived deterministically as specified in RFC 6979.
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    fn sign_deterministic(
+        &self,
+        private_key: &PKey<Private>,
+        message: &[u8],
+    ) -> anyhow::Result<(BigNum, BigNum)> {
+        let md = self.algorithm.hash_algorithm().message_digest();
+        let ec_key = private_key.ec_key()?;
+        let group = ec_key.group();
+        let mut ctx = BigNumContext::new()?;
+
+        let mut order = BigNum::new()?;
+        group.order(&mut order, &mut ctx)?;
+        let qlen = order.num_bits() as usize;
+        let rlen = util::ceiling(qlen, 8);
+
+        let mut private_key = ec_key.private_key().to_owned()?;
+        private_key.set_const_time();
+
+        let h1 = hash::hash(md, message)?;
+        let e = Self::bits_to_int(&h1, qlen)?;
+        let mut h1_reduced = BigNum::new()?;
+        h1_reduced.nnmod(&e, &order, &mut ctx)?;
+
+        let x = util::num_to_vec(&private_key, rlen);
+        let h1_octets = util::num_to_vec(&h1_reduced, rlen);
+
+        let mut v = vec![0x01; md.size()];
+        let mut k = vec![0x00; md.size()];
+        k = Self::hmac(md, &k, &[&v, &[0x00], &x, &h1_octets])?;
+        v = Self::hmac(md, &k, &[&v])?;
+        k = Self::hmac(md, &k, &[&v, &[0x01], &x, &h1_octets])?;
+        v = Self::hmac(md, &k, &[&v])?;
+
+        loop {
+            let mut t = Vec::with_capacity(rlen);
+            while t.len() < rlen {
+                v = Self::hmac(md, &k, &[&v])?;
+                t.extend_from_slice(&v);
+            }
+
+            let mut nonce = Self::bits_to_int(&t, qlen)?;
+            nonce.set_const_time();
+            if nonce.num_bits() > 0 && nonce.ucmp(&order) == Ordering::Less {
+                let mut point = EcPoint::new(group)?;
+                point.mul_generator2(group, &nonce, &mut ctx)?;
+                let mut px = BigNum::new()?;
+                let mut py = BigNum::new()?;
+                point.affine_coordinates(group, &mut px, &mut py, &mut ctx)?;
+
+                let mut r = BigNum::new()?;
+                r.nnmod(&px, &order, &mut ctx)?;
+                if r.num_bits() > 0 {
+                    let mut nonce_inv = BigNum::new()?;
+                    nonce_inv.mod_inverse(&nonce, &order, &mut ctx)?;
+                    let mut rd = BigNum::new()?;
+                    rd.mod_mul(&r, &private_key, &order, &mut ctx)?;
+                    let mut sum = BigNum::new()?;
+                    sum.mod_add(&e, &rd, &order, &mut ctx)?;
+                    let mut s = BigNum::new()?;
+                    s.mod_mul(&nonce_inv, &sum, &order, &mut ctx)?;
+                    if s.num_bits() > 0 {
+                        return Ok((r, s));
+                    }
+                }
+            }
+
+            k = Self::hmac(md, &k, &[&v, &[0x00]])?;
+            v = Self::hmac(md, &k, &[&v])?;
+        }
+    }
+
+    fn bits_to_int(input: &[u8], qlen: usize) -> anyhow::Result<BigNum> {
+        let mut num = BigNum::from_slice(input)?;
+        let blen = input.len() * 8;
+        if blen > qlen {
+            let tmp = num;
+            num = BigNum::new()?;
+            num.rshift(&tmp, (blen - qlen) as i32)?;
+        }
+        Ok(num)
+    }
+
+    fn hmac(md: MessageDigest, key: &[u8], inputs: &[&[u8]]) -> anyhow::Result<Vec<u8>> {
+        let pkey = PKey::hmac(key)?;
+        let mut signer = Signer::new(md, &pkey)?;
+        for input in inputs {
+            signer.update(input)?;
+        }
+        Ok(signer.sign_to_vec()?)
+    }
 }
 
 impl JwsSigner for EcdsaJwsSigner {
@@ -346,32 +656,39 @@ impl JwsSigner for EcdsaJwsSigner {
 
     fn sign(&self, message: &[u8]) -> Result<Vec<u8>, JoseError> {
         (|| -> anyhow::Result<Vec<u8>> {
-            let md = self.algorithm.hash_algorithm().message_digest();
+            #[allow(clippy::infallible_destructuring_match)]
+            let private_key = match &self.private_key {
+                EcdsaPrivateKey::Openssl(val) => val,
+                #[cfg(feature = "k256")]
+                EcdsaPrivateKey::K256(val) => {
+                    return k256_backend::sign(val, message, self.deterministic)
+                }
+            };
 
-            let mut signer = Signer::new(md, &self.private_key)?;
-            signer.update(message)?;
-            let der_signature = signer.sign_to_vec()?;
+            let (r, s) = if self.deterministic {
+                self.sign_deterministic(private_key, message)?
+            } else {
+                let md = self.algorithm.hash_algorithm().message_digest();
 
+                let mut signer = Signer::new(md, private_key)?;
+                signer.update(message)?;
+                let der_signature = signer.sign_to_vec()?;
+
+                let ecdsa_sig = EcdsaSig::from_der(&der_signature)?;
+                (ecdsa_sig.r().to_owned()?, ecdsa_sig.s().to_owned()?)
+            };
+
+            // r and s are padded to the coordinate size of the curve respectively.
             let signature_len = self.signature_len();
             let sep = signature_len / 2;
 
             let mut signature = Vec::with_capacity(signature_len);
-            let mut reader = DerReader::from_bytes(&der_signature);
-            match reader.next()? {
-                Some(DerType::Sequence) => {}
-                _ => unreachable!("A generated signature is invalid."),
-            }
-            match reader.next()? {
-                Some(DerType::Integer) => {
-                    signature.extend_from_slice(&reader.to_be_bytes(false, sep));
+            for num in &[r, s] {
+                let vec = util::num_to_vec(num, sep);
+                if vec.len() != sep {
+                    bail!("A generated signature is invalid.");
                 }
-                _ => unreachable!("A generated signature is invalid."),
-            }
-            match reader.next()? {
-                Some(DerType::Integer) => {
-                    signature.extend_from_slice(&reader.to_be_bytes(false, sep));
-                }
-                _ => unreachable!("A generated signature is invalid."),
+                signature.extend_from_slice(&vec);
             }
 
             Ok(signature)
@@ -395,11 +712,16 @@ impl Deref for EcdsaJwsSigner {
 #[derive(Debug, Clone)]
 pub struct EcdsaJwsVerifier {
     algorithm: EcdsaJwsAlgorithm,
-    public_key: PKey<Public>,
+    public_key: EcdsaPublicKey,
     key_id: Option<String>,
 }
 
 impl EcdsaJwsVerifier {
+    /// Return the curve of the key.
+    pub fn curve(&self) -> EcCurve {
+        self.algorithm.curve()
+    }
+
     pub fn set_key_id(&mut self, value: impl Into<String>) {
         self.key_id = Some(value.into());
     }
@@ -432,22 +754,21 @@ impl JwsVerifier for EcdsaJwsVerifier {
                 );
             }
 
-            let mut der_builder = DerBuilder::new();
-            der_builder.begin(DerType::Sequence);
-            {
-                let sep = signature_len / 2;
+            #[allow(clippy::infallible_destructuring_match)]
+            let public_key = match &self.public_key {
+                EcdsaPublicKey::Openssl(val) => val,
+                #[cfg(feature = "k256")]
+                EcdsaPublicKey::K256(val) => return k256_backend::verify(val, message, signature),
+            };
 
-                let zeros = signature[..sep].iter().take_while(|b| **b == 0).count();
-                der_builder.append_integer_from_be_slice(&signature[zeros..sep], true);
-                let zeros = signature[sep..].iter().take_while(|b| **b == 0).count();
-                der_builder.append_integer_from_be_slice(&signature[(sep + zeros)..], true);
-            }
-            der_builder.end();
-            let der_signature = der_builder.build();
+            let sep = signature_len / 2;
+            let r = BigNum::from_slice(&signature[..sep])?;
+            let s = BigNum::from_slice(&signature[sep..])?;
+            let der_signature = EcdsaSig::from_private_components(r, s)?.to_der()?;
 
             let md = self.algorithm.hash_algorithm().message_digest();
 
-            let mut verifier = Verifier::new(md, &self.public_key)?;
+            let mut verifier = Verifier::new(md, public_key)?;
             verifier.update(message)?;
             verifier.verify(&der_signature)?;
             Ok(())
@@ -468,6 +789,112 @@ impl Deref for EcdsaJwsVerifier {
     }
 }
 
+#[cfg(feature = "k256")]
+mod k256_backend {
+    use anyhow::bail;
+    use k256::ecdsa::signature::rand_core::{self, CryptoRng, RngCore};
+    use k256::ecdsa::signature::{RandomizedSigner, Signer, Verifier};
+    use k256::ecdsa::{Signature, SigningKey, VerifyingKey};
+    use k256::pkcs8::{DecodePrivateKey, DecodePublicKey};
+    use k256::SecretKey;
+    use openssl::rand;
+
+    use crate::jwk::Jwk;
+    use crate::util;
+    use crate::Value;
+
+    pub(super) fn signing_key_from_der(input: &[u8]) -> anyhow::Result<SigningKey> {
+        let secret_key = match SecretKey::from_pkcs8_der(input) {
+            Ok(val) => val,
+            Err(_) => SecretKey::from_sec1_der(input)?,
+        };
+        Ok(SigningKey::from(secret_key))
+    }
+
+    pub(super) fn signing_key_from_pem(input: &[u8]) -> anyhow::Result<SigningKey> {
+        let (alg, data) = util::parse_pem(input)?;
+        let secret_key = match alg.as_str() {
+            "PRIVATE KEY" => SecretKey::from_pkcs8_der(&data)?,
+            "EC PRIVATE KEY" => SecretKey::from_sec1_der(&data)?,
+            alg => bail!("Inappropriate algorithm: {}", alg),
+        };
+        Ok(SigningKey::from(secret_key))
+    }
+
+    pub(super) fn signing_key_from_jwk(jwk: &Jwk) -> anyhow::Result<SigningKey> {
+        let d = match jwk.parameter("d") {
+            Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
+            Some(_) => bail!("A parameter d must be a string."),
+            None => bail!("A parameter d is required."),
+        };
+        Ok(SigningKey::from_slice(&d)?)
+    }
+
+    pub(super) fn verifying_key_from_der(input: &[u8]) -> anyhow::Result<VerifyingKey> {
+        Ok(VerifyingKey::from_public_key_der(input)?)
+    }
+
+    pub(super) fn verifying_key_from_pem(input: &[u8]) -> anyhow::Result<VerifyingKey> {
+        let (alg, data) = util::parse_pem(input)?;
+        match alg.as_str() {
+            "PUBLIC KEY" => Ok(VerifyingKey::from_public_key_der(&data)?),
+            alg => bail!("Inappropriate algorithm: {}", alg),
+        }
+    }
+
+    pub(super) fn sign(
+        key: &SigningKey,
+        message: &[u8],
+        deterministic: bool,
+    ) -> anyhow::Result<Vec<u8>> {
+        let signature: Signature = if deterministic {
+            key.try_sign(message)?
+        } else {
+            key.try_sign_with_rng(&mut OpensslRng, message)?
+        };
+        Ok(signature.to_bytes().to_vec())
+    }
+
+    /// A random number generator backed by OpenSSL for the randomized signing.
+    struct OpensslRng;
+
+    impl RngCore for OpensslRng {
+        fn next_u32(&mut self) -> u32 {
+            let mut buf = [0; 4];
+            self.fill_bytes(&mut buf);
+            u32::from_le_bytes(buf)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            let mut buf = [0; 8];
+            self.fill_bytes(&mut buf);
+            u64::from_le_bytes(buf)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            rand::rand_bytes(dest).expect("OpenSSL random number generator failed.");
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            rand::rand_bytes(dest).map_err(rand_core::Error::new)
+        }
+    }
+
+    impl CryptoRng for OpensslRng {}
+
+    pub(super) fn verify(
+        key: &VerifyingKey,
+        message: &[u8],
+        signature: &[u8],
+    ) -> anyhow::Result<()> {
+        let signature = Signature::from_slice(signature)?;
+        // k256 rejects a high S value that OpenSSL may produce.
+        let signature = signature.normalize_s().unwrap_or(signature);
+        key.verify(message, &signature)?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -596,18 +1023,8 @@ mod tests {
             EcdsaJwsAlgorithm::Es512,
             EcdsaJwsAlgorithm::Es256k,
         ] {
-            let private_key = load_file(match alg {
-                EcdsaJwsAlgorithm::Es256 => "jwk/EC_P-256_private.jwk",
-                EcdsaJwsAlgorithm::Es384 => "jwk/EC_P-384_private.jwk",
-                EcdsaJwsAlgorithm::Es512 => "jwk/EC_P-521_private.jwk",
-                EcdsaJwsAlgorithm::Es256k => "jwk/EC_secp256k1_private.jwk",
-            })?;
-            let public_key = load_file(match alg {
-                EcdsaJwsAlgorithm::Es256 => "jwk/EC_P-256_public.jwk",
-                EcdsaJwsAlgorithm::Es384 => "jwk/EC_P-384_public.jwk",
-                EcdsaJwsAlgorithm::Es512 => "jwk/EC_P-521_public.jwk",
-                EcdsaJwsAlgorithm::Es256k => "jwk/EC_secp256k1_public.jwk",
-            })?;
+            let private_key = load_file(&format!("jwk/EC_{}_private.jwk", alg.curve()))?;
+            let public_key = load_file(&format!("jwk/EC_{}_public.jwk", alg.curve()))?;
 
             let signer = alg.signer_from_jwk(&Jwk::from_bytes(&private_key)?)?;
             let signature = signer.sign(input)?;
@@ -631,18 +1048,8 @@ mod tests {
         ] {
             println!("{}", alg);
 
-            let private_key = load_file(match alg {
-                EcdsaJwsAlgorithm::Es256 => "pem/EC_P-256_private.pem",
-                EcdsaJwsAlgorithm::Es384 => "pem/EC_P-384_private.pem",
-                EcdsaJwsAlgorithm::Es512 => "pem/EC_P-521_private.pem",
-                EcdsaJwsAlgorithm::Es256k => "pem/EC_secp256k1_private.pem",
-            })?;
-            let public_key = load_file(match alg {
-                EcdsaJwsAlgorithm::Es256 => "pem/EC_P-256_public.pem",
-                EcdsaJwsAlgorithm::Es384 => "pem/EC_P-384_public.pem",
-                EcdsaJwsAlgorithm::Es512 => "pem/EC_P-521_public.pem",
-                EcdsaJwsAlgorithm::Es256k => "pem/EC_secp256k1_public.pem",
-            })?;
+            let private_key = load_file(&format!("pem/EC_{}_private.pem", alg.curve()))?;
+            let public_key = load_file(&format!("pem/EC_{}_public.pem", alg.curve()))?;
 
             let signer = alg.signer_from_pem(&private_key)?;
             let signature = signer.sign(input)?;
@@ -664,18 +1071,8 @@ mod tests {
             EcdsaJwsAlgorithm::Es512,
             EcdsaJwsAlgorithm::Es256k,
         ] {
-            let private_key = load_file(match alg {
-                EcdsaJwsAlgorithm::Es256 => "der/EC_P-256_pkcs8_private.der",
-                EcdsaJwsAlgorithm::Es384 => "der/EC_P-384_pkcs8_private.der",
-                EcdsaJwsAlgorithm::Es512 => "der/EC_P-521_pkcs8_private.der",
-                EcdsaJwsAlgorithm::Es256k => "der/EC_secp256k1_pkcs8_private.der",
-            })?;
-            let public_key = load_file(match alg {
-                EcdsaJwsAlgorithm::Es256 => "der/EC_P-256_spki_public.der",
-                EcdsaJwsAlgorithm::Es384 => "der/EC_P-384_spki_public.der",
-                EcdsaJwsAlgorithm::Es512 => "der/EC_P-521_spki_public.der",
-                EcdsaJwsAlgorithm::Es256k => "der/EC_secp256k1_spki_public.der",
-            })?;
+            let private_key = load_file(&format!("der/EC_{}_pkcs8_private.der", alg.curve()))?;
+            let public_key = load_file(&format!("der/EC_{}_spki_public.der", alg.curve()))?;
 
             let signer = alg.signer_from_der(&private_key)?;
             let signature = signer.sign(input)?;
@@ -687,6 +1084,397 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn sign_and_verify_ecdsa_generated_sec1() -> Result<()> {
+        let input = b"abcde12345";
+
+        for alg in &[
+            EcdsaJwsAlgorithm::Es256,
+            EcdsaJwsAlgorithm::Es384,
+            EcdsaJwsAlgorithm::Es512,
+            EcdsaJwsAlgorithm::Es256k,
+        ] {
+            let key_pair = alg.generate_key_pair()?;
+
+            let signer = alg.signer_from_der(key_pair.to_der_private_key())?;
+            let signature = signer.sign(input)?;
+
+            let verifier = alg.verifier_from_sec1(&key_pair.to_sec1_public_key())?;
+            verifier.verify(input, &signature)?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn sign_and_verify_ecdsa_generated_compressed_sec1() -> Result<()> {
+        let input = b"abcde12345";
+
+        for alg in &[
+            EcdsaJwsAlgorithm::Es256,
+            EcdsaJwsAlgorithm::Es384,
+            EcdsaJwsAlgorithm::Es512,
+            EcdsaJwsAlgorithm::Es256k,
+        ] {
+            let key_pair = alg.generate_key_pair()?;
+
+            let signer = alg.signer_from_der(key_pair.to_der_private_key())?;
+            let signature = signer.sign(input)?;
+
+            let mut point = key_pair.to_sec1_public_key();
+            let coordinate_size = (point.len() - 1) / 2;
+            point[0] = 0x02 | (point[point.len() - 1] & 0x01);
+            point.truncate(1 + coordinate_size);
+
+            let verifier = alg.verifier_from_sec1(&point)?;
+            verifier.verify(input, &signature)?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn sign_and_verify_ecdsa_deterministic() -> Result<()> {
+        let input = b"abcde12345";
+
+        for alg in &[
+            EcdsaJwsAlgorithm::Es256,
+            EcdsaJwsAlgorithm::Es384,
+            EcdsaJwsAlgorithm::Es512,
+            EcdsaJwsAlgorithm::Es256k,
+        ] {
+            let key_pair = alg.generate_key_pair()?;
+
+            let mut signer = alg.signer_from_der(key_pair.to_der_private_key())?;
+            signer.set_deterministic(true);
+            let signature1 = signer.sign(input)?;
+            let signature2 = signer.sign(input)?;
+            assert_eq!(signature1, signature2);
+            assert_eq!(signature1.len(), signer.signature_len());
+
+            let verifier = alg.verifier_from_der(key_pair.to_der_public_key())?;
+            verifier.verify(input, &signature1)?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn sign_ecdsa_deterministic_rfc6979_p256_sha256() -> Result<()> {
+        // RFC 6979 A.2.5. ECDSA, 256 Bits (Prime Field)
+        let mut jwk = Jwk::new("EC");
+        jwk.set_parameter("crv", Some(Value::String("P-256".to_string())))?;
+        for (name, val) in &[
+            (
+                "d",
+                "C9AFA9D845BA75166B5C215767B1D6934E50C3DB36E89B127B8A622B120F6721",
+            ),
+            (
+                "x",
+                "60FED4BA255A9D31C961EB74C6356D68C049B8923B61FA6CE669622E60F29FB6",
+            ),
+            (
+                "y",
+                "7903FE1008B8BC99A41AE9E95628BC64F2F1B20C2D7E9F5177A3C294D4462299",
+            ),
+        ] {
+            let val = base64::encode_config(from_hex(val), base64::URL_SAFE_NO_PAD);
+            jwk.set_parameter(name, Some(Value::String(val)))?;
+        }
+
+        let alg = EcdsaJwsAlgorithm::Es256;
+        let mut signer = alg.signer_from_jwk(&jwk)?;
+        signer.set_deterministic(true);
+        let verifier = alg.verifier_from_jwk(&jwk.to_public_key()?)?;
+
+        for (message, r, s) in &[
+            (
+                "sample",
+                "EFD48B2AACB6A8FD1140DD9CD45E81D69D2C877B56AAF991C34D0EA84EAF3716",
+                "F7CB1C942D657C41D436C7A1B6E29F65F3E900DBB9AFF4064DC4AB2F843ACDA8",
+            ),
+            (
+                "test",
+                "F1ABB023518351CD71D881567B1EA663ED3EFCF6C5132B354F28D3B0B7D38367",
+                "019F4113742A2B14BD25926B49C649155F267E60D3814B4C0CC84250E46F0083",
+            ),
+        ] {
+            let mut expected = from_hex(r);
+            expected.extend_from_slice(&from_hex(s));
+
+            let signature = signer.sign(message.as_bytes())?;
+            assert_eq!(signature, expected);
+            verifier.verify(message.as_bytes(), &signature)?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn verifier_from_invalid_sec1() -> Result<()> {
+        let alg = EcdsaJwsAlgorithm::Es256;
+        let point = alg.generate_key_pair()?.to_sec1_public_key();
+
+        assert!(alg.verifier_from_sec1(&[]).is_err());
+        assert!(alg.verifier_from_sec1(&point[..64]).is_err());
+        assert!(EcdsaJwsAlgorithm::Es384.verifier_from_sec1(&point).is_err());
+
+        let mut invalid_prefix = point.clone();
+        invalid_prefix[0] = 0x05;
+        assert!(alg.verifier_from_sec1(&invalid_prefix).is_err());
+
+        let mut not_on_curve = point.clone();
+        not_on_curve[64] ^= 0x01;
+        assert!(alg.verifier_from_sec1(&not_on_curve).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn verifier_from_invalid_jwk_point() -> Result<()> {
+        let alg = EcdsaJwsAlgorithm::Es256;
+
+        let jwk = Jwk::from_bytes(&load_file("jwk/EC_P-256_public_short_x.jwk")?)?;
+        match alg.verifier_from_jwk(&jwk) {
+            Err(JoseError::InvalidKeyFormat(err)) => {
+                assert!(err.to_string().contains("coordinate length"))
+            }
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+
+        let jwk = Jwk::from_bytes(&load_file("jwk/EC_P-256_public_off_curve.jwk")?)?;
+        match alg.verifier_from_jwk(&jwk) {
+            Err(JoseError::InvalidKeyFormat(err)) => {
+                assert!(err.to_string().contains("not on the curve"))
+            }
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn ecdsa_signature_len() -> Result<()> {
+        for (alg, len) in &[
+            (EcdsaJwsAlgorithm::Es256, 64),
+            (EcdsaJwsAlgorithm::Es384, 96),
+            (EcdsaJwsAlgorithm::Es512, 132),
+            (EcdsaJwsAlgorithm::Es256k, 64),
+        ] {
+            assert_eq!(alg.signature_len(), *len);
+            assert_eq!(alg.signature_len(), alg.curve().coordinate_size() * 2);
+
+            let key_pair = alg.generate_key_pair()?;
+            let signer = alg.signer_from_der(key_pair.to_der_private_key())?;
+            assert_eq!(signer.signature_len(), *len);
+            for _ in 0..16 {
+                assert_eq!(signer.sign(b"abcde12345")?.len(), *len);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "brainpool")]
+    #[test]
+    fn sign_and_verify_ecdsa_brainpool() -> Result<()> {
+        let input = b"abcde12345";
+
+        for (alg, name, crv, len) in &[
+            (EcdsaJwsAlgorithm::Esb256, "ESB256", "BP256R1", 64),
+            (EcdsaJwsAlgorithm::Esb384, "ESB384", "BP384R1", 96),
+            (EcdsaJwsAlgorithm::Esb512, "ESB512", "BP512R1", 128),
+        ] {
+            assert_eq!(alg.name(), *name);
+            assert_eq!(alg.curve().name(), *crv);
+            assert_eq!(alg.signature_len(), *len);
+
+            if !alg.is_available() {
+                let err = alg.generate_key_pair().and_then(|key_pair| {
+                    alg.signer_from_der(key_pair.to_der_private_key())
+                        .map(|_| ())
+                });
+                assert!(err.is_err());
+                continue;
+            }
+
+            let key_pair = alg.generate_key_pair()?;
+            let jwk = key_pair.to_jwk_key_pair();
+            assert_eq!(jwk.parameter("crv"), Some(&Value::String(crv.to_string())));
+
+            let signer = alg.signer_from_jwk(&jwk)?;
+            let signature = signer.sign(input)?;
+            assert_eq!(signature.len(), *len);
+
+            let verifier = alg.verifier_from_jwk(&jwk.to_public_key()?)?;
+            verifier.verify(input, &signature)?;
+
+            let signer = alg.signer_from_pem(key_pair.to_pem_private_key())?;
+            let signature = signer.sign(input)?;
+            let verifier = alg.verifier_from_pem(key_pair.to_pem_public_key())?;
+            verifier.verify(input, &signature)?;
+
+            assert!(EcdsaJwsAlgorithm::Es256
+                .verifier_from_jwk(&key_pair.to_jwk_public_key())
+                .is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn sign_and_verify_ecdsa_leading_zero_signature() -> Result<()> {
+        for (alg, message, expected) in &[
+            (
+                EcdsaJwsAlgorithm::Es256,
+                "message-32",
+                "004c400d97cca6f145af9cae1de57035d9632f50b731564d3e32b80eaa5c2260\
+                 9c263b3a459b209b3a872449eb7e45d300fdc64331f492d10a94cc53e9419ae7",
+            ),
+            (
+                EcdsaJwsAlgorithm::Es384,
+                "message-124",
+                "00e804746bcd081efac0f9a28ca061390d2f5ee741bb8554b4bd04647d2bf802\
+                 1d1703980cdb65b4b27416d3e125eee7bf494945961758d46b85525148acfc0d\
+                 9b7f506afd21234a851288cb1e5884a2e2eb9b24730454c1529a6296281abb4a",
+            ),
+            (
+                EcdsaJwsAlgorithm::Es512,
+                "message-283",
+                "0000a30f3f0d4c573e30aec0126fa100ce51e6d077335b6e97035802080614ac\
+                 6b9bb830c3f4bee9026e38ec4e3bf3b163c83dae64de5ce7360590415dca1353\
+                 f4dc0008fec2060ccdb448d0f1de08334186a0c5c80f7592ac1dfb6f8e37bf41\
+                 af2af47695218ec52f223293ff6823a3d5a340bc0c3583f618de795ca42a962d\
+                 e0d28e6c",
+            ),
+            (
+                EcdsaJwsAlgorithm::Es256k,
+                "message-57",
+                "00b03379b5ecb1f60a7bfa01e9da915710ffb4f7a4421a7ee4c75bf4364162aa\
+                 f475c888148b1986f5554983c5fb7ef7e4f2f49a99037a401e5a272791f1faf2",
+            ),
+        ] {
+            let private_key = load_file(&format!("jwk/EC_{}_private.jwk", alg.curve()))?;
+            let public_key = load_file(&format!("jwk/EC_{}_public.jwk", alg.curve()))?;
+            let expected = from_hex(expected);
+            assert_eq!(expected.len(), alg.signature_len());
+
+            let mut signer = alg.signer_from_jwk(&Jwk::from_bytes(&private_key)?)?;
+            signer.set_deterministic(true);
+            assert_eq!(signer.sign(message.as_bytes())?, expected);
+
+            let verifier = alg.verifier_from_jwk(&Jwk::from_bytes(&public_key)?)?;
+            verifier.verify(message.as_bytes(), &expected)?;
+
+            // Leading zero bytes must not be truncated.
+            assert!(verifier.verify(message.as_bytes(), &expected[1..]).is_err());
+            let mut extended = vec![0];
+            extended.extend_from_slice(&expected);
+            assert!(verifier.verify(message.as_bytes(), &extended).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "k256")]
+    #[test]
+    fn sign_and_verify_ecdsa_k256_cross_backend() -> Result<()> {
+        let alg = EcdsaJwsAlgorithm::Es256k;
+
+        let private_key = load_file("jwk/EC_secp256k1_private.jwk")?;
+        let private_key = Jwk::from_bytes(&private_key)?;
+        let public_key = load_file("jwk/EC_secp256k1_public.jwk")?;
+        let public_key = Jwk::from_bytes(&public_key)?;
+
+        let openssl_signer = alg.signer_from_jwk(&private_key)?;
+        let openssl_verifier = alg.verifier_from_jwk(&public_key)?;
+        let k256_signer = EcdsaJwsSigner {
+            algorithm: alg,
+            private_key: EcdsaPrivateKey::K256(k256_backend::signing_key_from_jwk(&private_key)?),
+            key_id: None,
+            deterministic: false,
+        };
+        let mut point = vec![0x04];
+        for name in &["x", "y"] {
+            let val = public_key
+                .parameter(name)
+                .and_then(|val| val.as_str())
+                .unwrap();
+            point.extend(base64::decode_config(val, base64::URL_SAFE_NO_PAD)?);
+        }
+        let k256_verifier = EcdsaJwsVerifier {
+            algorithm: alg,
+            public_key: EcdsaPublicKey::K256(k256::ecdsa::VerifyingKey::from_sec1_bytes(&point)?),
+            key_id: None,
+        };
+
+        for i in 0..32 {
+            let input = format!("message-{}", i);
+
+            let signature = openssl_signer.sign(input.as_bytes())?;
+            assert_eq!(signature.len(), 64);
+            k256_verifier.verify(input.as_bytes(), &signature)?;
+
+            let signature = k256_signer.sign(input.as_bytes())?;
+            assert_eq!(signature.len(), 64);
+            openssl_verifier.verify(input.as_bytes(), &signature)?;
+        }
+
+        // Both backends implement RFC 6979, so the nonce and thus r must match.
+        // k256 normalizes s to the lower half of the order.
+        let input = b"abcde12345";
+        assert_ne!(k256_signer.sign(input)?, k256_signer.sign(input)?);
+
+        let mut openssl_signer = openssl_signer;
+        openssl_signer.set_deterministic(true);
+        let mut k256_signer = k256_signer;
+        k256_signer.set_deterministic(true);
+        let signature1 = openssl_signer.sign(input)?;
+        let signature2 = k256_signer.sign(input)?;
+        assert_eq!(signature1[..32], signature2[..32]);
+        assert_eq!(k256_signer.sign(input)?, signature2);
+        k256_verifier.verify(input, &signature1)?;
+        openssl_verifier.verify(input, &signature2)?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "k256")]
+    #[test]
+    fn load_ecdsa_k256_keys() -> Result<()> {
+        let alg = EcdsaJwsAlgorithm::Es256k;
+        let key_pair = alg.generate_key_pair()?;
+
+        for der in &[key_pair.to_der_private_key(), key_pair.to_raw_private_key()] {
+            let signer = EcdsaJwsSigner {
+                algorithm: alg,
+                private_key: EcdsaPrivateKey::K256(k256_backend::signing_key_from_der(der)?),
+                key_id: None,
+                deterministic: false,
+            };
+            let signature = signer.sign(b"abcde12345")?;
+            alg.verifier_from_der(&key_pair.to_der_public_key())?
+                .verify(b"abcde12345", &signature)?;
+        }
+
+        for pem in &[
+            key_pair.to_pem_private_key(),
+            key_pair.to_traditional_pem_private_key(),
+        ] {
+            k256_backend::signing_key_from_pem(pem)?;
+        }
+        k256_backend::verifying_key_from_der(&key_pair.to_der_public_key())?;
+
+        Ok(())
+    }
+
+    fn from_hex(input: &str) -> Vec<u8> {
+        (0..input.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&input[i..(i + 2)], 16).unwrap())
+            .collect()
+    }
+
     fn load_file(path: &str) -> Result<Vec<u8>> {
         let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         pb.push("data");
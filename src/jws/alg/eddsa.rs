@@ -7,7 +7,7 @@ use openssl::sign::{Signer, Verifier};
 
 use crate::jwk::{
     alg::ed::{EdCurve, EdKeyPair},
-    Jwk,
+    Jwk, JwkCurve, KeyOperation, OkpCurve,
 };
 use crate::jws::{JwsAlgorithm, JwsSigner, JwsVerifier};
 use crate::util;
@@ -56,6 +56,16 @@ impl EddsaJwsAlgorithm {
         Ok(key_pair)
     }
 
+    /// Create a Ed25519 key pair from a raw 32 bytes private key seed.
+    ///
+    /// # Arguments
+    /// * `seed` - A raw private key seed of Ed25519.
+    pub fn key_pair_from_raw_seed(&self, seed: &[u8]) -> Result<EdKeyPair, JoseError> {
+        let mut key_pair = EdKeyPair::from_raw_seed(seed, EdCurve::Ed25519)?;
+        key_pair.set_algorithm(Some(self.name()));
+        Ok(key_pair)
+    }
+
     /// Return a signer from a private key that is a DER encoded PKCS#8 PrivateKeyInfo.
     ///
     /// # Arguments
@@ -90,6 +100,37 @@ impl EddsaJwsAlgorithm {
         })
     }
 
+    /// Return a signer from a Ed25519 raw 32 bytes private key seed.
+    ///
+    /// # Arguments
+    /// * `seed` - A raw private key seed of Ed25519.
+    pub fn signer_from_raw_seed(&self, seed: &[u8]) -> Result<EddsaJwsSigner, JoseError> {
+        let key_pair = self.key_pair_from_raw_seed(seed)?;
+        Ok(EddsaJwsSigner {
+            algorithm: *self,
+            curve: key_pair.curve(),
+            private_key: key_pair.into_private_key(),
+            key_id: None,
+        })
+    }
+
+    /// Return a signer from a private key that is formatted by a JWK of OKP type
+    /// after optionally validating the consistency of the key parameters.
+    ///
+    /// # Arguments
+    /// * `jwk` - A private key that is formatted by a JWK of OKP type.
+    /// * `validate` - true if the key is validated by `Jwk::validate_consistency`.
+    pub fn signer_from_jwk_with_validation(
+        &self,
+        jwk: &Jwk,
+        validate: bool,
+    ) -> Result<EddsaJwsSigner, JoseError> {
+        if validate {
+            jwk.validate_consistency()?;
+        }
+        self.signer_from_jwk(jwk)
+    }
+
     /// Return a signer from a private key that is formatted by a JWK of OKP type.
     ///
     /// # Arguments
@@ -101,7 +142,7 @@ impl EddsaJwsAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be sig: {}", val),
             }
-            if !jwk.is_for_key_operation("sign") {
+            if !jwk.is_for_key_operation(&KeyOperation::Sign) {
                 bail!("A parameter key_ops must contains sign.");
             }
             match jwk.algorithm() {
@@ -199,7 +240,7 @@ impl EddsaJwsAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be sig: {}", val),
             }
-            if !jwk.is_for_key_operation("verify") {
+            if !jwk.is_for_key_operation(&KeyOperation::Verify) {
                 bail!("A parameter key_ops must contains verify.");
             }
             match jwk.algorithm() {
@@ -207,11 +248,9 @@ impl EddsaJwsAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter alg must be {} but {}", self.name(), val),
             }
-            let curve = match jwk.parameter("crv") {
-                Some(Value::String(val)) if val == "Ed25519" => EdCurve::Ed25519,
-                Some(Value::String(val)) if val == "Ed448" => EdCurve::Ed448,
-                Some(Value::String(val)) => bail!("A parameter crv must is invalid: {}", val),
-                Some(_) => bail!("A parameter crv must be a string."),
+            let curve = match jwk.curve() {
+                Some(JwkCurve::Okp(val)) if val.ed_curve().is_some() => val.ed_curve().unwrap(),
+                Some(val) => bail!("A parameter crv must is invalid: {}", val),
                 None => bail!("A parameter crv is required."),
             };
             let x = match jwk.parameter("x") {
@@ -282,10 +321,7 @@ impl JwsSigner for EddsaJwsSigner {
     }
 
     fn signature_len(&self) -> usize {
-        match self.curve {
-            EdCurve::Ed25519 => 64,
-            EdCurve::Ed448 => 114,
-        }
+        OkpCurve::from(self.curve).signature_len().unwrap()
     }
 
     fn key_id(&self) -> Option<&str> {
@@ -507,6 +543,48 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn sign_eddsa_raw_seed_rfc8037() -> Result<()> {
+        // RFC 8037 A.1. Ed25519 Private Key and A.4. Ed25519 Signing
+        let alg = EddsaJwsAlgorithm::Eddsa;
+        let d = "nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A";
+        let x = "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo";
+        let seed = base64::decode_config(d, base64::URL_SAFE_NO_PAD)?;
+
+        let key_pair = alg.key_pair_from_raw_seed(&seed)?;
+        let jwk = key_pair.to_jwk_private_key();
+        assert_eq!(jwk.curve(), Some(JwkCurve::Okp(OkpCurve::Ed25519)));
+        assert_eq!(jwk.parameter("d"), Some(&Value::String(d.to_string())));
+        assert_eq!(jwk.parameter("x"), Some(&Value::String(x.to_string())));
+
+        let input = b"eyJhbGciOiJFZERTQSJ9.RXhhbXBsZSBvZiBFZDI1NTE5IHNpZ25pbmc";
+        let expected = "hgyY0il_MGCjP0JzlnLWG1PPOt7-09PGcvMg3AIbQR6dWbhijcNR4ki4iylGjg5BhVsPt9g7sVvpAr_MuM0KAg";
+
+        let signer = alg.signer_from_raw_seed(&seed)?;
+        let signature = signer.sign(input)?;
+        assert_eq!(
+            base64::encode_config(&signature, base64::URL_SAFE_NO_PAD),
+            expected
+        );
+
+        let verifier = alg.verifier_from_jwk(&key_pair.to_jwk_public_key())?;
+        verifier.verify(input, &signature)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn signer_from_invalid_raw_seed() -> Result<()> {
+        let alg = EddsaJwsAlgorithm::Eddsa;
+
+        for len in &[0, 31, 33, 57, 64] {
+            assert!(alg.signer_from_raw_seed(&vec![0; *len]).is_err());
+            assert!(alg.key_pair_from_raw_seed(&vec![0; *len]).is_err());
+        }
+
+        Ok(())
+    }
+
     fn load_file(path: &str) -> Result<Vec<u8>> {
         let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         pb.push("data");
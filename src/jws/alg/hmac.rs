@@ -2,12 +2,12 @@ use std::fmt::Display;
 use std::ops::Deref;
 
 use anyhow::bail;
-use openssl::pkey::{PKey, Private};
+use openssl::pkey::PKey;
 use openssl::sign::Signer;
 
-use crate::jwk::Jwk;
+use crate::jwk::{Jwk, KeyOperation};
 use crate::jws::{JwsAlgorithm, JwsSigner, JwsVerifier};
-use crate::util::HashAlgorithm;
+use crate::util::{self, HashAlgorithm, Zeroizing};
 use crate::{JoseError, Value};
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
@@ -30,7 +30,7 @@ impl HmacJwsAlgorithm {
 
         let mut jwk = Jwk::new("oct");
         jwk.set_key_use("sig");
-        jwk.set_key_operations(vec!["sign", "verify"]);
+        jwk.set_key_operations(&[KeyOperation::Sign, KeyOperation::Verify]);
         jwk.set_algorithm(self.name());
         jwk.set_parameter("k", Some(Value::String(k))).unwrap();
 
@@ -54,7 +54,7 @@ impl HmacJwsAlgorithm {
                 );
             }
 
-            let private_key = PKey::hmac(input)?;
+            let private_key = input.to_vec();
 
             Ok(HmacJwsSigner {
                 algorithm: self.clone(),
@@ -65,6 +65,19 @@ impl HmacJwsAlgorithm {
         .map_err(|err| JoseError::InvalidKeyFormat(err))
     }
 
+    /// Return a signer from a base64url encoded secret key.
+    ///
+    /// The input is the same form as the `k` parameter of a JWK of oct type,
+    /// so `alg.signer_from_base64(k)` is equivalent to `alg.signer_from_jwk(&jwk)`
+    /// for a JWK `{"kty":"oct","k":k}`. Trailing padding characters are tolerated.
+    ///
+    /// # Arguments
+    /// * `input` - A base64url encoded secret key.
+    pub fn signer_from_base64(&self, input: &str) -> Result<HmacJwsSigner, JoseError> {
+        let key = Zeroizing::new(Self::decode_base64(input)?);
+        self.signer_from_bytes(&*key)
+    }
+
     /// Return a signer from a secret key that is formatted by a JWK of oct type.
     ///
     /// # Arguments
@@ -80,7 +93,7 @@ impl HmacJwsAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be sig: {}", val),
             }
-            if !jwk.is_for_key_operation("sign") {
+            if !jwk.is_for_key_operation(&KeyOperation::Sign) {
                 bail!("A parameter key_ops must contains sign.");
             }
             match jwk.algorithm() {
@@ -89,7 +102,9 @@ impl HmacJwsAlgorithm {
                 Some(val) => bail!("A parameter alg must be {} but {}", self.name(), val),
             }
             let k = match jwk.parameter("k") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
+                Some(Value::String(val)) => {
+                    Zeroizing::new(base64::decode_config(val, base64::URL_SAFE_NO_PAD)?)
+                }
                 Some(val) => bail!("A parameter k must be string type but {:?}", val),
                 None => bail!("A parameter k is required."),
             };
@@ -103,7 +118,7 @@ impl HmacJwsAlgorithm {
                 );
             }
 
-            let private_key = PKey::hmac(&k)?;
+            let private_key = k.to_vec();
             let key_id = jwk.key_id().map(|val| val.to_string());
 
             Ok(HmacJwsSigner {
@@ -135,7 +150,7 @@ impl HmacJwsAlgorithm {
                 );
             }
 
-            let private_key = PKey::hmac(input)?;
+            let private_key = input.to_vec();
 
             Ok(HmacJwsVerifier {
                 algorithm: self.clone(),
@@ -146,6 +161,19 @@ impl HmacJwsAlgorithm {
         .map_err(|err| JoseError::InvalidKeyFormat(err))
     }
 
+    /// Return a verifier from a base64url encoded secret key.
+    ///
+    /// The input is the same form as the `k` parameter of a JWK of oct type,
+    /// so `alg.verifier_from_base64(k)` is equivalent to `alg.verifier_from_jwk(&jwk)`
+    /// for a JWK `{"kty":"oct","k":k}`. Trailing padding characters are tolerated.
+    ///
+    /// # Arguments
+    /// * `input` - A base64url encoded secret key.
+    pub fn verifier_from_base64(&self, input: &str) -> Result<HmacJwsVerifier, JoseError> {
+        let key = Zeroizing::new(Self::decode_base64(input)?);
+        self.verifier_from_bytes(&*key)
+    }
+
     /// Return a verifier from a secret key that is formatted by a JWK of oct type.
     ///
     /// # Arguments
@@ -161,7 +189,7 @@ impl HmacJwsAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be sig: {}", val),
             }
-            if !jwk.is_for_key_operation("verify") {
+            if !jwk.is_for_key_operation(&KeyOperation::Verify) {
                 bail!("A parameter key_ops must contains verify.");
             }
             match jwk.algorithm() {
@@ -171,7 +199,9 @@ impl HmacJwsAlgorithm {
             }
 
             let k = match jwk.parameter("k") {
-                Some(Value::String(val)) => base64::decode_config(val, base64::URL_SAFE_NO_PAD)?,
+                Some(Value::String(val)) => {
+                    Zeroizing::new(base64::decode_config(val, base64::URL_SAFE_NO_PAD)?)
+                }
                 Some(val) => bail!("A parameter k must be string type but {:?}", val),
                 None => bail!("A parameter k is required."),
             };
@@ -185,7 +215,7 @@ impl HmacJwsAlgorithm {
                 );
             }
 
-            let private_key = PKey::hmac(&k)?;
+            let private_key = k.to_vec();
             let key_id = jwk.key_id().map(|val| val.to_string());
 
             Ok(HmacJwsVerifier {
@@ -197,6 +227,12 @@ impl HmacJwsAlgorithm {
         .map_err(|err| JoseError::InvalidKeyFormat(err))
     }
 
+    fn decode_base64(input: &str) -> Result<Vec<u8>, JoseError> {
+        let input = input.trim_end_matches('=');
+        base64::decode_config(input, base64::URL_SAFE_NO_PAD)
+            .map_err(|err| JoseError::InvalidKeyFormat(err.into()))
+    }
+
     fn hash_algorithm(&self) -> HashAlgorithm {
         match self {
             Self::Hs256 => HashAlgorithm::Sha256,
@@ -237,7 +273,7 @@ impl Deref for HmacJwsAlgorithm {
 #[derive(Debug, Clone)]
 pub struct HmacJwsSigner {
     algorithm: HmacJwsAlgorithm,
-    private_key: PKey<Private>,
+    private_key: Vec<u8>,
     key_id: Option<String>,
 }
 
@@ -271,7 +307,8 @@ impl JwsSigner for HmacJwsSigner {
         (|| -> anyhow::Result<Vec<u8>> {
             let md = self.algorithm.hash_algorithm().message_digest();
 
-            let mut signer = Signer::new(md, &self.private_key)?;
+            let private_key = PKey::hmac(&self.private_key)?;
+            let mut signer = Signer::new(md, &private_key)?;
             signer.update(message)?;
             let signature = signer.sign_to_vec()?;
             Ok(signature)
@@ -284,6 +321,12 @@ impl JwsSigner for HmacJwsSigner {
     }
 }
 
+impl Drop for HmacJwsSigner {
+    fn drop(&mut self) {
+        util::zeroize(&mut self.private_key);
+    }
+}
+
 impl Deref for HmacJwsSigner {
     type Target = dyn JwsSigner;
 
@@ -295,7 +338,7 @@ impl Deref for HmacJwsSigner {
 #[derive(Debug, Clone)]
 pub struct HmacJwsVerifier {
     algorithm: HmacJwsAlgorithm,
-    private_key: PKey<Private>,
+    private_key: Vec<u8>,
     key_id: Option<String>,
 }
 
@@ -325,7 +368,8 @@ impl JwsVerifier for HmacJwsVerifier {
         (|| -> anyhow::Result<()> {
             let md = self.algorithm.hash_algorithm().message_digest();
 
-            let mut signer = Signer::new(md, &self.private_key)?;
+            let private_key = PKey::hmac(&self.private_key)?;
+            let mut signer = Signer::new(md, &private_key)?;
             signer.update(message)?;
             let new_signature = signer.sign_to_vec()?;
             if new_signature.as_slice() != signature {
@@ -341,6 +385,12 @@ impl JwsVerifier for HmacJwsVerifier {
     }
 }
 
+impl Drop for HmacJwsVerifier {
+    fn drop(&mut self) {
+        util::zeroize(&mut self.private_key);
+    }
+}
+
 impl Deref for HmacJwsVerifier {
     type Target = dyn JwsVerifier;
 
@@ -423,6 +473,51 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn sign_and_verify_hmac_base64() -> Result<()> {
+        let private_key = util::random_bytes(64);
+        let input = b"abcde12345";
+
+        for alg in &[
+            HmacJwsAlgorithm::Hs256,
+            HmacJwsAlgorithm::Hs384,
+            HmacJwsAlgorithm::Hs512,
+        ] {
+            let jwk = alg.to_jwk(&private_key);
+            let k = jwk.parameter("k").and_then(|val| val.as_str()).unwrap();
+
+            let signer = alg.signer_from_base64(k)?;
+            let signature = signer.sign(input)?;
+
+            let verifier = alg.verifier_from_jwk(&jwk)?;
+            verifier.verify(input, &signature)?;
+
+            let padded = base64::encode_config(&private_key, base64::URL_SAFE);
+            let verifier = alg.verifier_from_base64(&padded)?;
+            verifier.verify(input, &signature)?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn hmac_invalid_base64() -> Result<()> {
+        let alg = HmacJwsAlgorithm::Hs256;
+
+        for input in &["not+base64/url", "a", "AAAA"] {
+            assert!(matches!(
+                alg.signer_from_base64(input),
+                Err(JoseError::InvalidKeyFormat(_))
+            ));
+            assert!(matches!(
+                alg.verifier_from_base64(input),
+                Err(JoseError::InvalidKeyFormat(_))
+            ));
+        }
+
+        Ok(())
+    }
+
     fn load_file(path: &str) -> Result<Vec<u8>> {
         let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         pb.push("data");
@@ -5,7 +5,7 @@ use anyhow::bail;
 use openssl::pkey::{PKey, Private, Public};
 use openssl::sign::{Signer, Verifier};
 
-use crate::jwk::{alg::rsa::RsaKeyPair, Jwk};
+use crate::jwk::{alg::rsa::RsaKeyPair, Jwk, KeyOperation};
 use crate::jws::{JwsAlgorithm, JwsSigner, JwsVerifier};
 use crate::util::der::{DerBuilder, DerType};
 use crate::util::{self, HashAlgorithm};
@@ -124,6 +124,23 @@ impl RsassaJwsAlgorithm {
         })
     }
 
+    /// Return a signer from a private key that is formatted by a JWK of RSA type
+    /// after optionally validating the consistency of the key parameters.
+    ///
+    /// # Arguments
+    /// * `jwk` - A private key that is formatted by a JWK of RSA type.
+    /// * `validate` - true if the key is validated by `Jwk::validate_consistency`.
+    pub fn signer_from_jwk_with_validation(
+        &self,
+        jwk: &Jwk,
+        validate: bool,
+    ) -> Result<RsassaJwsSigner, JoseError> {
+        if validate {
+            jwk.validate_consistency()?;
+        }
+        self.signer_from_jwk(jwk)
+    }
+
     /// Return a signer from a private key that is formatted by a JWK of RSA type.
     ///
     /// # Arguments
@@ -135,7 +152,7 @@ impl RsassaJwsAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be sig: {}", val),
             }
-            if !jwk.is_for_key_operation("sign") {
+            if !jwk.is_for_key_operation(&KeyOperation::Sign) {
                 bail!("A parameter key_ops must contains sign.");
             }
             match jwk.algorithm() {
@@ -256,7 +273,7 @@ impl RsassaJwsAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be sig: {}", val),
             }
-            if !jwk.is_for_key_operation("verify") {
+            if !jwk.is_for_key_operation(&KeyOperation::Verify) {
                 bail!("A parameter key_ops must contains verify.");
             }
             match jwk.algorithm() {
@@ -347,6 +364,11 @@ pub struct RsassaJwsSigner {
 }
 
 impl RsassaJwsSigner {
+    /// Return the bit length of the modulus.
+    pub fn key_bits(&self) -> u32 {
+        self.private_key.bits()
+    }
+
     pub fn set_key_id(&mut self, value: impl Into<String>) {
         self.key_id = Some(value.into());
     }
@@ -405,6 +427,11 @@ pub struct RsassaJwsVerifier {
 }
 
 impl RsassaJwsVerifier {
+    /// Return the bit length of the modulus.
+    pub fn key_bits(&self) -> u32 {
+        self.public_key.bits()
+    }
+
     pub fn set_key_id(&mut self, value: impl Into<String>) {
         self.key_id = Some(value.into());
     }
@@ -674,6 +701,121 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn sign_and_verify_rsassa_multi_prime_jwk() -> Result<()> {
+        let input = b"abcde12345";
+
+        let private_key = load_file("jwk/RSA_multi_prime_private.jwk")?;
+        let private_key = Jwk::from_bytes(&private_key)?;
+
+        let alg = RsassaJwsAlgorithm::Rs256;
+        let signer = alg.signer_from_jwk(&private_key)?;
+        let signature = signer.sign(input)?;
+
+        let verifier = alg.verifier_from_jwk(&private_key)?;
+        verifier.verify(input, &signature)?;
+
+        let mut private_key = private_key;
+        private_key.set_parameter("oth", None)?;
+        assert!(alg.signer_from_jwk(&private_key).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn sign_rsassa_jwk_without_crt_parameters() -> Result<()> {
+        let input = b"abcde12345";
+
+        let private_key = load_file("jwk/RSA_private.jwk")?;
+        let private_key = Jwk::from_bytes(&private_key)?;
+
+        for alg in &[
+            RsassaJwsAlgorithm::Rs256,
+            RsassaJwsAlgorithm::Rs384,
+            RsassaJwsAlgorithm::Rs512,
+        ] {
+            let signature = alg.signer_from_jwk(&private_key)?.sign(input)?;
+
+            for keys in &[
+                &["qi"][..],
+                &["dp", "dq", "qi"],
+                &["q", "dq"],
+                &["p", "q", "dp", "dq", "qi"],
+            ] {
+                let mut jwk = private_key.clone();
+                for key in keys.iter() {
+                    jwk.set_parameter(key, None)?;
+                }
+
+                let signer = alg.signer_from_jwk(&jwk)?;
+                assert_eq!(signer.sign(input)?, signature);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn sign_and_verify_rsassa_lenient_pem() -> Result<()> {
+        let input = b"abcde12345";
+        let alg = RsassaJwsAlgorithm::Rs256;
+
+        let public_key = load_file("pem/RSA_2048bit_public.pem")?;
+        let verifier = alg.verifier_from_pem(&public_key)?;
+
+        for path in &[
+            "pem/RSA_2048bit_private_crlf.pem",
+            "pem/RSA_2048bit_private_bag_attributes.pem",
+            "pem/RSA_2048bit_private_with_certificate.pem",
+            "pem/RSA_2048bit_certificate_with_private.pem",
+            "pem/RSA_2048bit_private_windows_ca.pem",
+        ] {
+            let signer = alg.signer_from_pem(&load_file(path)?)?;
+            let signature = signer.sign(input)?;
+            verifier.verify(input, &signature)?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn sign_rsassa_jwk_with_invalid_lone_prime() -> Result<()> {
+        let private_key = load_file("jwk/RSA_private.jwk")?;
+        let private_key = Jwk::from_bytes(&private_key)?;
+
+        for (key, value) in &[("p", "AA"), ("p", "AQ"), ("q", "Aw"), ("q", "Ag")] {
+            let mut jwk = private_key.clone();
+            jwk.set_parameter(if *key == "p" { "q" } else { "p" }, None)?;
+            jwk.set_parameter(key, Some(Value::String(value.to_string())))?;
+
+            assert!(RsassaJwsAlgorithm::Rs256.signer_from_jwk(&jwk).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn sign_rsassa_jwk_with_out_of_range_prime() -> Result<()> {
+        let private_key = load_file("jwk/RSA_private.jwk")?;
+        let private_key = Jwk::from_bytes(&private_key)?;
+
+        let n = private_key.parameter("n").cloned();
+        for value in &[
+            Some(Value::String("AA".to_string())),
+            Some(Value::String("AQ".to_string())),
+            n,
+        ] {
+            for key in &["p", "q"] {
+                let mut jwk = private_key.clone();
+                jwk.set_parameter(key, value.clone())?;
+
+                assert!(RsassaJwsAlgorithm::Rs256.signer_from_jwk(&jwk).is_err());
+            }
+        }
+
+        Ok(())
+    }
+
     fn load_file(path: &str) -> Result<Vec<u8>> {
         let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         pb.push("data");
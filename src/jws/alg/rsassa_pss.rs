@@ -6,7 +6,7 @@ use openssl::pkey::{PKey, Private, Public};
 use openssl::rsa::Rsa;
 use openssl::sign::{Signer, Verifier};
 
-use crate::jwk::{alg::rsa::RsaKeyPair, alg::rsapss::RsaPssKeyPair, Jwk};
+use crate::jwk::{alg::rsa::RsaKeyPair, alg::rsapss::RsaPssKeyPair, Jwk, KeyOperation};
 use crate::jws::{JwsAlgorithm, JwsSigner, JwsVerifier};
 use crate::util::der::{DerBuilder, DerType};
 use crate::util::{self, HashAlgorithm};
@@ -144,6 +144,23 @@ impl RsassaPssJwsAlgorithm {
         })
     }
 
+    /// Return a signer from a private key that is formatted by a JWK of RSA type
+    /// after optionally validating the consistency of the key parameters.
+    ///
+    /// # Arguments
+    /// * `jwk` - A private key that is formatted by a JWK of RSA type.
+    /// * `validate` - true if the key is validated by `Jwk::validate_consistency`.
+    pub fn signer_from_jwk_with_validation(
+        &self,
+        jwk: &Jwk,
+        validate: bool,
+    ) -> Result<RsassaPssJwsSigner, JoseError> {
+        if validate {
+            jwk.validate_consistency()?;
+        }
+        self.signer_from_jwk(jwk)
+    }
+
     /// Return a signer from a private key that is formatted by a JWK of RSA type.
     ///
     /// # Arguments
@@ -155,7 +172,7 @@ impl RsassaPssJwsAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be sig: {}", val),
             }
-            if !jwk.is_for_key_operation("sign") {
+            if !jwk.is_for_key_operation(&KeyOperation::Sign) {
                 bail!("A parameter key_ops must contains sign.");
             }
             match jwk.algorithm() {
@@ -326,7 +343,7 @@ impl RsassaPssJwsAlgorithm {
                 None => {}
                 Some(val) => bail!("A parameter use must be sig: {}", val),
             };
-            if !jwk.is_for_key_operation("verify") {
+            if !jwk.is_for_key_operation(&KeyOperation::Verify) {
                 bail!("A parameter key_ops must contains verify.");
             }
             match jwk.algorithm() {
@@ -430,6 +447,11 @@ pub struct RsassaPssJwsSigner {
 }
 
 impl RsassaPssJwsSigner {
+    /// Return the bit length of the modulus.
+    pub fn key_bits(&self) -> u32 {
+        self.private_key.bits()
+    }
+
     pub fn set_key_id(&mut self, value: impl Into<String>) {
         self.key_id = Some(value.into());
     }
@@ -488,6 +510,11 @@ pub struct RsassaPssJwsVerifier {
 }
 
 impl RsassaPssJwsVerifier {
+    /// Return the bit length of the modulus.
+    pub fn key_bits(&self) -> u32 {
+        self.public_key.bits()
+    }
+
     pub fn set_key_id(&mut self, value: impl Into<String>) {
         self.key_id = Some(value.into());
     }
@@ -749,6 +776,52 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn sign_and_verify_rsassa_pss_multi_prime_jwk() -> Result<()> {
+        let input = b"abcde12345";
+
+        let private_key = load_file("jwk/RSA_multi_prime_private.jwk")?;
+        let private_key = Jwk::from_bytes(&private_key)?;
+
+        let alg = RsassaPssJwsAlgorithm::Ps256;
+        let signer = alg.signer_from_jwk(&private_key)?;
+        let signature = signer.sign(input)?;
+
+        let verifier = alg.verifier_from_jwk(&private_key)?;
+        verifier.verify(input, &signature)?;
+
+        let mut private_key = private_key;
+        private_key.set_parameter("oth", None)?;
+        assert!(alg.signer_from_jwk(&private_key).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn sign_and_verify_rsassa_pss_jwk_without_crt_parameters() -> Result<()> {
+        let input = b"abcde12345";
+
+        let private_key = load_file("jwk/RSA_private.jwk")?;
+        let mut private_key = Jwk::from_bytes(&private_key)?;
+        for key in &["p", "q", "dp", "dq", "qi"] {
+            private_key.set_parameter(key, None)?;
+        }
+
+        for alg in &[
+            RsassaPssJwsAlgorithm::Ps256,
+            RsassaPssJwsAlgorithm::Ps384,
+            RsassaPssJwsAlgorithm::Ps512,
+        ] {
+            let signer = alg.signer_from_jwk(&private_key)?;
+            let signature = signer.sign(input)?;
+
+            let verifier = alg.verifier_from_jwk(&private_key)?;
+            verifier.verify(input, &signature)?;
+        }
+
+        Ok(())
+    }
+
     fn load_file(path: &str) -> Result<Vec<u8>> {
         let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         pb.push("data");
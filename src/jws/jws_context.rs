@@ -10,15 +10,51 @@ use crate::{JoseError, Map, Value};
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct JwsContext {
     acceptable_criticals: BTreeSet<String>,
+    duplicate_keys_rejected: bool,
+    lenient_header_accepted: bool,
 }
 
 impl JwsContext {
     pub fn new() -> Self {
         Self {
             acceptable_criticals: BTreeSet::new(),
+            duplicate_keys_rejected: false,
+            lenient_header_accepted: false,
         }
     }
 
+    /// Test whether a header that has duplicated member names is rejected when deserializing.
+    pub fn is_duplicate_keys_rejected(&self) -> bool {
+        self.duplicate_keys_rejected
+    }
+
+    /// Set whether a header that has duplicated member names is rejected when deserializing.
+    ///
+    /// It also applies to nested objects, and costs an additional scan of the JSON text.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - true if duplicated member names are rejected.
+    pub fn set_duplicate_keys_rejected(&mut self, value: bool) {
+        self.duplicate_keys_rejected = value;
+    }
+
+    /// Test whether a header is deserialized without checking the format of URI header claims
+    /// (jku, x5u, url) and media type header claims (typ, cty).
+    pub fn is_lenient_header_accepted(&self) -> bool {
+        self.lenient_header_accepted
+    }
+
+    /// Set whether a header is deserialized without checking the format of URI header claims
+    /// (jku, x5u, url) and media type header claims (typ, cty), as `JwsHeader::from_map_lenient`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - true if the format of those header claims is not checked.
+    pub fn set_lenient_header_accepted(&mut self, value: bool) {
+        self.lenient_header_accepted = value;
+    }
+
     /// Test a critical header claim name is acceptable.
     ///
     /// # Arguments
@@ -103,14 +139,22 @@ impl JwsContext {
             }
             let header_bytes = serde_json::to_vec(&header)?;
 
-            let mut capacity = 2;
-            capacity += util::ceiling(header_bytes.len() * 4, 3);
-            capacity += if b64 {
-                util::ceiling(payload.len() * 4, 3)
+            let payload_len = if b64 {
+                util::base64_len(payload.len())
             } else {
-                payload.len()
+                Some(payload.len())
+            };
+            let capacity = match [
+                util::base64_len(header_bytes.len()),
+                payload_len,
+                util::base64_len(signer.signature_len()),
+            ]
+            .iter()
+            .try_fold(2usize, |acc, val| acc.checked_add((*val)?))
+            {
+                Some(val) => val,
+                None => bail!("The JWS is too large to be serialized."),
             };
-            capacity += util::ceiling(signer.signature_len() * 4, 3);
 
             let mut message = String::with_capacity(capacity);
             base64::encode_config_buf(header_bytes, base64::URL_SAFE_NO_PAD, &mut message);
@@ -409,7 +453,7 @@ impl JwsContext {
                 .collect();
             if indexies.len() != 2 {
                 bail!(
-                    "The compact serialization form of JWS must be three parts separated by colon."
+                    "The compact serialization form of JWS must be three parts separated by period."
                 );
             }
 
@@ -418,8 +462,9 @@ impl JwsContext {
             let signature = &input[(indexies[1] + 1)..];
 
             let header = base64::decode_config(header, base64::URL_SAFE_NO_PAD)?;
-            let header: Map<String, Value> = serde_json::from_slice(&header)?;
-            let header = JwsHeader::from_map(header)?;
+            let header = util::parse_json_object(&header, self.duplicate_keys_rejected)?;
+            let header = self.header_from_map(header)?;
+            header.check_typed_claims()?;
 
             let verifier = match selector(&header)? {
                 Some(val) => val,
@@ -533,7 +578,7 @@ impl JwsContext {
     {
         (|| -> anyhow::Result<(Vec<u8>, JwsHeader)> {
             let input = input.as_ref();
-            let mut map: Map<String, Value> = serde_json::from_slice(input)?;
+            let mut map = util::parse_json_object(input, self.duplicate_keys_rejected)?;
 
             let payload_b64 = match map.remove("payload") {
                 Some(Value::String(val)) => val,
@@ -571,7 +616,8 @@ impl JwsContext {
                 };
 
                 let protected_vec = base64::decode_config(&protected_b64, base64::URL_SAFE_NO_PAD)?;
-                let protected_map: Map<String, Value> = serde_json::from_slice(&protected_vec)?;
+                let protected_map =
+                    util::parse_json_object(&protected_vec, self.duplicate_keys_rejected)?;
 
                 let mut b64 = true;
                 if let Some(Value::Array(vals)) = protected_map.get("critical") {
@@ -624,7 +670,8 @@ impl JwsContext {
                     None => bail!("The signature field is required."),
                 };
 
-                let merged = JwsHeader::from_map(merged_map)?;
+                let merged = self.header_from_map(merged_map)?;
+                merged.check_typed_claims()?;
                 let verifier = match selector(&merged)? {
                     Some(val) => val,
                     None => continue,
@@ -669,4 +716,12 @@ impl JwsContext {
             Err(err) => JoseError::InvalidJwsFormat(err),
         })
     }
+
+    pub(crate) fn header_from_map(&self, map: Map<String, Value>) -> Result<JwsHeader, JoseError> {
+        if self.lenient_header_accepted {
+            JwsHeader::from_map_lenient(map)
+        } else {
+            JwsHeader::from_map(map)
+        }
+    }
 }
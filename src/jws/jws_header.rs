@@ -1,11 +1,11 @@
 use std::fmt::{Debug, Display};
 use std::ops::Deref;
 
-use anyhow::bail;
+use anyhow::{anyhow, bail};
 
 use crate::jwk::Jwk;
-use crate::util;
-use crate::{JoseError, JoseHeader, Map, Value};
+use crate::util::{self, HashAlgorithm};
+use crate::{ConflictPolicy, JoseError, JoseHeader, Map, Value};
 
 /// Represent JWS header claims
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -25,11 +25,19 @@ impl JwsHeader {
     ///
     /// * `value` - The json style header claims
     pub fn from_bytes(value: &[u8]) -> Result<Self, JoseError> {
-        let claims = (|| -> anyhow::Result<Map<String, Value>> {
-            let claims: Map<String, Value> = serde_json::from_slice(value)?;
-            Ok(claims)
-        })()
-        .map_err(|err| JoseError::InvalidJson(err))?;
+        let claims = util::parse_json_object(value, false).map_err(JoseError::InvalidJson)?;
+
+        let header = Self::from_map(claims)?;
+        Ok(header)
+    }
+
+    /// Return a new header instance from json style header that has no duplicated member names.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The json style header claims
+    pub fn from_bytes_strict(value: &[u8]) -> Result<Self, JoseError> {
+        let claims = util::parse_json_object(value, true).map_err(JoseError::InvalidJson)?;
 
         let header = Self::from_map(claims)?;
         Ok(header)
@@ -37,13 +45,38 @@ impl JwsHeader {
 
     /// Return a new header instance from map.
     ///
+    /// The jku, x5u and url header claims must be absolute URIs, and the typ and cty
+    /// header claims must be media types.
+    ///
     /// # Arguments
     ///
     /// * `map` - JWT header claims.
     pub fn from_map(map: impl Into<Map<String, Value>>) -> Result<Self, JoseError> {
-        let map: Map<String, Value> = map.into();
+        Self::from_map_with(map.into(), ClaimCheck::Standard)
+    }
+
+    /// Return a new header instance from map whose URI header claims (jku, x5u, url) use https.
+    ///
+    /// # Arguments
+    ///
+    /// * `map` - JWT header claims.
+    pub fn from_map_https_only(map: impl Into<Map<String, Value>>) -> Result<Self, JoseError> {
+        Self::from_map_with(map.into(), ClaimCheck::HttpsOnly)
+    }
+
+    /// Return a new header instance from map without checking the format of
+    /// URI header claims (jku, x5u, url) and media type header claims (typ, cty).
+    ///
+    /// # Arguments
+    ///
+    /// * `map` - JWT header claims.
+    pub fn from_map_lenient(map: impl Into<Map<String, Value>>) -> Result<Self, JoseError> {
+        Self::from_map_with(map.into(), ClaimCheck::Lenient)
+    }
+
+    fn from_map_with(map: Map<String, Value>, check: ClaimCheck) -> Result<Self, JoseError> {
         for (key, value) in &map {
-            Self::check_claim(key, value)?;
+            Self::check_claim_with(key, value, check)?;
         }
 
         (|| -> anyhow::Result<()> {
@@ -109,13 +142,22 @@ impl JwsHeader {
     }
 
     /// Return the value for JWK header claim (jwk).
+    ///
+    /// None is also returned if the value is malformed.
     pub fn jwk(&self) -> Option<Jwk> {
+        self.try_jwk().unwrap_or(None)
+    }
+
+    /// Return the value for JWK header claim (jwk).
+    ///
+    /// An error is returned if the value is present but malformed.
+    pub fn try_jwk(&self) -> Result<Option<Jwk>, JoseError> {
         match self.claims.get("jwk") {
-            Some(Value::Object(vals)) => match Jwk::from_map(vals.clone()) {
-                Ok(val) => Some(val),
-                Err(_) => None,
-            },
-            _ => None,
+            Some(Value::Object(vals)) => Ok(Some(Jwk::from_map(vals.clone())?)),
+            Some(_) => Err(JoseError::InvalidJwsFormat(anyhow!(
+                "The JWS jwk header claim must be a JSON object."
+            ))),
+            None => Ok(None),
         }
     }
 
@@ -146,34 +188,50 @@ impl JwsHeader {
         let key = "x5c";
         let mut vec = Vec::with_capacity(values.len());
         for val in values {
+            // RFC 7515 section 4.1.6 uses the standard base64 encoding for x5c.
             vec.push(Value::String(base64::encode_config(
                 val.as_ref(),
-                base64::URL_SAFE_NO_PAD,
+                base64::STANDARD,
             )));
         }
         self.claims.insert(key.to_string(), Value::Array(vec));
     }
 
     /// Return values for a X.509 certificate chain header claim (x5c).
+    ///
+    /// None is also returned if the value is malformed.
     pub fn x509_certificate_chain(&self) -> Option<Vec<Vec<u8>>> {
-        match self.claims.get("x5c") {
-            Some(Value::Array(vals)) => {
-                let mut vec = Vec::with_capacity(vals.len());
-                for val in vals {
-                    match val {
-                        Value::String(val2) => {
-                            match base64::decode_config(val2, base64::URL_SAFE_NO_PAD) {
-                                Ok(val3) => vec.push(val3.clone()),
-                                Err(_) => return None,
-                            }
-                        }
-                        _ => return None,
-                    }
+        self.try_x509_certificate_chain().unwrap_or(None)
+    }
+
+    /// Return values for a X.509 certificate chain header claim (x5c).
+    ///
+    /// An error is returned if the value is present but malformed.
+    pub fn try_x509_certificate_chain(&self) -> Result<Option<Vec<Vec<u8>>>, JoseError> {
+        (|| -> anyhow::Result<Option<Vec<Vec<u8>>>> {
+            let vals = match self.claims.get("x5c") {
+                Some(Value::Array(vals)) => vals,
+                Some(_) => bail!("The JWS x5c header claim must be a array."),
+                None => return Ok(None),
+            };
+
+            let mut vec = Vec::with_capacity(vals.len());
+            for val in vals {
+                let val = match val {
+                    Value::String(val) => val,
+                    _ => bail!("An element of the JWS x5c header claim must be a string."),
+                };
+                // RFC 7515 uses the standard base64 encoding for x5c.
+                match base64::decode_config(val, base64::URL_SAFE_NO_PAD)
+                    .or_else(|_| base64::decode_config(val, base64::STANDARD))
+                {
+                    Ok(val2) => vec.push(val2),
+                    Err(err) => bail!("The JWS x5c header claim must be a base64 string: {}", err),
                 }
-                Some(vec)
             }
-            _ => None,
-        }
+            Ok(Some(vec))
+        })()
+        .map_err(JoseError::InvalidJwsFormat)
     }
 
     /// Set a value for X.509 certificate SHA-1 thumbprint header claim (x5t).
@@ -188,14 +246,17 @@ impl JwsHeader {
     }
 
     /// Return the value for X.509 certificate SHA-1 thumbprint header claim (x5t).
+    ///
+    /// None is also returned if the value is malformed.
     pub fn x509_certificate_sha1_thumbprint(&self) -> Option<Vec<u8>> {
-        match self.claims.get("x5t") {
-            Some(Value::String(val)) => match base64::decode_config(val, base64::URL_SAFE_NO_PAD) {
-                Ok(val2) => Some(val2),
-                Err(_) => None,
-            },
-            _ => None,
-        }
+        self.try_x509_certificate_sha1_thumbprint().unwrap_or(None)
+    }
+
+    /// Return the value for X.509 certificate SHA-1 thumbprint header claim (x5t).
+    ///
+    /// An error is returned if the value is present but malformed.
+    pub fn try_x509_certificate_sha1_thumbprint(&self) -> Result<Option<Vec<u8>>, JoseError> {
+        self.try_base64_claim("x5t")
     }
 
     /// Set a value for a x509 certificate SHA-256 thumbprint header claim (x5t#S256).
@@ -210,14 +271,84 @@ impl JwsHeader {
     }
 
     /// Return the value for X.509 certificate SHA-256 thumbprint header claim (x5t#S256).
+    ///
+    /// None is also returned if the value is malformed.
     pub fn x509_certificate_sha256_thumbprint(&self) -> Option<Vec<u8>> {
-        match self.claims.get("x5t#S256") {
-            Some(Value::String(val)) => match base64::decode_config(val, base64::URL_SAFE_NO_PAD) {
-                Ok(val2) => Some(val2),
-                Err(_) => None,
-            },
-            _ => None,
-        }
+        self.try_x509_certificate_sha256_thumbprint()
+            .unwrap_or(None)
+    }
+
+    /// Return the value for X.509 certificate SHA-256 thumbprint header claim (x5t#S256).
+    ///
+    /// An error is returned if the value is present but malformed.
+    pub fn try_x509_certificate_sha256_thumbprint(&self) -> Result<Option<Vec<u8>>, JoseError> {
+        self.try_base64_claim("x5t#S256")
+    }
+
+    /// Set the SHA-1 thumbprint of a DER encoded X.509 certificate to X.509 certificate
+    /// SHA-1 thumbprint header claim (x5t).
+    ///
+    /// # Arguments
+    ///
+    /// * `cert_der` - A DER encoded X.509 certificate
+    pub fn set_x509_certificate_sha1_thumbprint_from_der(
+        &mut self,
+        cert_der: impl AsRef<[u8]>,
+    ) -> Result<(), JoseError> {
+        let thumbprint = util::x509_thumbprint(cert_der.as_ref(), HashAlgorithm::Sha1)
+            .map_err(JoseError::InvalidJwsFormat)?;
+        self.set_x509_certificate_sha1_thumbprint(thumbprint);
+        Ok(())
+    }
+
+    /// Set the SHA-256 thumbprint of a DER encoded X.509 certificate to X.509 certificate
+    /// SHA-256 thumbprint header claim (x5t#S256).
+    ///
+    /// # Arguments
+    ///
+    /// * `cert_der` - A DER encoded X.509 certificate
+    pub fn set_x509_certificate_sha256_thumbprint_from_der(
+        &mut self,
+        cert_der: impl AsRef<[u8]>,
+    ) -> Result<(), JoseError> {
+        let thumbprint = util::x509_thumbprint(cert_der.as_ref(), HashAlgorithm::Sha256)
+            .map_err(JoseError::InvalidJwsFormat)?;
+        self.set_x509_certificate_sha256_thumbprint(thumbprint);
+        Ok(())
+    }
+
+    /// Set a DER encoded X.509 certificate to X.509 certificate chain header claim (x5c),
+    /// and its thumbprints to x5t and x5t#S256.
+    ///
+    /// # Arguments
+    ///
+    /// * `cert_der` - A DER encoded X.509 certificate
+    pub fn set_x509_from_certificate(
+        &mut self,
+        cert_der: impl AsRef<[u8]>,
+    ) -> Result<(), JoseError> {
+        let cert_der = cert_der.as_ref();
+        self.set_x509_certificate_sha1_thumbprint_from_der(cert_der)?;
+        self.set_x509_certificate_sha256_thumbprint_from_der(cert_der)?;
+        self.set_x509_certificate_chain(&vec![cert_der]);
+        Ok(())
+    }
+
+    /// Test whether x5t and x5t#S256 header claims are the thumbprints of a DER encoded
+    /// X.509 certificate (e.g. the first certificate of x5c).
+    ///
+    /// It returns false when neither header claim is present, and an error when
+    /// a header claim is malformed.
+    ///
+    /// # Arguments
+    ///
+    /// * `cert_der` - A DER encoded X.509 certificate
+    pub fn x509_thumbprint_matches(&self, cert_der: impl AsRef<[u8]>) -> Result<bool, JoseError> {
+        Ok(util::is_x509_thumbprint_match(
+            cert_der.as_ref(),
+            self.try_x509_certificate_sha1_thumbprint()?,
+            self.try_x509_certificate_sha256_thumbprint()?,
+        ))
     }
 
     /// Set a value for key ID header claim (kid).
@@ -256,6 +387,19 @@ impl JwsHeader {
         }
     }
 
+    /// Test whether token type header claim (typ) is the media type as described in
+    /// RFC 7515 section 4.1.9.
+    ///
+    /// # Arguments
+    ///
+    /// * `media_type` - a media type (e.g. "JWT")
+    pub fn is_token_type(&self, media_type: &str) -> bool {
+        match self.token_type() {
+            Some(val) => util::is_same_media_type(val, media_type),
+            None => false,
+        }
+    }
+
     /// Set a value for content type header claim (cty).
     ///
     /// # Arguments
@@ -274,6 +418,19 @@ impl JwsHeader {
         }
     }
 
+    /// Test whether content type header claim (cty) is the media type as described in
+    /// RFC 7515 section 4.1.10.
+    ///
+    /// # Arguments
+    ///
+    /// * `media_type` - a media type (e.g. "JWT")
+    pub fn is_content_type(&self, media_type: &str) -> bool {
+        match self.content_type() {
+            Some(val) => util::is_same_media_type(val, media_type),
+            None => false,
+        }
+    }
+
     /// Set values for critical header claim (crit).
     ///
     /// # Arguments
@@ -289,20 +446,33 @@ impl JwsHeader {
     }
 
     /// Return values for critical header claim (crit).
+    ///
+    /// None is also returned if the value is malformed.
     pub fn critical(&self) -> Option<Vec<&str>> {
-        match self.claims.get("crit") {
-            Some(Value::Array(vals)) => {
-                let mut vec = Vec::with_capacity(vals.len());
-                for val in vals {
-                    match val {
-                        Value::String(val2) => vec.push(val2.as_str()),
-                        _ => return None,
-                    }
+        self.try_critical().unwrap_or(None)
+    }
+
+    /// Return values for critical header claim (crit).
+    ///
+    /// An error is returned if the value is present but malformed.
+    pub fn try_critical(&self) -> Result<Option<Vec<&str>>, JoseError> {
+        (|| -> anyhow::Result<Option<Vec<&str>>> {
+            let vals = match self.claims.get("crit") {
+                Some(Value::Array(vals)) => vals,
+                Some(_) => bail!("The JWS crit header claim must be a array."),
+                None => return Ok(None),
+            };
+
+            let mut vec = Vec::with_capacity(vals.len());
+            for val in vals {
+                match val {
+                    Value::String(val2) => vec.push(val2.as_str()),
+                    _ => bail!("An element of the JWS crit header claim must be a string."),
                 }
-                Some(vec)
             }
-            _ => None,
-        }
+            Ok(Some(vec))
+        })()
+        .map_err(JoseError::InvalidJwsFormat)
     }
 
     /// Set a value for base64url-encode payload header claim (b64).
@@ -315,10 +485,22 @@ impl JwsHeader {
     }
 
     /// Return the value for base64url-encode payload header claim (b64).
+    ///
+    /// None is also returned if the value is malformed.
     pub fn base64url_encode_payload(&self) -> Option<bool> {
+        self.try_base64url_encode_payload().unwrap_or(None)
+    }
+
+    /// Return the value for base64url-encode payload header claim (b64).
+    ///
+    /// An error is returned if the value is present but malformed.
+    pub fn try_base64url_encode_payload(&self) -> Result<Option<bool>, JoseError> {
         match self.claims.get("b64") {
-            Some(Value::Bool(val)) => Some(*val),
-            _ => None,
+            Some(Value::Bool(val)) => Ok(Some(*val)),
+            Some(_) => Err(JoseError::InvalidJwsFormat(anyhow!(
+                "The JWS b64 header claim must be a bool."
+            ))),
+            None => Ok(None),
         }
     }
 
@@ -352,14 +534,17 @@ impl JwsHeader {
     }
 
     /// Return the value for nonce header claim (nonce).
+    ///
+    /// None is also returned if the value is malformed.
     pub fn nonce(&self) -> Option<Vec<u8>> {
-        match self.claims.get("nonce") {
-            Some(Value::String(val)) => match base64::decode_config(val, base64::URL_SAFE_NO_PAD) {
-                Ok(val2) => Some(val2),
-                Err(_) => None,
-            },
-            _ => None,
-        }
+        self.try_nonce().unwrap_or(None)
+    }
+
+    /// Return the value for nonce header claim (nonce).
+    ///
+    /// An error is returned if the value is present but malformed.
+    pub fn try_nonce(&self) -> Result<Option<Vec<u8>>, JoseError> {
+        self.try_base64_claim("nonce")
     }
 
     /// Set a value for header claim of a specified key.
@@ -382,6 +567,55 @@ impl JwsHeader {
         Ok(())
     }
 
+    /// Remove a header claim of a specified key and return the removed value.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - a key name of header claim
+    pub fn remove_claim(&mut self, key: &str) -> Option<Value> {
+        self.claims.remove(key)
+    }
+
+    /// Merge the claims of other header into this header.
+    ///
+    /// Every claim is checked in the same way as `set_claim`, and this header is unchanged
+    /// if the merge fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - a header whose claims are merged
+    /// * `on_conflict` - how to resolve a claim that is in both headers
+    pub fn merge(
+        &mut self,
+        other: &dyn JoseHeader,
+        on_conflict: ConflictPolicy,
+    ) -> Result<(), JoseError> {
+        let mut claims = self.claims.clone();
+        for (key, value) in other.iter() {
+            match claims.get(key) {
+                Some(existing) if existing == value => {}
+                Some(_) => match on_conflict {
+                    ConflictPolicy::Error => {
+                        return Err(JoseError::InvalidJwsFormat(anyhow!(
+                            "The JWS {} header claim conflicts in the merged headers.",
+                            key
+                        )));
+                    }
+                    ConflictPolicy::KeepExisting => {}
+                    ConflictPolicy::Overwrite => {
+                        claims.insert(key.to_string(), value.clone());
+                    }
+                },
+                None => {
+                    claims.insert(key.to_string(), value.clone());
+                }
+            }
+        }
+
+        *self = Self::from_map(claims)?;
+        Ok(())
+    }
+
     /// Return values for header claims set
     pub fn claims_set(&self) -> &Map<String, Value> {
         &self.claims
@@ -392,13 +626,73 @@ impl JwsHeader {
         self.claims
     }
 
+    pub(crate) fn check_typed_claims(&self) -> Result<(), JoseError> {
+        self.try_jwk()?;
+        self.try_x509_certificate_chain()?;
+        self.try_x509_certificate_sha1_thumbprint()?;
+        self.try_x509_certificate_sha256_thumbprint()?;
+        self.try_critical()?;
+        self.try_base64url_encode_payload()?;
+        self.try_nonce()?;
+        Ok(())
+    }
+
+    fn try_base64_claim(&self, key: &str) -> Result<Option<Vec<u8>>, JoseError> {
+        (|| -> anyhow::Result<Option<Vec<u8>>> {
+            match self.claims.get(key) {
+                Some(Value::String(val)) => {
+                    match base64::decode_config(val, base64::URL_SAFE_NO_PAD) {
+                        Ok(val2) => Ok(Some(val2)),
+                        Err(err) => bail!(
+                            "The JWS {} header claim must be a base64 string: {}",
+                            key,
+                            err
+                        ),
+                    }
+                }
+                Some(_) => bail!("The JWS {} header claim must be a string.", key),
+                None => Ok(None),
+            }
+        })()
+        .map_err(JoseError::InvalidJwsFormat)
+    }
+
     pub(crate) fn check_claim(key: &str, value: &Value) -> Result<(), JoseError> {
+        Self::check_claim_with(key, value, ClaimCheck::Standard)
+    }
+
+    fn check_claim_with(key: &str, value: &Value, check: ClaimCheck) -> Result<(), JoseError> {
         (|| -> anyhow::Result<()> {
             match key {
-                "alg" | "jku" | "x5u" | "kid" | "typ" | "cty" | "url" => match &value {
+                "alg" | "kid" => match &value {
                     Value::String(_) => {}
                     _ => bail!("The JWS {} header claim must be string.", key),
                 },
+                "jku" | "x5u" | "url" => match &value {
+                    Value::String(val) => {
+                        if check != ClaimCheck::Lenient && !util::is_absolute_uri(val) {
+                            bail!(
+                                "The JWS {} header claim must be an absolute URI: {}",
+                                key,
+                                val
+                            );
+                        }
+                        if check == ClaimCheck::HttpsOnly
+                            && !val.to_ascii_lowercase().starts_with("https://")
+                        {
+                            bail!("The JWS {} header claim must be a https URI: {}", key, val);
+                        }
+                    }
+                    _ => bail!("The JWS {} header claim must be string.", key),
+                },
+                "typ" | "cty" => match &value {
+                    Value::String(val) => {
+                        if check != ClaimCheck::Lenient && !util::is_media_type(val) {
+                            bail!("The JWS {} header claim must be a media type: {}", key, val);
+                        }
+                    }
+                    _ => bail!("The JWS {} header claim must be string.", key),
+                },
                 "crit" => match &value {
                     Value::Array(vals) => {
                         for val in vals {
@@ -455,6 +749,13 @@ impl JwsHeader {
     }
 }
 
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+enum ClaimCheck {
+    Lenient,
+    Standard,
+    HttpsOnly,
+}
+
 impl JoseHeader for JwsHeader {
     fn len(&self) -> usize {
         self.claims.len()
@@ -464,6 +765,10 @@ impl JoseHeader for JwsHeader {
         self.claims.get(key)
     }
 
+    fn iter(&self) -> Box<dyn Iterator<Item = (&str, &Value)> + '_> {
+        Box::new(self.claims.iter().map(|(key, value)| (key.as_str(), value)))
+    }
+
     fn box_clone(&self) -> Box<dyn JoseHeader> {
         Box::new(self.clone())
     }
@@ -481,6 +786,21 @@ impl Into<Map<String, Value>> for JwsHeader {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for JwsHeader {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&self.claims, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for JwsHeader {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let map: Map<String, Value> = serde::Deserialize::deserialize(deserializer)?;
+        Self::from_map(map).map_err(serde::de::Error::custom)
+    }
+}
+
 impl Display for JwsHeader {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         let val = serde_json::to_string(&self.claims).map_err(|_e| std::fmt::Error {})?;
@@ -498,11 +818,170 @@ impl Deref for JwsHeader {
 
 #[cfg(test)]
 mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
     use anyhow::Result;
+    use openssl::hash::MessageDigest;
+    use openssl::x509::X509;
     use serde_json::json;
 
     use crate::jwk::Jwk;
     use crate::jws::JwsHeader;
+    use crate::{ConflictPolicy, JoseError};
+
+    #[test]
+    fn test_jws_header_from_bytes_strict() -> Result<()> {
+        let input = br#"{"alg":"HS256","jwk":{"kty":"oct","k":"AA"},"alg":"none"}"#;
+        assert_eq!(JwsHeader::from_bytes(input)?.algorithm(), Some("none"));
+        assert!(matches!(
+            JwsHeader::from_bytes_strict(input),
+            Err(JoseError::InvalidJson(_))
+        ));
+
+        let input = br#"{"alg":"HS256","jwk":{"kty":"oct","k":"AA","k":"AQ"}}"#;
+        assert!(matches!(
+            JwsHeader::from_bytes_strict(input),
+            Err(JoseError::InvalidJson(_))
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_jws_header_serde() -> Result<()> {
+        let header = JwsHeader::from_bytes(
+            br#"{"alg":"HS256","kid":"kid","b64":false,"crit":["b64"],"exp":1300819380}"#,
+        )?;
+
+        let json = serde_json::to_string(&header)?;
+        assert_eq!(json, header.to_string());
+        assert_eq!(serde_json::from_str::<JwsHeader>(&json)?, header);
+
+        let mut cbor = Vec::new();
+        ciborium::ser::into_writer(&header, &mut cbor)?;
+        assert_eq!(
+            ciborium::de::from_reader::<JwsHeader, _>(cbor.as_slice())?,
+            header
+        );
+
+        assert!(serde_json::from_str::<JwsHeader>(r#"{"kid":1}"#).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jws_header_try_getters() -> Result<()> {
+        let header = JwsHeader::from_bytes(br#"{"alg":"HS256"}"#)?;
+        assert!(matches!(header.try_x509_certificate_chain(), Ok(None)));
+        assert!(matches!(header.try_nonce(), Ok(None)));
+
+        let header = JwsHeader::from_bytes(
+            br#"{"x5c":["+/+/"],"x5t":"a+b/","nonce":"a+b/","b64":"yes","crit":["exp"]}"#,
+        )?;
+        assert_eq!(
+            header.x509_certificate_chain(),
+            Some(vec![vec![251, 255, 191]])
+        );
+        assert_eq!(header.x509_certificate_sha1_thumbprint(), None);
+        assert!(matches!(
+            header.try_x509_certificate_sha1_thumbprint(),
+            Err(JoseError::InvalidJwsFormat(_))
+        ));
+        assert_eq!(header.nonce(), None);
+        assert!(header.try_nonce().is_err());
+        assert_eq!(header.base64url_encode_payload(), None);
+        assert!(header.try_base64url_encode_payload().is_err());
+        assert_eq!(header.try_critical()?, Some(vec!["exp"]));
+        assert!(header.check_typed_claims().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jws_header_merge() -> Result<()> {
+        let mut header = JwsHeader::new();
+        header.set_algorithm("HS256");
+        header.set_critical(&vec!["exp"]);
+
+        let other = JwsHeader::from_bytes(br#"{"alg":"HS256","b64":false}"#)?;
+        assert!(header.merge(&other, ConflictPolicy::Error).is_err());
+        assert_eq!(header.len(), 2);
+
+        let other = JwsHeader::from_bytes(br#"{"alg":"HS256","b64":false,"crit":["b64"]}"#)?;
+        header.merge(&other, ConflictPolicy::Overwrite)?;
+        assert_eq!(header, other);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jws_header_uri_and_media_type() -> Result<()> {
+        let map = |value: serde_json::Value| value.as_object().unwrap().clone();
+
+        let valid = map(json!({"jku": "http://example.com/jwks", "typ": "at+JWT"}));
+        let header = JwsHeader::from_map(valid.clone())?;
+        assert!(header.is_token_type("application/at+jwt"));
+        assert!(!header.is_content_type("JWT"));
+        assert!(JwsHeader::from_map_https_only(valid.clone()).is_err());
+        assert!(
+            JwsHeader::from_map_https_only(map(json!({"jku": "HTTPS://example.com/"}))).is_ok()
+        );
+        assert!(JwsHeader::from_map(map(json!({"x5u": "urn:example:certs"}))).is_ok());
+
+        for invalid in &[
+            json!({"jku": "javascript:alert(1)"}),
+            json!({"jku": "https://example.com/a b"}),
+            json!({"x5u": "/certs.pem"}),
+            json!({"url": "example.com"}),
+            json!({"typ": "JWT JWS"}),
+            json!({"cty": "application/"}),
+        ] {
+            assert!(JwsHeader::from_map(map(invalid.clone())).is_err());
+            assert!(JwsHeader::from_map_lenient(map(invalid.clone())).is_ok());
+        }
+
+        let mut header = JwsHeader::new();
+        assert!(header.set_claim("jku", Some(json!("jwks"))).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jws_header_x509_from_certificate() -> Result<()> {
+        let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        pb.push("data/pem/RSA_2048bit_private_with_certificate.pem");
+        let cert = X509::from_pem(&fs::read(&pb)?)?;
+        let der = cert.to_der()?;
+
+        let mut header = JwsHeader::new();
+        header.set_x509_from_certificate(&der)?;
+        assert_eq!(header.x509_certificate_chain(), Some(vec![der.clone()]));
+        assert_eq!(
+            header.claim("x5c"),
+            Some(&json!([base64::encode_config(&der, base64::STANDARD)]))
+        );
+        assert_eq!(
+            header.x509_certificate_sha1_thumbprint(),
+            Some(cert.digest(MessageDigest::sha1())?.to_vec())
+        );
+        assert!(header.x509_thumbprint_matches(&der)?);
+
+        header.set_x509_certificate_sha256_thumbprint(b"pinned");
+        assert!(!header.x509_thumbprint_matches(&der)?);
+
+        header.set_claim("x5t", Some(json!("a+b/")))?;
+        assert!(matches!(
+            header.x509_thumbprint_matches(&der),
+            Err(JoseError::InvalidJwsFormat(_))
+        ));
+        assert!(header
+            .set_x509_certificate_sha256_thumbprint_from_der(b"not a certificate")
+            .is_err());
+
+        Ok(())
+    }
 
     #[test]
     fn test_new_jws_header() -> Result<()> {
@@ -531,6 +1010,7 @@ mod tests {
                 b"x5c1".to_vec(),
             ])
         );
+        assert_eq!(header.claim("x5c"), Some(&json!(["eDVjMA==", "eDVjMQ=="])));
         assert!(
             matches!(header.x509_certificate_sha1_thumbprint(), Some(val) if val == b"x5t".to_vec())
         );
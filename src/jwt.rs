@@ -1,13 +1,21 @@
 //! JSON Web Token (JWT) support.
 
 pub mod alg;
+mod jti_store;
+mod jwt_claim_error;
+mod jwt_confirmation;
 mod jwt_context;
 mod jwt_payload;
 mod jwt_payload_validator;
+mod jwt_payload_validator_builder;
 
+pub use crate::jwt::jti_store::{JtiStore, MemoryJtiStore};
+pub use crate::jwt::jwt_claim_error::JwtClaimError;
+pub use crate::jwt::jwt_confirmation::{ConfirmationCheck, JwtConfirmation};
 pub use crate::jwt::jwt_context::JwtContext;
 pub use crate::jwt::jwt_payload::JwtPayload;
 pub use crate::jwt::jwt_payload_validator::JwtPayloadValidator;
+pub use crate::jwt::jwt_payload_validator_builder::JwtPayloadValidatorBuilder;
 
 pub use crate::jwt::alg::unsecured::UnsecuredJwsAlgorithm::None;
 
@@ -22,10 +30,12 @@ static DEFAULT_CONTEXT: Lazy<JwtContext> = Lazy::new(|| JwtContext::new());
 
 /// Return the string repsentation of the JWT with a "none" algorithm.
 ///
+/// The alg header claim is set to "none" and the signature part is empty.
+///
 /// # Arguments
 ///
 /// * `payload` - The payload data.
-/// * `header` - The JWT heaser claims.
+/// * `header` - The JWT heaser claims that must not have an alg header claim except "none".
 pub fn encode_unsecured(payload: &JwtPayload, header: &JwsHeader) -> Result<String, JoseError> {
     DEFAULT_CONTEXT.encode_unsecured(payload, header)
 }
@@ -45,6 +55,22 @@ pub fn encode_with_signer(
     DEFAULT_CONTEXT.encode_with_signer(payload, header, signer)
 }
 
+/// Return the string repsentation of the JWT whose payload is a serializable claims struct.
+///
+/// # Arguments
+///
+/// * `claims` - a value that is serialized as the JWT payload.
+/// * `header` - The JWS heaser claims.
+/// * `signer` - a signer object.
+#[cfg(feature = "serde")]
+pub fn encode_from<T: serde::Serialize>(
+    claims: &T,
+    header: &JwsHeader,
+    signer: &dyn JwsSigner,
+) -> Result<String, JoseError> {
+    DEFAULT_CONTEXT.encode_from(claims, header, signer)
+}
+
 /// Return the string repsentation of the JWT with the encrypting algorithm.
 ///
 /// # Arguments
@@ -60,6 +86,45 @@ pub fn encode_with_encrypter(
     DEFAULT_CONTEXT.encode_with_encrypter(payload, header, encrypter)
 }
 
+/// Return the string repsentation of the nested JWT that is signed and then encrypted.
+///
+/// The cty header claim of the JWE is set to "JWT".
+///
+/// # Arguments
+///
+/// * `payload` - The payload data.
+/// * `jws_header` - The JWS heaser claims.
+/// * `signer` - a signer object.
+/// * `jwe_header` - The JWE heaser claims.
+/// * `encrypter` - a encrypter object.
+pub fn encode_nested(
+    payload: &JwtPayload,
+    jws_header: &JwsHeader,
+    signer: &dyn JwsSigner,
+    jwe_header: &JweHeader,
+    encrypter: &dyn JweEncrypter,
+) -> Result<String, JoseError> {
+    DEFAULT_CONTEXT.encode_nested(payload, jws_header, signer, jwe_header, encrypter)
+}
+
+/// Return the JWT object decoded from the nested JWT that is decrypted and then verified.
+///
+/// # Arguments
+///
+/// * `input` - a JWT string representation.
+/// * `decrypter` - a decrypter of the decrypting algorithm.
+/// * `selector` - a function for selecting the verifier from the unverified header and payload.
+pub fn decode_nested<F>(
+    input: impl AsRef<[u8]>,
+    decrypter: &dyn JweDecrypter,
+    selector: F,
+) -> Result<(JwtPayload, JwsHeader, JweHeader), JoseError>
+where
+    F: FnOnce(&JwsHeader, &JwtPayload) -> Result<Box<dyn JwsVerifier>, JoseError>,
+{
+    DEFAULT_CONTEXT.decode_nested(input, decrypter, selector)
+}
+
 /// Return the Jose header decoded from JWT.
 ///
 /// # Arguments
@@ -71,6 +136,9 @@ pub fn decode_header(input: impl AsRef<[u8]>) -> Result<Box<dyn JoseHeader>, Jos
 
 /// Return the JWT object decoded with the "none" algorithm.
 ///
+/// The alg header claim must be exactly "none" and the signature part must be empty.
+/// It is separated from the verifying decode functions, which always reject an unsecured JWT.
+///
 /// # Arguments
 ///
 /// * `input` - a JWT string representation.
@@ -78,8 +146,24 @@ pub fn decode_unsecured(input: impl AsRef<[u8]>) -> Result<(JwtPayload, JwsHeade
     DEFAULT_CONTEXT.decode_unsecured(input)
 }
 
+/// Return the JWT object decoded WITHOUT verifying the signature.
+///
+/// **WARNING: The returned claims are not authenticated and must not be trusted.**
+/// Use it only to route or log a JWT before selecting a key, and verify it
+/// with `decode_with_verifier` afterwards.
+///
+/// # Arguments
+///
+/// * `input` - a JWT string representation.
+pub fn decode_unverified(input: impl AsRef<[u8]>) -> Result<(JwtPayload, JwsHeader), JoseError> {
+    DEFAULT_CONTEXT.decode_unverified(input)
+}
+
 /// Return the JWT object decoded by the selected verifier.
 ///
+/// An unsecured JWT is always rejected even with the verifier of "none" algorithm.
+/// Use `decode_unsecured` to decode it.
+///
 /// # Arguments
 ///
 /// * `verifier` - a verifier of the signing algorithm.
@@ -91,6 +175,61 @@ pub fn decode_with_verifier(
     DEFAULT_CONTEXT.decode_with_verifier(input, verifier)
 }
 
+/// Return the JWT payload deserialized into a user-defined struct after verification.
+///
+/// To validate the claims before deserializing, use
+/// `decode_with_verifier_into_and_validator` instead.
+///
+/// # Arguments
+///
+/// * `input` - a JWT string representation.
+/// * `verifier` - a verifier of the signing algorithm.
+#[cfg(feature = "serde")]
+pub fn decode_with_verifier_into<T: serde::de::DeserializeOwned>(
+    input: impl AsRef<[u8]>,
+    verifier: &dyn JwsVerifier,
+) -> Result<(T, JwsHeader), JoseError> {
+    DEFAULT_CONTEXT.decode_with_verifier_into(input, verifier)
+}
+
+/// Return the JWT payload deserialized into a user-defined struct after verification
+/// and validation.
+///
+/// The token type header claim (typ) and the payload claims are validated by the validator
+/// before the claims are deserialized.
+///
+/// # Arguments
+///
+/// * `input` - a JWT string representation.
+/// * `verifier` - a verifier of the signing algorithm.
+/// * `validator` - a validator of the header and payload claims.
+#[cfg(feature = "serde")]
+pub fn decode_with_verifier_into_and_validator<T: serde::de::DeserializeOwned>(
+    input: impl AsRef<[u8]>,
+    verifier: &dyn JwsVerifier,
+    validator: &JwtPayloadValidator,
+) -> Result<(T, JwsHeader), JoseError> {
+    DEFAULT_CONTEXT.decode_with_verifier_into_and_validator(input, verifier, validator)
+}
+
+/// Return the JWT object decoded by the selected verifier and then validated.
+///
+/// The token type header claim (typ) and the payload claims are validated by the validator
+/// after the signature is verified.
+///
+/// # Arguments
+///
+/// * `input` - a JWT string representation.
+/// * `verifier` - a verifier of the signing algorithm.
+/// * `validator` - a validator of the header and payload claims.
+pub fn decode_with_verifier_and_validator(
+    input: impl AsRef<[u8]>,
+    verifier: &dyn JwsVerifier,
+    validator: &JwtPayloadValidator,
+) -> Result<(JwtPayload, JwsHeader), JoseError> {
+    DEFAULT_CONTEXT.decode_with_verifier_and_validator(input, verifier, validator)
+}
+
 /// Return the JWT object decoded with a selected verifying algorithm.
 ///
 /// # Arguments
@@ -125,6 +264,43 @@ where
     DEFAULT_CONTEXT.decode_with_verifier_in_jwk_set(input, jwk_set, selector)
 }
 
+/// Return the JWT object decoded with a verifier selected from the header and the payload.
+///
+/// The selector receives the header and the payload before the signature is verified,
+/// so they must be used only for selecting the verifier. The selected verifier must be
+/// of the algorithm in the alg header claim.
+///
+/// # Arguments
+///
+/// * `input` - a JWT string representation.
+/// * `selector` - a function for selecting the verifier from the unverified header and payload.
+pub fn decode_with_payload_verifier_selector<F>(
+    input: impl AsRef<[u8]>,
+    selector: F,
+) -> Result<(JwtPayload, JwsHeader), JoseError>
+where
+    F: FnOnce(&JwsHeader, &JwtPayload) -> Result<Box<dyn JwsVerifier>, JoseError>,
+{
+    DEFAULT_CONTEXT.decode_with_payload_verifier_selector(input, selector)
+}
+
+/// Return the JWT object decoded by a key in a JWK set.
+///
+/// The candidate keys are the keys in the JWK set that match the kid header claim,
+/// or all the keys if the claim is absent. Each candidate that can verify the algorithm
+/// in the alg header claim is tried in order until one verifies the signature.
+///
+/// # Arguments
+///
+/// * `input` - a JWT string representation.
+/// * `jwk_set` - a JWK set.
+pub fn decode_with_verifier_from_jwk_set(
+    input: impl AsRef<[u8]>,
+    jwk_set: &JwkSet,
+) -> Result<(JwtPayload, JwsHeader), JoseError> {
+    DEFAULT_CONTEXT.decode_with_verifier_from_jwk_set(input, jwk_set)
+}
+
 /// Return the JWT object decoded by the selected decrypter.
 ///
 /// # Arguments
@@ -181,20 +357,21 @@ mod tests {
     use anyhow::Result;
     use serde_json::json;
 
+    use crate::jwe::{self, JweHeader};
     #[allow(deprecated)]
     use crate::jwe::{
         Dir, A128GCMKW, A128KW, A192GCMKW, A192KW, A256GCMKW, A256KW, ECDH_ES, ECDH_ES_A128KW,
         ECDH_ES_A192KW, ECDH_ES_A256KW, PBES2_HS256_A128KW, PBES2_HS384_A192KW, PBES2_HS512_A256KW,
         RSA1_5, RSA_OAEP, RSA_OAEP_256,
     };
-    use crate::jwk::Jwk;
+    use crate::jwk::{Jwk, JwkSet};
     use crate::jws::{
-        EdDSA, JwsHeader, ES256, ES256K, ES384, ES512, HS256, HS384, HS512, PS256, PS384, PS512,
-        RS256, RS384, RS512,
+        EdDSA, JwsContext, JwsHeader, JwsSigner, JwsVerifier, ES256, ES256K, ES384, ES512, HS256,
+        HS384, HS512, PS256, PS384, PS512, RS256, RS384, RS512,
     };
-    use crate::jwt::{self, JwtPayload};
+    use crate::jwt::{self, JwtClaimError, JwtContext, JwtPayload, JwtPayloadValidator};
     use crate::util;
-    use crate::Value;
+    use crate::{JoseError, Value};
 
     #[test]
     fn test_decode_header() -> Result<()> {
@@ -220,6 +397,30 @@ mod tests {
         src_header.set_claim("alg", Some(json!("none")))?;
         assert_eq!(src_header, dst_header);
         assert_eq!(src_payload, dst_payload);
+        assert!(jwt_string.ends_with('.'));
+
+        let mut header = JwsHeader::new();
+        header.set_algorithm("HS256");
+        assert!(jwt::encode_unsecured(&src_payload, &header).is_err());
+
+        let hs256_string = jwt::encode_with_signer(
+            &src_payload,
+            &src_header,
+            &HS256.signer_from_bytes(&[0; 32])?,
+        )?;
+        assert!(jwt::decode_unsecured(&hs256_string).is_err());
+        let (header_part, _) = hs256_string.split_at(hs256_string.rfind('.').unwrap());
+        assert!(jwt::decode_unsecured(format!("{}.", header_part)).is_err());
+        let none_part = jwt_string.trim_end_matches('.');
+        assert!(jwt::decode_unsecured(format!("{}.AA", none_part)).is_err());
+
+        let verifier = HS256.verifier_from_bytes(&[0; 32])?;
+        match jwt::decode_with_verifier(&jwt_string, &verifier) {
+            Err(JoseError::InvalidJwtFormat(err)) => {
+                assert!(err.to_string().contains("decode_unsecured"))
+            }
+            result => panic!("{:?}", result),
+        }
 
         Ok(())
     }
@@ -234,7 +435,8 @@ mod tests {
         let jwt_string = jwt::encode_with_signer(&src_payload, &src_header, &signer)?;
 
         let verifier = alg.verifier();
-        let (dst_payload, dst_header) = jwt::decode_with_verifier(&jwt_string, &verifier)?;
+        assert!(jwt::decode_with_verifier(&jwt_string, &verifier).is_err());
+        let (dst_payload, dst_header) = jwt::decode_unsecured(&jwt_string)?;
 
         src_header.set_claim("alg", Some(json!(alg.name())))?;
         assert_eq!(src_header, dst_header);
@@ -346,19 +548,14 @@ mod tests {
 
     #[test]
     fn test_jwt_with_ecdsa_pem() -> Result<()> {
-        for alg in &[ES256, ES384, ES512, ES256K] {
-            let private_key = load_file(match alg {
-                ES256 => "pem/EC_P-256_private.pem",
-                ES384 => "pem/EC_P-384_private.pem",
-                ES512 => "pem/EC_P-521_private.pem",
-                ES256K => "pem/EC_secp256k1_private.pem",
-            })?;
-            let public_key = load_file(match alg {
-                ES256 => "pem/EC_P-256_public.pem",
-                ES384 => "pem/EC_P-384_public.pem",
-                ES512 => "pem/EC_P-521_public.pem",
-                ES256K => "pem/EC_secp256k1_public.pem",
-            })?;
+        for (alg, crv) in &[
+            (ES256, "P-256"),
+            (ES384, "P-384"),
+            (ES512, "P-521"),
+            (ES256K, "secp256k1"),
+        ] {
+            let private_key = load_file(&format!("pem/EC_{}_private.pem", crv))?;
+            let public_key = load_file(&format!("pem/EC_{}_public.pem", crv))?;
 
             let mut src_header = JwsHeader::new();
             src_header.set_token_type("JWT");
@@ -379,19 +576,14 @@ mod tests {
 
     #[test]
     fn test_jwt_with_ecdsa_der() -> Result<()> {
-        for alg in &[ES256, ES384, ES512, ES256K] {
-            let private_key = load_file(match alg {
-                ES256 => "der/EC_P-256_pkcs8_private.der",
-                ES384 => "der/EC_P-384_pkcs8_private.der",
-                ES512 => "der/EC_P-521_pkcs8_private.der",
-                ES256K => "der/EC_secp256k1_pkcs8_private.der",
-            })?;
-            let public_key = load_file(match alg {
-                ES256 => "der/EC_P-256_spki_public.der",
-                ES384 => "der/EC_P-384_spki_public.der",
-                ES512 => "der/EC_P-521_spki_public.der",
-                ES256K => "der/EC_secp256k1_spki_public.der",
-            })?;
+        for (alg, crv) in &[
+            (ES256, "P-256"),
+            (ES384, "P-384"),
+            (ES512, "P-521"),
+            (ES256K, "secp256k1"),
+        ] {
+            let private_key = load_file(&format!("der/EC_{}_pkcs8_private.der", crv))?;
+            let public_key = load_file(&format!("der/EC_{}_spki_public.der", crv))?;
 
             let mut src_header = JwsHeader::new();
             src_header.set_token_type("JWT");
@@ -484,13 +676,13 @@ mod tests {
 
     #[test]
     fn test_external_jwt_verify_with_ecdsa() -> Result<()> {
-        for alg in &[ES256, ES384, ES512, ES256K] {
-            let jwk = Jwk::from_bytes(&load_file(match alg {
-                ES256 => "jwk/EC_P-256_public.jwk",
-                ES384 => "jwk/EC_P-384_public.jwk",
-                ES512 => "jwk/EC_P-521_public.jwk",
-                ES256K => "jwk/EC_secp256k1_public.jwk",
-            })?)?;
+        for (alg, crv) in &[
+            (ES256, "P-256"),
+            (ES384, "P-384"),
+            (ES512, "P-521"),
+            (ES256K, "secp256k1"),
+        ] {
+            let jwk = Jwk::from_bytes(&load_file(&format!("jwk/EC_{}_public.jwk", crv))?)?;
             let verifier = alg.verifier_from_jwk(&jwk)?;
             let jwt_string = String::from_utf8(load_file(&format!("jwt/{}.jwt", alg.name()))?)?;
             let (payload, header) = jwt::decode_with_verifier(&jwt_string, &verifier)?;
@@ -774,7 +966,11 @@ mod tests {
                     )?;
 
                     let jwk = Jwk::from_bytes(&jwk)?;
-                    let decrypter = alg.decrypter_from_jwk(&jwk)?;
+                    let decrypter = if alg == RSA1_5 {
+                        alg.decrypter_from_jwk_unsafe_rsa15(&jwk)?
+                    } else {
+                        alg.decrypter_from_jwk(&jwk)?
+                    };
                     let jwt_string = String::from_utf8(external_jwt)?;
                     let (payload, header) = jwt::decode_with_decrypter(&jwt_string, &decrypter)?;
 
@@ -797,6 +993,563 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_jwt_with_verifier_and_validator() -> Result<()> {
+        let key = util::random_bytes(32);
+        let signer = HS256.signer_from_bytes(&key)?;
+        let verifier = HS256.verifier_from_bytes(&key)?;
+
+        let mut payload = JwtPayload::new();
+        payload.set_issuer("https://issuer.example.com");
+        payload.set_subject("subject");
+        payload.set_audience(vec!["https://api.example.com"]);
+        payload.set_claim("client_id", Some(json!("client")))?;
+        payload.set_jwt_id("jti");
+        payload.set_issued_at(&SystemTime::now());
+        payload.set_expires_at(&(SystemTime::now() + Duration::from_secs(60)));
+
+        let mut validator = JwtPayloadValidator::for_access_token();
+        validator.add_acceptable_issuer("https://issuer.example.com");
+        validator.add_acceptable_audience("https://api.example.com");
+
+        let mut header = JwsHeader::new();
+        header.set_token_type("application/at+jwt");
+        let jwt_string = jwt::encode_with_signer(&payload, &header, &signer)?;
+        let (dst_payload, _) =
+            jwt::decode_with_verifier_and_validator(&jwt_string, &verifier, &validator)?;
+        assert_eq!(dst_payload, payload);
+
+        let jwt_string = jwt::encode_with_signer(&payload, &JwsHeader::new(), &signer)?;
+        match jwt::decode_with_verifier_and_validator(&jwt_string, &verifier, &validator) {
+            Err(JoseError::InvalidClaim(err)) => assert_eq!(
+                err.downcast::<JwtClaimError>().ok(),
+                Some(JwtClaimError::InvalidTokenType("JWT".to_string()))
+            ),
+            result => panic!("{:?}", result),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_with_signer_sets_token_type() -> Result<()> {
+        let hmac_key = util::random_bytes(64);
+        let keys: Vec<(Box<dyn JwsSigner>, Box<dyn JwsVerifier>)> = vec![
+            (
+                Box::new(HS256.signer_from_bytes(&hmac_key)?),
+                Box::new(HS256.verifier_from_bytes(&hmac_key)?),
+            ),
+            (
+                Box::new(RS256.signer_from_pem(&load_file("pem/RSA_2048bit_private.pem")?)?),
+                Box::new(RS256.verifier_from_pem(&load_file("pem/RSA_2048bit_public.pem")?)?),
+            ),
+            (
+                Box::new(ES256.signer_from_pem(&load_file("pem/EC_P-256_private.pem")?)?),
+                Box::new(ES256.verifier_from_pem(&load_file("pem/EC_P-256_public.pem")?)?),
+            ),
+            (
+                Box::new(EdDSA.signer_from_pem(&load_file("pem/ED25519_private.pem")?)?),
+                Box::new(EdDSA.verifier_from_pem(&load_file("pem/ED25519_public.pem")?)?),
+            ),
+        ];
+
+        for (signer, verifier) in &keys {
+            let mut src_payload = JwtPayload::new();
+            src_payload.set_issuer("joe");
+            let jwt_string =
+                jwt::encode_with_signer(&src_payload, &JwsHeader::new(), signer.as_ref())?;
+
+            let (dst_payload, dst_header) =
+                jwt::decode_with_verifier(&jwt_string, verifier.as_ref())?;
+            assert_eq!(dst_header.algorithm(), Some(signer.algorithm().name()));
+            assert_eq!(dst_header.token_type(), Some("JWT"));
+            assert_eq!(src_payload, dst_payload);
+
+            let mut src_header = JwsHeader::new();
+            src_header.set_token_type("at+jwt");
+            let jwt_string = jwt::encode_with_signer(&src_payload, &src_header, signer.as_ref())?;
+            let (_, dst_header) = jwt::decode_with_verifier(&jwt_string, verifier.as_ref())?;
+            assert_eq!(dst_header.token_type(), Some("at+jwt"));
+
+            let jws_string = JwsContext::new().serialize_compact(
+                b"[\"joe\"]",
+                &JwsHeader::new(),
+                signer.as_ref(),
+            )?;
+            assert!(matches!(
+                jwt::decode_with_verifier(&jws_string, verifier.as_ref()),
+                Err(JoseError::InvalidJwtFormat(_))
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_with_payload_verifier_selector() -> Result<()> {
+        let rsa_private_key = Jwk::from_bytes(&load_file("jwk/RSA_private.jwk")?)?;
+        let rsa_public_key = Jwk::from_bytes(&load_file("jwk/RSA_public.jwk")?)?;
+        let mut ec_private_key = Jwk::from_bytes(&load_file("jwk/EC_P-256_private.jwk")?)?;
+        ec_private_key.set_key_id("ec");
+        let mut ec_public_key = Jwk::from_bytes(&load_file("jwk/EC_P-256_public.jwk")?)?;
+        ec_public_key.set_key_id("ec");
+        let hmac_key = util::random_bytes(32);
+
+        let mut payload = JwtPayload::new();
+        payload.set_issuer("rsa-issuer");
+        let rsa_jwt = jwt::encode_with_signer(
+            &payload,
+            &JwsHeader::new(),
+            &RS256.signer_from_jwk(&rsa_private_key)?,
+        )?;
+        payload.set_issuer("ec-issuer");
+        let ec_jwt = jwt::encode_with_signer(
+            &payload,
+            &JwsHeader::new(),
+            &ES256.signer_from_jwk(&ec_private_key)?,
+        )?;
+
+        for jwt_string in &[&rsa_jwt, &ec_jwt] {
+            let (dst_payload, _) =
+                jwt::decode_with_payload_verifier_selector(jwt_string, |_, payload| {
+                    let verifier: Box<dyn JwsVerifier> = match payload.issuer() {
+                        Some("rsa-issuer") => Box::new(RS256.verifier_from_jwk(&rsa_public_key)?),
+                        _ => Box::new(ES256.verifier_from_jwk(&ec_public_key)?),
+                    };
+                    Ok(verifier)
+                })?;
+            assert!(dst_payload.issuer().is_some());
+        }
+
+        let result = jwt::decode_with_payload_verifier_selector(&rsa_jwt, |_, _| {
+            Ok(Box::new(HS256.verifier_from_bytes(&hmac_key)?))
+        });
+        assert!(matches!(result, Err(JoseError::InvalidJwsFormat(_))));
+
+        let mut jwk_set = JwkSet::new();
+        jwk_set.push_key(rsa_public_key.clone());
+        jwk_set.push_key(ec_public_key.clone());
+        let (dst_payload, _) = jwt::decode_with_verifier_from_jwk_set(&rsa_jwt, &jwk_set)?;
+        assert_eq!(dst_payload.issuer(), Some("rsa-issuer"));
+        let (dst_payload, _) = jwt::decode_with_verifier_from_jwk_set(&ec_jwt, &jwk_set)?;
+        assert_eq!(dst_payload.issuer(), Some("ec-issuer"));
+
+        let mut jwk_set = JwkSet::new();
+        jwk_set.push_key(ec_public_key);
+        assert!(matches!(
+            jwt::decode_with_verifier_from_jwk_set(&rsa_jwt, &jwk_set),
+            Err(JoseError::InvalidJwtFormat(_))
+        ));
+
+        // Without a kid, every key of the algorithm is tried until one verifies.
+        let old_key = Jwk::generate_oct_key(256)?;
+        let new_key = Jwk::generate_oct_key(256)?;
+        let mut jwk_set = JwkSet::new();
+        jwk_set.push_key(old_key);
+        jwk_set.push_key(new_key.clone());
+        let jwt_string = jwt::encode_with_signer(
+            &payload,
+            &JwsHeader::new(),
+            &HS256.signer_from_jwk(&new_key)?,
+        )?;
+        let (dst_payload, _) = jwt::decode_with_verifier_from_jwk_set(&jwt_string, &jwk_set)?;
+        assert_eq!(dst_payload, payload);
+
+        let other_key = Jwk::generate_oct_key(256)?;
+        let jwt_string = jwt::encode_with_signer(
+            &payload,
+            &JwsHeader::new(),
+            &HS256.signer_from_jwk(&other_key)?,
+        )?;
+        assert!(matches!(
+            jwt::decode_with_verifier_from_jwk_set(&jwt_string, &jwk_set),
+            Err(JoseError::InvalidSignature(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_malformed_jwt() -> Result<()> {
+        let key = util::random_bytes(32);
+        let verifier = HS256.verifier_from_bytes(&key)?;
+        let jwt_string = jwt::encode_with_signer(
+            &JwtPayload::new(),
+            &JwsHeader::new(),
+            &HS256.signer_from_bytes(&key)?,
+        )?;
+        jwt::decode_with_verifier(&jwt_string, &verifier)?;
+
+        let garbage = "A".repeat(10 * 1024 * 1024);
+        let four_dots = format!("{}.AA.AA", jwt_string);
+        let bad_char = jwt_string.replacen('.', "+.", 1);
+        for input in &[&garbage, &four_dots, &bad_char] {
+            assert!(matches!(
+                jwt::decode_with_verifier(input, &verifier),
+                Err(JoseError::MalformedJwt(_))
+            ));
+        }
+        for input in &[&garbage, &bad_char, &format!("{}.AA", jwt_string)] {
+            assert!(matches!(
+                jwt::decode_header(input),
+                Err(JoseError::MalformedJwt(_))
+            ));
+        }
+        assert!(matches!(
+            jwt::decode_with_decrypter(&jwt_string, &Dir.decrypter_from_bytes(&key)?),
+            Err(JoseError::MalformedJwt(_))
+        ));
+
+        let mut context = JwtContext::new();
+        context.set_max_token_size(jwt_string.len() - 1);
+        assert!(matches!(
+            context.decode_with_verifier(&jwt_string, &verifier),
+            Err(JoseError::MalformedJwt(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_unverified() -> Result<()> {
+        let jwt_string = String::from_utf8(load_file("jwt/RS256.jwt")?)?;
+        let (payload, header) = jwt::decode_unverified(&jwt_string)?;
+        assert_eq!(header.algorithm(), Some("RS256"));
+        assert_eq!(payload.issuer(), Some("joe"));
+
+        let parts: Vec<&str> = jwt_string.split('.').collect();
+        let array_payload = base64::encode_config(b"[1]", base64::URL_SAFE_NO_PAD);
+        for input in &[
+            format!("{}.{}", parts[0], parts[1]),
+            format!("{}.{}*.{}", parts[0], parts[1], parts[2]),
+            format!("{}.{}.{}*", parts[0], parts[1], parts[2]),
+        ] {
+            assert!(matches!(
+                jwt::decode_unverified(input),
+                Err(JoseError::MalformedJwt(_))
+            ));
+        }
+        assert!(matches!(
+            jwt::decode_unverified(format!("{}.{}.{}", parts[0], array_payload, parts[2])),
+            Err(JoseError::InvalidJwtFormat(_))
+        ));
+
+        let jwe_string = String::from_utf8(load_file("jwt/dir_A256GCM.jwt")?)?;
+        match jwt::decode_unverified(&jwe_string) {
+            Err(JoseError::MalformedJwt(err)) => {
+                assert!(err.to_string().contains("decode_with_decrypter"))
+            }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_encrypted_only() -> Result<()> {
+        let key = util::random_bytes(16);
+        let encrypter = Dir.encrypter_from_bytes(&key)?;
+        let decrypter = Dir.decrypter_from_bytes(&key)?;
+        let mut header = JweHeader::new();
+        header.set_content_encryption("A128GCM");
+
+        let mut src_payload = JwtPayload::new();
+        src_payload.set_subject("session");
+        let jwt_string = jwt::encode_with_encrypter(&src_payload, &header, &encrypter)?;
+        let (dst_payload, dst_header) = jwt::decode_with_decrypter(&jwt_string, &decrypter)?;
+        assert_eq!(src_payload, dst_payload);
+        assert_eq!(dst_header.token_type(), Some("JWT"));
+        assert_eq!(dst_header.content_type(), None);
+
+        let mut json_header = header.clone();
+        json_header.set_content_type("application/json");
+        let jwt_string = jwt::encode_with_encrypter(&src_payload, &json_header, &encrypter)?;
+        jwt::decode_with_decrypter(&jwt_string, &decrypter)?;
+
+        let mut jwt_header = header.clone();
+        jwt_header.set_content_type("JWT");
+        assert!(jwt::encode_with_encrypter(&src_payload, &jwt_header, &encrypter).is_err());
+
+        let signer = HS256.signer_from_bytes(&util::random_bytes(32))?;
+        let nested = jwt::encode_nested(
+            &src_payload,
+            &JwsHeader::new(),
+            &signer,
+            &header,
+            &encrypter,
+        )?;
+        match jwt::decode_with_decrypter(&nested, &decrypter) {
+            Err(JoseError::InvalidJwtFormat(err)) => {
+                assert!(err.to_string().contains("decode_nested"))
+            }
+            result => panic!("{:?}", result),
+        }
+
+        let jws_string = jwt::encode_with_signer(&src_payload, &JwsHeader::new(), &signer)?;
+        match jwt::decode_with_decrypter(&jws_string, &decrypter) {
+            Err(JoseError::MalformedJwt(err)) => {
+                assert!(err.to_string().contains("decode_with_verifier"))
+            }
+            result => panic!("{:?}", result),
+        }
+
+        let array_payload = jwe::serialize_compact(b"[1]", &header, &encrypter)?;
+        assert!(matches!(
+            jwt::decode_with_decrypter(&array_payload, &decrypter),
+            Err(JoseError::InvalidJwtFormat(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_replicated_claims() -> Result<()> {
+        let key = util::random_bytes(16);
+        let encrypter = Dir.encrypter_from_bytes(&key)?;
+        let decrypter = Dir.decrypter_from_bytes(&key)?;
+        let sign_key = util::random_bytes(32);
+        let signer = HS256.signer_from_bytes(&sign_key)?;
+        let mut header = JweHeader::new();
+        header.set_content_encryption("A128GCM");
+
+        let mut src_payload = JwtPayload::new();
+        src_payload.set_issuer("issuer");
+        src_payload.set_subject("subject");
+        src_payload.set_audience(vec!["aud0", "aud1"]);
+
+        let mut context = JwtContext::new();
+        context.set_claims_replicated(true);
+        let jwt_string = context.encode_with_encrypter(&src_payload, &header, &encrypter)?;
+        let (_, dst_header) = jwt::decode_with_decrypter(&jwt_string, &decrypter)?;
+        assert_eq!(dst_header.issuer(), Some("issuer"));
+        assert_eq!(dst_header.subject(), Some("subject"));
+        assert_eq!(dst_header.audience(), Some(vec!["aud0", "aud1"]));
+        context.decode_with_decrypter(&jwt_string, &decrypter)?;
+
+        let nested = context.encode_nested(
+            &src_payload,
+            &JwsHeader::new(),
+            &signer,
+            &header,
+            &encrypter,
+        )?;
+        let (_, _, dst_header) = context.decode_nested(&nested, &decrypter, |_, _| {
+            Ok(Box::new(HS256.verifier_from_bytes(&sign_key)?))
+        })?;
+        assert_eq!(dst_header.issuer(), Some("issuer"));
+
+        let claim_error = |result: Result<(JwtPayload, JweHeader), JoseError>| match result {
+            Err(JoseError::InvalidClaim(err)) => err.downcast::<JwtClaimError>().ok(),
+            _ => None,
+        };
+
+        let mut forged_header = header.clone();
+        forged_header.set_issuer("router");
+        let jwt_string = jwt::encode_with_encrypter(&src_payload, &forged_header, &encrypter)?;
+        jwt::decode_with_decrypter(&jwt_string, &decrypter)?;
+        assert!(matches!(
+            claim_error(context.decode_with_decrypter(&jwt_string, &decrypter)),
+            Some(JwtClaimError::InvalidClaim(key, _)) if key == "iss"
+        ));
+
+        let mut forged_header = header.clone();
+        forged_header.set_audience(vec!["aud0"]);
+        let jwt_string = jwt::encode_with_encrypter(&src_payload, &forged_header, &encrypter)?;
+        assert!(matches!(
+            claim_error(context.decode_with_decrypter(&jwt_string, &decrypter)),
+            Some(JwtClaimError::InvalidClaim(key, _)) if key == "aud"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_nested() -> Result<()> {
+        let enc_key = util::random_bytes(16);
+        let mut jwe_header = JweHeader::new();
+        jwe_header.set_content_encryption("A128GCM");
+        let encrypter = Dir.encrypter_from_bytes(&enc_key)?;
+        let decrypter = Dir.decrypter_from_bytes(&enc_key)?;
+        let signer = ES256.signer_from_pem(&load_file("pem/EC_P-256_private.pem")?)?;
+        let public_key = load_file("pem/EC_P-256_public.pem")?;
+        let selector = |_: &JwsHeader, _: &JwtPayload| {
+            let verifier: Box<dyn JwsVerifier> = Box::new(ES256.verifier_from_pem(&public_key)?);
+            Ok(verifier)
+        };
+
+        let mut src_payload = JwtPayload::new();
+        src_payload.set_subject("patient");
+        let jwt_string = jwt::encode_nested(
+            &src_payload,
+            &JwsHeader::new(),
+            &signer,
+            &jwe_header,
+            &encrypter,
+        )?;
+        let (dst_payload, jws_header, dst_jwe_header) =
+            jwt::decode_nested(&jwt_string, &decrypter, selector)?;
+        assert_eq!(src_payload, dst_payload);
+        assert_eq!(jws_header.algorithm(), Some("ES256"));
+        assert_eq!(dst_jwe_header.content_type(), Some("JWT"));
+
+        let jws_string = jwt::encode_with_signer(&src_payload, &JwsHeader::new(), &signer)?;
+        let mut lower_header = jwe_header.clone();
+        lower_header.set_content_type("jwt");
+        let jwt_string = jwe::serialize_compact(jws_string.as_bytes(), &lower_header, &encrypter)?;
+        jwt::decode_nested(&jwt_string, &decrypter, selector)?;
+
+        let jwt_string = jwe::serialize_compact(jws_string.as_bytes(), &jwe_header, &encrypter)?;
+        assert!(matches!(
+            jwt::decode_nested(&jwt_string, &decrypter, selector),
+            Err(JoseError::InvalidJwtFormat(_))
+        ));
+
+        let mut outer_header = jwe_header.clone();
+        outer_header.set_content_type("JWT");
+        let inner = jwt::encode_nested(
+            &src_payload,
+            &JwsHeader::new(),
+            &signer,
+            &jwe_header,
+            &encrypter,
+        )?;
+        let jwt_string = jwe::serialize_compact(inner.as_bytes(), &outer_header, &encrypter)?;
+        assert!(matches!(
+            jwt::decode_nested(&jwt_string, &decrypter, selector),
+            Err(JoseError::InvalidJwtFormat(_))
+        ));
+
+        let mut context = JwtContext::new();
+        context.set_max_nesting_depth(2);
+        let (dst_payload, _, _) = context.decode_nested(&jwt_string, &decrypter, selector)?;
+        assert_eq!(src_payload, dst_payload);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_with_claims_struct() -> Result<()> {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Claims {
+            sub: String,
+            scope: Vec<String>,
+            exp: u64,
+        }
+
+        let key = util::random_bytes(32);
+        let signer = HS256.signer_from_bytes(&key)?;
+        let verifier = HS256.verifier_from_bytes(&key)?;
+
+        let src_claims = Claims {
+            sub: "user".to_string(),
+            scope: vec!["read".to_string(), "write".to_string()],
+            exp: 60,
+        };
+        let jwt_string = jwt::encode_from(&src_claims, &JwsHeader::new(), &signer)?;
+
+        let (dst_claims, header) =
+            jwt::decode_with_verifier_into::<Claims>(&jwt_string, &verifier)?;
+        assert_eq!(src_claims, dst_claims);
+        assert_eq!(header.token_type(), Some("JWT"));
+
+        let mut validator = JwtPayloadValidator::new();
+        validator.set_subject("user");
+        validator.set_base_time(SystemTime::UNIX_EPOCH + Duration::from_secs(59));
+        let (dst_claims, _) = jwt::decode_with_verifier_into_and_validator::<Claims>(
+            &jwt_string,
+            &verifier,
+            &validator,
+        )?;
+        assert_eq!(src_claims, dst_claims);
+
+        validator.set_base_time(SystemTime::UNIX_EPOCH + Duration::from_secs(60));
+        assert!(matches!(
+            jwt::decode_with_verifier_into_and_validator::<Claims>(
+                &jwt_string,
+                &verifier,
+                &validator
+            ),
+            Err(JoseError::InvalidClaim(_))
+        ));
+
+        let mut payload = JwtPayload::new();
+        payload.set_subject("user");
+        let jwt_string = jwt::encode_with_signer(&payload, &JwsHeader::new(), &signer)?;
+        match jwt::decode_with_verifier_into::<Claims>(&jwt_string, &verifier) {
+            Err(JoseError::InvalidClaim(err)) => {
+                assert!(err.downcast_ref::<serde_json::Error>().is_some());
+            }
+            _ => unreachable!(),
+        }
+
+        assert!(matches!(
+            jwt::encode_from(&vec!["user"], &JwsHeader::new(), &signer),
+            Err(JoseError::InvalidJwtFormat(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_with_duplicate_keys() -> Result<()> {
+        let key = util::random_bytes(32);
+        let signer = HS256.signer_from_bytes(&key)?;
+        let verifier = HS256.verifier_from_bytes(&key)?;
+
+        let header = br#"{"alg":"HS256","typ":"JWT","alg":"HS256"}"#;
+        let payload = br#"{"sub":"user","exp":1,"exp":9999999999}"#;
+        for (header, payload) in &[
+            (&header[..], &b"{}"[..]),
+            (&br#"{"alg":"HS256"}"#[..], &payload[..]),
+        ] {
+            let mut message = base64::encode_config(header, base64::URL_SAFE_NO_PAD);
+            message.push('.');
+            message.push_str(&base64::encode_config(payload, base64::URL_SAFE_NO_PAD));
+            let signature = signer.sign(message.as_bytes())?;
+            message.push('.');
+            message.push_str(&base64::encode_config(signature, base64::URL_SAFE_NO_PAD));
+
+            jwt::decode_with_verifier(&message, &verifier)?;
+
+            let mut context = JwtContext::new();
+            context.set_duplicate_keys_rejected(true);
+            assert!(context.decode_with_verifier(&message, &verifier).is_err());
+            assert!(context.decode_unverified(&message).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_with_lenient_header() -> Result<()> {
+        let key = util::random_bytes(32);
+        let signer = HS256.signer_from_bytes(&key)?;
+        let verifier = HS256.verifier_from_bytes(&key)?;
+
+        let header = br#"{"alg":"HS256","typ":"JWT","jku":"jwks.json"}"#;
+        let mut message = base64::encode_config(header, base64::URL_SAFE_NO_PAD);
+        message.push('.');
+        message.push_str(&base64::encode_config(b"{}", base64::URL_SAFE_NO_PAD));
+        let signature = signer.sign(message.as_bytes())?;
+        message.push('.');
+        message.push_str(&base64::encode_config(signature, base64::URL_SAFE_NO_PAD));
+
+        assert!(jwt::decode_with_verifier(&message, &verifier).is_err());
+        assert!(jwt::decode_unverified(&message).is_err());
+
+        let mut context = JwtContext::new();
+        context.set_lenient_header_accepted(true);
+        let (_, header) = context.decode_with_verifier(&message, &verifier)?;
+        assert_eq!(header.jwk_set_url(), Some("jwks.json"));
+        context.decode_unverified(&message)?;
+
+        let mut context = JwsContext::new();
+        context.set_lenient_header_accepted(true);
+        context.deserialize_compact(&message, &verifier)?;
+
+        Ok(())
+    }
+
     fn load_file(path: &str) -> Result<Vec<u8>> {
         let mut pb = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         pb.push("data");
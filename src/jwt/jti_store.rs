@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use anyhow::anyhow;
+
+use crate::JoseError;
+
+/// A store of JWT IDs (jti) that have already been used.
+pub trait JtiStore: Debug + Send + Sync {
+    /// Store a JWT ID and return true if it has not been stored yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `jti` - a JWT ID
+    /// * `exp` - the time that the JWT ID can be forgotten after.
+    /// * `now` - the current time of the validation, used to evict expired JWT IDs.
+    fn check_and_store(
+        &self,
+        jti: &str,
+        exp: Option<SystemTime>,
+        now: SystemTime,
+    ) -> Result<bool, JoseError>;
+}
+
+/// An in-memory JWT ID store that evicts expired JWT IDs.
+///
+/// A JWT ID stored without an expiration time is never evicted.
+#[derive(Debug)]
+pub struct MemoryJtiStore {
+    entries: Mutex<HashMap<String, Option<SystemTime>>>,
+}
+
+impl MemoryJtiStore {
+    /// Return a new empty store.
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Return the number of stored JWT IDs.
+    pub fn len(&self) -> usize {
+        match self.entries.lock() {
+            Ok(entries) => entries.len(),
+            Err(_) => 0,
+        }
+    }
+
+    /// Test whether no JWT ID is stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for MemoryJtiStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JtiStore for MemoryJtiStore {
+    fn check_and_store(
+        &self,
+        jti: &str,
+        exp: Option<SystemTime>,
+        now: SystemTime,
+    ) -> Result<bool, JoseError> {
+        let mut entries = self
+            .entries
+            .lock()
+            .map_err(|err| JoseError::InvalidClaim(anyhow!("{}", err)))?;
+
+        entries.retain(|_, exp| match exp {
+            Some(val) => *val > now,
+            None => true,
+        });
+
+        if entries.contains_key(jti) {
+            return Ok(false);
+        }
+        entries.insert(jti.to_string(), exp);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use anyhow::Result;
+
+    use super::{JtiStore, MemoryJtiStore};
+
+    #[test]
+    fn test_memory_jti_store() -> Result<()> {
+        let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        let store = MemoryJtiStore::new();
+
+        assert!(store.check_and_store("jti0", Some(at(60)), at(0))?);
+        assert!(store.check_and_store("jti1", None, at(0))?);
+        assert!(!store.check_and_store("jti0", Some(at(60)), at(59))?);
+        assert_eq!(store.len(), 2);
+
+        assert!(!store.check_and_store("jti1", None, at(60))?);
+        assert_eq!(store.len(), 1);
+        assert!(store.check_and_store("jti0", Some(at(60)), at(60))?);
+
+        Ok(())
+    }
+}
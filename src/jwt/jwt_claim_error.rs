@@ -0,0 +1,61 @@
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+/// Represent the detail of a JWT payload claim validation failure.
+///
+/// It is returned as the source of `JoseError::InvalidClaim` by `JwtPayloadValidator`.
+#[derive(Error, Debug, Eq, PartialEq, Clone)]
+pub enum JwtClaimError {
+    #[error("The JWT {0} payload claim is missing.")]
+    MissingClaim(String),
+
+    #[error("The JWT {0} payload claim is invalid: {1}")]
+    InvalidClaim(String, String),
+
+    #[error("The JWT typ header claim is missing.")]
+    MissingTokenType,
+
+    #[error("The token type is not acceptable: {0}")]
+    InvalidTokenType(String),
+
+    #[error("The token issuer is not acceptable: {0}")]
+    InvalidIssuer(String),
+
+    #[error("The token subject is not acceptable: {0}")]
+    InvalidSubject(String),
+
+    #[error("The token audience is not acceptable: {}", .0.join(", "))]
+    InvalidAudience(Vec<String>),
+
+    #[error("The nonce is not the expected value: {0}")]
+    InvalidNonce(String),
+
+    #[error("The authorized party is not the expected client: {0}")]
+    InvalidAuthorizedParty(String),
+
+    #[error("The token lacks a required scope: {0}")]
+    MissingScope(String),
+
+    #[error("The token is not bound to the key by the {0} confirmation method.")]
+    UnconfirmedKey(String),
+
+    #[error("The JWT ID has already been used: {0}")]
+    ReplayedJwtId(String),
+
+    #[error("The JWT {0} payload claim is out of range: {1}")]
+    OutOfRangeNumericDate(String, String),
+
+    #[error("The token has expired: {}", DateTime::<Utc>::from(*.0))]
+    Expired(SystemTime),
+
+    #[error("The token is not yet valid: {}", DateTime::<Utc>::from(*.0))]
+    NotYetValid(SystemTime),
+
+    #[error("The issued time is too old: {}", DateTime::<Utc>::from(*.0))]
+    IssuedTooOld(SystemTime),
+
+    #[error("The issued time is too new: {}", DateTime::<Utc>::from(*.0))]
+    IssuedTooNew(SystemTime),
+}
@@ -0,0 +1,144 @@
+use anyhow::bail;
+use openssl::hash::{self, MessageDigest};
+
+use crate::jwk::Jwk;
+use crate::jwt::JwtClaimError;
+use crate::util::{self, HashAlgorithm};
+use crate::Value;
+
+/// Represent the confirmation methods of confirmation payload claim (cnf)
+/// defined in RFC 7800.
+///
+/// The jkt member is defined in RFC 9449 (DPoP) and the x5t#S256 member is defined in
+/// RFC 8705 (mutual-TLS). The other members are ignored.
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct JwtConfirmation {
+    jwk: Option<Jwk>,
+    jwk_thumbprint: Option<String>,
+    x509_thumbprint: Option<Vec<u8>>,
+    key_id: Option<String>,
+}
+
+impl JwtConfirmation {
+    pub(crate) fn from_value(value: &Value) -> anyhow::Result<Self> {
+        let map = match value {
+            Value::Object(val) => val,
+            _ => bail!("The cnf payload claim must be a JSON object."),
+        };
+
+        let jwk = match map.get("jwk") {
+            Some(Value::Object(val)) => match Jwk::from_map(val.clone()) {
+                Ok(val2) => Some(val2),
+                Err(err) => bail!("The jwk confirmation method is invalid: {}", err),
+            },
+            Some(_) => bail!("The jwk confirmation method must be a JSON object."),
+            None => None,
+        };
+
+        let jwk_thumbprint = match map.get("jkt") {
+            Some(Value::String(val)) if util::is_base64_url_safe_nopad(val) && !val.is_empty() => {
+                Some(val.to_string())
+            }
+            Some(_) => bail!("The jkt confirmation method must be a base64url encoded string."),
+            None => None,
+        };
+
+        let x509_thumbprint = match map.get("x5t#S256") {
+            Some(Value::String(val)) => match base64::decode_config(val, base64::URL_SAFE_NO_PAD) {
+                Ok(val2) if !val2.is_empty() => Some(val2),
+                _ => bail!("The x5t#S256 confirmation method must be a base64url encoded string."),
+            },
+            Some(_) => bail!("The x5t#S256 confirmation method must be a string."),
+            None => None,
+        };
+
+        let key_id = match map.get("kid") {
+            Some(Value::String(val)) => Some(val.to_string()),
+            Some(_) => bail!("The kid confirmation method must be a string."),
+            None => None,
+        };
+
+        Ok(Self {
+            jwk,
+            jwk_thumbprint,
+            x509_thumbprint,
+            key_id,
+        })
+    }
+
+    /// Return the public key of jwk confirmation method.
+    pub fn jwk(&self) -> Option<&Jwk> {
+        self.jwk.as_ref()
+    }
+
+    /// Return the base64url encoded SHA-256 JWK thumbprint of jkt confirmation method.
+    pub fn jwk_thumbprint(&self) -> Option<&str> {
+        self.jwk_thumbprint.as_deref()
+    }
+
+    /// Return the X.509 certificate SHA-256 thumbprint of x5t#S256 confirmation method.
+    pub fn x509_thumbprint(&self) -> Option<&[u8]> {
+        self.x509_thumbprint.as_deref()
+    }
+
+    /// Return the key ID of kid confirmation method.
+    pub fn key_id(&self) -> Option<&str> {
+        self.key_id.as_deref()
+    }
+}
+
+/// Represent a proof-of-possession key that confirmation payload claim (cnf) must be bound to.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum ConfirmationCheck {
+    /// The jkt confirmation method must equal the SHA-256 JWK thumbprint of the key.
+    JwkThumbprint(Jwk),
+
+    /// The jwk confirmation method must have the same SHA-256 JWK thumbprint as the key.
+    Jwk(Jwk),
+
+    /// The x5t#S256 confirmation method must equal the SHA-256 thumbprint of
+    /// the DER encoded X.509 certificate.
+    X509Certificate(Vec<u8>),
+
+    /// The kid confirmation method must equal the key ID.
+    KeyId(String),
+}
+
+impl ConfirmationCheck {
+    pub(crate) fn check(&self, confirmation: &JwtConfirmation) -> anyhow::Result<()> {
+        let (method, is_match) = match self {
+            Self::JwkThumbprint(jwk) => match confirmation.jwk_thumbprint() {
+                Some(val) => ("jkt", val == Self::jwk_thumbprint(jwk)?),
+                None => ("jkt", false),
+            },
+            Self::Jwk(jwk) => match confirmation.jwk() {
+                Some(val) => (
+                    "jwk",
+                    Self::jwk_thumbprint(val)? == Self::jwk_thumbprint(jwk)?,
+                ),
+                None => ("jwk", false),
+            },
+            Self::X509Certificate(der) => match confirmation.x509_thumbprint() {
+                Some(val) => (
+                    "x5t#S256",
+                    val == hash::hash(MessageDigest::sha256(), der)?.as_ref(),
+                ),
+                None => ("x5t#S256", false),
+            },
+            Self::KeyId(key_id) => match confirmation.key_id() {
+                Some(val) => ("kid", val == key_id),
+                None => ("kid", false),
+            },
+        };
+
+        if !is_match {
+            bail!(JwtClaimError::UnconfirmedKey(method.to_string()));
+        }
+        Ok(())
+    }
+
+    fn jwk_thumbprint(jwk: &Jwk) -> anyhow::Result<String> {
+        let thumbprint = jwk.thumbprint(HashAlgorithm::Sha256)?;
+        Ok(base64::encode_config(&thumbprint, base64::URL_SAFE_NO_PAD))
+    }
+}
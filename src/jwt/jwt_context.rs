@@ -1,15 +1,20 @@
-use anyhow::bail;
+use anyhow::{anyhow, bail};
 
 use crate::jwe::{JweContext, JweDecrypter, JweEncrypter, JweHeader};
 use crate::jwk::{Jwk, JwkSet};
-use crate::jws::{JwsContext, JwsHeader, JwsSigner, JwsVerifier};
-use crate::jwt::{self, JwtPayload};
-use crate::{JoseError, JoseHeader, Map, Value};
+use crate::jws::{self, JwsContext, JwsHeader, JwsSigner, JwsVerifier};
+use crate::jwt::{JwtClaimError, JwtPayload, JwtPayloadValidator};
+use crate::util;
+use crate::{JoseError, JoseHeader, Value};
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct JwtContext {
     jws_context: JwsContext,
     jwe_context: JweContext,
+    max_nesting_depth: usize,
+    max_token_size: usize,
+    duplicate_keys_rejected: bool,
+    claims_replicated: bool,
 }
 
 impl JwtContext {
@@ -17,9 +22,98 @@ impl JwtContext {
         Self {
             jws_context: JwsContext::new(),
             jwe_context: JweContext::new(),
+            max_nesting_depth: 1,
+            max_token_size: 16 * 1024,
+            duplicate_keys_rejected: false,
+            claims_replicated: false,
         }
     }
 
+    /// Test whether a header or payload that has duplicated member names is rejected when decoding.
+    pub fn is_duplicate_keys_rejected(&self) -> bool {
+        self.duplicate_keys_rejected
+    }
+
+    /// Set whether a header or payload that has duplicated member names is rejected when decoding.
+    ///
+    /// It also applies to nested objects, and costs an additional scan of the JSON text.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - true if duplicated member names are rejected.
+    pub fn set_duplicate_keys_rejected(&mut self, value: bool) {
+        self.jws_context.set_duplicate_keys_rejected(value);
+        self.jwe_context.set_duplicate_keys_rejected(value);
+        self.duplicate_keys_rejected = value;
+    }
+
+    /// Test whether a JWS header is decoded without checking the format of URI header claims
+    /// (jku, x5u, url) and media type header claims (typ, cty).
+    pub fn is_lenient_header_accepted(&self) -> bool {
+        self.jws_context.is_lenient_header_accepted()
+    }
+
+    /// Set whether a JWS header is decoded without checking the format of URI header claims
+    /// (jku, x5u, url) and media type header claims (typ, cty), as `JwsHeader::from_map_lenient`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - true if the format of those header claims is not checked.
+    pub fn set_lenient_header_accepted(&mut self, value: bool) {
+        self.jws_context.set_lenient_header_accepted(value);
+    }
+
+    /// Return the maximum number of JWE layers around the JWS of a nested JWT.
+    pub fn max_nesting_depth(&self) -> usize {
+        self.max_nesting_depth
+    }
+
+    /// Set the maximum number of JWE layers around the JWS of a nested JWT.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_nesting_depth` - a maximum number of JWE layers (default: 1).
+    pub fn set_max_nesting_depth(&mut self, max_nesting_depth: usize) {
+        self.max_nesting_depth = max_nesting_depth;
+    }
+
+    /// Return the maximum byte length of a JWT to decode.
+    pub fn max_token_size(&self) -> usize {
+        self.max_token_size
+    }
+
+    /// Set the maximum byte length of a JWT to decode.
+    ///
+    /// A longer input is rejected as `JoseError::MalformedJwt` before any base64 or JSON
+    /// decoding, as well as an input that has a wrong number of parts or a character
+    /// other than base64url alphabets and periods.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_token_size` - a maximum byte length (default: 16 KiB).
+    pub fn set_max_token_size(&mut self, max_token_size: usize) {
+        self.max_token_size = max_token_size;
+    }
+
+    /// Test whether iss, sub and aud payload claims are replicated into the JWE header.
+    pub fn is_claims_replicated(&self) -> bool {
+        self.claims_replicated
+    }
+
+    /// Set whether iss, sub and aud payload claims are replicated into the JWE header as
+    /// described in RFC 7519 section 5.3.
+    ///
+    /// When encoding an encrypted or nested JWT, these claims are copied from the payload
+    /// to the JWE header. When decoding, the replicated header claims must match the claims
+    /// of the decrypted payload, or `JoseError::InvalidClaim` is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - true if the claims are replicated.
+    pub fn set_claims_replicated(&mut self, value: bool) {
+        self.claims_replicated = value;
+    }
+
     /// Test a critical header claim name is acceptable.
     ///
     /// # Arguments
@@ -51,20 +145,59 @@ impl JwtContext {
 
     /// Return the string repsentation of the JWT with a "none" algorithm.
     ///
+    /// The alg header claim is set to "none" and the signature part is empty. The typ header
+    /// claim is set to "JWT" unless it is specified.
+    ///
     /// # Arguments
     ///
     /// * `payload` - The payload data.
-    /// * `header` - The JWT heaser claims.
+    /// * `header` - The JWT heaser claims that must not have an alg header claim except "none".
     pub fn encode_unsecured(
         &self,
         payload: &JwtPayload,
         header: &JwsHeader,
     ) -> Result<String, JoseError> {
-        self.encode_with_signer(payload, header, &jwt::None.signer())
+        (|| -> anyhow::Result<String> {
+            match header.claim("alg") {
+                Some(Value::String(val)) if val == "none" => {}
+                Some(val) => bail!(
+                    "The alg header claim of an unsecured JWT must be none: {}",
+                    val
+                ),
+                None => {}
+            }
+            if let Some(vals) = header.critical() {
+                if vals.contains(&"b64") {
+                    bail!("JWT is not support b64 header claim.");
+                }
+            }
+
+            let mut header = header.clone();
+            header.set_algorithm("none");
+            if header.token_type().is_none() {
+                header.set_token_type("JWT");
+            }
+
+            let header_bytes = serde_json::to_vec(header.claims_set())?;
+            let payload_bytes = serde_json::to_vec(payload.claims_set())?;
+
+            let mut jwt = base64::encode_config(header_bytes, base64::URL_SAFE_NO_PAD);
+            jwt.push('.');
+            base64::encode_config_buf(payload_bytes, base64::URL_SAFE_NO_PAD, &mut jwt);
+            jwt.push('.');
+            Ok(jwt)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidJwtFormat(err),
+        })
     }
 
     /// Return the string repsentation of the JWT with the siginig algorithm.
     ///
+    /// The alg and kid header claims are set from the signer, and the typ header claim
+    /// is set to "JWT" unless it is specified.
+    ///
     /// # Arguments
     ///
     /// * `payload` - The payload data.
@@ -84,9 +217,15 @@ impl JwtContext {
             }
 
             let payload_bytes = serde_json::to_vec(payload.claims_set()).unwrap();
-            let jwt = self
-                .jws_context
-                .serialize_compact(&payload_bytes, header, signer)?;
+            let jwt = if header.token_type().is_none() {
+                let mut header = header.clone();
+                header.set_token_type("JWT");
+                self.jws_context
+                    .serialize_compact(&payload_bytes, &header, signer)?
+            } else {
+                self.jws_context
+                    .serialize_compact(&payload_bytes, header, signer)?
+            };
             Ok(jwt)
         })()
         .map_err(|err| match err.downcast::<JoseError>() {
@@ -95,8 +234,30 @@ impl JwtContext {
         })
     }
 
+    /// Return the string repsentation of the JWT whose payload is a serializable claims struct.
+    ///
+    /// # Arguments
+    ///
+    /// * `claims` - a value that is serialized as the JWT payload.
+    /// * `header` - The JWS heaser claims.
+    /// * `signer` - a signer object.
+    #[cfg(feature = "serde")]
+    pub fn encode_from<T: serde::Serialize>(
+        &self,
+        claims: &T,
+        header: &JwsHeader,
+        signer: &dyn JwsSigner,
+    ) -> Result<String, JoseError> {
+        let payload = JwtPayload::from_claims(claims)?;
+        self.encode_with_signer(&payload, header, signer)
+    }
+
     /// Return the string repsentation of the JWT with the encrypting algorithm.
     ///
+    /// The claims are encrypted directly without a signature. The cty header claim must be
+    /// absent or "json", and the typ header claim is set to "JWT" unless it is specified.
+    /// Use `encode_nested` to encrypt a signed JWT.
+    ///
     /// # Arguments
     ///
     /// * `payload` - The payload data.
@@ -108,13 +269,118 @@ impl JwtContext {
         header: &JweHeader,
         encrypter: &dyn JweEncrypter,
     ) -> Result<String, JoseError> {
-        let payload_bytes = serde_json::to_vec(payload.claims_set()).unwrap();
+        (|| -> anyhow::Result<String> {
+            Self::check_encrypted_content_type(header)?;
+
+            let mut header = header.clone();
+            if header.token_type().is_none() {
+                header.set_token_type("JWT");
+            }
+            if self.claims_replicated {
+                Self::replicate_claims(payload, &mut header)?;
+            }
+
+            let payload_bytes = serde_json::to_vec(payload.claims_set())?;
+            let jwt = self
+                .jwe_context
+                .serialize_compact(&payload_bytes, &header, encrypter)?;
+            Ok(jwt)
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidJwtFormat(err),
+        })
+    }
+
+    /// Return the string repsentation of the nested JWT that is signed and then encrypted.
+    ///
+    /// The cty header claim of the JWE is set to "JWT".
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The payload data.
+    /// * `jws_header` - The JWS heaser claims.
+    /// * `signer` - a signer object.
+    /// * `jwe_header` - The JWE heaser claims.
+    /// * `encrypter` - a encrypter object.
+    pub fn encode_nested(
+        &self,
+        payload: &JwtPayload,
+        jws_header: &JwsHeader,
+        signer: &dyn JwsSigner,
+        jwe_header: &JweHeader,
+        encrypter: &dyn JweEncrypter,
+    ) -> Result<String, JoseError> {
+        let jws = self.encode_with_signer(payload, jws_header, signer)?;
+
+        let mut jwe_header = jwe_header.clone();
+        jwe_header.set_content_type("JWT");
+        if self.claims_replicated {
+            Self::replicate_claims(payload, &mut jwe_header)?;
+        }
         let jwt = self
             .jwe_context
-            .serialize_compact(&payload_bytes, header, encrypter)?;
+            .serialize_compact(jws.as_bytes(), &jwe_header, encrypter)?;
         Ok(jwt)
     }
 
+    /// Return the JWT object decoded from the nested JWT that is decrypted and then verified.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - a JWT string representation.
+    /// * `decrypter` - a decrypter of the decrypting algorithm.
+    /// * `selector` - a function for selecting the verifier from the unverified header and payload.
+    pub fn decode_nested<F>(
+        &self,
+        input: impl AsRef<[u8]>,
+        decrypter: &dyn JweDecrypter,
+        selector: F,
+    ) -> Result<(JwtPayload, JwsHeader, JweHeader), JoseError>
+    where
+        F: FnOnce(&JwsHeader, &JwtPayload) -> Result<Box<dyn JwsVerifier>, JoseError>,
+    {
+        (|| -> anyhow::Result<(JwtPayload, JwsHeader, JweHeader)> {
+            self.check_structure(input.as_ref(), &[5])?;
+            let (mut inner, jwe_header) = self.jwe_context.deserialize_compact(input, decrypter)?;
+            let mut header = jwe_header.clone();
+            let mut depth = 1;
+            loop {
+                match header.content_type() {
+                    Some(val) if val.eq_ignore_ascii_case("JWT") => {}
+                    Some(val) => bail!("The JWE cty header claim must be JWT: {}", val),
+                    None => bail!("The JWE cty header claim is required for a nested JWT."),
+                }
+
+                if inner.iter().filter(|b| **b == b'.').count() != 4 {
+                    break;
+                }
+                if depth >= self.max_nesting_depth {
+                    bail!(
+                        "The nested JWT exceeds the maximum nesting depth: {}",
+                        depth
+                    );
+                }
+
+                let (val, val_header) = self.jwe_context.deserialize_compact(&inner, decrypter)?;
+                inner = val;
+                header = val_header;
+                depth += 1;
+            }
+
+            let (payload, jws_header) =
+                self.decode_with_payload_verifier_selector(&inner, selector)?;
+            if self.claims_replicated {
+                Self::check_replicated_claims(&payload, &jwe_header)?;
+            }
+            Ok((payload, jws_header, jwe_header))
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidJwtFormat(err),
+        })
+    }
+
     /// Return the Jose header decoded from JWT.
     ///
     /// # Arguments
@@ -123,17 +389,18 @@ impl JwtContext {
     pub fn decode_header(&self, input: impl AsRef<[u8]>) -> Result<Box<dyn JoseHeader>, JoseError> {
         (|| -> anyhow::Result<Box<dyn JoseHeader>> {
             let input = input.as_ref();
+            self.check_structure(input, &[3, 5])?;
             let parts: Vec<&[u8]> = input.split(|b| *b == '.' as u8).collect();
             if parts.len() == 3 {
                 // JWS
                 let header = base64::decode_config(parts[0], base64::URL_SAFE_NO_PAD)?;
-                let header: Map<String, Value> = serde_json::from_slice(&header)?;
-                let header = JwsHeader::from_map(header)?;
+                let header = util::parse_json_object(&header, self.duplicate_keys_rejected)?;
+                let header = self.jws_context.header_from_map(header)?;
                 Ok(Box::new(header))
             } else if parts.len() == 5 {
                 // JWE
                 let header = base64::decode_config(parts[0], base64::URL_SAFE_NO_PAD)?;
-                let header: Map<String, Value> = serde_json::from_slice(&header)?;
+                let header = util::parse_json_object(&header, self.duplicate_keys_rejected)?;
                 let header = JweHeader::from_map(header)?;
                 Ok(Box::new(header))
             } else {
@@ -148,6 +415,9 @@ impl JwtContext {
 
     /// Return the JWT object decoded with the "none" algorithm.
     ///
+    /// The alg header claim must be exactly "none" and the signature part must be empty.
+    /// It is separated from the verifying decode functions, which always reject an unsecured JWT.
+    ///
     /// # Arguments
     ///
     /// * `input` - a JWT string representation.
@@ -155,11 +425,88 @@ impl JwtContext {
         &self,
         input: impl AsRef<[u8]>,
     ) -> Result<(JwtPayload, JwsHeader), JoseError> {
-        self.decode_with_verifier(input, &jwt::None.verifier())
+        (|| -> anyhow::Result<(JwtPayload, JwsHeader)> {
+            self.check_structure(input.as_ref(), &[3])?;
+            let parts: Vec<&[u8]> = input.as_ref().split(|b| *b == b'.').collect();
+            if !parts[2].is_empty() {
+                bail!("The signature part of an unsecured JWT must be empty.");
+            }
+
+            let header = base64::decode_config(parts[0], base64::URL_SAFE_NO_PAD)?;
+            let header = util::parse_json_object(&header, self.duplicate_keys_rejected)?;
+            let header = self.jws_context.header_from_map(header)?;
+            header.check_typed_claims()?;
+
+            match header.claim("alg") {
+                Some(Value::String(val)) if val == "none" => {}
+                Some(val) => bail!(
+                    "The alg header claim of an unsecured JWT must be none: {}",
+                    val
+                ),
+                None => bail!("The JWS alg header claim is required."),
+            }
+            if let Some(Value::Array(vals)) = header.claim("crit") {
+                for val in vals {
+                    if let Value::String(val2) = val {
+                        if val2 == "b64" {
+                            bail!("JWT is not supported b64 header claim.");
+                        } else if !self.is_acceptable_critical(val2) {
+                            bail!("The critical name '{}' is not supported.", val2);
+                        }
+                    }
+                }
+            }
+
+            let payload = base64::decode_config(parts[1], base64::URL_SAFE_NO_PAD)?;
+            let payload = self.parse_payload(&payload)?;
+
+            Ok((payload, header))
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidJwtFormat(err),
+        })
+    }
+
+    /// Return the JWT object decoded WITHOUT verifying the signature.
+    ///
+    /// **WARNING: The returned claims are not authenticated and must not be trusted.**
+    /// Use it only to route or log a JWT before selecting a key, and verify it
+    /// with `decode_with_verifier` afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - a JWT string representation.
+    pub fn decode_unverified(
+        &self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<(JwtPayload, JwsHeader), JoseError> {
+        (|| -> anyhow::Result<(JwtPayload, JwsHeader)> {
+            self.check_structure(input.as_ref(), &[3])?;
+            let parts: Vec<&[u8]> = input.as_ref().split(|b| *b == b'.').collect();
+
+            let header = base64::decode_config(parts[0], base64::URL_SAFE_NO_PAD)?;
+            let header = util::parse_json_object(&header, self.duplicate_keys_rejected)?;
+            let header = self.jws_context.header_from_map(header)?;
+
+            let payload = base64::decode_config(parts[1], base64::URL_SAFE_NO_PAD)?;
+            let payload = self.parse_payload(&payload)?;
+
+            base64::decode_config(parts[2], base64::URL_SAFE_NO_PAD)?;
+
+            Ok((payload, header))
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidJwtFormat(err),
+        })
     }
 
     /// Return the JWT object decoded by the selected verifier.
     ///
+    /// An unsecured JWT is always rejected even with the verifier of "none" algorithm.
+    /// Use `decode_unsecured` to decode it.
+    ///
     /// # Arguments
     ///
     /// * `verifier` - a verifier of the signing algorithm.
@@ -172,8 +519,74 @@ impl JwtContext {
         self.decode_with_verifier_selector(input, |_header| Ok(Some(verifier)))
     }
 
+    /// Return the JWT object decoded by the selected verifier and then validated.
+    ///
+    /// The token type header claim (typ) and the payload claims are validated by the validator
+    /// after the signature is verified.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - a JWT string representation.
+    /// * `verifier` - a verifier of the signing algorithm.
+    /// * `validator` - a validator of the header and payload claims.
+    pub fn decode_with_verifier_and_validator(
+        &self,
+        input: impl AsRef<[u8]>,
+        verifier: &dyn JwsVerifier,
+        validator: &JwtPayloadValidator,
+    ) -> Result<(JwtPayload, JwsHeader), JoseError> {
+        let (payload, header) = self.decode_with_verifier(input, verifier)?;
+        validator.validate_token_type(&header)?;
+        validator.validate(&payload)?;
+        Ok((payload, header))
+    }
+
+    /// Return the JWT payload deserialized into a user-defined struct after verification.
+    ///
+    /// To validate the claims before deserializing, use
+    /// `decode_with_verifier_into_and_validator` instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - a JWT string representation.
+    /// * `verifier` - a verifier of the signing algorithm.
+    #[cfg(feature = "serde")]
+    pub fn decode_with_verifier_into<T: serde::de::DeserializeOwned>(
+        &self,
+        input: impl AsRef<[u8]>,
+        verifier: &dyn JwsVerifier,
+    ) -> Result<(T, JwsHeader), JoseError> {
+        let (payload, header) = self.decode_with_verifier(input, verifier)?;
+        Ok((payload.to_claims()?, header))
+    }
+
+    /// Return the JWT payload deserialized into a user-defined struct after verification
+    /// and validation.
+    ///
+    /// The token type header claim (typ) and the payload claims are validated by the validator
+    /// before the claims are deserialized.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - a JWT string representation.
+    /// * `verifier` - a verifier of the signing algorithm.
+    /// * `validator` - a validator of the header and payload claims.
+    #[cfg(feature = "serde")]
+    pub fn decode_with_verifier_into_and_validator<T: serde::de::DeserializeOwned>(
+        &self,
+        input: impl AsRef<[u8]>,
+        verifier: &dyn JwsVerifier,
+        validator: &JwtPayloadValidator,
+    ) -> Result<(T, JwsHeader), JoseError> {
+        let (payload, header) =
+            self.decode_with_verifier_and_validator(input, verifier, validator)?;
+        Ok((payload.to_claims()?, header))
+    }
+
     /// Return the JWT object decoded with a selected verifying algorithm.
     ///
+    /// An unsecured JWT is always rejected even if the verifier of "none" algorithm is selected.
+    ///
     /// # Arguments
     ///
     /// * `input` - a JWT string representation.
@@ -187,6 +600,14 @@ impl JwtContext {
         F: Fn(&JwsHeader) -> Result<Option<&'a dyn JwsVerifier>, JoseError>,
     {
         (|| -> anyhow::Result<(JwtPayload, JwsHeader)> {
+            let input = input.as_ref();
+            self.check_structure(input, &[3])?;
+            if Self::is_unsecured(input) {
+                bail!(
+                    "The JWT is unsecured (alg none). Use decode_unsecured to decode it instead."
+                );
+            }
+
             let (payload, header) =
                 self.jws_context
                     .deserialize_compact_with_selector(input, |header| {
@@ -199,6 +620,9 @@ impl JwtContext {
                             if self.is_acceptable_critical("b64") {
                                 bail!("JWT is not supported b64 header claim.");
                             }
+                            if verifier.algorithm().name() == "none" {
+                                bail!("The JWT is unsecured (alg none). Use decode_unsecured to decode it instead.");
+                            }
 
                             Ok(Some(verifier))
                         })()
@@ -210,8 +634,7 @@ impl JwtContext {
                         })
                     })?;
 
-            let payload: Map<String, Value> = serde_json::from_slice(&payload)?;
-            let payload = JwtPayload::from_map(payload)?;
+            let payload = self.parse_payload(&payload)?;
 
             Ok((payload, header))
         })()
@@ -252,8 +675,86 @@ impl JwtContext {
         })
     }
 
+    /// Return the JWT object decoded with a verifier selected from the header and the payload.
+    ///
+    /// The selector receives the header and the payload before the signature is verified,
+    /// so they must be used only for selecting the verifier. The selected verifier must be
+    /// of the algorithm in the alg header claim.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - a JWT string representation.
+    /// * `selector` - a function for selecting the verifier from the unverified header and payload.
+    pub fn decode_with_payload_verifier_selector<F>(
+        &self,
+        input: impl AsRef<[u8]>,
+        selector: F,
+    ) -> Result<(JwtPayload, JwsHeader), JoseError>
+    where
+        F: FnOnce(&JwsHeader, &JwtPayload) -> Result<Box<dyn JwsVerifier>, JoseError>,
+    {
+        let input = input.as_ref();
+        let (payload, header) = self.decode_unverified(input)?;
+        let verifier = selector(&header, &payload)?;
+        self.decode_with_verifier(input, verifier.as_ref())
+    }
+
+    /// Return the JWT object decoded by a key in a JWK set.
+    ///
+    /// The candidate keys are the keys in the JWK set that match the kid header claim,
+    /// or all the keys if the claim is absent. Each candidate that can verify the algorithm
+    /// in the alg header claim is tried in order until one verifies the signature.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - a JWT string representation.
+    /// * `jwk_set` - a JWK set.
+    pub fn decode_with_verifier_from_jwk_set(
+        &self,
+        input: impl AsRef<[u8]>,
+        jwk_set: &JwkSet,
+    ) -> Result<(JwtPayload, JwsHeader), JoseError> {
+        (|| -> anyhow::Result<(JwtPayload, JwsHeader)> {
+            let input = input.as_ref();
+            let (_, header) = self.decode_unverified(input)?;
+            let alg = match header.algorithm() {
+                Some(val) => val,
+                None => bail!("The JWS alg header claim is required."),
+            };
+
+            let jwks = match header.key_id() {
+                Some(val) => jwk_set.get(val),
+                None => jwk_set.keys().collect(),
+            };
+            let mut last_err = None;
+            for jwk in jwks {
+                let verifier = match jws::verifier_from_jwk(alg, jwk) {
+                    Ok(val) => val,
+                    Err(_) => continue,
+                };
+                match self.decode_with_verifier(input, verifier.as_ref()) {
+                    Ok(val) => return Ok(val),
+                    Err(err) => last_err = Some(err),
+                }
+            }
+
+            match (last_err, header.key_id()) {
+                (Some(err), _) => Err(err.into()),
+                (None, Some(val)) => bail!("A verifier is not found for the key {}: {}", val, alg),
+                (None, None) => bail!("A verifier is not found: {}", alg),
+            }
+        })()
+        .map_err(|err| match err.downcast::<JoseError>() {
+            Ok(err) => err,
+            Err(err) => JoseError::InvalidJwtFormat(err),
+        })
+    }
+
     /// Return the JWT object decoded by the selected decrypter.
     ///
+    /// The claims must be encrypted directly, so the cty header claim must be absent
+    /// or "json". Use `decode_nested` to decode a signed and then encrypted JWT.
+    ///
     /// # Arguments
     ///
     /// * `input` - a JWT string representation.
@@ -281,6 +782,7 @@ impl JwtContext {
         F: Fn(&JweHeader) -> Result<Option<&'a dyn JweDecrypter>, JoseError>,
     {
         (|| -> anyhow::Result<(JwtPayload, JweHeader)> {
+            self.check_structure(input.as_ref(), &[5])?;
             let (payload, header) =
                 self.jwe_context
                     .deserialize_compact_with_selector(input, |header| {
@@ -292,8 +794,11 @@ impl JwtContext {
                         Ok(Some(decrypter))
                     })?;
 
-            let payload: Map<String, Value> = serde_json::from_slice(&payload)?;
-            let payload = JwtPayload::from_map(payload)?;
+            Self::check_encrypted_content_type(&header)?;
+            let payload = self.parse_payload(&payload)?;
+            if self.claims_replicated {
+                Self::check_replicated_claims(&payload, &header)?;
+            }
 
             Ok((payload, header))
         })()
@@ -333,4 +838,120 @@ impl JwtContext {
             Ok(None)
         })
     }
+
+    fn replicate_claims(payload: &JwtPayload, header: &mut JweHeader) -> Result<(), JoseError> {
+        for key in &["iss", "sub", "aud"] {
+            if let Some(val) = payload.claim(key) {
+                header.set_claim(key, Some(val.clone()))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn check_replicated_claims(payload: &JwtPayload, header: &JweHeader) -> Result<(), JoseError> {
+        let mismatched = if header.issuer().is_some() && header.issuer() != payload.issuer() {
+            Some("iss")
+        } else if header.subject().is_some() && header.subject() != payload.subject() {
+            Some("sub")
+        } else if header.audience().is_some() && header.audience() != payload.audience() {
+            Some("aud")
+        } else {
+            None
+        };
+
+        match mismatched {
+            Some(key) => Err(JoseError::InvalidClaim(anyhow!(
+                JwtClaimError::InvalidClaim(
+                    key.to_string(),
+                    "The value does not match the replicated JWE header claim.".to_string(),
+                )
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    fn check_encrypted_content_type(header: &JweHeader) -> anyhow::Result<()> {
+        match header.content_type() {
+            Some(val) if util::is_same_media_type(val, "json") => {}
+            Some(val) if val.eq_ignore_ascii_case("JWT") => {
+                bail!("The JWE cty header claim JWT is for a nested JWT. Use encode_nested or decode_nested instead.")
+            }
+            Some(val) => bail!("The JWE cty header claim must be absent or json: {}", val),
+            None => {}
+        }
+        Ok(())
+    }
+
+    fn check_structure(&self, input: &[u8], part_counts: &[usize]) -> Result<(), JoseError> {
+        if input.len() > self.max_token_size {
+            return Err(JoseError::MalformedJwt(anyhow!(
+                "The JWT exceeds the maximum size of {} bytes: {}",
+                self.max_token_size,
+                input.len()
+            )));
+        }
+
+        let mut part_count = 1;
+        for (i, b) in input.iter().enumerate() {
+            match b {
+                b'.' => part_count += 1,
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' => {}
+                _ => {
+                    return Err(JoseError::MalformedJwt(anyhow!(
+                        "The JWT has a character other than base64url alphabets and periods at {}.",
+                        i
+                    )))
+                }
+            }
+        }
+
+        if !part_counts.contains(&part_count) {
+            return Err(JoseError::MalformedJwt(match part_count {
+                5 => {
+                    anyhow!("The input is a JWE. Use decode_with_decrypter to decrypt it instead.")
+                }
+                3 => anyhow!("The input is a JWS. Use decode_with_verifier to verify it instead."),
+                _ => anyhow!(
+                    "The JWT must have {} parts separated by periods: {}",
+                    part_counts
+                        .iter()
+                        .map(|val| val.to_string())
+                        .collect::<Vec<String>>()
+                        .join(" or "),
+                    part_count
+                ),
+            }));
+        }
+        Ok(())
+    }
+
+    fn is_unsecured(input: &[u8]) -> bool {
+        let header = match input.split(|b| *b == b'.').next() {
+            Some(val) => val,
+            None => return false,
+        };
+        let header = match base64::decode_config(header, base64::URL_SAFE_NO_PAD) {
+            Ok(val) => val,
+            Err(_) => return false,
+        };
+        match serde_json::from_slice::<Value>(&header) {
+            Ok(Value::Object(map)) => match map.get("alg") {
+                Some(Value::String(val)) => val.eq_ignore_ascii_case("none"),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn parse_payload(&self, input: &[u8]) -> anyhow::Result<JwtPayload> {
+        let payload = match serde_json::from_slice::<Value>(input)? {
+            Value::Object(val) => val,
+            _ => bail!("The JWT payload must be a JSON object."),
+        };
+        if self.duplicate_keys_rejected {
+            util::parse_json_object(input, true)?;
+        }
+        let payload = JwtPayload::from_map(payload)?;
+        Ok(payload)
+    }
 }
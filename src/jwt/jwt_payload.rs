@@ -1,10 +1,18 @@
-use std::convert::Into;
+use std::convert::{Into, TryFrom};
 use std::fmt::Display;
 use std::time::{Duration, SystemTime};
 
+use crate::jwk::Jwk;
+use crate::jwt::JwtConfirmation;
+use crate::util;
 use crate::{JoseError, Map, Number, Value};
 use anyhow::bail;
+use chrono::{DateTime, TimeZone, Utc};
 
+/// Represent JWT payload claims.
+///
+/// The NumericDate claims (exp, nbf, iat) are read and written with millisecond precision.
+/// A time without a fractional second is written as an integer.
 #[derive(Debug, Eq, PartialEq, Clone, Default)]
 pub struct JwtPayload {
     claims: Map<String, Value>,
@@ -16,6 +24,51 @@ impl JwtPayload {
         Self { claims: Map::new() }
     }
 
+    /// Return the JWT payload from a JSON object.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - JSON style JWT payload claims.
+    pub fn from_bytes(input: impl AsRef<[u8]>) -> Result<Self, JoseError> {
+        let map = util::parse_json_object(input.as_ref(), false).map_err(JoseError::InvalidJson)?;
+        Self::from_map(map)
+    }
+
+    /// Return the JWT payload from a JSON object that has no duplicated member names.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - JSON style JWT payload claims.
+    pub fn from_bytes_strict(input: impl AsRef<[u8]>) -> Result<Self, JoseError> {
+        let map = util::parse_json_object(input.as_ref(), true).map_err(JoseError::InvalidJson)?;
+        Self::from_map(map)
+    }
+
+    /// Return the JWT payload from a serializable claims struct.
+    ///
+    /// # Arguments
+    ///
+    /// * `claims` - a value that is serialized as a JSON object.
+    #[cfg(feature = "serde")]
+    pub fn from_claims<T: serde::Serialize>(claims: &T) -> Result<Self, JoseError> {
+        match serde_json::to_value(claims) {
+            Ok(Value::Object(map)) => Self::from_map(map),
+            Ok(_) => Err(JoseError::InvalidJwtFormat(anyhow::anyhow!(
+                "The JWT payload must be a JSON object."
+            ))),
+            Err(err) => Err(JoseError::InvalidJson(err.into())),
+        }
+    }
+
+    /// Return the JWT payload claims deserialized into a user-defined struct.
+    ///
+    /// A deserialization failure is returned as `JoseError::InvalidClaim`.
+    #[cfg(feature = "serde")]
+    pub fn to_claims<T: serde::de::DeserializeOwned>(&self) -> Result<T, JoseError> {
+        serde_json::from_value(Value::Object(self.claims.clone()))
+            .map_err(|err| JoseError::InvalidClaim(err.into()))
+    }
+
     /// Return the JWT payload from map.
     ///
     /// # Arguments
@@ -68,6 +121,9 @@ impl JwtPayload {
 
     /// Set values for audience payload claim (aud).
     ///
+    /// A single audience is set as a string and multiple audiences are set as an array
+    /// as described in RFC 7519 section 4.1.3.
+    ///
     /// # Arguments
     ///
     /// * `values` - a list of audiences
@@ -117,25 +173,12 @@ impl JwtPayload {
     ///
     /// * `value` - A expiration time on or after which the JWT must not be accepted for processing.
     pub fn set_expires_at(&mut self, value: &SystemTime) {
-        let key = "exp".to_string();
-        let val = Number::from(
-            value
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        );
-        self.claims.insert(key.clone(), Value::Number(val));
+        self.set_numeric_date("exp", value);
     }
 
     /// Return the system time for expires at payload claim (exp).
     pub fn expires_at(&self) -> Option<SystemTime> {
-        match self.claims.get("exp") {
-            Some(Value::Number(val)) => match val.as_u64() {
-                Some(val) => Some(SystemTime::UNIX_EPOCH + Duration::from_secs(val)),
-                None => None,
-            },
-            _ => None,
-        }
+        self.numeric_date("exp")
     }
 
     /// Set a system time for not before payload claim (nbf).
@@ -144,25 +187,12 @@ impl JwtPayload {
     ///
     /// * `value` - A time before which the JWT must not be accepted for processing.
     pub fn set_not_before(&mut self, value: &SystemTime) {
-        let key = "nbf".to_string();
-        let val = Number::from(
-            value
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        );
-        self.claims.insert(key.clone(), Value::Number(val));
+        self.set_numeric_date("nbf", value);
     }
 
     /// Return the system time for not before payload claim (nbf).
     pub fn not_before(&self) -> Option<SystemTime> {
-        match self.claims.get("nbf") {
-            Some(Value::Number(val)) => match val.as_u64() {
-                Some(val) => Some(SystemTime::UNIX_EPOCH + Duration::from_secs(val)),
-                None => None,
-            },
-            _ => None,
-        }
+        self.numeric_date("nbf")
     }
 
     /// Set a time for issued at payload claim (iat).
@@ -171,25 +201,102 @@ impl JwtPayload {
     ///
     /// * `value` - a time at which the JWT was issued.
     pub fn set_issued_at(&mut self, value: &SystemTime) {
-        let key = "iat".to_string();
-        let val = Number::from(
-            value
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        );
-        self.claims.insert(key.clone(), Value::Number(val));
+        self.set_numeric_date("iat", value);
     }
 
     /// Return the time for a issued at payload claim (iat).
     pub fn issued_at(&self) -> Option<SystemTime> {
-        match self.claims.get("iat") {
-            Some(Value::Number(val)) => match val.as_u64() {
-                Some(val) => Some(SystemTime::UNIX_EPOCH + Duration::from_secs(val)),
-                None => None,
-            },
-            _ => None,
-        }
+        self.numeric_date("iat")
+    }
+
+    /// Set a UTC date time for expires at payload claim (exp).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A UTC date time that is truncated to milliseconds.
+    pub fn set_expires_at_chrono(&mut self, value: &DateTime<Utc>) {
+        self.set_numeric_date("exp", &SystemTime::from(*value));
+    }
+
+    /// Return the UTC date time for expires at payload claim (exp).
+    pub fn expires_at_chrono(&self) -> Option<DateTime<Utc>> {
+        self.numeric_date_chrono("exp")
+    }
+
+    /// Set a offset date time for expires at payload claim (exp).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A offset date time that is truncated to milliseconds.
+    #[cfg(feature = "time")]
+    pub fn set_expires_at_time(&mut self, value: &time::OffsetDateTime) {
+        self.set_numeric_date("exp", &SystemTime::from(*value));
+    }
+
+    /// Return the UTC offset date time for expires at payload claim (exp).
+    #[cfg(feature = "time")]
+    pub fn expires_at_time(&self) -> Option<time::OffsetDateTime> {
+        self.numeric_date_time("exp")
+    }
+
+    /// Set a UTC date time for not before payload claim (nbf).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A UTC date time that is truncated to milliseconds.
+    pub fn set_not_before_chrono(&mut self, value: &DateTime<Utc>) {
+        self.set_numeric_date("nbf", &SystemTime::from(*value));
+    }
+
+    /// Return the UTC date time for not before payload claim (nbf).
+    pub fn not_before_chrono(&self) -> Option<DateTime<Utc>> {
+        self.numeric_date_chrono("nbf")
+    }
+
+    /// Set a offset date time for not before payload claim (nbf).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A offset date time that is truncated to milliseconds.
+    #[cfg(feature = "time")]
+    pub fn set_not_before_time(&mut self, value: &time::OffsetDateTime) {
+        self.set_numeric_date("nbf", &SystemTime::from(*value));
+    }
+
+    /// Return the UTC offset date time for not before payload claim (nbf).
+    #[cfg(feature = "time")]
+    pub fn not_before_time(&self) -> Option<time::OffsetDateTime> {
+        self.numeric_date_time("nbf")
+    }
+
+    /// Set a UTC date time for issued at payload claim (iat).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A UTC date time that is truncated to milliseconds.
+    pub fn set_issued_at_chrono(&mut self, value: &DateTime<Utc>) {
+        self.set_numeric_date("iat", &SystemTime::from(*value));
+    }
+
+    /// Return the UTC date time for issued at payload claim (iat).
+    pub fn issued_at_chrono(&self) -> Option<DateTime<Utc>> {
+        self.numeric_date_chrono("iat")
+    }
+
+    /// Set a offset date time for issued at payload claim (iat).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A offset date time that is truncated to milliseconds.
+    #[cfg(feature = "time")]
+    pub fn set_issued_at_time(&mut self, value: &time::OffsetDateTime) {
+        self.set_numeric_date("iat", &SystemTime::from(*value));
+    }
+
+    /// Return the UTC offset date time for issued at payload claim (iat).
+    #[cfg(feature = "time")]
+    pub fn issued_at_time(&self) -> Option<time::OffsetDateTime> {
+        self.numeric_date_time("iat")
     }
 
     /// Set a value for JWT ID payload claim (jti).
@@ -210,6 +317,162 @@ impl JwtPayload {
         }
     }
 
+    /// Set a value for nonce payload claim (nonce) of an ID token.
+    ///
+    /// It is the string value sent in the authentication request as is, unlike the
+    /// base64url encoded nonce header claim of JWS.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - a nonce
+    pub fn set_nonce(&mut self, value: impl Into<String>) {
+        let value: String = value.into();
+        self.claims
+            .insert("nonce".to_string(), Value::String(value));
+    }
+
+    /// Return the value for nonce payload claim (nonce) of an ID token.
+    pub fn nonce(&self) -> Option<&str> {
+        match self.claims.get("nonce") {
+            Some(Value::String(val)) => Some(val),
+            _ => None,
+        }
+    }
+
+    /// Set a value for authorized party payload claim (azp) of an ID token.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - a client ID of the party to which the ID token was issued
+    pub fn set_authorized_party(&mut self, value: impl Into<String>) {
+        let value: String = value.into();
+        self.claims.insert("azp".to_string(), Value::String(value));
+    }
+
+    /// Return the value for authorized party payload claim (azp) of an ID token.
+    pub fn authorized_party(&self) -> Option<&str> {
+        match self.claims.get("azp") {
+            Some(Value::String(val)) => Some(val),
+            _ => None,
+        }
+    }
+
+    /// Set values for scope payload claim (scope) as a space-delimited string.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - a list of scopes that must not be empty or contain a space.
+    pub fn set_scopes(&mut self, values: &[impl AsRef<str>]) -> Result<(), JoseError> {
+        let mut vec = Vec::with_capacity(values.len());
+        for val in values {
+            let val = val.as_ref();
+            if val.is_empty() || val.contains(' ') {
+                return Err(JoseError::InvalidJwtFormat(anyhow::anyhow!(
+                    "A scope must not be empty or contain a space: {:?}",
+                    val
+                )));
+            }
+            vec.push(val);
+        }
+        self.claims
+            .insert("scope".to_string(), Value::String(vec.join(" ")));
+        Ok(())
+    }
+
+    /// Return values for scope payload claim (scope).
+    ///
+    /// The scope claim is read as a space-delimited string or as an array of strings.
+    /// When it is absent, the scp claim that some identity providers use instead is read
+    /// in the same way.
+    pub fn scopes(&self) -> Option<Vec<&str>> {
+        let value = match self.claims.get("scope") {
+            Some(val) => val,
+            None => self.claims.get("scp")?,
+        };
+        match value {
+            Value::String(val) => Some(val.split(' ').filter(|val| !val.is_empty()).collect()),
+            Value::Array(vals) => {
+                let mut vec = Vec::with_capacity(vals.len());
+                for val in vals {
+                    match val {
+                        Value::String(val2) => vec.push(val2.as_str()),
+                        _ => return None,
+                    }
+                }
+                Some(vec)
+            }
+            _ => None,
+        }
+    }
+
+    /// Test whether the scope payload claim (scope) contains a specified scope.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - a scope
+    pub fn has_scope(&self, value: &str) -> bool {
+        match self.scopes() {
+            Some(vals) => vals.contains(&value),
+            None => false,
+        }
+    }
+
+    /// Set a public key for jwk confirmation method of confirmation payload claim (cnf).
+    ///
+    /// The other confirmation methods are kept as they are.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - a public key that must not have any private parameter.
+    pub fn set_confirmation_jwk(&mut self, value: Jwk) -> Result<(), JoseError> {
+        value.ensure_public()?;
+        self.set_confirmation_method("jwk", Value::Object(value.into()));
+        Ok(())
+    }
+
+    /// Set a value for jkt confirmation method of confirmation payload claim (cnf).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - a base64url encoded SHA-256 JWK thumbprint
+    pub fn set_confirmation_jwk_thumbprint(&mut self, value: impl Into<String>) {
+        let value: String = value.into();
+        self.set_confirmation_method("jkt", Value::String(value));
+    }
+
+    /// Set a value for x5t#S256 confirmation method of confirmation payload claim (cnf).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - a X.509 certificate SHA-256 thumbprint
+    pub fn set_confirmation_x509_thumbprint(&mut self, value: impl AsRef<[u8]>) {
+        let val = base64::encode_config(&value, base64::URL_SAFE_NO_PAD);
+        self.set_confirmation_method("x5t#S256", Value::String(val));
+    }
+
+    /// Set a value for kid confirmation method of confirmation payload claim (cnf).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - a key ID
+    pub fn set_confirmation_key_id(&mut self, value: impl Into<String>) {
+        let value: String = value.into();
+        self.set_confirmation_method("kid", Value::String(value));
+    }
+
+    /// Return the confirmation methods of confirmation payload claim (cnf).
+    ///
+    /// A malformed confirmation method is returned as `JoseError::InvalidJwtFormat`.
+    pub fn confirmation(&self) -> Result<Option<JwtConfirmation>, JoseError> {
+        match self.claims.get("cnf") {
+            Some(val) => match JwtConfirmation::from_value(val) {
+                Ok(val2) => Ok(Some(val2)),
+                Err(err) => Err(JoseError::InvalidJwtFormat(err)),
+            },
+            None => Ok(None),
+        }
+    }
+
     /// Set a value for payload claim of a specified key.
     ///
     /// # Arguments
@@ -217,20 +480,17 @@ impl JwtPayload {
     /// * `key` - a key name of payload claim
     /// * `value` - a typed value of payload claim
     pub fn set_claim(&mut self, key: &str, value: Option<Value>) -> Result<(), JoseError> {
-        (|| -> anyhow::Result<()> {
-            match value {
-                Some(val) => {
-                    Self::check_claim(key, &val)?;
-                    self.claims.insert(key.to_string(), val);
-                }
-                None => {
-                    self.claims.remove(key);
-                }
+        match value {
+            Some(val) => {
+                Self::check_claim(key, &val)?;
+                self.claims.insert(key.to_string(), val);
             }
+            None => {
+                self.claims.remove(key);
+            }
+        }
 
-            Ok(())
-        })()
-        .map_err(|err| JoseError::InvalidJwtFormat(err))
+        Ok(())
     }
 
     /// Return a value for payload claim of a specified key.
@@ -247,12 +507,79 @@ impl JwtPayload {
         &self.claims
     }
 
+    fn set_confirmation_method(&mut self, key: &str, value: Value) {
+        match self.claims.get_mut("cnf") {
+            Some(Value::Object(map)) => {
+                map.insert(key.to_string(), value);
+            }
+            _ => {
+                let mut map = Map::new();
+                map.insert(key.to_string(), value);
+                self.claims.insert("cnf".to_string(), Value::Object(map));
+            }
+        }
+    }
+
+    fn set_numeric_date(&mut self, key: &str, value: &SystemTime) {
+        let millis = match value.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(val) => val.as_millis(),
+            Err(_) => 0,
+        };
+        let val = if millis % 1000 == 0 {
+            Number::from((millis / 1000) as u64)
+        } else {
+            match Number::from_f64(millis as f64 / 1000.0) {
+                Some(val) => val,
+                None => Number::from((millis / 1000) as u64),
+            }
+        };
+        self.claims.insert(key.to_string(), Value::Number(val));
+    }
+
+    fn numeric_date(&self, key: &str) -> Option<SystemTime> {
+        let duration = match self.claims.get(key) {
+            Some(Value::Number(val)) => match val.as_u64() {
+                Some(val) => Duration::from_secs(val),
+                None => match val.as_f64() {
+                    Some(val) if val >= 0.0 && val < (u64::MAX / 1000) as f64 => {
+                        Duration::from_millis((val * 1000.0).round() as u64)
+                    }
+                    _ => return None,
+                },
+            },
+            _ => return None,
+        };
+        SystemTime::UNIX_EPOCH.checked_add(duration)
+    }
+
+    fn numeric_date_chrono(&self, key: &str) -> Option<DateTime<Utc>> {
+        let duration = self
+            .numeric_date(key)?
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .ok()?;
+        let secs = i64::try_from(duration.as_secs()).ok()?;
+        Utc.timestamp_opt(secs, duration.subsec_nanos()).single()
+    }
+
+    #[cfg(feature = "time")]
+    fn numeric_date_time(&self, key: &str) -> Option<time::OffsetDateTime> {
+        let duration = self
+            .numeric_date(key)?
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .ok()?;
+        time::OffsetDateTime::from_unix_timestamp_nanos(duration.as_nanos() as i128).ok()
+    }
+
     fn check_claim(key: &str, value: &Value) -> Result<(), JoseError> {
         (|| -> anyhow::Result<()> {
             match key {
-                "iss" | "sub" | "jti" => match &value {
+                "iss" | "sub" | "jti" | "nonce" | "azp" => match &value {
                     Value::String(_) => {}
-                    _ => bail!("The JWT {} payload claim must be a string.", key),
+                    _ => bail!(
+                        "The JWT {} payload claim must be a string: {}",
+                        key,
+                        Self::json_type(value)
+                    ),
                 },
                 "aud" => match &value {
                     Value::String(_) => {}
@@ -261,23 +588,41 @@ impl JwtPayload {
                             match val {
                                 Value::String(_) => {}
                                 _ => bail!(
-                                    "An element of the JWT {} payload claim must be a string.",
-                                    key
+                                    "An element of the JWT {} payload claim must be a string: {}",
+                                    key,
+                                    Self::json_type(val)
                                 ),
                             }
                         }
                     }
-                    _ => bail!("The JWT {} payload claim must be a string or array.", key),
+                    _ => bail!(
+                        "The JWT {} payload claim must be a string or array: {}",
+                        key,
+                        Self::json_type(value)
+                    ),
+                },
+                "cnf" => match &value {
+                    Value::Object(_) => {}
+                    _ => bail!(
+                        "The JWT {} payload claim must be a JSON object: {}",
+                        key,
+                        Self::json_type(value)
+                    ),
                 },
                 "exp" | "nbf" | "iat" => match &value {
-                    Value::Number(val) => match val.as_u64() {
-                        Some(_) => {}
-                        None => bail!(
-                            "The JWT {} payload claim must be a positive integer within 64bit.",
+                    Value::Number(val) if val.is_u64() => {}
+                    Value::Number(val) => match val.as_f64() {
+                        Some(val) if val >= 0.0 && val < u64::MAX as f64 => {}
+                        _ => bail!(
+                            "The JWT {} payload claim must be a non-negative number within 64bit.",
                             key
                         ),
                     },
-                    _ => bail!("The JWT {} header claim must be a string.", key),
+                    _ => bail!(
+                        "The JWT {} payload claim must be a number: {}",
+                        key,
+                        Self::json_type(value)
+                    ),
                 },
                 _ => {}
             }
@@ -286,6 +631,17 @@ impl JwtPayload {
         })()
         .map_err(|err| JoseError::InvalidJwtFormat(err))
     }
+
+    fn json_type(value: &Value) -> &'static str {
+        match value {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        }
+    }
 }
 
 impl AsRef<Map<String, Value>> for JwtPayload {
@@ -300,6 +656,21 @@ impl Into<Map<String, Value>> for JwtPayload {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for JwtPayload {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&self.claims, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for JwtPayload {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let map: Map<String, Value> = serde::Deserialize::deserialize(deserializer)?;
+        Self::from_map(map).map_err(serde::de::Error::custom)
+    }
+}
+
 impl Display for JwtPayload {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         let val = serde_json::to_string(&self.claims).map_err(|_e| std::fmt::Error {})?;
@@ -309,12 +680,17 @@ impl Display for JwtPayload {
 
 #[cfg(test)]
 mod tests {
-    use std::time::SystemTime;
+    use std::time::{Duration, SystemTime};
 
     use anyhow::Result;
+    use chrono::{TimeZone, Utc};
     use serde_json::json;
 
     use super::JwtPayload;
+    use crate::jwk::alg::ec::EcCurve;
+    use crate::jwk::Jwk;
+    use crate::jwt::JwtConfirmation;
+    use crate::JoseError;
 
     #[test]
     fn test_new_payload() -> Result<()> {
@@ -343,4 +719,191 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_payload_from_bytes() -> Result<()> {
+        let payload = JwtPayload::from_bytes(
+            br#"{"iss":"iss","aud":"aud","exp":1.5,"nbf":0,"iat":1,"custom":true}"#,
+        )?;
+        assert_eq!(payload.issuer(), Some("iss"));
+        assert_eq!(payload.audience(), Some(vec!["aud"]));
+        assert_eq!(
+            payload.expires_at(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_millis(1500))
+        );
+        assert_eq!(payload.not_before(), Some(SystemTime::UNIX_EPOCH));
+        assert_eq!(
+            payload.issued_at(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1))
+        );
+        assert_eq!(payload.claim("custom"), Some(&json!(true)));
+        assert_eq!(
+            payload.to_string(),
+            r#"{"iss":"iss","aud":"aud","exp":1.5,"nbf":0,"iat":1,"custom":true}"#
+        );
+
+        for input in &[
+            r#"{"iss":1}"#,
+            r#"{"sub":true}"#,
+            r#"{"jti":["jti"]}"#,
+            r#"{"aud":{}}"#,
+            r#"{"aud":["aud",1]}"#,
+            r#"{"exp":"1"}"#,
+            r#"{"nbf":-1}"#,
+            r#"{"iat":1e20}"#,
+        ] {
+            assert!(matches!(
+                JwtPayload::from_bytes(input),
+                Err(JoseError::InvalidJwtFormat(_))
+            ));
+        }
+        for (input, message) in &[
+            (
+                r#"{"exp":"1712345678"}"#,
+                "exp payload claim must be a number: string",
+            ),
+            (
+                r#"{"aud":1}"#,
+                "aud payload claim must be a string or array: number",
+            ),
+            (
+                r#"{"iss":null}"#,
+                "iss payload claim must be a string: null",
+            ),
+        ] {
+            match JwtPayload::from_bytes(input) {
+                Err(JoseError::InvalidJwtFormat(err)) => {
+                    assert!(err.to_string().contains(message), "{}", err)
+                }
+                _ => unreachable!(),
+            }
+        }
+        assert!(matches!(
+            JwtPayload::from_bytes("[]"),
+            Err(JoseError::InvalidJson(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_payload_nonce_and_authorized_party() -> Result<()> {
+        let mut payload = JwtPayload::new();
+        payload.set_nonce("n-0S6_WzA2Mj");
+        payload.set_authorized_party("client");
+        assert_eq!(payload.claim("nonce"), Some(&json!("n-0S6_WzA2Mj")));
+        assert_eq!(payload.nonce(), Some("n-0S6_WzA2Mj"));
+        assert_eq!(payload.authorized_party(), Some("client"));
+
+        assert!(JwtPayload::from_bytes(br#"{"nonce":1}"#).is_err());
+        assert!(JwtPayload::from_bytes(br#"{"azp":["client"]}"#).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_payload_scopes() -> Result<()> {
+        let mut payload = JwtPayload::new();
+        assert_eq!(payload.scopes(), None);
+        assert!(!payload.has_scope("read"));
+
+        payload.set_scopes(&["read", "write"])?;
+        assert_eq!(payload.claim("scope"), Some(&json!("read write")));
+        assert_eq!(payload.scopes(), Some(vec!["read", "write"]));
+        assert!(payload.has_scope("write"));
+        assert!(!payload.has_scope("read write"));
+        assert!(payload.set_scopes(&["read write"]).is_err());
+        assert!(payload.set_scopes(&[""]).is_err());
+
+        let payload = JwtPayload::from_bytes(br#"{"scope":"  read  write "}"#)?;
+        assert_eq!(payload.scopes(), Some(vec!["read", "write"]));
+
+        let payload = JwtPayload::from_bytes(br#"{"scope":["read"],"scp":["write"]}"#)?;
+        assert_eq!(payload.scopes(), Some(vec!["read"]));
+
+        let payload = JwtPayload::from_bytes(br#"{"scp":["read","write"]}"#)?;
+        assert!(payload.has_scope("write"));
+
+        let payload = JwtPayload::from_bytes(br#"{"scope":["read",1]}"#)?;
+        assert_eq!(payload.scopes(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_payload_confirmation() -> Result<()> {
+        let mut payload = JwtPayload::new();
+        assert_eq!(payload.confirmation()?, None);
+
+        let jwk = Jwk::generate_ec_key(EcCurve::P256)?;
+        assert!(payload.set_confirmation_jwk(jwk.clone()).is_err());
+        payload.set_confirmation_jwk(jwk.to_public_key()?)?;
+        payload.set_confirmation_jwk_thumbprint("0ZcOCORZNYy-DWpqq30jZyJGHTN0d2HglBV3uiguA4I");
+        payload.set_confirmation_x509_thumbprint(b"thumbprint");
+        payload.set_confirmation_key_id("kid");
+
+        let confirmation = JwtPayload::from_bytes(payload.to_string())?
+            .confirmation()?
+            .unwrap();
+        assert_eq!(confirmation.jwk(), Some(&jwk.to_public_key()?));
+        assert_eq!(
+            confirmation.jwk_thumbprint(),
+            Some("0ZcOCORZNYy-DWpqq30jZyJGHTN0d2HglBV3uiguA4I")
+        );
+        assert_eq!(confirmation.x509_thumbprint(), Some(&b"thumbprint"[..]));
+        assert_eq!(confirmation.key_id(), Some("kid"));
+
+        let payload = JwtPayload::from_bytes(br#"{"cnf":{"jku":"https://example.com"}}"#)?;
+        assert_eq!(payload.confirmation()?, Some(JwtConfirmation::default()));
+
+        let payload = JwtPayload::from_bytes(br#"{"cnf":{"jkt":1}}"#)?;
+        assert!(payload.confirmation().is_err());
+        assert!(JwtPayload::from_bytes(br#"{"cnf":"jkt"}"#).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_payload_chrono_date_time() -> Result<()> {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_millis(1700000000250);
+        let date_time = Utc.timestamp_opt(1700000000, 250_999_999).unwrap();
+
+        let mut payload = JwtPayload::new();
+        payload.set_expires_at_chrono(&date_time);
+        payload.set_not_before(&time);
+        assert_eq!(payload.expires_at(), Some(time));
+        assert_eq!(
+            payload.not_before_chrono(),
+            Some(Utc.timestamp_opt(1700000000, 250_000_000).unwrap())
+        );
+        assert_eq!(payload.issued_at_chrono(), None);
+
+        let payload = JwtPayload::from_bytes(br#"{"exp":9.9e18}"#)?;
+        assert_eq!(payload.expires_at_chrono(), None);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_payload_time_date_time() -> Result<()> {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_millis(1700000000250);
+        let date_time = time::OffsetDateTime::from_unix_timestamp_nanos(1700000000250999999)?;
+
+        let mut payload = JwtPayload::new();
+        payload.set_issued_at_time(&date_time);
+        payload.set_expires_at(&time);
+        assert_eq!(payload.issued_at(), Some(time));
+        assert_eq!(
+            payload.expires_at_time(),
+            Some(time::OffsetDateTime::from_unix_timestamp_nanos(
+                1700000000250000000
+            )?)
+        );
+
+        let payload = JwtPayload::from_bytes(br#"{"exp":9.9e18}"#)?;
+        assert_eq!(payload.expires_at_time(), None);
+
+        Ok(())
+    }
 }
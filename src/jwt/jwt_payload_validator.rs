@@ -1,40 +1,153 @@
 use std::convert::Into;
-use std::time::SystemTime;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 use anyhow::bail;
-use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
 
-use crate::jwt::JwtPayload;
-use crate::{JoseError, Map, Value};
+use crate::jwt::{
+    ConfirmationCheck, JtiStore, JwtClaimError, JwtConfirmation, JwtPayload,
+    JwtPayloadValidatorBuilder,
+};
+use crate::util;
+use crate::{Clock, FixedClock, JoseError, JoseHeader, Map, SystemClock, Value};
+
+type ClaimCheck = Arc<dyn Fn(&Value) -> Result<(), String> + Send + Sync>;
+
+// 9999-12-31T23:59:59Z
+const MAX_NUMERIC_DATE_SECS: u64 = 253402300799;
+
+// The validators share the default clock, so that they compare equal.
+static SYSTEM_CLOCK: Lazy<Arc<dyn Clock>> = Lazy::new(|| Arc::new(SystemClock));
 
 /// Represents JWT payload validator.
-#[derive(Debug, Eq, PartialEq)]
+///
+/// It is cheap to clone because the claim settings are shared until one of the clones
+/// is changed. It is `Send` and `Sync`, so a validator can be shared across threads,
+/// for example by using `validate_with_base_time` instead of changing the base time.
+#[derive(Clone)]
 pub struct JwtPayloadValidator {
+    clock: Arc<dyn Clock>,
     base_time: Option<SystemTime>,
+    acceptable_leeway: Duration,
+    expires_at_required: bool,
+    future_issued_at_rejected: bool,
     min_issued_time: Option<SystemTime>,
     max_issued_time: Option<SystemTime>,
-    audience: Option<String>,
-    claims: Map<String, Value>,
+    min_numeric_date: SystemTime,
+    max_numeric_date: SystemTime,
+    token_types: Arc<Vec<String>>,
+    required_claims: Arc<Vec<String>>,
+    issuers: Arc<Vec<String>>,
+    subject: Option<String>,
+    subject_pattern: Option<Regex>,
+    anchored_subject_pattern: Option<Regex>,
+    audiences: Arc<Vec<String>>,
+    audience_subset_required: bool,
+    nonce: Option<String>,
+    authorized_party: Option<String>,
+    required_scopes: Arc<Vec<String>>,
+    required_confirmation: Option<ConfirmationCheck>,
+    claims: Arc<Map<String, Value>>,
+    claim_checks: Arc<Vec<(String, ClaimCheck)>>,
+    checked_claim_required: bool,
+    jti_store: Option<Arc<dyn JtiStore>>,
 }
 
 impl JwtPayloadValidator {
+    /// Return a new builder of JwtPayloadValidator.
+    pub fn builder() -> JwtPayloadValidatorBuilder {
+        JwtPayloadValidatorBuilder::new()
+    }
+
     /// Return a new JwtPayloadValidator.
     pub fn new() -> Self {
         Self {
+            clock: Arc::clone(&SYSTEM_CLOCK),
             base_time: None,
+            acceptable_leeway: Duration::from_secs(0),
+            expires_at_required: false,
+            future_issued_at_rejected: true,
             min_issued_time: None,
             max_issued_time: None,
-            audience: None,
-            claims: Map::new(),
+            min_numeric_date: SystemTime::UNIX_EPOCH,
+            max_numeric_date: SystemTime::UNIX_EPOCH + Duration::from_secs(MAX_NUMERIC_DATE_SECS),
+            token_types: Arc::new(Vec::new()),
+            required_claims: Arc::new(Vec::new()),
+            issuers: Arc::new(Vec::new()),
+            subject: None,
+            subject_pattern: None,
+            anchored_subject_pattern: None,
+            audiences: Arc::new(Vec::new()),
+            audience_subset_required: false,
+            nonce: None,
+            authorized_party: None,
+            required_scopes: Arc::new(Vec::new()),
+            required_confirmation: None,
+            claims: Arc::new(Map::new()),
+            claim_checks: Arc::new(Vec::new()),
+            checked_claim_required: true,
+            jti_store: None,
+        }
+    }
+
+    /// Return a new JwtPayloadValidator for JWT access tokens defined in RFC 9068.
+    ///
+    /// The typ header claim must be "at+jwt" and the iss, exp, aud, sub, client_id, iat
+    /// and jti payload claims are required. The acceptable issuers and audiences should be
+    /// added to it.
+    pub fn for_access_token() -> Self {
+        let mut validator = Self::new();
+        validator.add_acceptable_token_type("at+jwt");
+        for key in &["iss", "exp", "aud", "sub", "client_id", "iat", "jti"] {
+            validator.add_required_claim(key);
+        }
+        validator.set_expires_at_required(true);
+        validator
+    }
+
+    /// Return a new JwtPayloadValidator for ID tokens defined in OpenID Connect Core 1.0.
+    ///
+    /// The iss, sub, aud, exp and iat payload claims are required. The acceptable issuers
+    /// and audiences should be added to it, and the nonce and the client ID should be set
+    /// by `set_expected_nonce` and `set_expected_authorized_party` if they are expected.
+    pub fn for_id_token() -> Self {
+        let mut validator = Self::new();
+        for key in &["iss", "sub", "aud", "exp", "iat"] {
+            validator.add_required_claim(key);
         }
+        validator.set_expires_at_required(true);
+        validator
+    }
+
+    /// Set a clock for time related claims (exp, nbf, iat) validation.
+    ///
+    /// It defaults to the system clock.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - a clock
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+        self.base_time = None;
+    }
+
+    /// Return the clock for time related claims (exp, nbf, iat) validation.
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
     }
 
     /// Set a base time for time related claims (exp, nbf) validation.
     ///
+    /// It is equivalent to setting a `FixedClock` of the time.
+    ///
     /// # Arguments
     ///
     /// * `base_time` - a min time
     pub fn set_base_time(&mut self, base_time: SystemTime) {
+        self.clock = Arc::new(FixedClock(base_time));
         self.base_time = Some(base_time);
     }
 
@@ -43,6 +156,50 @@ impl JwtPayloadValidator {
         self.base_time.as_ref()
     }
 
+    /// Set an acceptable clock skew for time related claims (exp, nbf, iat) validation.
+    ///
+    /// # Arguments
+    ///
+    /// * `acceptable_leeway` - an acceptable clock skew.
+    pub fn set_acceptable_leeway(&mut self, acceptable_leeway: Duration) {
+        self.acceptable_leeway = acceptable_leeway;
+    }
+
+    /// Return the acceptable clock skew for time related claims (exp, nbf, iat) validation.
+    pub fn acceptable_leeway(&self) -> &Duration {
+        &self.acceptable_leeway
+    }
+
+    /// Set whether the expiration time payload claim (exp) is required.
+    ///
+    /// # Arguments
+    ///
+    /// * `expires_at_required` - true if a JWT without exp claim is invalid.
+    pub fn set_expires_at_required(&mut self, expires_at_required: bool) {
+        self.expires_at_required = expires_at_required;
+    }
+
+    /// Test whether the expiration time payload claim (exp) is required.
+    pub fn is_expires_at_required(&self) -> bool {
+        self.expires_at_required
+    }
+
+    /// Set whether a issued at payload claim (iat) later than the base time is invalid.
+    ///
+    /// It is ignored when the maximum issued time is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `future_issued_at_rejected` - true if a JWT issued in the future is invalid.
+    pub fn set_future_issued_at_rejected(&mut self, future_issued_at_rejected: bool) {
+        self.future_issued_at_rejected = future_issued_at_rejected;
+    }
+
+    /// Test whether a issued at payload claim (iat) later than the base time is invalid.
+    pub fn is_future_issued_at_rejected(&self) -> bool {
+        self.future_issued_at_rejected
+    }
+
     /// Set a minimum time for issued at payload claim (iat) validation.
     ///
     /// # Arguments
@@ -71,22 +228,83 @@ impl JwtPayloadValidator {
         self.max_issued_time.as_ref()
     }
 
+    /// Set the range of acceptable values for time related claims (exp, nbf, iat).
+    ///
+    /// The default is from 1970-01-01T00:00:00Z to 9999-12-31T23:59:59Z.
+    ///
+    /// # Arguments
+    ///
+    /// * `min` - a minimum acceptable time.
+    /// * `max` - a maximum acceptable time.
+    pub fn set_numeric_date_range(&mut self, min: SystemTime, max: SystemTime) {
+        self.min_numeric_date = min;
+        self.max_numeric_date = max;
+    }
+
+    /// Return the range of acceptable values for time related claims (exp, nbf, iat).
+    pub fn numeric_date_range(&self) -> (SystemTime, SystemTime) {
+        (self.min_numeric_date, self.max_numeric_date)
+    }
+
     /// Set a value for issuer payload claim (iss) validation.
     ///
     /// # Arguments
     ///
     /// * `value` - a issuer
     pub fn set_issuer(&mut self, value: impl Into<String>) {
-        let value: String = value.into();
-        self.claims.insert("iss".to_string(), Value::String(value));
+        self.issuers = Arc::new(vec![value.into()]);
     }
 
-    /// Return the value for issuer payload claim (iss) validation.
+    /// Return the first acceptable issuer for issuer payload claim (iss) validation.
     pub fn issuer(&self) -> Option<&str> {
-        match self.claims.get("iss") {
-            Some(Value::String(val)) => Some(val),
-            _ => None,
-        }
+        self.issuers.first().map(|val| val.as_str())
+    }
+
+    /// Add a acceptable token type for token type header claim (typ) validation.
+    ///
+    /// Token types are compared as media types, so "at+jwt" also accepts "application/at+jwt".
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - a acceptable token type
+    pub fn add_acceptable_token_type(&mut self, value: impl Into<String>) {
+        Arc::make_mut(&mut self.token_types).push(value.into());
+    }
+
+    /// Return the acceptable token types for token type header claim (typ) validation.
+    pub fn acceptable_token_types(&self) -> &[String] {
+        &self.token_types
+    }
+
+    /// Add a payload claim that must be present.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - a key name of payload claim
+    pub fn add_required_claim(&mut self, key: &str) {
+        Arc::make_mut(&mut self.required_claims).push(key.to_string());
+    }
+
+    /// Return the payload claims that must be present.
+    pub fn required_claims(&self) -> &[String] {
+        &self.required_claims
+    }
+
+    /// Add a acceptable issuer for issuer payload claim (iss) validation.
+    ///
+    /// Issuers are compared by exact string match. URLs are not normalized, so
+    /// "https://example.com" and "https://example.com/" are different issuers.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - a acceptable issuer
+    pub fn add_acceptable_issuer(&mut self, value: impl Into<String>) {
+        Arc::make_mut(&mut self.issuers).push(value.into());
+    }
+
+    /// Return the acceptable issuers for issuer payload claim (iss) validation.
+    pub fn acceptable_issuers(&self) -> &[String] {
+        &self.issuers
     }
 
     /// Set a value for subject payload claim (sub) validation.
@@ -95,34 +313,143 @@ impl JwtPayloadValidator {
     ///
     /// * `value` - a subject
     pub fn set_subject(&mut self, value: impl Into<String>) {
-        let value: String = value.into();
-        self.claims.insert("sub".to_string(), Value::String(value));
+        self.subject = Some(value.into());
     }
 
     /// Return the value for subject payload claim (sub) validation.
     pub fn subject(&self) -> Option<&str> {
-        match self.claims.get("sub") {
-            Some(Value::String(val)) => Some(val),
-            _ => None,
-        }
+        self.subject.as_deref()
+    }
+
+    /// Set a pattern that the whole subject payload claim (sub) must match.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - a subject pattern
+    pub fn set_subject_pattern(&mut self, pattern: Regex) {
+        // Wrapping a valid pattern in a non-capturing group keeps it valid, except that
+        // a trailing comment of a verbose pattern swallows the end of the group. The group
+        // is closed on a new line in that case, where the verbose mode ignores the newline.
+        let anchored = Regex::new(&format!("^(?:{})$", pattern.as_str()))
+            .or_else(|_| Regex::new(&format!("^(?:{}\n)$", pattern.as_str())))
+            .unwrap();
+        self.subject_pattern = Some(pattern);
+        self.anchored_subject_pattern = Some(anchored);
+    }
+
+    /// Return the pattern for subject payload claim (sub) validation.
+    pub fn subject_pattern(&self) -> Option<&Regex> {
+        self.subject_pattern.as_ref()
     }
 
     /// Set a value for audience payload claim (aud) validation.
     ///
+    /// It replaces all acceptable audiences.
+    ///
     /// # Arguments
     ///
     /// * `value` - a audience
     pub fn set_audience(&mut self, value: impl Into<String>) {
-        let value: String = value.into();
-        self.audience = Some(value);
+        self.audiences = Arc::new(vec![value.into()]);
     }
 
-    /// Return the value for audience payload claim (aud) validation.
+    /// Return the first acceptable audience for audience payload claim (aud) validation.
     pub fn audience(&self) -> Option<&str> {
-        match self.audience {
-            Some(ref val) => Some(val),
-            _ => None,
-        }
+        self.audiences.first().map(|val| val.as_str())
+    }
+
+    /// Add a acceptable audience for audience payload claim (aud) validation.
+    ///
+    /// A JWT is valid if any of its audiences is one of the acceptable audiences.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - a acceptable audience
+    pub fn add_acceptable_audience(&mut self, value: impl Into<String>) {
+        Arc::make_mut(&mut self.audiences).push(value.into());
+    }
+
+    /// Return the acceptable audiences for audience payload claim (aud) validation.
+    pub fn acceptable_audiences(&self) -> &[String] {
+        &self.audiences
+    }
+
+    /// Set whether all audiences of a JWT must be acceptable audiences.
+    ///
+    /// # Arguments
+    ///
+    /// * `audience_subset_required` - true if any unacceptable audience makes a JWT invalid.
+    pub fn set_audience_subset_required(&mut self, audience_subset_required: bool) {
+        self.audience_subset_required = audience_subset_required;
+    }
+
+    /// Test whether all audiences of a JWT must be acceptable audiences.
+    pub fn is_audience_subset_required(&self) -> bool {
+        self.audience_subset_required
+    }
+
+    /// Set a expected value for nonce payload claim (nonce) validation.
+    ///
+    /// When it is set, an ID token without nonce claim is invalid as described in
+    /// OpenID Connect Core 1.0 section 3.1.3.7.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - the nonce sent in the authentication request
+    pub fn set_expected_nonce(&mut self, value: impl Into<String>) {
+        self.nonce = Some(value.into());
+    }
+
+    /// Return the expected value for nonce payload claim (nonce) validation.
+    pub fn expected_nonce(&self) -> Option<&str> {
+        self.nonce.as_deref()
+    }
+
+    /// Set a expected value for authorized party payload claim (azp) validation.
+    ///
+    /// As described in OpenID Connect Core 1.0 section 3.1.3.7, azp claim is required
+    /// when aud claim has multiple values, and it must be the client ID if it is present.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - the client ID
+    pub fn set_expected_authorized_party(&mut self, value: impl Into<String>) {
+        self.authorized_party = Some(value.into());
+    }
+
+    /// Return the expected value for authorized party payload claim (azp) validation.
+    pub fn expected_authorized_party(&self) -> Option<&str> {
+        self.authorized_party.as_deref()
+    }
+
+    /// Add a scope that must be in scope payload claim (scope).
+    ///
+    /// The claim is read in the same way as `JwtPayload::scopes`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A required scope
+    pub fn add_required_scope(&mut self, value: impl Into<String>) {
+        Arc::make_mut(&mut self.required_scopes).push(value.into());
+    }
+
+    /// Return the scopes that must be in scope payload claim (scope).
+    pub fn required_scopes(&self) -> &[String] {
+        &self.required_scopes
+    }
+
+    /// Set a proof-of-possession key that confirmation payload claim (cnf) must be bound to.
+    ///
+    /// # Arguments
+    ///
+    /// * `check` - A confirmation method and the key to compare with
+    pub fn set_required_confirmation(&mut self, check: ConfirmationCheck) {
+        self.required_confirmation = Some(check);
+    }
+
+    /// Return the proof-of-possession key that confirmation payload claim (cnf) must be bound to.
+    pub fn required_confirmation(&self) -> Option<&ConfirmationCheck> {
+        self.required_confirmation.as_ref()
     }
 
     /// Set a value for JWT ID payload claim (jti) validation.
@@ -132,7 +459,7 @@ impl JwtPayloadValidator {
     /// * `value` - A JWT ID
     pub fn set_jwt_id(&mut self, value: impl Into<String>) {
         let value: String = value.into();
-        self.claims.insert("jti".to_string(), Value::String(value));
+        Arc::make_mut(&mut self.claims).insert("jti".to_string(), Value::String(value));
     }
 
     /// Return the value for JWT ID payload claim (jti) validation.
@@ -150,7 +477,7 @@ impl JwtPayloadValidator {
     /// * `key` - a key name of payload claim
     /// * `value` - a typed value of payload claim
     pub fn set_claim(&mut self, key: &str, value: Value) {
-        self.claims.insert(key.to_string(), value);
+        Arc::make_mut(&mut self.claims).insert(key.to_string(), value);
     }
 
     /// Return the value for payload claim of a specified key.
@@ -162,61 +489,270 @@ impl JwtPayloadValidator {
         self.claims.get(key)
     }
 
+    /// Add a custom check for payload claim of a specified key.
+    ///
+    /// The checks are invoked in the order of registration after the built-in checks.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - a key name of payload claim
+    /// * `check` - a function that returns an error message if the claim is invalid.
+    pub fn add_claim_check(
+        &mut self,
+        key: &str,
+        check: impl Fn(&Value) -> Result<(), String> + Send + Sync + 'static,
+    ) {
+        Arc::make_mut(&mut self.claim_checks).push((key.to_string(), Arc::new(check)));
+    }
+
+    /// Set whether a JWT that lacks a claim with a custom check is invalid.
+    ///
+    /// # Arguments
+    ///
+    /// * `checked_claim_required` - false if a custom check of a missing claim is skipped.
+    pub fn set_checked_claim_required(&mut self, checked_claim_required: bool) {
+        self.checked_claim_required = checked_claim_required;
+    }
+
+    /// Test whether a JWT that lacks a claim with a custom check is invalid.
+    pub fn is_checked_claim_required(&self) -> bool {
+        self.checked_claim_required
+    }
+
+    /// Set a store for JWT ID payload claim (jti) replay detection.
+    ///
+    /// When it is set, a JWT without jti claim is invalid and a JWT ID is stored
+    /// only if all the other checks succeed.
+    ///
+    /// # Arguments
+    ///
+    /// * `jti_store` - a store of used JWT IDs
+    pub fn set_jti_store(&mut self, jti_store: Arc<dyn JtiStore>) {
+        self.jti_store = Some(jti_store);
+    }
+
+    /// Return the store for JWT ID payload claim (jti) replay detection.
+    pub fn jti_store(&self) -> Option<&dyn JtiStore> {
+        self.jti_store.as_deref()
+    }
+
+    /// Validate token type header claim (typ) of a decoded JWT.
+    ///
+    /// It always succeeds when no acceptable token type is added.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - a decoded JWT header.
+    pub fn validate_token_type(&self, header: &dyn JoseHeader) -> Result<(), JoseError> {
+        if self.token_types.is_empty() {
+            return Ok(());
+        }
+
+        let error = match header.claim("typ") {
+            Some(Value::String(val)) => {
+                if self
+                    .token_types
+                    .iter()
+                    .any(|token_type| util::is_same_media_type(token_type, val))
+                {
+                    return Ok(());
+                }
+                JwtClaimError::InvalidTokenType(val.to_string())
+            }
+            Some(val) => JwtClaimError::InvalidTokenType(val.to_string()),
+            None => JwtClaimError::MissingTokenType,
+        };
+        Err(JoseError::InvalidClaim(error.into()))
+    }
+
     /// Validate a decoded JWT payload.
     ///
+    /// The exp claim must be strictly after the base time minus the acceptable leeway,
+    /// and the nbf claim must be at or before the base time plus the acceptable leeway.
+    /// A time related failure is reported as `JoseError::InvalidClaim` with a `JwtClaimError`
+    /// source.
+    ///
     /// # Arguments
     ///
     /// * `payload` - a decoded JWT payload.
     pub fn validate(&self, payload: &JwtPayload) -> Result<(), JoseError> {
+        self.validate_with_base_time(payload, self.clock.now())
+    }
+
+    /// Validate a decoded JWT payload at a specified base time instead of the clock.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - a decoded JWT payload.
+    /// * `base_time` - a base time for time related claims (exp, nbf, iat) validation.
+    pub fn validate_with_base_time(
+        &self,
+        payload: &JwtPayload,
+        base_time: SystemTime,
+    ) -> Result<(), JoseError> {
         (|| -> anyhow::Result<()> {
-            let now = SystemTime::now();
-            let current_time = self.base_time().unwrap_or(&now);
-            let min_issued_time = self.min_issued_time().unwrap_or(&SystemTime::UNIX_EPOCH);
-            let max_issued_time = self.max_issued_time().unwrap_or(&now);
+            let current_time = base_time;
+
+            for key in self.required_claims.iter() {
+                if payload.claim(key).is_none() {
+                    bail!(JwtClaimError::MissingClaim(key.to_string()));
+                }
+            }
+            let earliest_time = current_time
+                .checked_sub(self.acceptable_leeway)
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            let latest_time = current_time
+                .checked_add(self.acceptable_leeway)
+                .unwrap_or(current_time);
+
+            for (key, value) in &[
+                ("exp", payload.expires_at()),
+                ("nbf", payload.not_before()),
+                ("iat", payload.issued_at()),
+            ] {
+                if let Some(claim) = payload.claim(key) {
+                    match value {
+                        Some(val)
+                            if *val >= self.min_numeric_date && *val <= self.max_numeric_date => {}
+                        _ => bail!(JwtClaimError::OutOfRangeNumericDate(
+                            key.to_string(),
+                            claim.to_string()
+                        )),
+                    }
+                }
+            }
 
             if let Some(not_before) = payload.not_before() {
-                if &not_before > current_time {
-                    bail!(
-                        "The token is not yet valid: {}",
-                        DateTime::<Utc>::from(not_before)
-                    );
+                if not_before > latest_time {
+                    bail!(JwtClaimError::NotYetValid(not_before));
                 }
             }
 
             if let Some(expires_at) = payload.expires_at() {
-                if &expires_at <= current_time {
-                    bail!(
-                        "The token has expired: {}",
-                        DateTime::<Utc>::from(expires_at)
-                    );
+                if expires_at <= earliest_time {
+                    bail!(JwtClaimError::Expired(expires_at));
                 }
+            } else if self.expires_at_required {
+                bail!(JwtClaimError::MissingClaim("exp".to_string()));
             }
 
             if let Some(issued_at) = payload.issued_at() {
-                if &issued_at < min_issued_time {
-                    bail!(
-                        "The issued time is too old: {}",
-                        DateTime::<Utc>::from(issued_at)
-                    );
+                if let Some(min_issued_time) = self.min_issued_time {
+                    if issued_at < min_issued_time {
+                        bail!(JwtClaimError::IssuedTooOld(issued_at));
+                    }
+                }
+
+                let max_issued_time = match self.max_issued_time {
+                    Some(val) => Some(val),
+                    None if self.future_issued_at_rejected => Some(latest_time),
+                    None => None,
+                };
+                if let Some(max_issued_time) = max_issued_time {
+                    if issued_at > max_issued_time {
+                        bail!(JwtClaimError::IssuedTooNew(issued_at));
+                    }
+                }
+            }
+
+            if !self.issuers.is_empty() {
+                let issuer = match payload.issuer() {
+                    Some(val) => val,
+                    None => bail!(JwtClaimError::MissingClaim("iss".to_string())),
+                };
+                if !self.issuers.iter().any(|val| val == issuer) {
+                    bail!(JwtClaimError::InvalidIssuer(issuer.to_string()));
+                }
+            }
+
+            if self.subject.is_some() || self.subject_pattern.is_some() {
+                let subject = match payload.subject() {
+                    Some(val) => val,
+                    None => bail!(JwtClaimError::MissingClaim("sub".to_string())),
+                };
+                if let Some(expected) = &self.subject {
+                    if expected != subject {
+                        bail!(JwtClaimError::InvalidSubject(subject.to_string()));
+                    }
+                }
+                if let Some(pattern) = &self.anchored_subject_pattern {
+                    if !pattern.is_match(subject) {
+                        bail!(JwtClaimError::InvalidSubject(subject.to_string()));
+                    }
+                }
+            }
+
+            if !self.audiences.is_empty() {
+                let audiences = match payload.audience() {
+                    Some(val) => val,
+                    None => bail!(JwtClaimError::MissingClaim("aud".to_string())),
+                };
+
+                let is_acceptable = |val: &&str| self.audiences.iter().any(|aud| aud == val);
+                let is_valid = if self.audience_subset_required {
+                    !audiences.is_empty() && audiences.iter().all(is_acceptable)
+                } else {
+                    audiences.iter().any(is_acceptable)
+                };
+                if !is_valid {
+                    bail!(JwtClaimError::InvalidAudience(
+                        audiences.iter().map(|val| val.to_string()).collect()
+                    ));
+                }
+            }
+
+            if let Some(expected) = &self.nonce {
+                match payload.nonce() {
+                    Some(val) if val == expected => {}
+                    Some(val) => bail!(JwtClaimError::InvalidNonce(val.to_string())),
+                    None => bail!(JwtClaimError::MissingClaim("nonce".to_string())),
                 }
+            }
 
-                if &issued_at > max_issued_time {
-                    bail!(
-                        "The issued time is too new: {}",
-                        DateTime::<Utc>::from(issued_at)
-                    );
+            if let Some(expected) = &self.authorized_party {
+                match payload.authorized_party() {
+                    Some(val) if val == expected => {}
+                    Some(val) => bail!(JwtClaimError::InvalidAuthorizedParty(val.to_string())),
+                    None => {
+                        if let Some(audiences) = payload.audience() {
+                            if audiences.len() > 1 {
+                                bail!(JwtClaimError::MissingClaim("azp".to_string()));
+                            }
+                        }
+                    }
                 }
             }
 
-            if let Some(audience) = &self.audience {
-                if let Some(audiences) = payload.audience() {
-                    if !audiences.contains(&audience.as_str()) {
-                        bail!("Key aud is invalid: {}", audiences.join(", "));
+            if !self.required_scopes.is_empty() {
+                let scopes = match payload.scopes() {
+                    Some(val) => val,
+                    None => bail!(JwtClaimError::MissingClaim("scope".to_string())),
+                };
+                for required_scope in self.required_scopes.iter() {
+                    if !scopes.contains(&required_scope.as_str()) {
+                        bail!(JwtClaimError::MissingScope(required_scope.to_string()));
                     }
                 }
             }
 
-            for (key, value1) in &self.claims {
+            let confirmation = match payload.claim("cnf") {
+                Some(val) => match JwtConfirmation::from_value(val) {
+                    Ok(val2) => Some(val2),
+                    Err(err) => bail!(JwtClaimError::InvalidClaim(
+                        "cnf".to_string(),
+                        err.to_string()
+                    )),
+                },
+                None => None,
+            };
+            if let Some(check) = &self.required_confirmation {
+                match &confirmation {
+                    Some(val) => check.check(val)?,
+                    None => bail!(JwtClaimError::MissingClaim("cnf".to_string())),
+                }
+            }
+
+            for (key, value1) in self.claims.iter() {
                 if let Some(value2) = payload.claim(key) {
                     if value1 != value2 {
                         bail!("Key {} is invalid: {}", key, value2);
@@ -226,6 +762,34 @@ impl JwtPayloadValidator {
                 }
             }
 
+            for (key, check) in self.claim_checks.iter() {
+                match payload.claim(key) {
+                    Some(value) => {
+                        if let Err(message) = check(value) {
+                            bail!(JwtClaimError::InvalidClaim(key.to_string(), message));
+                        }
+                    }
+                    None if self.checked_claim_required => {
+                        bail!(JwtClaimError::MissingClaim(key.to_string()));
+                    }
+                    None => {}
+                }
+            }
+
+            if let Some(jti_store) = &self.jti_store {
+                let jti = match payload.jwt_id() {
+                    Some(val) => val,
+                    None => bail!(JwtClaimError::MissingClaim("jti".to_string())),
+                };
+                // The JWT is accepted until exp plus the leeway, so the JWT ID must be kept as long.
+                let exp = payload
+                    .expires_at()
+                    .map(|val| val.checked_add(self.acceptable_leeway).unwrap_or(val));
+                if !jti_store.check_and_store(jti, exp, current_time)? {
+                    bail!(JwtClaimError::ReplayedJwtId(jti.to_string()));
+                }
+            }
+
             Ok(())
         })()
         .map_err(|err| match err.downcast::<JoseError>() {
@@ -235,14 +799,111 @@ impl JwtPayloadValidator {
     }
 }
 
+/// The clock is compared by the base time when it is set and by identity otherwise.
+/// The claim check functions and the jti store are compared by identity,
+/// and the subject pattern is compared by its source.
+impl PartialEq for JwtPayloadValidator {
+    fn eq(&self, other: &Self) -> bool {
+        let clock_eq = match (&self.base_time, &other.base_time) {
+            (Some(val1), Some(val2)) => val1 == val2,
+            (None, None) => Arc::ptr_eq(&self.clock, &other.clock),
+            _ => false,
+        };
+
+        clock_eq
+            && self.acceptable_leeway == other.acceptable_leeway
+            && self.expires_at_required == other.expires_at_required
+            && self.future_issued_at_rejected == other.future_issued_at_rejected
+            && self.min_issued_time == other.min_issued_time
+            && self.max_issued_time == other.max_issued_time
+            && self.min_numeric_date == other.min_numeric_date
+            && self.max_numeric_date == other.max_numeric_date
+            && self.token_types == other.token_types
+            && self.required_claims == other.required_claims
+            && self.issuers == other.issuers
+            && self.subject == other.subject
+            && self.subject_pattern.as_ref().map(|val| val.as_str())
+                == other.subject_pattern.as_ref().map(|val| val.as_str())
+            && self.audiences == other.audiences
+            && self.audience_subset_required == other.audience_subset_required
+            && self.nonce == other.nonce
+            && self.authorized_party == other.authorized_party
+            && self.required_scopes == other.required_scopes
+            && self.required_confirmation == other.required_confirmation
+            && self.claims == other.claims
+            && self.claim_checks.len() == other.claim_checks.len()
+            && self
+                .claim_checks
+                .iter()
+                .zip(other.claim_checks.iter())
+                .all(|((key1, check1), (key2, check2))| key1 == key2 && Arc::ptr_eq(check1, check2))
+            && self.checked_claim_required == other.checked_claim_required
+            && match (&self.jti_store, &other.jti_store) {
+                (Some(val1), Some(val2)) => Arc::ptr_eq(val1, val2),
+                (None, None) => true,
+                _ => false,
+            }
+    }
+}
+
+impl Eq for JwtPayloadValidator {}
+
+impl Debug for JwtPayloadValidator {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let claim_checks: Vec<&str> = self
+            .claim_checks
+            .iter()
+            .map(|(key, _)| key.as_str())
+            .collect();
+
+        fmt.debug_struct("JwtPayloadValidator")
+            .field("clock", &self.clock)
+            .field("base_time", &self.base_time)
+            .field("acceptable_leeway", &self.acceptable_leeway)
+            .field("expires_at_required", &self.expires_at_required)
+            .field("future_issued_at_rejected", &self.future_issued_at_rejected)
+            .field("min_issued_time", &self.min_issued_time)
+            .field("max_issued_time", &self.max_issued_time)
+            .field("min_numeric_date", &self.min_numeric_date)
+            .field("max_numeric_date", &self.max_numeric_date)
+            .field("token_types", &self.token_types)
+            .field("required_claims", &self.required_claims)
+            .field("issuers", &self.issuers)
+            .field("subject", &self.subject)
+            .field("subject_pattern", &self.subject_pattern)
+            .field("audiences", &self.audiences)
+            .field("audience_subset_required", &self.audience_subset_required)
+            .field("nonce", &self.nonce)
+            .field("authorized_party", &self.authorized_party)
+            .field("required_scopes", &self.required_scopes)
+            .field("required_confirmation", &self.required_confirmation)
+            .field("claims", &self.claims)
+            .field("claim_checks", &claim_checks)
+            .field("checked_claim_required", &self.checked_claim_required)
+            .field("jti_store", &self.jti_store)
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
     use std::time::{Duration, SystemTime};
 
     use anyhow::Result;
+    use openssl::hash::{self, MessageDigest};
+    use regex::Regex;
     use serde_json::json;
 
-    use crate::jwt::{JwtPayload, JwtPayloadValidator};
+    use crate::jwk::alg::ec::EcCurve;
+    use crate::jwk::Jwk;
+    use crate::jws::JwsHeader;
+    use crate::jwt::{
+        ConfirmationCheck, JwtClaimError, JwtPayload, JwtPayloadValidator, MemoryJtiStore,
+    };
+    use crate::util::HashAlgorithm;
+    use crate::{Clock, FixedClock, JoseError, SkewedClock};
 
     #[test]
     fn test_jwt_payload_validate() -> Result<()> {
@@ -265,4 +926,534 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_jwt_payload_validate_time_claims() -> Result<()> {
+        let at = |secs: u64| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        let payload = JwtPayload::from_bytes(br#"{"exp":100.5,"nbf":50,"iat":40}"#)?;
+        let mut validator = JwtPayloadValidator::new();
+
+        validator.set_base_time(at(100));
+        validator.validate(&payload)?;
+
+        validator.set_base_time(at(101));
+        assert_eq!(
+            claim_error(validator.validate(&payload)),
+            Some(JwtClaimError::Expired(at(100) + Duration::from_millis(500)))
+        );
+        validator.set_acceptable_leeway(Duration::from_secs(1));
+        validator.validate(&payload)?;
+
+        validator.set_acceptable_leeway(Duration::from_secs(0));
+        validator.set_base_time(at(49));
+        assert_eq!(
+            claim_error(validator.validate(&payload)),
+            Some(JwtClaimError::NotYetValid(at(50)))
+        );
+        validator.set_acceptable_leeway(Duration::from_secs(1));
+        validator.validate(&payload)?;
+
+        validator.set_acceptable_leeway(Duration::from_secs(0));
+        validator.set_base_time(at(39));
+        let payload = JwtPayload::from_bytes(br#"{"iat":40}"#)?;
+        assert_eq!(
+            claim_error(validator.validate(&payload)),
+            Some(JwtClaimError::IssuedTooNew(at(40)))
+        );
+        validator.set_future_issued_at_rejected(false);
+        validator.validate(&payload)?;
+
+        validator.set_expires_at_required(true);
+        assert_eq!(
+            claim_error(validator.validate(&payload)),
+            Some(JwtClaimError::MissingClaim("exp".to_string()))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_payload_validate_audience() -> Result<()> {
+        let mut validator = JwtPayloadValidator::new();
+        validator.add_acceptable_audience("aud0");
+        validator.add_acceptable_audience("aud1");
+        assert_eq!(validator.audience(), Some("aud0"));
+
+        validator.validate(&JwtPayload::from_bytes(br#"{"aud":"aud1"}"#)?)?;
+        validator.validate(&JwtPayload::from_bytes(br#"{"aud":["aud2","aud0"]}"#)?)?;
+        assert_eq!(
+            claim_error(validator.validate(&JwtPayload::from_bytes(br#"{"aud":"AUD0"}"#)?)),
+            Some(JwtClaimError::InvalidAudience(vec!["AUD0".to_string()]))
+        );
+        assert_eq!(
+            claim_error(validator.validate(&JwtPayload::from_bytes(br#"{"aud":[]}"#)?)),
+            Some(JwtClaimError::InvalidAudience(vec![]))
+        );
+        assert_eq!(
+            claim_error(validator.validate(&JwtPayload::new())),
+            Some(JwtClaimError::MissingClaim("aud".to_string()))
+        );
+
+        validator.set_audience_subset_required(true);
+        validator.validate(&JwtPayload::from_bytes(br#"{"aud":["aud1","aud0"]}"#)?)?;
+        assert_eq!(
+            claim_error(
+                validator.validate(&JwtPayload::from_bytes(br#"{"aud":["aud2","aud0"]}"#)?)
+            ),
+            Some(JwtClaimError::InvalidAudience(vec![
+                "aud2".to_string(),
+                "aud0".to_string()
+            ]))
+        );
+        assert_eq!(
+            claim_error(validator.validate(&JwtPayload::from_bytes(br#"{"aud":[]}"#)?)),
+            Some(JwtClaimError::InvalidAudience(vec![]))
+        );
+
+        validator.set_audience("aud2");
+        assert_eq!(validator.acceptable_audiences(), &["aud2".to_string()]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_payload_validate_issuer_and_subject() -> Result<()> {
+        let mut validator = JwtPayloadValidator::new();
+        validator.add_acceptable_issuer("https://a.example.com");
+        validator.add_acceptable_issuer("https://b.example.com");
+        validator.set_subject_pattern(Regex::new(r"user:[0-9]+")?);
+
+        validator.validate(&JwtPayload::from_bytes(
+            br#"{"iss":"https://b.example.com","sub":"user:42"}"#,
+        )?)?;
+        assert_eq!(
+            claim_error(validator.validate(&JwtPayload::from_bytes(
+                br#"{"iss":"https://b.example.com/","sub":"user:42"}"#
+            )?)),
+            Some(JwtClaimError::InvalidIssuer(
+                "https://b.example.com/".to_string()
+            ))
+        );
+        assert_eq!(
+            claim_error(validator.validate(&JwtPayload::from_bytes(br#"{"sub":"user:42"}"#)?)),
+            Some(JwtClaimError::MissingClaim("iss".to_string()))
+        );
+        assert_eq!(
+            claim_error(validator.validate(&JwtPayload::from_bytes(
+                br#"{"iss":"https://a.example.com","sub":"user:42x"}"#
+            )?)),
+            Some(JwtClaimError::InvalidSubject("user:42x".to_string()))
+        );
+
+        validator.set_subject("user:1");
+        assert_eq!(
+            claim_error(validator.validate(&JwtPayload::from_bytes(
+                br#"{"iss":"https://a.example.com","sub":"user:42"}"#
+            )?)),
+            Some(JwtClaimError::InvalidSubject("user:42".to_string()))
+        );
+        assert_eq!(
+            claim_error(validator.validate(&JwtPayload::from_bytes(
+                br#"{"iss":"https://a.example.com"}"#
+            )?)),
+            Some(JwtClaimError::MissingClaim("sub".to_string()))
+        );
+
+        // The whole subject is matched even if an earlier alternative matches a prefix.
+        let mut validator = JwtPayloadValidator::new();
+        validator.set_subject_pattern(Regex::new(r"user|user:[0-9]+")?);
+        validator.validate(&JwtPayload::from_bytes(br#"{"sub":"user:42"}"#)?)?;
+        assert_eq!(
+            claim_error(validator.validate(&JwtPayload::from_bytes(br#"{"sub":"xuser"}"#)?)),
+            Some(JwtClaimError::InvalidSubject("xuser".to_string()))
+        );
+        assert_eq!(
+            validator.subject_pattern().map(|val| val.as_str()),
+            Some(r"user|user:[0-9]+")
+        );
+
+        // A trailing comment of a verbose pattern does not break the anchoring.
+        validator.set_subject_pattern(Regex::new("(?x) user : [0-9]+ # numeric id")?);
+        validator.validate(&JwtPayload::from_bytes(br#"{"sub":"user:42"}"#)?)?;
+        assert_eq!(
+            claim_error(validator.validate(&JwtPayload::from_bytes(br#"{"sub":"user:42x"}"#)?)),
+            Some(JwtClaimError::InvalidSubject("user:42x".to_string()))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_payload_validator_eq() -> Result<()> {
+        let mut validator = JwtPayloadValidator::new();
+        validator.add_acceptable_issuer("https://a.example.com");
+        validator.set_subject_pattern(Regex::new(r"user:[0-9]+")?);
+        validator.add_claim_check("scope", |_| Ok(()));
+        assert_eq!(validator.clone(), validator);
+
+        let mut other = validator.clone();
+        other.set_subject_pattern(Regex::new(r"user:[a-z]+")?);
+        assert_ne!(other, validator);
+
+        let mut other = validator.clone();
+        other.add_claim_check("scope", |_| Ok(()));
+        assert_ne!(other, validator);
+
+        assert_eq!(JwtPayloadValidator::new(), JwtPayloadValidator::new());
+
+        let base_time = SystemTime::UNIX_EPOCH + Duration::from_secs(30);
+        let mut validator1 = JwtPayloadValidator::new();
+        validator1.set_base_time(base_time);
+        let mut validator2 = JwtPayloadValidator::new();
+        validator2.set_base_time(base_time);
+        assert_eq!(validator1, validator2);
+
+        validator2.set_base_time(base_time + Duration::from_secs(1));
+        assert_ne!(validator1, validator2);
+
+        validator2.set_clock(Arc::new(FixedClock(base_time)));
+        assert_ne!(validator1, validator2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_payload_validate_scope() -> Result<()> {
+        let mut validator = JwtPayloadValidator::new();
+        validator.add_required_scope("read");
+        validator.add_required_scope("write");
+
+        validator.validate(&JwtPayload::from_bytes(br#"{"scope":"write read"}"#)?)?;
+        validator.validate(&JwtPayload::from_bytes(br#"{"scp":["read","write"]}"#)?)?;
+        assert_eq!(
+            claim_error(validator.validate(&JwtPayload::from_bytes(br#"{"scope":"read"}"#)?)),
+            Some(JwtClaimError::MissingScope("write".to_string()))
+        );
+        assert_eq!(
+            claim_error(validator.validate(&JwtPayload::new())),
+            Some(JwtClaimError::MissingClaim("scope".to_string()))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_payload_validate_confirmation() -> Result<()> {
+        let jwk = Jwk::generate_ec_key(EcCurve::P256)?.to_public_key()?;
+        let other_jwk = Jwk::generate_ec_key(EcCurve::P256)?.to_public_key()?;
+        let thumbprint = base64::encode_config(
+            &jwk.thumbprint(HashAlgorithm::Sha256)?,
+            base64::URL_SAFE_NO_PAD,
+        );
+
+        let mut payload = JwtPayload::new();
+        payload.set_confirmation_jwk_thumbprint(thumbprint);
+
+        let mut validator = JwtPayloadValidator::new();
+        validator.validate(&payload)?;
+        validator.set_required_confirmation(ConfirmationCheck::JwkThumbprint(jwk.clone()));
+        validator.validate(&payload)?;
+        assert_eq!(
+            claim_error(validator.validate(&JwtPayload::new())),
+            Some(JwtClaimError::MissingClaim("cnf".to_string()))
+        );
+
+        validator.set_required_confirmation(ConfirmationCheck::JwkThumbprint(other_jwk.clone()));
+        assert_eq!(
+            claim_error(validator.validate(&payload)),
+            Some(JwtClaimError::UnconfirmedKey("jkt".to_string()))
+        );
+
+        payload.set_confirmation_jwk(jwk.clone())?;
+        validator.set_required_confirmation(ConfirmationCheck::Jwk(jwk));
+        validator.validate(&payload)?;
+        validator.set_required_confirmation(ConfirmationCheck::Jwk(other_jwk));
+        assert_eq!(
+            claim_error(validator.validate(&payload)),
+            Some(JwtClaimError::UnconfirmedKey("jwk".to_string()))
+        );
+
+        let der = b"certificate".to_vec();
+        payload.set_confirmation_x509_thumbprint(hash::hash(MessageDigest::sha256(), &der)?);
+        validator.set_required_confirmation(ConfirmationCheck::X509Certificate(der));
+        validator.validate(&payload)?;
+        validator.set_required_confirmation(ConfirmationCheck::KeyId("kid".to_string()));
+        assert_eq!(
+            claim_error(validator.validate(&payload)),
+            Some(JwtClaimError::UnconfirmedKey("kid".to_string()))
+        );
+
+        let payload = JwtPayload::from_bytes(br#"{"cnf":{"kid":1}}"#)?;
+        assert_eq!(
+            claim_error(JwtPayloadValidator::new().validate(&payload)),
+            Some(JwtClaimError::InvalidClaim(
+                "cnf".to_string(),
+                "The kid confirmation method must be a string.".to_string()
+            ))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_payload_validate_nonce_and_authorized_party() -> Result<()> {
+        let mut validator = JwtPayloadValidator::for_id_token();
+        validator.add_acceptable_issuer("https://server.example.com");
+        validator.add_acceptable_audience("client");
+        validator.set_expected_nonce("n-0S6_WzA2Mj");
+        validator.set_expected_authorized_party("client");
+
+        let id_token = |extra: &str| {
+            JwtPayload::from_bytes(format!(
+                r#"{{"iss":"https://server.example.com","sub":"24400320","exp":1311281970,"iat":1311280970{}}}"#,
+                extra
+            ))
+        };
+        let base_time = SystemTime::UNIX_EPOCH + Duration::from_secs(1311281000);
+
+        for (extra, expected) in vec![
+            (r#","aud":"client","nonce":"n-0S6_WzA2Mj""#, None),
+            (
+                r#","aud":"client","nonce":"n-0S6_WzA2Mj","azp":"client""#,
+                None,
+            ),
+            (
+                r#","aud":["client","other"],"nonce":"n-0S6_WzA2Mj","azp":"client""#,
+                None,
+            ),
+            (
+                r#","aud":"client""#,
+                Some(JwtClaimError::MissingClaim("nonce".to_string())),
+            ),
+            (
+                r#","aud":"client","nonce":"n-0S6_WzA2Mj-invalid""#,
+                Some(JwtClaimError::InvalidNonce(
+                    "n-0S6_WzA2Mj-invalid".to_string(),
+                )),
+            ),
+            (
+                r#","aud":["client","other"],"nonce":"n-0S6_WzA2Mj""#,
+                Some(JwtClaimError::MissingClaim("azp".to_string())),
+            ),
+            (
+                r#","aud":"client","nonce":"n-0S6_WzA2Mj","azp":"other""#,
+                Some(JwtClaimError::InvalidAuthorizedParty("other".to_string())),
+            ),
+        ] {
+            let result = validator.validate_with_base_time(&id_token(extra)?, base_time);
+            match expected {
+                Some(val) => assert_eq!(claim_error(result), Some(val)),
+                None => result?,
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_payload_validate_profiles() -> Result<()> {
+        let mut payload = JwtPayload::new();
+        payload.set_issuer("iss");
+        payload.set_subject("sub");
+        payload.set_audience(vec!["aud"]);
+        payload.set_issued_at(&SystemTime::UNIX_EPOCH);
+        payload.set_expires_at(&(SystemTime::UNIX_EPOCH + Duration::from_secs(60)));
+
+        let mut validator = JwtPayloadValidator::for_id_token();
+        validator.add_acceptable_audience("aud");
+        validator.validate_with_base_time(&payload, SystemTime::UNIX_EPOCH)?;
+        validator.validate_token_type(&JwsHeader::new())?;
+
+        let mut validator = JwtPayloadValidator::for_access_token();
+        validator.add_acceptable_issuer("iss");
+        assert_eq!(
+            claim_error(validator.validate_with_base_time(&payload, SystemTime::UNIX_EPOCH)),
+            Some(JwtClaimError::MissingClaim("client_id".to_string()))
+        );
+        payload.set_claim("client_id", Some(json!("client")))?;
+        payload.set_jwt_id("jti");
+        validator.validate_with_base_time(&payload, SystemTime::UNIX_EPOCH)?;
+
+        let mut header = JwsHeader::new();
+        assert_eq!(
+            claim_error(validator.validate_token_type(&header)),
+            Some(JwtClaimError::MissingTokenType)
+        );
+        header.set_token_type("AT+JWT");
+        validator.validate_token_type(&header)?;
+        header.set_token_type("JWT");
+        assert_eq!(
+            claim_error(validator.validate_token_type(&header)),
+            Some(JwtClaimError::InvalidTokenType("JWT".to_string()))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_payload_validate_with_clock() -> Result<()> {
+        #[derive(Debug)]
+        struct StepClock(AtomicU64);
+
+        impl Clock for StepClock {
+            fn now(&self) -> SystemTime {
+                SystemTime::UNIX_EPOCH + Duration::from_secs(self.0.load(Ordering::SeqCst))
+            }
+        }
+
+        let clock = Arc::new(StepClock(AtomicU64::new(59)));
+        let payload = JwtPayload::from_bytes(br#"{"exp":60}"#)?;
+
+        let mut validator = JwtPayloadValidator::new();
+        validator.set_clock(clock.clone());
+        validator.validate(&payload)?;
+
+        clock.0.store(60, Ordering::SeqCst);
+        assert!(matches!(
+            validator.validate(&payload),
+            Err(JoseError::InvalidClaim(_))
+        ));
+
+        validator.set_clock(Arc::new(FixedClock(
+            SystemTime::UNIX_EPOCH + Duration::from_secs(30),
+        )));
+        validator.validate(&payload)?;
+
+        validator.set_clock(Arc::new(SkewedClock::behind(Duration::from_secs(60))));
+        assert!(validator.clock().now() < SystemTime::now());
+        assert!(matches!(
+            validator.validate(&payload),
+            Err(JoseError::InvalidClaim(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_payload_validate_with_claim_check() -> Result<()> {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+        let permission = "write".to_string();
+        let mut validator = JwtPayloadValidator::new();
+        validator.add_claim_check("scope", move |value| match value.as_str() {
+            Some(val) if val.split(' ').any(|val| val == permission) => Ok(()),
+            _ => Err("write permission is required".to_string()),
+        });
+        assert_send_sync(&validator);
+
+        validator.validate(&JwtPayload::from_bytes(br#"{"scope":"read write"}"#)?)?;
+        assert_eq!(
+            claim_error(validator.validate(&JwtPayload::from_bytes(br#"{"scope":"read"}"#)?)),
+            Some(JwtClaimError::InvalidClaim(
+                "scope".to_string(),
+                "write permission is required".to_string()
+            ))
+        );
+        assert_eq!(
+            claim_error(validator.validate(&JwtPayload::new())),
+            Some(JwtClaimError::MissingClaim("scope".to_string()))
+        );
+
+        validator.set_checked_claim_required(false);
+        validator.validate(&JwtPayload::new())?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_payload_validate_with_jti_store() -> Result<()> {
+        let mut validator = JwtPayloadValidator::new();
+        validator.set_base_time(SystemTime::UNIX_EPOCH);
+        validator.set_issuer("iss");
+        validator.set_jti_store(Arc::new(MemoryJtiStore::new()));
+
+        let payload = JwtPayload::from_bytes(br#"{"iss":"iss","jti":"jti0","exp":60}"#)?;
+        let invalid_payload = JwtPayload::from_bytes(br#"{"iss":"other","jti":"jti1"}"#)?;
+        assert_eq!(
+            claim_error(validator.validate(&invalid_payload)),
+            Some(JwtClaimError::InvalidIssuer("other".to_string()))
+        );
+
+        validator.validate(&payload)?;
+        assert_eq!(
+            claim_error(validator.validate(&payload)),
+            Some(JwtClaimError::ReplayedJwtId("jti0".to_string()))
+        );
+        validator.validate(&JwtPayload::from_bytes(br#"{"iss":"iss","jti":"jti1"}"#)?)?;
+        assert_eq!(
+            claim_error(validator.validate(&JwtPayload::from_bytes(br#"{"iss":"iss"}"#)?)),
+            Some(JwtClaimError::MissingClaim("jti".to_string()))
+        );
+
+        // The JWT ID is kept while the JWT is accepted within the leeway.
+        let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        let payload = JwtPayload::from_bytes(br#"{"iss":"iss","jti":"jti2","exp":60}"#)?;
+        validator.set_acceptable_leeway(Duration::from_secs(10));
+        validator.validate(&payload)?;
+        assert_eq!(
+            claim_error(validator.validate_with_base_time(&payload, at(65))),
+            Some(JwtClaimError::ReplayedJwtId("jti2".to_string()))
+        );
+        validator.set_clock(Arc::new(FixedClock(at(69))));
+        assert_eq!(
+            claim_error(validator.validate(&payload)),
+            Some(JwtClaimError::ReplayedJwtId("jti2".to_string()))
+        );
+        assert_eq!(
+            claim_error(validator.validate_with_base_time(&payload, at(70))),
+            Some(JwtClaimError::Expired(at(60)))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_payload_validate_numeric_date_range() -> Result<()> {
+        let mut validator = JwtPayloadValidator::new();
+        validator.set_base_time(SystemTime::UNIX_EPOCH + Duration::from_secs(1700000000));
+
+        let payload = JwtPayload::from_bytes(br#"{"exp":9.9e18}"#)?;
+        assert_eq!(
+            claim_error(validator.validate(&payload)),
+            Some(JwtClaimError::OutOfRangeNumericDate(
+                "exp".to_string(),
+                "9.9e+18".to_string()
+            ))
+        );
+        let payload = JwtPayload::from_bytes(br#"{"exp":18446744073709551615}"#)?;
+        assert!(claim_error(validator.validate(&payload)).is_some());
+
+        let payload = JwtPayload::from_bytes(br#"{"exp":253402300799,"iat":1699999999.5}"#)?;
+        validator.validate(&payload)?;
+        assert_eq!(
+            payload.issued_at(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_millis(1699999999500))
+        );
+
+        validator.set_numeric_date_range(
+            SystemTime::UNIX_EPOCH,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(2000000000),
+        );
+        assert_eq!(
+            claim_error(validator.validate(&payload)),
+            Some(JwtClaimError::OutOfRangeNumericDate(
+                "exp".to_string(),
+                "253402300799".to_string()
+            ))
+        );
+
+        let mut payload = JwtPayload::new();
+        payload.set_issued_at(&(SystemTime::UNIX_EPOCH + Duration::from_millis(1500)));
+        payload.set_expires_at(&(SystemTime::UNIX_EPOCH + Duration::from_millis(2000)));
+        assert_eq!(payload.to_string(), r#"{"iat":1.5,"exp":2}"#);
+
+        Ok(())
+    }
+
+    fn claim_error(result: Result<(), JoseError>) -> Option<JwtClaimError> {
+        match result {
+            Err(JoseError::InvalidClaim(err)) => err.downcast::<JwtClaimError>().ok(),
+            _ => None,
+        }
+    }
 }
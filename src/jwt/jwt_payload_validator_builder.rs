@@ -0,0 +1,356 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use regex::Regex;
+
+use crate::jwt::{ConfirmationCheck, JtiStore, JwtPayloadValidator};
+use crate::{Clock, Value};
+
+/// Represent a builder for JwtPayloadValidator.
+///
+/// Each method corresponds to a setter of `JwtPayloadValidator` and returns the builder,
+/// so the settings can be chained.
+#[derive(Debug, Clone)]
+pub struct JwtPayloadValidatorBuilder {
+    validator: JwtPayloadValidator,
+}
+
+impl JwtPayloadValidatorBuilder {
+    /// Return a new builder that has the default settings of `JwtPayloadValidator::new`.
+    pub fn new() -> Self {
+        Self {
+            validator: JwtPayloadValidator::new(),
+        }
+    }
+
+    /// Set a clock for time related claims (exp, nbf, iat) validation.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - a clock
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.validator.set_clock(clock);
+        self
+    }
+
+    /// Set a base time for time related claims (exp, nbf) validation.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_time` - a min time
+    pub fn base_time(mut self, base_time: SystemTime) -> Self {
+        self.validator.set_base_time(base_time);
+        self
+    }
+
+    /// Set an acceptable clock skew for time related claims (exp, nbf, iat) validation.
+    ///
+    /// # Arguments
+    ///
+    /// * `acceptable_leeway` - an acceptable clock skew.
+    pub fn acceptable_leeway(mut self, acceptable_leeway: Duration) -> Self {
+        self.validator.set_acceptable_leeway(acceptable_leeway);
+        self
+    }
+
+    /// Set whether a JWT without expires at payload claim (exp) is invalid.
+    ///
+    /// # Arguments
+    ///
+    /// * `expires_at_required` - true if exp claim is required.
+    pub fn expires_at_required(mut self, expires_at_required: bool) -> Self {
+        self.validator.set_expires_at_required(expires_at_required);
+        self
+    }
+
+    /// Set whether issued at payload claim (iat) in the future is invalid.
+    ///
+    /// # Arguments
+    ///
+    /// * `future_issued_at_rejected` - false if iat claim in the future is acceptable.
+    pub fn future_issued_at_rejected(mut self, future_issued_at_rejected: bool) -> Self {
+        self.validator
+            .set_future_issued_at_rejected(future_issued_at_rejected);
+        self
+    }
+
+    /// Set a minimum time for issued at payload claim (iat) validation.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_issued_time` - a minimum time at which the JWT was issued.
+    pub fn min_issued_time(mut self, min_issued_time: SystemTime) -> Self {
+        self.validator.set_min_issued_time(min_issued_time);
+        self
+    }
+
+    /// Set a maximum time for issued at payload claim (iat) validation.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_issued_time` - a maximum time at which the JWT was issued.
+    pub fn max_issued_time(mut self, max_issued_time: SystemTime) -> Self {
+        self.validator.set_max_issued_time(max_issued_time);
+        self
+    }
+
+    /// Set an acceptable range for NumericDate payload claims (exp, nbf, iat).
+    ///
+    /// # Arguments
+    ///
+    /// * `min` - a minimum acceptable time
+    /// * `max` - a maximum acceptable time
+    pub fn numeric_date_range(mut self, min: SystemTime, max: SystemTime) -> Self {
+        self.validator.set_numeric_date_range(min, max);
+        self
+    }
+
+    /// Add an acceptable value for token type header claim (typ).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - An acceptable token type
+    pub fn acceptable_token_type(mut self, value: impl Into<String>) -> Self {
+        self.validator.add_acceptable_token_type(value);
+        self
+    }
+
+    /// Add a payload claim that must be present.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - a key name of payload claim
+    pub fn required_claim(mut self, key: &str) -> Self {
+        self.validator.add_required_claim(key);
+        self
+    }
+
+    /// Add an acceptable value for issuer payload claim (iss).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - An acceptable issuer
+    pub fn acceptable_issuer(mut self, value: impl Into<String>) -> Self {
+        self.validator.add_acceptable_issuer(value);
+        self
+    }
+
+    /// Set a value for subject payload claim (sub) validation.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A subject
+    pub fn subject(mut self, value: impl Into<String>) -> Self {
+        self.validator.set_subject(value);
+        self
+    }
+
+    /// Set a pattern that subject payload claim (sub) must match as a whole.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - A regular expression for subject
+    pub fn subject_pattern(mut self, pattern: Regex) -> Self {
+        self.validator.set_subject_pattern(pattern);
+        self
+    }
+
+    /// Add an acceptable value for audience payload claim (aud).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - An acceptable audience
+    pub fn acceptable_audience(mut self, value: impl Into<String>) -> Self {
+        self.validator.add_acceptable_audience(value);
+        self
+    }
+
+    /// Set whether all the audiences in audience payload claim (aud) must be acceptable.
+    ///
+    /// # Arguments
+    ///
+    /// * `audience_subset_required` - true if every audience must be acceptable.
+    pub fn audience_subset_required(mut self, audience_subset_required: bool) -> Self {
+        self.validator
+            .set_audience_subset_required(audience_subset_required);
+        self
+    }
+
+    /// Set a expected value for nonce payload claim (nonce) validation.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - the nonce sent in the authentication request
+    pub fn expected_nonce(mut self, value: impl Into<String>) -> Self {
+        self.validator.set_expected_nonce(value);
+        self
+    }
+
+    /// Set a expected value for authorized party payload claim (azp) validation.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - the client ID
+    pub fn expected_authorized_party(mut self, value: impl Into<String>) -> Self {
+        self.validator.set_expected_authorized_party(value);
+        self
+    }
+
+    /// Add a scope that must be in scope payload claim (scope).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A required scope
+    pub fn required_scope(mut self, value: impl Into<String>) -> Self {
+        self.validator.add_required_scope(value);
+        self
+    }
+
+    /// Set a proof-of-possession key that confirmation payload claim (cnf) must be bound to.
+    ///
+    /// # Arguments
+    ///
+    /// * `check` - A confirmation method and the key to compare with
+    pub fn required_confirmation(mut self, check: ConfirmationCheck) -> Self {
+        self.validator.set_required_confirmation(check);
+        self
+    }
+
+    /// Set a value for JWT ID payload claim (jti) validation.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A JWT ID
+    pub fn jwt_id(mut self, value: impl Into<String>) -> Self {
+        self.validator.set_jwt_id(value);
+        self
+    }
+
+    /// Set a value for payload claim of a specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - a key name of payload claim
+    /// * `value` - a typed value of payload claim
+    pub fn claim(mut self, key: &str, value: Value) -> Self {
+        self.validator.set_claim(key, value);
+        self
+    }
+
+    /// Add a custom check for payload claim of a specified key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - a key name of payload claim
+    /// * `check` - a function that returns an error message if the claim is invalid.
+    pub fn claim_check(
+        mut self,
+        key: &str,
+        check: impl Fn(&Value) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        self.validator.add_claim_check(key, check);
+        self
+    }
+
+    /// Set whether a JWT that lacks a claim with a custom check is invalid.
+    ///
+    /// # Arguments
+    ///
+    /// * `checked_claim_required` - false if a custom check of a missing claim is skipped.
+    pub fn checked_claim_required(mut self, checked_claim_required: bool) -> Self {
+        self.validator
+            .set_checked_claim_required(checked_claim_required);
+        self
+    }
+
+    /// Set a store for JWT ID payload claim (jti) replay detection.
+    ///
+    /// # Arguments
+    ///
+    /// * `jti_store` - a store of used JWT IDs
+    pub fn jti_store(mut self, jti_store: Arc<dyn JtiStore>) -> Self {
+        self.validator.set_jti_store(jti_store);
+        self
+    }
+
+    /// Return the validator that has the settings of this builder.
+    pub fn build(self) -> JwtPayloadValidator {
+        self.validator
+    }
+}
+
+impl Default for JwtPayloadValidatorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<JwtPayloadValidator> for JwtPayloadValidatorBuilder {
+    fn from(validator: JwtPayloadValidator) -> Self {
+        Self { validator }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, SystemTime};
+
+    use anyhow::Result;
+    use serde_json::json;
+
+    use crate::jwt::{JwtPayload, JwtPayloadValidator, JwtPayloadValidatorBuilder, MemoryJtiStore};
+
+    #[test]
+    fn test_jwt_payload_validator_builder() -> Result<()> {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<JwtPayloadValidator>();
+
+        let at = |secs: u64| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        let shared = JwtPayloadValidator::builder()
+            .acceptable_audience("api")
+            .acceptable_leeway(Duration::from_secs(5))
+            .expires_at_required(true)
+            .required_scope("read")
+            .claim_check("tenant", |value| match value.as_str() {
+                Some(_) => Ok(()),
+                None => Err("must be a string".to_string()),
+            })
+            .jti_store(Arc::new(MemoryJtiStore::new()))
+            .build();
+        assert_eq!(shared.acceptable_leeway(), &Duration::from_secs(5));
+
+        let mut issuer1 = shared.clone();
+        issuer1.add_acceptable_issuer("issuer1");
+        let issuer2 = JwtPayloadValidatorBuilder::from(shared.clone())
+            .acceptable_issuer("issuer2")
+            .build();
+        assert!(shared.acceptable_issuers().is_empty());
+        assert_eq!(issuer1.acceptable_issuers(), &["issuer1".to_string()]);
+        assert_eq!(issuer2.acceptable_issuers(), &["issuer2".to_string()]);
+
+        let mut payload = JwtPayload::new();
+        payload.set_issuer("issuer2");
+        payload.set_audience(vec!["api"]);
+        payload.set_expires_at(&at(100));
+        payload.set_scopes(&["read"])?;
+        payload.set_claim("tenant", Some(json!("tenant")))?;
+
+        let handle = thread::spawn(move || -> Result<()> {
+            for (i, base_time) in [at(50), at(104)].iter().enumerate() {
+                payload.set_jwt_id(format!("jti{}", i));
+                issuer2.validate_with_base_time(&payload, *base_time)?;
+                assert!(issuer1
+                    .validate_with_base_time(&payload, *base_time)
+                    .is_err());
+            }
+            payload.set_jwt_id("jti2");
+            assert!(issuer2.validate_with_base_time(&payload, at(105)).is_err());
+            Ok(())
+        });
+        handle.join().unwrap()?;
+
+        Ok(())
+    }
+}
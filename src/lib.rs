@@ -1,6 +1,30 @@
 //! # josekit
 //!
 //! `josekit` is a JOSE (Javascript Object Signing and Encryption: JWT, JWS, JWE, JWA, JWK) library.
+//!
+//! ## Signing and verifying a JWT
+//!
+//! ```rust
+//! use josekit::jws::{JwsHeader, HS256};
+//! use josekit::jwt::{self, JwtPayload};
+//!
+//! fn main() -> Result<(), josekit::JoseError> {
+//!     let key = b"0123456789ABCDEF0123456789ABCDEF";
+//!
+//!     let mut payload = JwtPayload::new();
+//!     payload.set_subject("subject");
+//!
+//!     // The alg, kid and typ header claims are set automatically.
+//!     let signer = HS256.signer_from_bytes(key)?;
+//!     let jwt = jwt::encode_with_signer(&payload, &JwsHeader::new(), &signer)?;
+//!
+//!     let verifier = HS256.verifier_from_bytes(key)?;
+//!     let (payload, header) = jwt::decode_with_verifier(&jwt, &verifier)?;
+//!     assert_eq!(payload.subject(), Some("subject"));
+//!     assert_eq!(header.token_type(), Some("JWT"));
+//!     Ok(())
+//! }
+//! ```
 
 pub mod jwe;
 pub mod jwk;
@@ -8,11 +32,15 @@ pub mod jws;
 pub mod jwt;
 pub mod util;
 
+mod capabilities;
+mod clock;
 mod jose_error;
 mod jose_header;
 
+pub use crate::capabilities::{capabilities, Capabilities};
+pub use crate::clock::{Clock, FixedClock, SkewedClock, SystemClock};
 pub use crate::jose_error::JoseError;
-pub use crate::jose_header::JoseHeader;
+pub use crate::jose_header::{ConflictPolicy, JoseHeader};
 
 pub use serde_json::{Map, Number, Value};
 
@@ -42,13 +42,12 @@ impl DerBuilder {
     }
 
     pub fn append_integer_from_u64(&mut self, value: u64) {
-        let mut vec = Vec::new();
-        let mut rest = value;
-        while rest > 0 {
-            vec.push((rest & 0xFF) as u8);
-            rest >>= 8;
-        }
-        self.append(DerType::Integer, None, &vec);
+        let bytes = value.to_be_bytes();
+        let start = bytes
+            .iter()
+            .position(|b| *b != 0)
+            .unwrap_or(bytes.len() - 1);
+        self.append_integer_from_be_slice(&bytes[start..], true);
     }
 
     pub fn append_integer_from_be_slice(&mut self, value: &[u8], sign: bool) {
@@ -193,6 +192,15 @@ mod tests {
         let mut builder = DerBuilder::new();
         builder.append_integer_from_u64(1);
         assert_eq!(builder.build(), vec![2, 1, 1]);
+
+        let mut builder = DerBuilder::new();
+        builder.append_integer_from_u64(0);
+        builder.append_integer_from_u64(1000);
+        builder.append_integer_from_u64(128);
+        assert_eq!(
+            builder.build(),
+            vec![2, 1, 0, 2, 2, 0x03, 0xE8, 2, 2, 0x00, 0x80]
+        );
         Ok(())
     }
 
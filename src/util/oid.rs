@@ -36,6 +36,18 @@ pub static OID_SECP521R1: Lazy<ObjectIdentifier> =
 pub static OID_SECP256K1: Lazy<ObjectIdentifier> =
     Lazy::new(|| ObjectIdentifier::from_slice(&[1, 3, 132, 0, 10]));
 
+#[cfg(feature = "brainpool")]
+pub static OID_BRAINPOOL_P256R1: Lazy<ObjectIdentifier> =
+    Lazy::new(|| ObjectIdentifier::from_slice(&[1, 3, 36, 3, 3, 2, 8, 1, 1, 7]));
+
+#[cfg(feature = "brainpool")]
+pub static OID_BRAINPOOL_P384R1: Lazy<ObjectIdentifier> =
+    Lazy::new(|| ObjectIdentifier::from_slice(&[1, 3, 36, 3, 3, 2, 8, 1, 1, 11]));
+
+#[cfg(feature = "brainpool")]
+pub static OID_BRAINPOOL_P512R1: Lazy<ObjectIdentifier> =
+    Lazy::new(|| ObjectIdentifier::from_slice(&[1, 3, 36, 3, 3, 2, 8, 1, 1, 13]));
+
 pub static OID_ED25519: Lazy<ObjectIdentifier> =
     Lazy::new(|| ObjectIdentifier::from_slice(&[1, 3, 101, 112]));
 
@@ -48,6 +60,18 @@ pub static OID_X25519: Lazy<ObjectIdentifier> =
 pub static OID_X448: Lazy<ObjectIdentifier> =
     Lazy::new(|| ObjectIdentifier::from_slice(&[1, 3, 101, 111]));
 
+pub static OID_PBES2: Lazy<ObjectIdentifier> =
+    Lazy::new(|| ObjectIdentifier::from_slice(&[1, 2, 840, 113549, 1, 5, 13]));
+
+pub static OID_PBKDF2: Lazy<ObjectIdentifier> =
+    Lazy::new(|| ObjectIdentifier::from_slice(&[1, 2, 840, 113549, 1, 5, 12]));
+
+pub static OID_HMAC_WITH_SHA256: Lazy<ObjectIdentifier> =
+    Lazy::new(|| ObjectIdentifier::from_slice(&[1, 2, 840, 113549, 2, 9]));
+
+pub static OID_AES256_CBC: Lazy<ObjectIdentifier> =
+    Lazy::new(|| ObjectIdentifier::from_slice(&[2, 16, 840, 1, 101, 3, 4, 1, 42]));
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct ObjectIdentifier {
     values: Vec<u64>,
@@ -0,0 +1,121 @@
+//! Build matrix checks for the algorithm family features.
+//!
+//! Run with the default feature set and with a minimal set, e.g.
+//! `cargo test --no-default-features --features jws-ecdsa,enc-gcm --test features`.
+
+use josekit::capabilities;
+
+fn assert_family(enabled: bool, names: &[&str], supported: impl Fn(&str) -> bool) {
+    for name in names {
+        assert_eq!(supported(name), enabled, "{}", name);
+    }
+}
+
+#[test]
+fn test_jws_capabilities() {
+    let caps = capabilities();
+    let supported = |name: &str| caps.is_supported_jws_algorithm(name);
+
+    assert_family(
+        cfg!(feature = "jws-hmac"),
+        &["HS256", "HS384", "HS512"],
+        supported,
+    );
+    assert_family(
+        cfg!(feature = "jws-rsa"),
+        &["RS256", "RS384", "RS512", "PS256", "PS384", "PS512"],
+        supported,
+    );
+    assert_family(
+        cfg!(feature = "jws-ecdsa"),
+        &["ES256", "ES384", "ES512", "ES256K"],
+        supported,
+    );
+    assert_family(cfg!(feature = "jws-eddsa"), &["EdDSA"], supported);
+}
+
+#[test]
+fn test_jwe_capabilities() {
+    let caps = capabilities();
+    let supported = |name: &str| caps.is_supported_jwe_algorithm(name);
+
+    assert_family(true, &["dir"], supported);
+    assert_family(
+        cfg!(feature = "jwe-rsa"),
+        &[
+            "RSA1_5",
+            "RSA-OAEP",
+            "RSA-OAEP-256",
+            "RSA-OAEP-384",
+            "RSA-OAEP-512",
+        ],
+        supported,
+    );
+    assert_family(
+        cfg!(feature = "jwe-ecdh"),
+        &[
+            "ECDH-ES",
+            "ECDH-ES+A128KW",
+            "ECDH-ES+A192KW",
+            "ECDH-ES+A256KW",
+        ],
+        supported,
+    );
+    assert_family(
+        cfg!(feature = "jwe-aeskw"),
+        &[
+            "A128KW",
+            "A192KW",
+            "A256KW",
+            "A128GCMKW",
+            "A192GCMKW",
+            "A256GCMKW",
+        ],
+        supported,
+    );
+    assert_family(
+        cfg!(feature = "jwe-pbes2"),
+        &[
+            "PBES2-HS256+A128KW",
+            "PBES2-HS384+A192KW",
+            "PBES2-HS512+A256KW",
+        ],
+        supported,
+    );
+}
+
+#[test]
+fn test_content_encryption_capabilities() {
+    let caps = capabilities();
+    let supported = |name: &str| caps.is_supported_content_encryption(name);
+
+    assert_family(
+        cfg!(feature = "enc-cbc"),
+        &["A128CBC-HS256", "A192CBC-HS384", "A256CBC-HS512"],
+        supported,
+    );
+    assert_family(
+        cfg!(feature = "enc-gcm"),
+        &["A128GCM", "A192GCM", "A256GCM"],
+        supported,
+    );
+    assert_eq!(caps.compressions(), &["DEF".to_string()]);
+}
+
+#[cfg(feature = "jws-ecdsa")]
+#[test]
+fn test_es256_roundtrip() -> Result<(), josekit::JoseError> {
+    use josekit::jws::{self, JwsHeader, ES256};
+
+    let key_pair = ES256.generate_key_pair()?;
+    let signer = ES256.signer_from_der(key_pair.to_der_private_key())?;
+    let verifier = ES256.verifier_from_der(key_pair.to_der_public_key())?;
+
+    let mut header = JwsHeader::new();
+    header.set_token_type("JWT");
+    let jws = jws::serialize_compact(b"payload", &header, &signer)?;
+    let (payload, _) = jws::deserialize_compact(&jws, &verifier)?;
+    assert_eq!(payload, b"payload");
+
+    Ok(())
+}